@@ -0,0 +1,149 @@
+use std::io::{self, Read, Write};
+
+const MAGIC: &[u8; 8] = b"RSFLUENT";
+const VERSION: u32 = 2;
+
+/// A bundle's resources and settings, saved in a compact binary format so that large
+/// deployments can load one file instead of reading hundreds of FTL files.
+///
+/// All integers are little-endian, and strings are a `u32` length followed by UTF-8 bytes:
+///
+/// ```text
+/// magic, version: u32, variable_separator, strict: u8, settings, locale count: u32
+/// for each locale: language count: u32, languages..., resource count: u32
+///     for each resource: name, path (empty if not from a file), file hash: u64, source
+/// ```
+pub(crate) struct CachedBundle {
+    pub variable_separator: String,
+    pub strict: bool,
+    /// The bundle's other settings, as JSON.
+    pub settings: String,
+    pub locales: Vec<CachedLocale>,
+}
+
+pub(crate) struct CachedLocale {
+    /// The locale's language followed by its fallbacks.
+    pub languages: Vec<String>,
+    pub resources: Vec<CachedResource>,
+}
+
+pub(crate) struct CachedResource {
+    pub name: String,
+    /// The FTL file the resource was loaded from, if any.
+    pub path: Option<String>,
+    /// The `file_hash` of the FTL file when it was read, or 0 if the resource isn't from one.
+    pub file_hash: u64,
+    pub source: String,
+}
+
+impl CachedBundle {
+    pub fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        write_u32(writer, VERSION)?;
+        write_str(writer, &self.variable_separator)?;
+        writer.write_all(&[u8::from(self.strict)])?;
+        write_str(writer, &self.settings)?;
+        write_len(writer, self.locales.len())?;
+        for locale in self.locales.iter() {
+            write_len(writer, locale.languages.len())?;
+            for language in locale.languages.iter() {
+                write_str(writer, language)?;
+            }
+            write_len(writer, locale.resources.len())?;
+            for resource in locale.resources.iter() {
+                write_str(writer, &resource.name)?;
+                write_str(writer, resource.path.as_deref().unwrap_or(""))?;
+                writer.write_all(&resource.file_hash.to_le_bytes())?;
+                write_str(writer, &resource.source)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read(reader: &mut impl Read) -> io::Result<Self> {
+        let mut magic = [0; MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a bundle cache".to_string()));
+        }
+        let version = read_u32(reader)?;
+        if version != VERSION {
+            return Err(invalid_data(format!(
+                "unsupported cache version {version}, expected {VERSION}"
+            )));
+        }
+        let variable_separator = read_str(reader)?;
+        let mut strict = [0];
+        reader.read_exact(&mut strict)?;
+        let settings = read_str(reader)?;
+
+        let mut locales = vec![];
+        for _ in 0..read_u32(reader)? {
+            let languages = (0..read_u32(reader)?)
+                .map(|_| read_str(reader))
+                .collect::<io::Result<_>>()?;
+            let mut resources = vec![];
+            for _ in 0..read_u32(reader)? {
+                let name = read_str(reader)?;
+                let path = Some(read_str(reader)?).filter(|path| !path.is_empty());
+                let mut file_hash = [0; 8];
+                reader.read_exact(&mut file_hash)?;
+                resources.push(CachedResource {
+                    name,
+                    path,
+                    file_hash: u64::from_le_bytes(file_hash),
+                    source: read_str(reader)?,
+                });
+            }
+            locales.push(CachedLocale {
+                languages,
+                resources,
+            });
+        }
+
+        Ok(Self {
+            variable_separator,
+            strict: strict[0] != 0,
+            settings,
+            locales,
+        })
+    }
+}
+
+/// A hash of an FTL file's bytes which, unlike `std`'s hashers, is stable between releases
+/// (64-bit FNV-1a).
+pub(crate) fn file_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_u32(writer: &mut impl Write, value: u32) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn write_len(writer: &mut impl Write, len: usize) -> io::Result<()> {
+    let len = u32::try_from(len).map_err(|_| invalid_data("too large to cache".to_string()))?;
+    write_u32(writer, len)
+}
+
+fn write_str(writer: &mut impl Write, value: &str) -> io::Result<()> {
+    write_len(writer, value.len())?;
+    writer.write_all(value.as_bytes())
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_str(reader: &mut impl Read) -> io::Result<String> {
+    let mut bytes = vec![0; read_u32(reader)? as usize];
+    reader.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|error| invalid_data(error.to_string()))
+}
@@ -0,0 +1,1102 @@
+use chrono::{Datelike, NaiveDateTime, Timelike};
+use fluent::{FluentArgs, FluentValue};
+use fluent_bundle::types::FluentType;
+use intl_memoizer::Memoizable;
+use std::borrow::Cow;
+use std::convert::Infallible;
+use unic_langid::LanguageIdentifier;
+
+/// A date, or a date and time, formatted using the bundle's locale.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FluentDateTime {
+    pub value: NaiveDateTime,
+    /// Whether the value came from a `datetime.datetime` rather than a `datetime.date`.
+    pub has_time: bool,
+    options: DateTimeOptions,
+}
+
+impl FluentDateTime {
+    pub fn new(value: NaiveDateTime, has_time: bool) -> Self {
+        Self {
+            value,
+            has_time,
+            options: DateTimeOptions::default(),
+        }
+    }
+}
+
+impl FluentType for FluentDateTime {
+    fn duplicate(&self) -> Box<dyn FluentType + Send> {
+        Box::new(self.clone())
+    }
+
+    fn as_string(&self, intls: &intl_memoizer::IntlLangMemoizer) -> Cow<'static, str> {
+        intls
+            .with_try_get::<DateTimeFormatter, _, _>((), |formatter| formatter.format(self))
+            .unwrap_or_else(|never| match never {})
+            .into()
+    }
+
+    fn as_string_threadsafe(
+        &self,
+        intls: &intl_memoizer::concurrent::IntlLangMemoizer,
+    ) -> Cow<'static, str> {
+        intls
+            .with_try_get::<DateTimeFormatter, _, _>((), |formatter| formatter.format(self))
+            .unwrap_or_else(|never| match never {})
+            .into()
+    }
+}
+
+/// The `DATETIME()` Fluent function, applying formatting options to a date variable.
+pub(crate) fn datetime<'a>(positional: &[FluentValue<'a>], named: &FluentArgs) -> FluentValue<'a> {
+    let Some(FluentValue::Custom(value)) = positional.first() else {
+        return FluentValue::Error;
+    };
+    let Some(datetime) = value.as_any().downcast_ref::<FluentDateTime>() else {
+        return FluentValue::Error;
+    };
+    let mut datetime = datetime.clone();
+    datetime.options.merge(named);
+    FluentValue::Custom(Box::new(datetime))
+}
+
+/// How a date or time field is displayed, as in `Intl.DateTimeFormat`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Width {
+    Numeric,
+    TwoDigit,
+    Long,
+    Short,
+    Narrow,
+}
+
+impl Width {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "numeric" => Some(Self::Numeric),
+            "2-digit" => Some(Self::TwoDigit),
+            "long" => Some(Self::Long),
+            "short" => Some(Self::Short),
+            "narrow" => Some(Self::Narrow),
+            _ => None,
+        }
+    }
+}
+
+/// A preset combination of fields, for the `dateStyle` and `timeStyle` options.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Style {
+    Full,
+    Long,
+    Medium,
+    Short,
+}
+
+impl Style {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "full" => Some(Self::Full),
+            "long" => Some(Self::Long),
+            "medium" => Some(Self::Medium),
+            "short" => Some(Self::Short),
+            _ => None,
+        }
+    }
+}
+
+/// The named arguments accepted by `DATETIME()`.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct DateTimeOptions {
+    date_style: Option<Style>,
+    time_style: Option<Style>,
+    weekday: Option<Width>,
+    year: Option<Width>,
+    month: Option<Width>,
+    day: Option<Width>,
+    hour: Option<Width>,
+    minute: Option<Width>,
+    second: Option<Width>,
+    hour12: Option<bool>,
+}
+
+impl DateTimeOptions {
+    fn merge(&mut self, named: &FluentArgs) {
+        for (key, value) in named.iter() {
+            let FluentValue::String(value) = value else {
+                continue;
+            };
+            match key {
+                "dateStyle" => self.date_style = Style::parse(value),
+                "timeStyle" => self.time_style = Style::parse(value),
+                "weekday" => self.weekday = Width::parse(value),
+                "year" => self.year = Width::parse(value),
+                "month" => self.month = Width::parse(value),
+                "day" => self.day = Width::parse(value),
+                "hour" => self.hour = Width::parse(value),
+                "minute" => self.minute = Width::parse(value),
+                "second" => self.second = Width::parse(value),
+                "hour12" => self.hour12 = value.parse().ok(),
+                _ => {}
+            }
+        }
+    }
+
+    /// The fields to display. As with `Intl.DateTimeFormat`, styles take precedence
+    /// over individual fields, and a numeric date is shown when neither is given.
+    fn resolve(&self, has_time: bool) -> Self {
+        let mut resolved = Self {
+            hour12: self.hour12,
+            ..Self::default()
+        };
+        if self.date_style.is_some() || self.time_style.is_some() {
+            if let Some(style) = self.date_style {
+                resolved.weekday = (style == Style::Full).then_some(Width::Long);
+                resolved.year = Some(Width::Numeric);
+                resolved.month = Some(match style {
+                    Style::Full | Style::Long => Width::Long,
+                    Style::Medium => Width::Short,
+                    Style::Short => Width::Numeric,
+                });
+                resolved.day = Some(Width::Numeric);
+            }
+            if let Some(style) = self.time_style {
+                resolved.hour = Some(Width::Numeric);
+                resolved.minute = Some(Width::TwoDigit);
+                resolved.second = (style != Style::Short).then_some(Width::TwoDigit);
+            }
+            return resolved;
+        }
+
+        let fields = [
+            self.weekday,
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            self.second,
+        ];
+        if fields.iter().any(Option::is_some) {
+            return self.clone();
+        }
+        resolved.year = Some(Width::Numeric);
+        resolved.month = Some(Width::Numeric);
+        resolved.day = Some(Width::Numeric);
+        if has_time {
+            resolved.hour = Some(Width::Numeric);
+            resolved.minute = Some(Width::TwoDigit);
+            resolved.second = Some(Width::TwoDigit);
+        }
+        resolved
+    }
+}
+
+/// The names and patterns used to format dates in a locale, taken from the CLDR.
+///
+/// Patterns use `{y}`, `{M}` and `{d}` for the year, month and day. Fields which
+/// aren't displayed are dropped along with the text following them.
+struct DateSymbols {
+    /// Month names as used in a date with a day, e.g. the genitive in Russian.
+    months: [&'static str; 12],
+    /// Month names on their own or with just a year, if they're different.
+    months_standalone: Option<[&'static str; 12]>,
+    months_short: [&'static str; 12],
+    /// Weekday names, starting from Monday.
+    weekdays: [&'static str; 7],
+    weekdays_short: [&'static str; 7],
+    numeric_date: &'static str,
+    text_date: &'static str,
+    /// Whether numeric days and months are zero-padded.
+    pad_numeric: bool,
+    weekday_date: &'static str,
+    date_time: &'static str,
+    hour12: bool,
+    /// The separator between hours, minutes and seconds.
+    time_separator: &'static str,
+}
+
+const EN: DateSymbols = DateSymbols {
+    months: [
+        "January",
+        "February",
+        "March",
+        "April",
+        "May",
+        "June",
+        "July",
+        "August",
+        "September",
+        "October",
+        "November",
+        "December",
+    ],
+    months_standalone: None,
+    months_short: [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ],
+    weekdays: [
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+        "Sunday",
+    ],
+    weekdays_short: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+    numeric_date: "{M}/{d}/{y}",
+    text_date: "{M} {d}, {y}",
+    pad_numeric: false,
+    weekday_date: "{weekday}, {date}",
+    date_time: "{date}, {time}",
+    hour12: true,
+    time_separator: ":",
+};
+
+const EN_GB: DateSymbols = DateSymbols {
+    numeric_date: "{d}/{M}/{y}",
+    text_date: "{d} {M} {y}",
+    pad_numeric: true,
+    weekday_date: "{weekday} {date}",
+    hour12: false,
+    ..EN
+};
+
+const DE: DateSymbols = DateSymbols {
+    months: [
+        "Januar",
+        "Februar",
+        "März",
+        "April",
+        "Mai",
+        "Juni",
+        "Juli",
+        "August",
+        "September",
+        "Oktober",
+        "November",
+        "Dezember",
+    ],
+    months_standalone: None,
+    months_short: [
+        "Jan.", "Feb.", "März", "Apr.", "Mai", "Juni", "Juli", "Aug.", "Sept.", "Okt.", "Nov.",
+        "Dez.",
+    ],
+    weekdays: [
+        "Montag",
+        "Dienstag",
+        "Mittwoch",
+        "Donnerstag",
+        "Freitag",
+        "Samstag",
+        "Sonntag",
+    ],
+    weekdays_short: ["Mo.", "Di.", "Mi.", "Do.", "Fr.", "Sa.", "So."],
+    numeric_date: "{d}.{M}.{y}",
+    text_date: "{d}. {M} {y}",
+    pad_numeric: false,
+    weekday_date: "{weekday}, {date}",
+    date_time: "{date}, {time}",
+    hour12: false,
+    time_separator: ":",
+};
+
+const FR: DateSymbols = DateSymbols {
+    months: [
+        "janvier",
+        "février",
+        "mars",
+        "avril",
+        "mai",
+        "juin",
+        "juillet",
+        "août",
+        "septembre",
+        "octobre",
+        "novembre",
+        "décembre",
+    ],
+    months_standalone: None,
+    months_short: [
+        "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.", "nov.",
+        "déc.",
+    ],
+    weekdays: [
+        "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi", "dimanche",
+    ],
+    weekdays_short: ["lun.", "mar.", "mer.", "jeu.", "ven.", "sam.", "dim."],
+    numeric_date: "{d}/{M}/{y}",
+    text_date: "{d} {M} {y}",
+    pad_numeric: true,
+    weekday_date: "{weekday} {date}",
+    date_time: "{date} {time}",
+    hour12: false,
+    time_separator: ":",
+};
+
+const ES: DateSymbols = DateSymbols {
+    months: [
+        "enero",
+        "febrero",
+        "marzo",
+        "abril",
+        "mayo",
+        "junio",
+        "julio",
+        "agosto",
+        "septiembre",
+        "octubre",
+        "noviembre",
+        "diciembre",
+    ],
+    months_standalone: None,
+    months_short: [
+        "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sept", "oct", "nov", "dic",
+    ],
+    weekdays: [
+        "lunes",
+        "martes",
+        "miércoles",
+        "jueves",
+        "viernes",
+        "sábado",
+        "domingo",
+    ],
+    weekdays_short: ["lun", "mar", "mié", "jue", "vie", "sáb", "dom"],
+    numeric_date: "{d}/{M}/{y}",
+    text_date: "{d} de {M} de {y}",
+    pad_numeric: false,
+    weekday_date: "{weekday}, {date}",
+    date_time: "{date}, {time}",
+    hour12: false,
+    time_separator: ":",
+};
+
+const IT: DateSymbols = DateSymbols {
+    months: [
+        "gennaio",
+        "febbraio",
+        "marzo",
+        "aprile",
+        "maggio",
+        "giugno",
+        "luglio",
+        "agosto",
+        "settembre",
+        "ottobre",
+        "novembre",
+        "dicembre",
+    ],
+    months_standalone: None,
+    months_short: [
+        "gen", "feb", "mar", "apr", "mag", "giu", "lug", "ago", "set", "ott", "nov", "dic",
+    ],
+    weekdays: [
+        "lunedì",
+        "martedì",
+        "mercoledì",
+        "giovedì",
+        "venerdì",
+        "sabato",
+        "domenica",
+    ],
+    weekdays_short: ["lun", "mar", "mer", "gio", "ven", "sab", "dom"],
+    numeric_date: "{d}/{M}/{y}",
+    text_date: "{d} {M} {y}",
+    pad_numeric: false,
+    weekday_date: "{weekday} {date}",
+    date_time: "{date}, {time}",
+    hour12: false,
+    time_separator: ":",
+};
+
+const NL: DateSymbols = DateSymbols {
+    months: [
+        "januari",
+        "februari",
+        "maart",
+        "april",
+        "mei",
+        "juni",
+        "juli",
+        "augustus",
+        "september",
+        "oktober",
+        "november",
+        "december",
+    ],
+    months_standalone: None,
+    months_short: [
+        "jan", "feb", "mrt", "apr", "mei", "jun", "jul", "aug", "sep", "okt", "nov", "dec",
+    ],
+    weekdays: [
+        "maandag",
+        "dinsdag",
+        "woensdag",
+        "donderdag",
+        "vrijdag",
+        "zaterdag",
+        "zondag",
+    ],
+    weekdays_short: ["ma", "di", "wo", "do", "vr", "za", "zo"],
+    numeric_date: "{d}-{M}-{y}",
+    text_date: "{d} {M} {y}",
+    pad_numeric: false,
+    weekday_date: "{weekday} {date}",
+    date_time: "{date}, {time}",
+    hour12: false,
+    time_separator: ":",
+};
+
+const PT: DateSymbols = DateSymbols {
+    months: [
+        "janeiro",
+        "fevereiro",
+        "março",
+        "abril",
+        "maio",
+        "junho",
+        "julho",
+        "agosto",
+        "setembro",
+        "outubro",
+        "novembro",
+        "dezembro",
+    ],
+    months_standalone: None,
+    months_short: [
+        "jan.", "fev.", "mar.", "abr.", "mai.", "jun.", "jul.", "ago.", "set.", "out.", "nov.",
+        "dez.",
+    ],
+    weekdays: [
+        "segunda-feira",
+        "terça-feira",
+        "quarta-feira",
+        "quinta-feira",
+        "sexta-feira",
+        "sábado",
+        "domingo",
+    ],
+    weekdays_short: ["seg.", "ter.", "qua.", "qui.", "sex.", "sáb.", "dom."],
+    numeric_date: "{d}/{M}/{y}",
+    text_date: "{d} de {M} de {y}",
+    pad_numeric: true,
+    weekday_date: "{weekday}, {date}",
+    date_time: "{date}, {time}",
+    hour12: false,
+    time_separator: ":",
+};
+
+const DA: DateSymbols = DateSymbols {
+    months: [
+        "januar",
+        "februar",
+        "marts",
+        "april",
+        "maj",
+        "juni",
+        "juli",
+        "august",
+        "september",
+        "oktober",
+        "november",
+        "december",
+    ],
+    months_standalone: None,
+    months_short: [
+        "jan.", "feb.", "mar.", "apr.", "maj", "jun.", "jul.", "aug.", "sep.", "okt.", "nov.",
+        "dec.",
+    ],
+    weekdays: [
+        "mandag", "tirsdag", "onsdag", "torsdag", "fredag", "lørdag", "søndag",
+    ],
+    weekdays_short: ["man.", "tirs.", "ons.", "tors.", "fre.", "lør.", "søn."],
+    numeric_date: "{d}.{M}.{y}",
+    text_date: "{d}. {M} {y}",
+    pad_numeric: false,
+    weekday_date: "{weekday} den {date}",
+    date_time: "{date} {time}",
+    hour12: false,
+    time_separator: ".",
+};
+
+const NB: DateSymbols = DateSymbols {
+    months: [
+        "januar",
+        "februar",
+        "mars",
+        "april",
+        "mai",
+        "juni",
+        "juli",
+        "august",
+        "september",
+        "oktober",
+        "november",
+        "desember",
+    ],
+    months_standalone: None,
+    months_short: [
+        "jan.", "feb.", "mar.", "apr.", "mai", "jun.", "jul.", "aug.", "sep.", "okt.", "nov.",
+        "des.",
+    ],
+    weekdays: [
+        "mandag", "tirsdag", "onsdag", "torsdag", "fredag", "lørdag", "søndag",
+    ],
+    weekdays_short: ["man.", "tir.", "ons.", "tor.", "fre.", "lør.", "søn."],
+    numeric_date: "{d}.{M}.{y}",
+    text_date: "{d}. {M} {y}",
+    pad_numeric: true,
+    weekday_date: "{weekday} {date}",
+    date_time: "{date}, {time}",
+    hour12: false,
+    time_separator: ":",
+};
+
+const SV: DateSymbols = DateSymbols {
+    months: [
+        "januari",
+        "februari",
+        "mars",
+        "april",
+        "maj",
+        "juni",
+        "juli",
+        "augusti",
+        "september",
+        "oktober",
+        "november",
+        "december",
+    ],
+    months_standalone: None,
+    months_short: [
+        "jan.", "feb.", "mars", "apr.", "maj", "juni", "juli", "aug.", "sep.", "okt.", "nov.",
+        "dec.",
+    ],
+    weekdays: [
+        "måndag", "tisdag", "onsdag", "torsdag", "fredag", "lördag", "söndag",
+    ],
+    weekdays_short: ["mån", "tis", "ons", "tors", "fre", "lör", "sön"],
+    numeric_date: "{y}-{M}-{d}",
+    text_date: "{d} {M} {y}",
+    pad_numeric: true,
+    weekday_date: "{weekday} {date}",
+    date_time: "{date} {time}",
+    hour12: false,
+    time_separator: ":",
+};
+
+const FI: DateSymbols = DateSymbols {
+    months: [
+        "tammikuuta",
+        "helmikuuta",
+        "maaliskuuta",
+        "huhtikuuta",
+        "toukokuuta",
+        "kesäkuuta",
+        "heinäkuuta",
+        "elokuuta",
+        "syyskuuta",
+        "lokakuuta",
+        "marraskuuta",
+        "joulukuuta",
+    ],
+    months_standalone: Some([
+        "tammikuu",
+        "helmikuu",
+        "maaliskuu",
+        "huhtikuu",
+        "toukokuu",
+        "kesäkuu",
+        "heinäkuu",
+        "elokuu",
+        "syyskuu",
+        "lokakuu",
+        "marraskuu",
+        "joulukuu",
+    ]),
+    months_short: [
+        "tammik.", "helmik.", "maalisk.", "huhtik.", "toukok.", "kesäk.", "heinäk.", "elok.",
+        "syysk.", "lokak.", "marrask.", "jouluk.",
+    ],
+    weekdays: [
+        "maanantai",
+        "tiistai",
+        "keskiviikko",
+        "torstai",
+        "perjantai",
+        "lauantai",
+        "sunnuntai",
+    ],
+    weekdays_short: ["ma", "ti", "ke", "to", "pe", "la", "su"],
+    numeric_date: "{d}.{M}.{y}",
+    text_date: "{d}. {M} {y}",
+    pad_numeric: false,
+    weekday_date: "{weekday} {date}",
+    date_time: "{date} {time}",
+    hour12: false,
+    time_separator: ".",
+};
+
+const PL: DateSymbols = DateSymbols {
+    months: [
+        "stycznia",
+        "lutego",
+        "marca",
+        "kwietnia",
+        "maja",
+        "czerwca",
+        "lipca",
+        "sierpnia",
+        "września",
+        "października",
+        "listopada",
+        "grudnia",
+    ],
+    months_standalone: Some([
+        "styczeń",
+        "luty",
+        "marzec",
+        "kwiecień",
+        "maj",
+        "czerwiec",
+        "lipiec",
+        "sierpień",
+        "wrzesień",
+        "październik",
+        "listopad",
+        "grudzień",
+    ]),
+    months_short: [
+        "sty", "lut", "mar", "kwi", "maj", "cze", "lip", "sie", "wrz", "paź", "lis", "gru",
+    ],
+    weekdays: [
+        "poniedziałek",
+        "wtorek",
+        "środa",
+        "czwartek",
+        "piątek",
+        "sobota",
+        "niedziela",
+    ],
+    weekdays_short: ["pon.", "wt.", "śr.", "czw.", "pt.", "sob.", "niedz."],
+    numeric_date: "{d}.{M}.{y}",
+    text_date: "{d} {M} {y}",
+    pad_numeric: true,
+    weekday_date: "{weekday}, {date}",
+    date_time: "{date}, {time}",
+    hour12: false,
+    time_separator: ":",
+};
+
+const CS: DateSymbols = DateSymbols {
+    months: [
+        "ledna",
+        "února",
+        "března",
+        "dubna",
+        "května",
+        "června",
+        "července",
+        "srpna",
+        "září",
+        "října",
+        "listopadu",
+        "prosince",
+    ],
+    months_standalone: Some([
+        "leden",
+        "únor",
+        "březen",
+        "duben",
+        "květen",
+        "červen",
+        "červenec",
+        "srpen",
+        "září",
+        "říjen",
+        "listopad",
+        "prosinec",
+    ]),
+    months_short: [
+        "led", "úno", "bře", "dub", "kvě", "čvn", "čvc", "srp", "zář", "říj", "lis", "pro",
+    ],
+    weekdays: [
+        "pondělí",
+        "úterý",
+        "středa",
+        "čtvrtek",
+        "pátek",
+        "sobota",
+        "neděle",
+    ],
+    weekdays_short: ["po", "út", "st", "čt", "pá", "so", "ne"],
+    numeric_date: "{d}. {M}. {y}",
+    text_date: "{d}. {M} {y}",
+    pad_numeric: false,
+    weekday_date: "{weekday} {date}",
+    date_time: "{date} {time}",
+    hour12: false,
+    time_separator: ":",
+};
+
+const SK: DateSymbols = DateSymbols {
+    months: [
+        "januára",
+        "februára",
+        "marca",
+        "apríla",
+        "mája",
+        "júna",
+        "júla",
+        "augusta",
+        "septembra",
+        "októbra",
+        "novembra",
+        "decembra",
+    ],
+    months_standalone: Some([
+        "január",
+        "február",
+        "marec",
+        "apríl",
+        "máj",
+        "jún",
+        "júl",
+        "august",
+        "september",
+        "október",
+        "november",
+        "december",
+    ]),
+    months_short: [
+        "jan", "feb", "mar", "apr", "máj", "jún", "júl", "aug", "sep", "okt", "nov", "dec",
+    ],
+    weekdays: [
+        "pondelok", "utorok", "streda", "štvrtok", "piatok", "sobota", "nedeľa",
+    ],
+    weekdays_short: ["po", "ut", "st", "št", "pi", "so", "ne"],
+    numeric_date: "{d}. {M}. {y}",
+    text_date: "{d}. {M} {y}",
+    pad_numeric: false,
+    weekday_date: "{weekday} {date}",
+    date_time: "{date}, {time}",
+    hour12: false,
+    time_separator: ":",
+};
+
+const RU: DateSymbols = DateSymbols {
+    months: [
+        "января",
+        "февраля",
+        "марта",
+        "апреля",
+        "мая",
+        "июня",
+        "июля",
+        "августа",
+        "сентября",
+        "октября",
+        "ноября",
+        "декабря",
+    ],
+    months_standalone: Some([
+        "январь",
+        "февраль",
+        "март",
+        "апрель",
+        "май",
+        "июнь",
+        "июль",
+        "август",
+        "сентябрь",
+        "октябрь",
+        "ноябрь",
+        "декабрь",
+    ]),
+    months_short: [
+        "янв.",
+        "февр.",
+        "мар.",
+        "апр.",
+        "мая",
+        "июн.",
+        "июл.",
+        "авг.",
+        "сент.",
+        "окт.",
+        "нояб.",
+        "дек.",
+    ],
+    weekdays: [
+        "понедельник",
+        "вторник",
+        "среда",
+        "четверг",
+        "пятница",
+        "суббота",
+        "воскресенье",
+    ],
+    weekdays_short: ["пн", "вт", "ср", "чт", "пт", "сб", "вс"],
+    numeric_date: "{d}.{M}.{y}",
+    text_date: "{d} {M} {y} г.",
+    pad_numeric: true,
+    weekday_date: "{weekday}, {date}",
+    date_time: "{date}, {time}",
+    hour12: false,
+    time_separator: ":",
+};
+
+const UK: DateSymbols = DateSymbols {
+    months: [
+        "січня",
+        "лютого",
+        "березня",
+        "квітня",
+        "травня",
+        "червня",
+        "липня",
+        "серпня",
+        "вересня",
+        "жовтня",
+        "листопада",
+        "грудня",
+    ],
+    months_standalone: Some([
+        "січень",
+        "лютий",
+        "березень",
+        "квітень",
+        "травень",
+        "червень",
+        "липень",
+        "серпень",
+        "вересень",
+        "жовтень",
+        "листопад",
+        "грудень",
+    ]),
+    months_short: [
+        "січ.",
+        "лют.",
+        "бер.",
+        "квіт.",
+        "трав.",
+        "черв.",
+        "лип.",
+        "серп.",
+        "вер.",
+        "жовт.",
+        "лист.",
+        "груд.",
+    ],
+    weekdays: [
+        "понеділок",
+        "вівторок",
+        "середа",
+        "четвер",
+        "пʼятниця",
+        "субота",
+        "неділя",
+    ],
+    weekdays_short: ["пн", "вт", "ср", "чт", "пт", "сб", "нд"],
+    numeric_date: "{d}.{M}.{y}",
+    text_date: "{d} {M} {y} р.",
+    pad_numeric: true,
+    weekday_date: "{weekday}, {date}",
+    date_time: "{date}, {time}",
+    hour12: false,
+    time_separator: ":",
+};
+
+impl DateSymbols {
+    fn for_locale(locale: &LanguageIdentifier) -> &'static Self {
+        let region = locale.region.as_ref().map(|region| region.as_str());
+        match (locale.language.as_str(), region) {
+            ("en", Some("AU" | "GB" | "IE" | "IN" | "NZ")) => &EN_GB,
+            ("de", _) => &DE,
+            ("fr", _) => &FR,
+            ("es", _) => &ES,
+            ("it", _) => &IT,
+            ("nl", _) => &NL,
+            ("pt", _) => &PT,
+            ("da", _) => &DA,
+            ("nb" | "nn" | "no", _) => &NB,
+            ("sv", _) => &SV,
+            ("fi", _) => &FI,
+            ("pl", _) => &PL,
+            ("cs", _) => &CS,
+            ("sk", _) => &SK,
+            ("ru", _) => &RU,
+            ("uk", _) => &UK,
+            _ => &EN,
+        }
+    }
+}
+
+/// Formats dates and times according to the bundle's locale.
+pub(crate) struct DateTimeFormatter {
+    symbols: &'static DateSymbols,
+}
+
+impl Memoizable for DateTimeFormatter {
+    type Args = ();
+    type Error = Infallible;
+
+    fn construct(lang: LanguageIdentifier, _args: Self::Args) -> Result<Self, Self::Error> {
+        Ok(Self {
+            symbols: DateSymbols::for_locale(&lang),
+        })
+    }
+}
+
+impl DateTimeFormatter {
+    fn format(&self, datetime: &FluentDateTime) -> String {
+        let fields = datetime.options.resolve(datetime.has_time);
+        let date = self.format_date(&datetime.value, &fields);
+        let time = self.format_time(&datetime.value, &fields);
+        match (date, time) {
+            (Some(date), Some(time)) => self
+                .symbols
+                .date_time
+                .replace("{date}", &date)
+                .replace("{time}", &time),
+            (Some(part), None) | (None, Some(part)) => part,
+            (None, None) => String::new(),
+        }
+    }
+
+    fn format_date(&self, value: &NaiveDateTime, fields: &DateTimeOptions) -> Option<String> {
+        let symbols = self.symbols;
+        let month_index = value.month0() as usize;
+        let months = match symbols.months_standalone {
+            Some(standalone) if fields.day.is_none() => standalone,
+            _ => symbols.months,
+        };
+        let textual = matches!(
+            fields.month,
+            Some(Width::Long | Width::Short | Width::Narrow)
+        );
+        let pad = symbols.pad_numeric && !textual;
+        let numeric = |number: u32, width: Width| match width {
+            Width::TwoDigit => format!("{number:02}"),
+            _ if pad => format!("{number:02}"),
+            _ => number.to_string(),
+        };
+
+        let date = render_pattern(
+            if textual {
+                symbols.text_date
+            } else {
+                symbols.numeric_date
+            },
+            |field| match field {
+                "y" => fields.year.map(|width| match width {
+                    Width::TwoDigit => format!("{:02}", value.year().rem_euclid(100)),
+                    _ => value.year().to_string(),
+                }),
+                "M" => fields.month.map(|width| match width {
+                    Width::Long => months[month_index].to_string(),
+                    Width::Short => symbols.months_short[month_index].to_string(),
+                    Width::Narrow => narrow(months[month_index]),
+                    _ => numeric(value.month(), width),
+                }),
+                "d" => fields.day.map(|width| numeric(value.day(), width)),
+                _ => None,
+            },
+        );
+
+        let weekday_index = value.weekday().num_days_from_monday() as usize;
+        let weekday = fields.weekday.map(|width| match width {
+            Width::Short => symbols.weekdays_short[weekday_index].to_string(),
+            Width::Narrow => narrow(symbols.weekdays[weekday_index]),
+            _ => symbols.weekdays[weekday_index].to_string(),
+        });
+        match (weekday, date) {
+            (Some(weekday), Some(date)) => Some(
+                symbols
+                    .weekday_date
+                    .replace("{weekday}", &weekday)
+                    .replace("{date}", &date),
+            ),
+            (weekday, date) => weekday.or(date),
+        }
+    }
+
+    fn format_time(&self, value: &NaiveDateTime, fields: &DateTimeOptions) -> Option<String> {
+        let hour12 = fields.hour12.unwrap_or(self.symbols.hour12);
+        let mut parts = vec![];
+        if let Some(width) = fields.hour {
+            let hour = if hour12 {
+                (value.hour() + 11) % 12 + 1
+            } else {
+                value.hour()
+            };
+            parts.push(if hour12 && width != Width::TwoDigit {
+                hour.to_string()
+            } else {
+                format!("{hour:02}")
+            });
+        }
+        if fields.minute.is_some() {
+            parts.push(format!("{:02}", value.minute()));
+        }
+        if fields.second.is_some() {
+            parts.push(format!("{:02}", value.second()));
+        }
+        if parts.is_empty() {
+            return None;
+        }
+
+        let mut time = parts.join(self.symbols.time_separator);
+        if hour12 && fields.hour.is_some() {
+            time.push('\u{202f}');
+            time.push_str(if value.hour() < 12 { "AM" } else { "PM" });
+        }
+        Some(time)
+    }
+}
+
+/// Fill in a date pattern such as `{M}/{d}/{y}`, dropping any fields without a value
+/// along with the text that follows them.
+fn render_pattern(pattern: &str, field: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let (prefix, rest) = pattern.split_once('{').unwrap_or((pattern, ""));
+    let mut values = vec![];
+    for part in rest.split('{') {
+        let Some((name, literal)) = part.split_once('}') else {
+            continue;
+        };
+        values.push((field(name), literal));
+    }
+    let first_shown = values.first().is_some_and(|(value, _)| value.is_some());
+    let last_shown = values.last().is_some_and(|(value, _)| value.is_some());
+    let shown: Vec<_> = values
+        .into_iter()
+        .filter_map(|(value, literal)| Some((value?, literal)))
+        .collect();
+    if shown.is_empty() {
+        return None;
+    }
+
+    let mut rendered = String::new();
+    if first_shown {
+        rendered.push_str(prefix);
+    }
+    for (index, (value, literal)) in shown.iter().enumerate() {
+        rendered.push_str(value);
+        if index + 1 < shown.len() || last_shown {
+            rendered.push_str(literal);
+        }
+    }
+    Some(rendered)
+}
+
+/// The narrow form of a month or weekday name, e.g. `J` for January.
+fn narrow(name: &str) -> String {
+    name.chars()
+        .next()
+        .into_iter()
+        .flat_map(char::to_uppercase)
+        .collect()
+}
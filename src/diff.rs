@@ -0,0 +1,205 @@
+use crate::introspection::LoadedResource;
+use crate::python_repr;
+use fluent_syntax::ast;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+/// The messages and terms that differ between two versions of an FTL file, as returned by
+/// `diff_resources`.
+#[pyclass(frozen, get_all, module = "rustfluent")]
+pub(crate) struct ResourceDiff {
+    /// The messages in the new file but not the old one, in the new file's order.
+    added: Vec<MessageDiff>,
+    /// The messages in the old file but not the new one, in the old file's order.
+    removed: Vec<MessageDiff>,
+    /// The messages whose value or attributes are different, in the new file's order.
+    changed: Vec<MessageDiff>,
+}
+
+#[pymethods]
+impl ResourceDiff {
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        let ids = |messages: &[MessageDiff]| {
+            let ids: Vec<&str> = messages
+                .iter()
+                .map(|message| message.message_id.as_str())
+                .collect();
+            python_repr(py, ids)
+        };
+        Ok(format!(
+            "ResourceDiff(added={}, removed={}, changed={})",
+            ids(&self.added)?,
+            ids(&self.removed)?,
+            ids(&self.changed)?
+        ))
+    }
+}
+
+/// A message (or term) that was added, removed or changed between two versions of an FTL
+/// file.
+#[pyclass(frozen, get_all, skip_from_py_object, module = "rustfluent")]
+#[derive(Clone)]
+pub(crate) struct MessageDiff {
+    /// The ID of the message, or of the term with its leading `-`.
+    message_id: String,
+    /// Whether the message's value was added, removed or changed.
+    value_changed: bool,
+    /// The attributes that were added, removed or changed.
+    attributes: Vec<String>,
+    /// The name of the file the message is in: the new one, or the old one if it was removed.
+    filename: String,
+    /// Where the message's ID is, counting from 1.
+    line: usize,
+    column: usize,
+    /// The byte offsets of the message's ID in its file.
+    byte_start: usize,
+    byte_end: usize,
+}
+
+#[pymethods]
+impl MessageDiff {
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!(
+            "MessageDiff(message_id={}, value_changed={}, attributes={})",
+            python_repr(py, &self.message_id)?,
+            python_repr(py, self.value_changed)?,
+            python_repr(py, &self.attributes)?
+        ))
+    }
+}
+
+/// A message or term defined in a resource.
+struct Definition<'r> {
+    id: String,
+    name: &'r str,
+    value: Option<&'r ast::Pattern<&'r str>>,
+    attributes: &'r [ast::Attribute<&'r str>],
+}
+
+impl<'r> Definition<'r> {
+    fn new(entry: &'r ast::Entry<&'r str>) -> Option<Self> {
+        match entry {
+            ast::Entry::Message(message) => Some(Self {
+                id: message.id.name.to_string(),
+                name: message.id.name,
+                value: message.value.as_ref(),
+                attributes: &message.attributes,
+            }),
+            ast::Entry::Term(term) => Some(Self {
+                id: format!("-{}", term.id.name),
+                name: term.id.name,
+                value: Some(&term.value),
+                attributes: &term.attributes,
+            }),
+            _ => None,
+        }
+    }
+
+    fn attribute(&self, name: &str) -> Option<&'r ast::Pattern<&'r str>> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.id.name == name)
+            .map(|attribute| &attribute.value)
+    }
+
+    /// Describe the definition as a difference, located in the resource it's defined in.
+    fn diff(
+        &self,
+        loaded: &LoadedResource,
+        value_changed: bool,
+        attributes: Vec<String>,
+    ) -> MessageDiff {
+        let range = loaded.source_range(self.name, self.name).unwrap_or(0..0);
+        let (line, column) = loaded.line_and_column(range.start).unwrap_or((1, 1));
+        MessageDiff {
+            message_id: self.id.clone(),
+            value_changed,
+            attributes,
+            filename: loaded.name.clone(),
+            line,
+            column,
+            byte_start: range.start,
+            byte_end: range.end,
+        }
+    }
+
+    /// Describe the definition as added or removed as a whole.
+    fn diff_all(&self, loaded: &LoadedResource) -> MessageDiff {
+        let attributes = self
+            .attributes
+            .iter()
+            .map(|attribute| attribute.id.name.to_string())
+            .collect();
+        self.diff(loaded, self.value.is_some(), attributes)
+    }
+}
+
+/// Return the messages and terms of a resource, in order. Only the last definition of those
+/// defined more than once is kept, as it's the one that's used.
+fn definitions(loaded: &LoadedResource) -> Vec<Definition<'_>> {
+    let mut definitions: Vec<Definition<'_>> = vec![];
+    let mut indexes = HashMap::new();
+    for definition in loaded.resource.entries().filter_map(Definition::new) {
+        match indexes.get(&definition.id) {
+            Some(&index) => definitions[index] = definition,
+            None => {
+                indexes.insert(definition.id.clone(), definitions.len());
+                definitions.push(definition);
+            }
+        }
+    }
+    definitions
+}
+
+/// Compare the messages and terms of two versions of an FTL file. Formatting and comments
+/// aren't differences, only the values and attributes of the messages.
+pub(crate) fn diff(old: &LoadedResource, new: &LoadedResource) -> ResourceDiff {
+    let old_definitions = definitions(old);
+    let new_definitions = definitions(new);
+    let old_by_id: HashMap<&str, &Definition<'_>> = old_definitions
+        .iter()
+        .map(|definition| (definition.id.as_str(), definition))
+        .collect();
+    let new_by_id: HashMap<&str, &Definition<'_>> = new_definitions
+        .iter()
+        .map(|definition| (definition.id.as_str(), definition))
+        .collect();
+
+    let mut added = vec![];
+    let mut changed = vec![];
+    for definition in new_definitions.iter() {
+        let Some(old_definition) = old_by_id.get(definition.id.as_str()) else {
+            added.push(definition.diff_all(new));
+            continue;
+        };
+        let value_changed = old_definition.value != definition.value;
+        // The attributes in the new version, then those only in the old one.
+        let names = definition
+            .attributes
+            .iter()
+            .chain(
+                old_definition
+                    .attributes
+                    .iter()
+                    .filter(|attribute| definition.attribute(attribute.id.name).is_none()),
+            )
+            .map(|attribute| attribute.id.name);
+        let attributes: Vec<String> = names
+            .filter(|name| old_definition.attribute(name) != definition.attribute(name))
+            .map(str::to_string)
+            .collect();
+        if value_changed || !attributes.is_empty() {
+            changed.push(definition.diff(new, value_changed, attributes));
+        }
+    }
+    let removed = old_definitions
+        .iter()
+        .filter(|definition| !new_by_id.contains_key(definition.id.as_str()))
+        .map(|definition| definition.diff_all(old))
+        .collect();
+    ResourceDiff {
+        added,
+        removed,
+        changed,
+    }
+}
@@ -0,0 +1,119 @@
+use crate::dates::FluentDateTime;
+use crate::lists::FluentList;
+use crate::numbers::Number;
+use crate::variables::{date_value, decimal_value, int_value};
+use fluent::{FluentArgs, FluentValue};
+use pyo3::IntoPyObjectExt;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyFloat, PyInt, PyString, PyTuple};
+use std::cell::RefCell;
+
+thread_local! {
+    /// The first exception raised by a Python function while formatting a message.
+    ///
+    /// Fluent functions can't fail, so the exception is stashed here and raised
+    /// once formatting has finished.
+    static PENDING_ERROR: RefCell<Option<PyErr>> = const { RefCell::new(None) };
+}
+
+/// Take the exception raised by a Python function during formatting, if any.
+pub(crate) fn take_function_error() -> Option<PyErr> {
+    PENDING_ERROR.with(|pending| pending.borrow_mut().take())
+}
+
+/// Whether `name` is a valid Fluent function name, e.g. `PLATFORM`.
+pub(crate) fn is_function_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|first| first.is_ascii_uppercase())
+        && chars.all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_' || c == '-')
+}
+
+/// Wrap a Python callable so it can be called as a Fluent function.
+///
+/// Positional arguments are passed positionally and named arguments as keyword arguments.
+pub(crate) fn python_function(
+    function: Py<PyAny>,
+) -> impl for<'a> Fn(&[FluentValue<'a>], &FluentArgs) -> FluentValue<'a> + Send + Sync + 'static {
+    move |positional, named| {
+        Python::attach(
+            |py| match call_python_function(function.bind(py), positional, named) {
+                Ok(value) => value,
+                Err(error) => {
+                    PENDING_ERROR.with(|pending| {
+                        pending.borrow_mut().get_or_insert(error);
+                    });
+                    FluentValue::Error
+                }
+            },
+        )
+    }
+}
+
+fn call_python_function<'a>(
+    function: &Bound<'_, PyAny>,
+    positional: &[FluentValue<'_>],
+    named: &FluentArgs,
+) -> PyResult<FluentValue<'a>> {
+    let py = function.py();
+    let args = positional
+        .iter()
+        .map(|value| to_python(py, value))
+        .collect::<PyResult<Vec<_>>>()?;
+    let kwargs = PyDict::new(py);
+    for (name, value) in named.iter() {
+        kwargs.set_item(name, to_python(py, value)?)?;
+    }
+    let result = function.call(PyTuple::new(py, args)?, Some(&kwargs))?;
+    from_python(&result)
+}
+
+/// Convert a Fluent value into the equivalent Python object.
+fn to_python<'py>(py: Python<'py>, value: &FluentValue<'_>) -> PyResult<Bound<'py, PyAny>> {
+    match value {
+        FluentValue::String(string) => string.as_ref().into_bound_py_any(py),
+        FluentValue::Number(number) => {
+            let has_fraction = number.value.fract() != 0.0
+                || number
+                    .options
+                    .minimum_fraction_digits
+                    .is_some_and(|digits| digits > 0);
+            if has_fraction || !number.value.is_finite() {
+                number.value.into_bound_py_any(py)
+            } else {
+                (number.value as i64).into_bound_py_any(py)
+            }
+        }
+        FluentValue::Custom(custom) => {
+            if let Some(list) = custom.as_any().downcast_ref::<FluentList>() {
+                return list.items.clone().into_bound_py_any(py);
+            }
+            match custom.as_any().downcast_ref::<FluentDateTime>() {
+                Some(datetime) if datetime.has_time => datetime.value.into_bound_py_any(py),
+                Some(datetime) => datetime.value.date().into_bound_py_any(py),
+                None => Ok(py.None().into_bound(py)),
+            }
+        }
+        FluentValue::None | FluentValue::Error => Ok(py.None().into_bound(py)),
+    }
+}
+
+/// Convert the return value of a Python function into a Fluent value.
+fn from_python<'a>(value: &Bound<'_, PyAny>) -> PyResult<FluentValue<'a>> {
+    if value.is_none() {
+        Ok(FluentValue::None)
+    } else if value.is_instance_of::<PyString>() {
+        Ok(value.to_string().into())
+    } else if value.is_instance_of::<PyInt>() {
+        Ok(int_value(value))
+    } else if value.is_instance_of::<PyFloat>() {
+        Ok(value.extract::<f64>()?.into())
+    } else if let Some(number) = decimal_value(value)? {
+        Ok(number)
+    } else if let Ok(number) = value.cast::<Number>() {
+        Ok(number.get().fluent_number().into())
+    } else if let Some(date) = date_value(value)? {
+        Ok(date)
+    } else {
+        Ok(value.str()?.to_string().into())
+    }
+}
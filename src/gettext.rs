@@ -0,0 +1,551 @@
+use crate::interchange::{Definitions, Piece, ftl_pattern, pattern_source, pieces_pattern, units};
+use crate::introspection::{LoadedResource, PLURAL_CATEGORIES};
+use crate::rewrite::is_identifier;
+use crate::validation::plural_categories;
+use fluent_syntax::ast;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::fmt::Write;
+use unic_langid::LanguageIdentifier;
+
+/// The variants of a pattern that is a single select expression on a number, with a variant
+/// for each plural category, e.g. `{ $count -> [one] … *[other] … }`.
+struct PluralVariants<'r> {
+    variants: Vec<(&'r str, &'r ast::Pattern<&'r str>)>,
+    default: &'r ast::Pattern<&'r str>,
+}
+
+impl<'r> PluralVariants<'r> {
+    fn new(pattern: &'r ast::Pattern<&'r str>) -> Option<Self> {
+        let [ast::PatternElement::Placeable { expression }] = pattern.elements.as_slice() else {
+            return None;
+        };
+        let ast::Expression::Select { selector, variants } = expression else {
+            return None;
+        };
+        let on_number = match selector {
+            ast::InlineExpression::VariableReference { .. } => true,
+            ast::InlineExpression::FunctionReference { id, arguments } => {
+                id.name == "NUMBER"
+                    && matches!(
+                        arguments.positional.first(),
+                        Some(ast::InlineExpression::VariableReference { .. })
+                    )
+            }
+            _ => false,
+        };
+        if !on_number {
+            return None;
+        }
+        let mut plural_variants = vec![];
+        let mut default = None;
+        for variant in variants {
+            let ast::VariantKey::Identifier { name } = variant.key else {
+                return None;
+            };
+            if !PLURAL_CATEGORIES.contains(&name) {
+                return None;
+            }
+            plural_variants.push((name, &variant.value));
+            if variant.default {
+                default = Some(&variant.value);
+            }
+        }
+        Some(Self {
+            variants: plural_variants,
+            default: default?,
+        })
+    }
+
+    /// Return the variant for a plural category, or the default one.
+    fn get(&self, category: &str) -> &'r ast::Pattern<&'r str> {
+        self.variants
+            .iter()
+            .find(|(name, _)| *name == category)
+            .map_or(self.default, |(_, value)| value)
+    }
+}
+
+/// Quote a string for a PO file, splitting it after each newline as gettext does.
+fn write_string(po: &mut String, keyword: &str, text: &str) {
+    let escape = |text: &str| {
+        text.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\t', "\\t")
+            .replace('\r', "\\r")
+            .replace('\n', "\\n")
+    };
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    if lines.len() <= 1 {
+        let _ = writeln!(po, "{keyword} \"{}\"", escape(text));
+    } else {
+        let _ = writeln!(po, "{keyword} \"\"");
+        for line in lines {
+            let _ = writeln!(po, "\"{}\"", escape(line));
+        }
+    }
+}
+
+/// Export the messages, terms and attributes of an FTL resource as a PO file, with each
+/// one's ID as its `msgctxt` and its FTL source as its `msgid`. Plural selectors are exported
+/// as gettext plurals, with a `msgstr[n]` for each of the locale's plural categories in
+/// order, or English's without a locale.
+///
+/// Without a reference, the PO file is a template with empty translations. With one, the
+/// `msgid`s are the reference's and the translations are the resource's.
+pub(crate) fn ftl_to_po(
+    loaded: &LoadedResource,
+    reference: Option<&LoadedResource>,
+    locale: Option<&str>,
+) -> PyResult<String> {
+    let language = locale.map(parse_locale).transpose()?;
+    let categories = plural_categories(language.as_ref().unwrap_or(&english()));
+    let source = reference.unwrap_or(loaded);
+    let source_ast = source.full_ast();
+    let translation_ast = reference.map(|_| loaded.full_ast());
+    let translations: HashMap<String, &ast::Pattern<&str>> = translation_ast
+        .as_ref()
+        .map(|resource| {
+            units(resource)
+                .into_iter()
+                .map(|unit| (unit.context, unit.value))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut po = String::new();
+    write_string(&mut po, "msgid", "");
+    let mut header = "Content-Type: text/plain; charset=UTF-8\n".to_string();
+    if let Some(language) = &language {
+        let _ = writeln!(header, "Language: {language}");
+    }
+    write_string(&mut po, "msgstr", &header);
+
+    for unit in units(&source_ast) {
+        po.push('\n');
+        for line in unit.comment.iter().flat_map(|comment| comment.lines()) {
+            let _ = writeln!(po, "#. {line}");
+        }
+        if let Some(range) = source.source_range(unit.name, unit.name)
+            && let Some((line, _)) = source.line_and_column(range.start)
+        {
+            let _ = writeln!(po, "#: {}:{line}", source.name);
+        }
+        write_string(&mut po, "msgctxt", &unit.context);
+        let translation = translations.get(&unit.context).copied();
+        match PluralVariants::new(unit.value) {
+            Some(variants) => {
+                write_string(&mut po, "msgid", &pattern_source(variants.get("one")));
+                write_string(
+                    &mut po,
+                    "msgid_plural",
+                    &pattern_source(variants.get("other")),
+                );
+                let translated = translation.map(|pattern| (PluralVariants::new(pattern), pattern));
+                for (index, category) in categories.iter().enumerate() {
+                    let text = match &translated {
+                        Some((Some(variants), _)) => pattern_source(variants.get(category)),
+                        Some((None, pattern)) => pattern_source(pattern),
+                        None => String::new(),
+                    };
+                    write_string(&mut po, &format!("msgstr[{index}]"), &text);
+                }
+            }
+            None => {
+                write_string(&mut po, "msgid", &pattern_source(unit.value));
+                let text = translation.map(pattern_source).unwrap_or_default();
+                write_string(&mut po, "msgstr", &text);
+            }
+        }
+    }
+    Ok(po)
+}
+
+/// An entry of a PO file.
+#[derive(Default)]
+struct PoEntry {
+    /// The translator and extracted comments.
+    comments: Vec<String>,
+    flags: Vec<String>,
+    context: Option<String>,
+    id: String,
+    id_plural: Option<String>,
+    /// The `msgstr`, or the `msgstr[n]` of a plural.
+    strings: Vec<String>,
+}
+
+impl PoEntry {
+    fn has_flag(&self, flag: &str) -> bool {
+        self.flags.iter().any(|other| other == flag)
+    }
+}
+
+/// Which string of a PO entry a line of it continues.
+#[derive(Clone, Copy)]
+enum Field {
+    Context,
+    Id,
+    IdPlural,
+    String(usize),
+}
+
+/// Parse a quoted string of a PO file, with its C escapes.
+fn unquote(quoted: &str) -> Option<String> {
+    let inner = quoted.strip_prefix('"')?.strip_suffix('"')?;
+    let mut text = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => text.push('\n'),
+            't' => text.push('\t'),
+            'r' => text.push('\r'),
+            other => text.push(other),
+        }
+    }
+    Some(text)
+}
+
+/// Parse a PO file into its entries. Obsolete entries (`#~`) are left out.
+fn parse_po(name: &str, source: &str) -> PyResult<Vec<PoEntry>> {
+    let mut entries = vec![];
+    let mut entry = PoEntry::default();
+    let mut field = None;
+    for (index, line) in source.lines().enumerate() {
+        let line = line.trim();
+        let invalid = |problem: &str| {
+            PyValueError::new_err(format!(
+                "Invalid PO file {name}, line {}: {problem}",
+                index + 1
+            ))
+        };
+        if line.is_empty() || line.starts_with("#~") || line.starts_with("#|") {
+            continue;
+        }
+        let starts_entry =
+            line.starts_with('#') || line.starts_with("msgctxt ") || line.starts_with("msgid ");
+        if starts_entry && !entry.strings.is_empty() {
+            entries.push(std::mem::take(&mut entry));
+            field = None;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            if let Some(flags) = comment.strip_prefix(',') {
+                entry
+                    .flags
+                    .extend(flags.split(',').map(|flag| flag.trim().to_string()));
+            } else if !comment.starts_with(':') {
+                let comment = comment.strip_prefix('.').unwrap_or(comment);
+                entry
+                    .comments
+                    .push(comment.strip_prefix(' ').unwrap_or(comment).to_string());
+            }
+            continue;
+        }
+        let (keyword, quoted) = match line.split_once(char::is_whitespace) {
+            Some((keyword, quoted)) if !line.starts_with('"') => (keyword, quoted.trim()),
+            _ => ("", line),
+        };
+        let text = unquote(quoted).ok_or_else(|| invalid("expected a quoted string"))?;
+        let next_field = match keyword {
+            "" => field.ok_or_else(|| invalid("expected a keyword"))?,
+            "msgctxt" => Field::Context,
+            "msgid" => Field::Id,
+            "msgid_plural" => Field::IdPlural,
+            "msgstr" => Field::String(0),
+            _ => keyword
+                .strip_prefix("msgstr[")
+                .and_then(|index| index.strip_suffix(']'))
+                .and_then(|index| index.parse().ok())
+                .map(Field::String)
+                .ok_or_else(|| invalid(&format!("unknown keyword '{keyword}'")))?,
+        };
+        let string = match next_field {
+            Field::Context => entry.context.get_or_insert_default(),
+            Field::Id => &mut entry.id,
+            Field::IdPlural => entry.id_plural.get_or_insert_default(),
+            Field::String(index) => {
+                if entry.strings.len() <= index {
+                    entry.strings.resize(index + 1, String::new());
+                }
+                &mut entry.strings[index]
+            }
+        };
+        string.push_str(&text);
+        field = Some(next_field);
+    }
+    if !entry.strings.is_empty() {
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Split a string with `python-format` (or `c-format`) placeholders into pieces, e.g.
+/// `%(name)s`. Placeholders without a name, like `%d`, are the `count` variable if one is
+/// given, as in plurals.
+fn percent_pieces(text: &str, count: Option<&str>) -> Vec<Piece> {
+    let mut pieces = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find('%') {
+        pieces.push(Piece::Text(rest[..start].to_string()));
+        let placeholder = &rest[start + 1..];
+        if let Some(after) = placeholder.strip_prefix('%') {
+            pieces.push(Piece::Text("%".to_string()));
+            rest = after;
+            continue;
+        }
+        let (name, spec) = match placeholder
+            .strip_prefix('(')
+            .and_then(|named| named.split_once(')'))
+        {
+            Some((name, spec)) => (Some(name), spec),
+            None => (None, placeholder),
+        };
+        let flags = spec.len()
+            - spec
+                .trim_start_matches(|c: char| "#0- +.*".contains(c) || c.is_ascii_digit())
+                .len();
+        let conversion = spec[flags..].chars().next();
+        let variable = match (name, conversion) {
+            (_, None) => None,
+            (_, Some(conversion)) if !"diouxXeEfFgGcrsa".contains(conversion) => None,
+            (Some(name), _) => Some(name),
+            (None, _) => count,
+        };
+        match (variable, conversion) {
+            (Some(variable), Some(conversion)) if is_identifier(variable) => {
+                pieces.push(Piece::Variable(variable.to_string()));
+                rest = &spec[flags + conversion.len_utf8()..];
+            }
+            _ => {
+                pieces.push(Piece::Text("%".to_string()));
+                rest = placeholder;
+            }
+        }
+    }
+    pieces.push(Piece::Text(rest.to_string()));
+    pieces
+}
+
+/// Split a string with `python-brace-format` placeholders into pieces, e.g. `{name}`.
+fn brace_pieces(text: &str) -> Vec<Piece> {
+    let mut pieces = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find(['{', '}']) {
+        pieces.push(Piece::Text(rest[..start].to_string()));
+        let brace = &rest[start..start + 1];
+        let after = &rest[start + 1..];
+        if let Some(after) = after.strip_prefix(brace) {
+            pieces.push(Piece::Text(brace.to_string()));
+            rest = after;
+            continue;
+        }
+        let placeholder = (brace == "{")
+            .then(|| after.split_once('}'))
+            .flatten()
+            .map(|(field, after)| (field.split([':', '!']).next().unwrap_or(field), after))
+            .filter(|(name, _)| is_identifier(name));
+        match placeholder {
+            Some((name, after)) => {
+                pieces.push(Piece::Variable(name.to_string()));
+                rest = after;
+            }
+            None => {
+                pieces.push(Piece::Text(brace.to_string()));
+                rest = after;
+            }
+        }
+    }
+    pieces.push(Piece::Text(rest.to_string()));
+    pieces
+}
+
+/// How the placeholders of a PO entry's strings are written, from its flags.
+#[derive(Clone, Copy)]
+enum Placeholders {
+    Percent,
+    Brace,
+    /// FTL syntax, as exported by `ftl_to_po`, or plain text.
+    Fluent,
+}
+
+impl Placeholders {
+    fn new(entry: &PoEntry) -> Self {
+        if entry.has_flag("python-format") || entry.has_flag("c-format") {
+            Self::Percent
+        } else if entry.has_flag("python-brace-format") {
+            Self::Brace
+        } else {
+            Self::Fluent
+        }
+    }
+
+    fn pattern(self, text: &str, count: Option<&str>) -> ast::Pattern<String> {
+        match self {
+            Self::Percent => pieces_pattern(percent_pieces(text, count)),
+            Self::Brace => pieces_pattern(brace_pieces(text)),
+            Self::Fluent => ftl_pattern(text)
+                .unwrap_or_else(|| pieces_pattern(vec![Piece::Text(text.to_string())])),
+        }
+    }
+
+    /// Return the text of a string without its placeholders.
+    fn text(self, text: &str) -> String {
+        self.pattern(text, Some("count"))
+            .elements
+            .into_iter()
+            .filter_map(|element| match element {
+                ast::PatternElement::TextElement { value } => Some(value),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Return the name of the first variable in a pattern, e.g. `count` in `{ $count } items`.
+fn first_variable(pattern: &ast::Pattern<String>) -> Option<String> {
+    pattern.elements.iter().find_map(|element| {
+        let ast::PatternElement::Placeable {
+            expression: ast::Expression::Inline(expression),
+        } = element
+        else {
+            return None;
+        };
+        match expression {
+            ast::InlineExpression::VariableReference { id } => Some(id.name.clone()),
+            ast::InlineExpression::FunctionReference { arguments, .. } => {
+                match arguments.positional.first() {
+                    Some(ast::InlineExpression::VariableReference { id }) => Some(id.name.clone()),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    })
+}
+
+/// Make the pattern of a gettext plural: a select expression on the count, with a variant
+/// for each plural category that has a translation. The count is the first variable of the
+/// last form, or `$count`.
+fn plural_pattern(
+    forms: &[String],
+    categories: &[&str],
+    placeholders: Placeholders,
+) -> ast::Pattern<String> {
+    let count = forms
+        .last()
+        .and_then(|form| first_variable(&placeholders.pattern(form, None)))
+        .unwrap_or_else(|| "count".to_string());
+    let mut variants: Vec<ast::Variant<String>> = categories
+        .iter()
+        .zip(forms)
+        .filter(|(_, form)| !form.is_empty())
+        .map(|(category, form)| ast::Variant {
+            key: ast::VariantKey::Identifier {
+                name: category.to_string(),
+            },
+            value: placeholders.pattern(form, Some(&count)),
+            default: false,
+        })
+        .collect();
+    let default = variants
+        .iter()
+        .position(|variant| matches!(&variant.key, ast::VariantKey::Identifier { name } if name == "other"))
+        .unwrap_or(variants.len().saturating_sub(1));
+    // Forms beyond the language's plural categories are left out, so if only those are
+    // translated, the last of them is used for every number.
+    if variants.is_empty()
+        && let Some(form) = forms.iter().rfind(|form| !form.is_empty())
+    {
+        return placeholders.pattern(form, Some(&count));
+    }
+    if variants.len() == 1 {
+        return variants.remove(0).value;
+    }
+    if let Some(variant) = variants.get_mut(default) {
+        variant.default = true;
+    }
+    ast::Pattern {
+        elements: vec![ast::PatternElement::Placeable {
+            expression: ast::Expression::Select {
+                selector: ast::InlineExpression::VariableReference {
+                    id: ast::Identifier { name: count },
+                },
+                variants,
+            },
+        }],
+    }
+}
+
+/// The language of a PO file, from the `Language` field of its header.
+fn header_language(entries: &[PoEntry]) -> Option<LanguageIdentifier> {
+    entries
+        .iter()
+        .find(|entry| entry.context.is_none() && entry.id.is_empty())?
+        .strings
+        .first()?
+        .lines()
+        .find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            (name.trim() == "Language").then(|| value.trim().parse().ok())?
+        })
+}
+
+fn english() -> LanguageIdentifier {
+    "en".parse().expect("valid language")
+}
+
+/// Parse the locale given for a conversion.
+fn parse_locale(locale: &str) -> PyResult<LanguageIdentifier> {
+    locale
+        .parse()
+        .map_err(|_| PyValueError::new_err(format!("Invalid language: '{locale}'")))
+}
+
+/// Convert a PO file to FTL. Each entry's `msgctxt` is used as its message ID (or term ID, if
+/// it starts with `-`), with an attribute after a `.`, as exported by `ftl_to_po`; entries
+/// without one get an ID made from their `msgid`. The `msgstr[n]` of plurals are the
+/// variants for the plural categories of the locale, or the file's `Language`, in order.
+///
+/// Untranslated and fuzzy entries are left out, unless `include_untranslated` is set, in
+/// which case their `msgid` (and `msgid_plural`, as the `one` and `other` variants) is used.
+pub(crate) fn po_to_ftl(
+    name: &str,
+    source: &str,
+    locale: Option<&str>,
+    include_untranslated: bool,
+) -> PyResult<String> {
+    let entries = parse_po(name, source)?;
+    let language = match locale {
+        Some(locale) => parse_locale(locale)?,
+        None => header_language(&entries).unwrap_or_else(english),
+    };
+    let categories = plural_categories(&language);
+    let mut definitions = Definitions::default();
+    for entry in entries {
+        if entry.context.is_none() && entry.id.is_empty() {
+            continue;
+        }
+        let translated =
+            !entry.has_flag("fuzzy") && entry.strings.iter().any(|string| !string.is_empty());
+        let placeholders = Placeholders::new(&entry);
+        let value = match (&entry.id_plural, translated) {
+            (_, false) if !include_untranslated => continue,
+            (Some(_), true) => plural_pattern(&entry.strings, &categories, placeholders),
+            (Some(id_plural), false) => plural_pattern(
+                &[entry.id.clone(), id_plural.clone()],
+                &["one", "other"],
+                placeholders,
+            ),
+            (None, true) => placeholders.pattern(&entry.strings[0], None),
+            (None, false) => placeholders.pattern(&entry.id, None),
+        };
+
+        let text = placeholders.text(entry.context.as_deref().unwrap_or(&entry.id));
+        definitions.add(entry.context.as_deref(), &text, entry.comments, value);
+    }
+    Ok(definitions.serialize())
+}
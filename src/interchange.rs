@@ -0,0 +1,344 @@
+use crate::rewrite::{empty_pattern, is_identifier, parse_all};
+use fluent_syntax::ast;
+use fluent_syntax::serializer::{Options, serialize_with_options};
+use std::collections::HashMap;
+
+/// A message, term or attribute exported as a unit to be translated, such as a PO entry.
+pub(crate) struct Unit<'r> {
+    /// The unit's ID, e.g. a PO entry's `msgctxt`: the ID of the message, or of the term with
+    /// its leading `-`, and the attribute's name after a `.`.
+    pub context: String,
+    /// The comment of the message or term, on its value or first attribute.
+    pub comment: Option<String>,
+    /// The ID in the source, to locate the entry.
+    pub name: &'r str,
+    pub value: &'r ast::Pattern<&'r str>,
+}
+
+/// Return the values and attributes of the messages and terms of a resource, in order. Only
+/// the last definition of those defined more than once is kept, as it's the one that's used.
+pub(crate) fn units<'r>(resource: &'r ast::Resource<&'r str>) -> Vec<Unit<'r>> {
+    let mut entries: Vec<(String, &ast::Entry<&str>)> = vec![];
+    let mut indexes = HashMap::new();
+    for entry in resource.body.iter() {
+        let id = match entry {
+            ast::Entry::Message(message) => message.id.name.to_string(),
+            ast::Entry::Term(term) => format!("-{}", term.id.name),
+            _ => continue,
+        };
+        match indexes.get(&id) {
+            Some(&index) => entries[index] = (id, entry),
+            None => {
+                indexes.insert(id.clone(), entries.len());
+                entries.push((id, entry));
+            }
+        }
+    }
+
+    let mut units = vec![];
+    for (id, entry) in entries {
+        let (name, value, attributes, comment) = match entry {
+            ast::Entry::Message(message) => (
+                message.id.name,
+                message.value.as_ref(),
+                &message.attributes,
+                &message.comment,
+            ),
+            ast::Entry::Term(term) => (
+                term.id.name,
+                Some(&term.value),
+                &term.attributes,
+                &term.comment,
+            ),
+            _ => continue,
+        };
+        let mut comment = comment.as_ref().map(|comment| comment.content.join("\n"));
+        if let Some(value) = value {
+            units.push(Unit {
+                context: id.clone(),
+                comment: comment.take(),
+                name,
+                value,
+            });
+        }
+        for attribute in attributes {
+            units.push(Unit {
+                context: format!("{id}.{}", attribute.id.name),
+                comment: comment.take(),
+                name: attribute.id.name,
+                value: &attribute.value,
+            });
+        }
+    }
+    units
+}
+
+/// Return the FTL source of a pattern, without the indentation of its lines, e.g.
+/// `Hello, { $name }!`.
+pub(crate) fn pattern_source(pattern: &ast::Pattern<&str>) -> String {
+    let resource = ast::Resource {
+        body: vec![ast::Entry::Message(ast::Message {
+            id: ast::Identifier { name: "x" },
+            value: Some(pattern.clone()),
+            attributes: vec![],
+            comment: None,
+        })],
+    };
+    let serialized = serialize_with_options(&resource, Options { with_junk: false });
+    let value = serialized.strip_prefix("x =").unwrap_or(&serialized);
+    let value = value
+        .strip_prefix(' ')
+        .or_else(|| value.strip_prefix('\n'))
+        .unwrap_or(value);
+    let lines: Vec<&str> = value
+        .strip_suffix('\n')
+        .unwrap_or(value)
+        .split('\n')
+        .map(|line| {
+            let spaces = line.len() - line.trim_start_matches(' ').len();
+            &line[spaces.min(4)..]
+        })
+        .collect();
+    lines.join("\n")
+}
+
+/// Write a pattern as an i18next string, with `{{name}}` placeholders for its variables, and
+/// the default variant of its select expressions. Other placeables are formatted with `format`.
+pub(crate) fn i18next_string(
+    pattern: &ast::Pattern<&str>,
+    format: &mut impl FnMut(&ast::Pattern<&str>) -> String,
+) -> String {
+    let mut string = String::new();
+    for element in &pattern.elements {
+        match element {
+            ast::PatternElement::TextElement { value } => string.push_str(value),
+            ast::PatternElement::Placeable { expression } => {
+                string.push_str(&i18next_expression(expression, format));
+            }
+        }
+    }
+    string
+}
+
+fn i18next_expression(
+    expression: &ast::Expression<&str>,
+    format: &mut impl FnMut(&ast::Pattern<&str>) -> String,
+) -> String {
+    let expression = match expression {
+        ast::Expression::Select { variants, .. } => {
+            return variants
+                .iter()
+                .find(|variant| variant.default)
+                .map(|variant| i18next_string(&variant.value, format))
+                .unwrap_or_default();
+        }
+        ast::Expression::Inline(expression) => expression,
+    };
+    match expression {
+        ast::InlineExpression::VariableReference { id } => format!("{{{{{}}}}}", id.name),
+        // Functions such as `NUMBER($count)` format their variable in i18next.
+        ast::InlineExpression::FunctionReference { arguments, .. }
+            if let Some(ast::InlineExpression::VariableReference { id }) =
+                arguments.positional.first() =>
+        {
+            format!("{{{{{}}}}}", id.name)
+        }
+        ast::InlineExpression::Placeable { expression } => i18next_expression(expression, format),
+        _ => format(&ast::Pattern {
+            elements: vec![ast::PatternElement::Placeable {
+                expression: ast::Expression::Inline(expression.clone()),
+            }],
+        }),
+    }
+}
+
+/// A piece of a translated string: text, or a placeholder for a variable.
+pub(crate) enum Piece {
+    Text(String),
+    Variable(String),
+}
+
+/// Make a pattern from pieces of text and variables. Braces in the text are string literals,
+/// as they'd otherwise start or end placeables.
+pub(crate) fn pieces_pattern(pieces: Vec<Piece>) -> ast::Pattern<String> {
+    let mut elements = vec![];
+    let mut text = String::new();
+    let flush = |text: &mut String, elements: &mut Vec<ast::PatternElement<String>>| {
+        if !text.is_empty() {
+            elements.push(ast::PatternElement::TextElement {
+                value: std::mem::take(text),
+            });
+        }
+    };
+    for piece in pieces {
+        let expression = match piece {
+            Piece::Text(piece) => {
+                for c in piece.chars() {
+                    if c == '{' || c == '}' {
+                        flush(&mut text, &mut elements);
+                        elements.push(ast::PatternElement::Placeable {
+                            expression: ast::Expression::Inline(
+                                ast::InlineExpression::StringLiteral {
+                                    value: c.to_string(),
+                                },
+                            ),
+                        });
+                    } else {
+                        text.push(c);
+                    }
+                }
+                continue;
+            }
+            Piece::Variable(name) => ast::InlineExpression::VariableReference {
+                id: ast::Identifier { name },
+            },
+        };
+        flush(&mut text, &mut elements);
+        elements.push(ast::PatternElement::Placeable {
+            expression: ast::Expression::Inline(expression),
+        });
+    }
+    flush(&mut text, &mut elements);
+    if elements.is_empty() {
+        return empty_pattern();
+    }
+    ast::Pattern { elements }
+}
+
+/// Parse a string as the FTL source of a pattern, as exported by `units`, or `None` if
+/// it isn't valid FTL.
+pub(crate) fn ftl_pattern(text: &str) -> Option<ast::Pattern<String>> {
+    let resource = parse_all(format!("x = {}\n", text.replace('\n', "\n    ")));
+    match <[_; 1]>::try_from(resource.body) {
+        Ok([ast::Entry::Message(message)]) if message.attributes.is_empty() => message.value,
+        _ => None,
+    }
+}
+
+/// Make a message ID from the text of a translated message, without its placeholders, e.g.
+/// `hello-world` for `Hello, world!`.
+fn generated_id(text: &str) -> String {
+    let mut id = String::new();
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if id.len() >= 40 {
+            break;
+        }
+        if c.is_ascii_alphanumeric() {
+            id.push(c);
+        } else if !id.is_empty() && !id.ends_with('-') {
+            id.push('-');
+        }
+    }
+    let id = id.trim_end_matches('-');
+    if id.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        id.to_string()
+    } else if id.is_empty() {
+        "message".to_string()
+    } else {
+        format!("message-{id}")
+    }
+}
+
+/// A message or term being imported.
+struct Definition {
+    id: String,
+    comments: Vec<String>,
+    value: Option<ast::Pattern<String>>,
+    attributes: Vec<ast::Attribute<String>>,
+}
+
+/// The messages and terms imported from the units of a translation tool's file, in order.
+#[derive(Default)]
+pub(crate) struct Definitions {
+    definitions: Vec<Definition>,
+    indexes: HashMap<String, usize>,
+}
+
+impl Definitions {
+    /// Add the value or attribute of a message or term from its unit's ID, as in `Unit`.
+    /// Units without a valid ID get one made from `text`.
+    pub(crate) fn add(
+        &mut self,
+        context: Option<&str>,
+        text: &str,
+        comments: Vec<String>,
+        value: ast::Pattern<String>,
+    ) {
+        let (id, attribute) = match context.unwrap_or_default().split_once('.') {
+            Some((id, attribute)) => (id, Some(attribute)),
+            None => (context.unwrap_or_default(), None),
+        };
+        let valid_id = is_identifier(id.strip_prefix('-').unwrap_or(id));
+        let attribute = attribute.filter(|attribute| valid_id && is_identifier(attribute));
+        let mut id = if valid_id {
+            id.to_string()
+        } else {
+            generated_id(text)
+        };
+        // Messages can only be defined once, so values for the same ID get their own.
+        if attribute.is_none() {
+            let base = id.clone();
+            let mut number = 1;
+            while self
+                .indexes
+                .get(&id)
+                .is_some_and(|&index| self.definitions[index].value.is_some())
+            {
+                number += 1;
+                id = format!("{base}-{number}");
+            }
+        }
+        let index = *self.indexes.entry(id.clone()).or_insert_with(|| {
+            self.definitions.push(Definition {
+                id,
+                comments: vec![],
+                value: None,
+                attributes: vec![],
+            });
+            self.definitions.len() - 1
+        });
+        let definition = &mut self.definitions[index];
+        definition.comments.extend(comments);
+        match attribute {
+            Some(attribute) => definition.attributes.push(ast::Attribute {
+                id: ast::Identifier {
+                    name: attribute.to_string(),
+                },
+                value,
+            }),
+            None => definition.value = Some(value),
+        }
+    }
+
+    /// Serialize the messages and terms as FTL.
+    pub(crate) fn serialize(self) -> String {
+        let body = self
+            .definitions
+            .into_iter()
+            .map(|definition| {
+                let comment = (!definition.comments.is_empty()).then_some(ast::Comment {
+                    content: definition.comments,
+                });
+                match definition.id.strip_prefix('-') {
+                    Some(name) => ast::Entry::Term(ast::Term {
+                        id: ast::Identifier {
+                            name: name.to_string(),
+                        },
+                        value: definition.value.unwrap_or_else(empty_pattern),
+                        attributes: definition.attributes,
+                        comment,
+                    }),
+                    None => ast::Entry::Message(ast::Message {
+                        id: ast::Identifier {
+                            name: definition.id,
+                        },
+                        value: definition.value,
+                        attributes: definition.attributes,
+                        comment,
+                    }),
+                }
+            })
+            .collect();
+        serialize_with_options(&ast::Resource { body }, Options { with_junk: false })
+    }
+}
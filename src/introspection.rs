@@ -0,0 +1,783 @@
+use crate::dates::FluentDateTime;
+use crate::lists::FluentList;
+use crate::validation::{check_severity, error_code};
+use fluent_bundle::{FluentMessage, FluentResource, FluentValue};
+use fluent_syntax::ast;
+use fluent_syntax::parser::{ParserError, parse};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyType};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// An FTL resource loaded into a bundle, along with the name it was loaded from.
+pub(crate) struct LoadedResource {
+    pub name: String,
+    /// The FTL file the resource was loaded from, if it wasn't passed as a string.
+    pub path: Option<PathBuf>,
+    /// A hash of the FTL file's bytes when it was read.
+    pub file_hash: Option<u64>,
+    pub resource: Arc<FluentResource>,
+    /// Syntax errors in the source. The invalid entries are left out of the resource.
+    pub errors: Vec<ParserError>,
+}
+
+impl LoadedResource {
+    /// Return the line and column (in characters) of a byte offset in the source,
+    /// counting from 1.
+    pub fn line_and_column(&self, offset: usize) -> Option<(usize, usize)> {
+        let before = self.resource.source().get(..offset)?;
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+        Some((
+            before.matches('\n').count() + 1,
+            before[line_start..].chars().count() + 1,
+        ))
+    }
+
+    /// Return the byte range of the source from the start of one slice of it to the end of
+    /// another. The resource's AST is made of slices of its source, so this finds where AST
+    /// nodes are, which the AST doesn't record itself.
+    pub fn source_range(&self, start: &str, end: &str) -> Option<Range<usize>> {
+        let source = self.resource.source();
+        let offset = |slice: &str| {
+            (slice.as_ptr() as usize)
+                .checked_sub(source.as_ptr() as usize)
+                .filter(|offset| offset + slice.len() <= source.len())
+        };
+        Some(offset(start)?..offset(end)? + end.len())
+    }
+
+    /// Return the FTL source of a message or term, given its ID (with a term's leading `-`)
+    /// and the name from its AST, which is a slice of the source. The lines of an entry after
+    /// the first are indented, so it ends before the next line that isn't, other than blank
+    /// lines. Its comment isn't included.
+    pub fn entry_source(&self, id: &str, name: &str) -> Option<MessageSource> {
+        let source = self.resource.source();
+        let start = self
+            .source_range(name, name)?
+            .start
+            .checked_sub(id.len() - name.len())?;
+        let mut end = source.len();
+        let mut line_start = start;
+        while let Some(newline) = source[line_start..].find('\n') {
+            line_start += newline + 1;
+            let line = &source[line_start..];
+            if !line.starts_with([' ', '\r', '\n']) {
+                end = line_start;
+                break;
+            }
+        }
+        let end = start + source[start..end].trim_end().len();
+        let (line, column) = self.line_and_column(start)?;
+        Some(MessageSource {
+            id: id.to_string(),
+            source: source[start..end].to_string(),
+            filename: self.name.clone(),
+            line,
+            column,
+            byte_start: start,
+            byte_end: end,
+        })
+    }
+
+    /// Return the entries that the parser skipped because of syntax errors, with where they
+    /// are, the errors in them and the severity of syntax errors.
+    pub fn junk(&self, severity: &str) -> Vec<Junk> {
+        self.resource
+            .entries()
+            .filter_map(|entry| match entry {
+                ast::Entry::Junk { content } => Some(*content),
+                _ => None,
+            })
+            .filter_map(|content| {
+                let range = self.source_range(content, content)?;
+                let (line, column) = self.line_and_column(range.start)?;
+                let errors = self
+                    .errors
+                    .iter()
+                    .filter(|error| range.contains(&error.pos.start))
+                    .map(|error| error.kind.to_string())
+                    .collect();
+                Some(Junk {
+                    content: content.to_string(),
+                    filename: self.name.clone(),
+                    line,
+                    column,
+                    byte_start: range.start,
+                    byte_end: range.end,
+                    errors,
+                    severity: severity.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Parse the full AST of the resource, including the comments
+    /// that are dropped from the runtime AST.
+    pub fn full_ast(&self) -> ast::Resource<&str> {
+        match parse(self.resource.source()) {
+            Ok(resource) => resource,
+            Err((resource, _errors)) => resource,
+        }
+    }
+}
+
+/// Source that the parser skipped because of syntax errors, as returned by `Bundle.get_junk`.
+#[pyclass(frozen, get_all, eq, hash, module = "rustfluent")]
+#[derive(PartialEq, Eq, Hash)]
+pub(crate) struct Junk {
+    /// The skipped source.
+    content: String,
+    /// The name of the file the source is in.
+    filename: String,
+    /// Where the skipped source starts, counting from 1.
+    line: usize,
+    column: usize,
+    /// The byte offsets of the skipped source in its file.
+    byte_start: usize,
+    byte_end: usize,
+    /// Descriptions of the syntax errors in the skipped source.
+    errors: Vec<String>,
+    /// How serious the syntax errors are: `error` unless the bundle's `severity` for
+    /// `ParseError` says otherwise.
+    severity: String,
+}
+
+#[pymethods]
+impl Junk {
+    #[new]
+    #[pyo3(signature = (content, filename, line, column, byte_start, byte_end, errors=vec![], severity="error"))]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        content: String,
+        filename: String,
+        line: usize,
+        column: usize,
+        byte_start: usize,
+        byte_end: usize,
+        errors: Vec<String>,
+        severity: &str,
+    ) -> PyResult<Self> {
+        check_severity(severity)?;
+        Ok(Self {
+            content,
+            filename,
+            line,
+            column,
+            byte_start,
+            byte_end,
+            errors,
+            severity: severity.to_string(),
+        })
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn __reduce__<'py>(
+        slf: &Bound<'py, Self>,
+    ) -> (
+        Bound<'py, PyType>,
+        (
+            String,
+            String,
+            usize,
+            usize,
+            usize,
+            usize,
+            Vec<String>,
+            String,
+        ),
+    ) {
+        let this = slf.get();
+        (
+            slf.get_type(),
+            (
+                this.content.clone(),
+                this.filename.clone(),
+                this.line,
+                this.column,
+                this.byte_start,
+                this.byte_end,
+                this.errors.clone(),
+                this.severity.clone(),
+            ),
+        )
+    }
+
+    /// The stable code of syntax errors, `RF0017`.
+    #[getter]
+    fn code(&self) -> Option<&'static str> {
+        error_code("ParseError")
+    }
+
+    /// Return the junk's fields as a dict, e.g. to serialize as JSON.
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("content", &self.content)?;
+        dict.set_item("filename", &self.filename)?;
+        dict.set_item("line", self.line)?;
+        dict.set_item("column", self.column)?;
+        dict.set_item("byte_start", self.byte_start)?;
+        dict.set_item("byte_end", self.byte_end)?;
+        dict.set_item("errors", &self.errors)?;
+        dict.set_item("code", self.code())?;
+        dict.set_item("severity", &self.severity)?;
+        Ok(dict)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Junk(filename={:?}, line={}, content={:?})",
+            self.filename, self.line, self.content
+        )
+    }
+}
+
+/// The FTL source of a message or term, as returned by `Bundle.get_message_source`.
+#[pyclass(frozen, get_all, module = "rustfluent")]
+pub(crate) struct MessageSource {
+    /// The ID of the message, or of the term with its leading `-`.
+    id: String,
+    /// The source of the message or term, without its comment.
+    source: String,
+    /// The name of the file the message or term is in.
+    filename: String,
+    /// Where the message or term starts, counting from 1.
+    line: usize,
+    column: usize,
+    /// The byte offsets of the source in its file.
+    byte_start: usize,
+    byte_end: usize,
+}
+
+#[pymethods]
+impl MessageSource {
+    fn __repr__(&self) -> String {
+        format!(
+            "MessageSource(id={:?}, filename={:?}, line={})",
+            self.id, self.filename, self.line
+        )
+    }
+}
+
+/// A set of messages following a `##` group comment in an FTL file.
+#[pyclass(frozen, get_all, module = "rustfluent")]
+pub(crate) struct MessageGroup {
+    /// The first line of the group comment.
+    name: String,
+    /// The full text of the group comment.
+    comment: String,
+    /// The IDs of the messages in the group, in file order.
+    message_ids: Vec<String>,
+    /// The name of the file the group was found in.
+    filename: String,
+}
+
+#[pymethods]
+impl MessageGroup {
+    fn __repr__(&self) -> String {
+        format!(
+            "MessageGroup(name={:?}, message_ids={:?})",
+            self.name, self.message_ids
+        )
+    }
+}
+
+/// A message's attributes, comment and variables, as returned by `Bundle.get_message`.
+#[pyclass(frozen, get_all, module = "rustfluent")]
+pub(crate) struct Message {
+    id: String,
+    /// The names of the message's attributes, in file order.
+    attributes: Vec<String>,
+    /// Whether the message has a value, rather than only attributes.
+    has_value: bool,
+    /// The comment directly above the message, if any.
+    comment: Option<String>,
+    /// The `##` comment of the group the message is in, if any.
+    group_comment: Option<String>,
+    /// The `###` comments of the file the message is in, if any.
+    resource_comment: Option<String>,
+    /// The variables needed to format the message's value and attributes.
+    variables: Vec<String>,
+    /// The tags from `tags:` lines in the message's comments, in order.
+    tags: Vec<String>,
+}
+
+impl Message {
+    pub fn new<'r>(
+        message: &'r ast::Message<&'r str>,
+        comments: EntryComments,
+        get_message: impl Fn(&str) -> Option<FluentMessage<'r>> + Copy,
+    ) -> Self {
+        let mut variables = vec![];
+        let patterns = message
+            .value
+            .iter()
+            .chain(message.attributes.iter().map(|attribute| &attribute.value));
+        for pattern in patterns {
+            for name in pattern_variables(pattern, get_message) {
+                if !variables.contains(&name) {
+                    variables.push(name);
+                }
+            }
+        }
+        Self {
+            id: message.id.name.to_string(),
+            attributes: message
+                .attributes
+                .iter()
+                .map(|attribute| attribute.id.name.to_string())
+                .collect(),
+            has_value: message.value.is_some(),
+            tags: comments.tags(),
+            comment: comments.comment,
+            group_comment: comments.group_comment,
+            resource_comment: comments.resource_comment,
+            variables,
+        }
+    }
+}
+
+#[pymethods]
+impl Message {
+    fn __repr__(&self) -> String {
+        format!(
+            "Message(id={:?}, attributes={:?})",
+            self.id, self.attributes
+        )
+    }
+}
+
+/// The comments that apply to a message or term.
+pub(crate) struct EntryComments {
+    /// The `#` comment directly above the entry.
+    pub comment: Option<String>,
+    /// The `##` comment of the group the entry is in.
+    pub group_comment: Option<String>,
+    /// The `###` comments of the resource, separated by blank lines.
+    pub resource_comment: Option<String>,
+}
+
+impl EntryComments {
+    /// Return the tags from the entry's comment, then its group's and its resource's.
+    fn tags(&self) -> Vec<String> {
+        let comments = [&self.comment, &self.group_comment, &self.resource_comment];
+        let mut tags = vec![];
+        for comment in comments.into_iter().flatten() {
+            add_tags(&mut tags, comment);
+        }
+        tags
+    }
+}
+
+/// Add the tags from the `tags:` lines of a comment, like `tags: checkout, email`, that
+/// aren't already there.
+fn add_tags(tags: &mut Vec<String>, comment: &str) {
+    for line in comment.lines() {
+        let Some(line_tags) = line.trim().strip_prefix("tags:") else {
+            continue;
+        };
+        for tag in line_tags.split(',').map(str::trim) {
+            if !tag.is_empty() && !tags.iter().any(|other| other == tag) {
+                tags.push(tag.to_string());
+            }
+        }
+    }
+}
+
+/// A variable documented in a message's comment, as returned by `Bundle.get_variable_docs`.
+#[pyclass(frozen, get_all, module = "rustfluent")]
+pub(crate) struct VariableDoc {
+    /// The name of the variable, without its `$`.
+    pub(crate) name: String,
+    /// The type given in parentheses after the name, if any.
+    #[pyo3(name = "type")]
+    pub(crate) variable_type: Option<String>,
+    /// The description after the name and type, if any.
+    description: Option<String>,
+}
+
+#[pymethods]
+impl VariableDoc {
+    fn __repr__(&self) -> String {
+        format!(
+            "VariableDoc(name={:?}, type={:?}, description={:?})",
+            self.name, self.variable_type, self.description
+        )
+    }
+}
+
+/// Return the variables documented in a comment, with a line for each like
+/// `$user (String) - the user's display name`. The type and description are optional, and
+/// the description may follow a `-` or a `:`. Other lines are ignored.
+pub(crate) fn variable_docs(comment: &str) -> Vec<VariableDoc> {
+    let mut docs = vec![];
+    for line in comment.lines() {
+        let Some(line) = line.trim().strip_prefix('$') else {
+            continue;
+        };
+        let name_end = line
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+            .unwrap_or(line.len());
+        let (name, mut rest) = line.split_at(name_end);
+        rest = rest.trim_start();
+        let mut variable_type = None;
+        if let Some(after) = rest.strip_prefix('(')
+            && let Some((type_name, after)) = after.split_once(')')
+        {
+            variable_type = Some(type_name.trim().to_string());
+            rest = after.trim_start();
+        }
+        let description = match rest.strip_prefix(['-', ':']) {
+            Some(description) => Some(description.trim()),
+            None if rest.is_empty() => None,
+            // Not documentation, but a comment that happens to start with a variable.
+            None => continue,
+        };
+        if name.is_empty() {
+            continue;
+        }
+        docs.push(VariableDoc {
+            name: name.to_string(),
+            variable_type,
+            description: description
+                .filter(|description| !description.is_empty())
+                .map(str::to_string),
+        });
+    }
+    docs
+}
+
+/// Return the tags of the messages in the resources, as for `Message.tags`, by message ID.
+/// Later definitions of a message override earlier ones.
+pub(crate) fn message_tags(resources: &[LoadedResource]) -> HashMap<String, Vec<String>> {
+    let mut message_tags = HashMap::new();
+    for loaded in resources {
+        let resource = loaded.full_ast();
+        // Resource comments apply to the whole file, wherever they are.
+        let mut resource_tags = vec![];
+        for entry in &resource.body {
+            if let ast::Entry::ResourceComment(comment) = entry {
+                add_tags(&mut resource_tags, &comment.content.join("\n"));
+            }
+        }
+        let mut group_tags = vec![];
+        for entry in &resource.body {
+            match entry {
+                ast::Entry::GroupComment(comment) => {
+                    group_tags.clear();
+                    add_tags(&mut group_tags, &comment.content.join("\n"));
+                }
+                ast::Entry::Message(message) => {
+                    let mut tags = vec![];
+                    if let Some(comment) = &message.comment {
+                        add_tags(&mut tags, &comment.content.join("\n"));
+                    }
+                    for tag in group_tags.iter().chain(&resource_tags) {
+                        if !tags.contains(tag) {
+                            tags.push(tag.clone());
+                        }
+                    }
+                    message_tags.insert(message.id.name.to_string(), tags);
+                }
+                _ => {}
+            }
+        }
+    }
+    message_tags
+}
+
+/// Return the comments for a message, or a term if the ID starts with `-`,
+/// or `None` if the resource doesn't contain it.
+pub(crate) fn entry_comments(loaded: &LoadedResource, id: &str) -> Option<EntryComments> {
+    let term_id = id.strip_prefix('-');
+    let wanted_id = term_id.unwrap_or(id);
+    let mut resource_comments = vec![];
+    let mut group_comment = None;
+    let mut found = None;
+    for entry in loaded.full_ast().body {
+        let (entry_id, comment) = match entry {
+            ast::Entry::ResourceComment(comment) => {
+                resource_comments.push(comment.content.join("\n"));
+                continue;
+            }
+            // An empty group comment closes the current group.
+            ast::Entry::GroupComment(comment) => {
+                group_comment = Some(comment.content.join("\n")).filter(|text| !text.is_empty());
+                continue;
+            }
+            ast::Entry::Message(message) if term_id.is_none() => (message.id.name, message.comment),
+            ast::Entry::Term(term) if term_id.is_some() => (term.id.name, term.comment),
+            _ => continue,
+        };
+        // Later entries override earlier ones, so keep looking.
+        if entry_id == wanted_id {
+            found = Some((comment, group_comment.clone()));
+        }
+    }
+
+    let (comment, group_comment) = found?;
+    Some(EntryComments {
+        comment: comment.map(|comment| comment.content.join("\n")),
+        group_comment,
+        resource_comment: Some(resource_comments.join("\n\n")).filter(|text| !text.is_empty()),
+    })
+}
+
+/// Collect the message groups from the resources, in load order.
+///
+/// A group runs from its `##` comment up to the next group comment or the end of
+/// the file. An empty group comment closes the current group without starting a new one.
+pub(crate) fn message_groups(resources: &[LoadedResource]) -> Vec<MessageGroup> {
+    let mut groups = vec![];
+    for loaded in resources {
+        let mut current: Option<MessageGroup> = None;
+        for entry in loaded.full_ast().body {
+            match entry {
+                ast::Entry::GroupComment(comment) => {
+                    groups.extend(current.take());
+                    let comment = comment.content.join("\n");
+                    if let Some(name) = comment.lines().next().filter(|name| !name.is_empty()) {
+                        current = Some(MessageGroup {
+                            name: name.to_string(),
+                            comment: comment.clone(),
+                            message_ids: vec![],
+                            filename: loaded.name.clone(),
+                        });
+                    }
+                }
+                ast::Entry::Message(message) => {
+                    if let Some(group) = current.as_mut() {
+                        group.message_ids.push(message.id.name.to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+        groups.extend(current);
+    }
+    groups
+}
+
+/// Find a term by its ID (without the leading `-`). Later resources override earlier
+/// ones, as do later entries within a resource.
+pub(crate) fn find_term<'r>(
+    resources: &'r [LoadedResource],
+    id: &str,
+) -> Option<(&'r LoadedResource, &'r ast::Term<&'r str>)> {
+    resources.iter().rev().find_map(|loaded| {
+        loaded
+            .resource
+            .entries()
+            .filter_map(|entry| match entry {
+                ast::Entry::Term(term) if term.id.name == id => Some(term),
+                _ => None,
+            })
+            .last()
+            .map(|term| (loaded, term))
+    })
+}
+
+/// Return the pattern of a message entry, or of one of its attributes.
+pub(crate) fn message_pattern<'r>(
+    entry: &'r ast::Entry<&'r str>,
+    attribute: Option<usize>,
+) -> Option<&'r ast::Pattern<&'r str>> {
+    let ast::Entry::Message(message) = entry else {
+        return None;
+    };
+    match attribute {
+        Some(index) => message
+            .attributes
+            .get(index)
+            .map(|attribute| &attribute.value),
+        None => message.value.as_ref(),
+    }
+}
+
+/// The type of value a variable is expected to have, inferred from how it's used.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum VariableType {
+    /// Used only in ways that accept any value, e.g. as an argument to a custom function.
+    Unknown,
+    String,
+    Number,
+    DateTime,
+    List,
+}
+
+impl VariableType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Unknown => "unknown",
+            Self::String => "string",
+            Self::Number => "number",
+            Self::DateTime => "datetime",
+            Self::List => "list",
+        }
+    }
+
+    /// Parse the name of a type in a variable schema. Types documented in comments are
+    /// capitalized, e.g. `Number`, and `Date` is taken as a datetime.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "string" => Some(Self::String),
+            "number" => Some(Self::Number),
+            "date" | "datetime" => Some(Self::DateTime),
+            "list" => Some(Self::List),
+            _ => None,
+        }
+    }
+
+    /// Return the type of a variable passed to Fluent, if it has one.
+    pub fn of(value: &FluentValue) -> Option<Self> {
+        match value {
+            FluentValue::String(_) => Some(Self::String),
+            FluentValue::Number(_) => Some(Self::Number),
+            FluentValue::Custom(custom) if custom.as_any().is::<FluentDateTime>() => {
+                Some(Self::DateTime)
+            }
+            FluentValue::Custom(custom) if custom.as_any().is::<FluentList>() => Some(Self::List),
+            _ => None,
+        }
+    }
+
+    /// Combine the types inferred from two uses of a variable, preferring the more specific one.
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (this, other) if this == other => this,
+            (Self::Unknown, other) | (other, Self::Unknown) => other,
+            (Self::String, other) | (other, Self::String) => other,
+            // Used as two different types, e.g. a number and a date.
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// The plural categories used as variant keys when selecting on a number.
+pub(crate) const PLURAL_CATEGORIES: [&str; 6] = ["zero", "one", "two", "few", "many", "other"];
+
+/// Return the names of the variables needed to format a pattern, in order of first appearance.
+///
+/// Messages referenced by the pattern are followed (using `get_message` to look them up),
+/// so their variables are included too. Variables inside a term are the term's own
+/// parameters rather than the caller's, so only the arguments passed to a term are included.
+pub(crate) fn pattern_variables<'r>(
+    pattern: &'r ast::Pattern<&'r str>,
+    get_message: impl Fn(&str) -> Option<FluentMessage<'r>>,
+) -> Vec<String> {
+    pattern_variable_types(pattern, get_message)
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// Return the variables needed to format a pattern, as for `pattern_variables`,
+/// along with the type each is expected to have.
+///
+/// Variables passed to `NUMBER()` or selected on using plural categories or number keys
+/// are numbers, and those passed to `DATETIME()` are dates. Other variables used directly
+/// in the pattern or as selectors are strings.
+pub(crate) fn pattern_variable_types<'r>(
+    pattern: &'r ast::Pattern<&'r str>,
+    get_message: impl Fn(&str) -> Option<FluentMessage<'r>>,
+) -> Vec<(String, VariableType)> {
+    let mut collector = VariableCollector {
+        get_message,
+        variables: vec![],
+        visited: vec![],
+    };
+    collector.pattern(pattern);
+    collector.variables
+}
+
+struct VariableCollector<'r, F> {
+    get_message: F,
+    variables: Vec<(String, VariableType)>,
+    /// The message references already followed, so that reference cycles terminate.
+    visited: Vec<(&'r str, Option<&'r str>)>,
+}
+
+impl<'r, F: Fn(&str) -> Option<FluentMessage<'r>>> VariableCollector<'r, F> {
+    fn pattern(&mut self, pattern: &'r ast::Pattern<&'r str>) {
+        for element in &pattern.elements {
+            if let ast::PatternElement::Placeable { expression } = element {
+                self.expression(expression);
+            }
+        }
+    }
+
+    fn expression(&mut self, expression: &'r ast::Expression<&'r str>) {
+        match expression {
+            ast::Expression::Select { selector, variants } => {
+                let is_numeric = variants.iter().all(|variant| match &variant.key {
+                    ast::VariantKey::NumberLiteral { .. } => true,
+                    ast::VariantKey::Identifier { name } => PLURAL_CATEGORIES.contains(name),
+                });
+                let selector_type = if is_numeric {
+                    VariableType::Number
+                } else {
+                    VariableType::String
+                };
+                self.inline(selector, selector_type);
+                for variant in variants {
+                    self.pattern(&variant.value);
+                }
+            }
+            ast::Expression::Inline(expression) => self.inline(expression, VariableType::String),
+        }
+    }
+
+    /// Collect the variables in an expression, which is used as a value of type `usage`.
+    fn inline(&mut self, expression: &'r ast::InlineExpression<&'r str>, usage: VariableType) {
+        match expression {
+            ast::InlineExpression::VariableReference { id } => {
+                match self.variables.iter_mut().find(|(name, _)| name == id.name) {
+                    Some((_, variable_type)) => *variable_type = variable_type.merge(usage),
+                    None => self.variables.push((id.name.to_string(), usage)),
+                }
+            }
+            ast::InlineExpression::MessageReference { id, attribute } => {
+                let reference = (id.name, attribute.as_ref().map(|attribute| attribute.name));
+                if self.visited.contains(&reference) {
+                    return;
+                }
+                self.visited.push(reference);
+                let Some(message) = (self.get_message)(id.name) else {
+                    return;
+                };
+                let pattern = match attribute {
+                    Some(attribute) => message
+                        .get_attribute(attribute.name)
+                        .map(|attribute| attribute.value()),
+                    None => message.value(),
+                };
+                if let Some(pattern) = pattern {
+                    self.pattern(pattern);
+                }
+            }
+            ast::InlineExpression::FunctionReference { id, arguments } => {
+                let positional_type = match id.name {
+                    "NUMBER" => VariableType::Number,
+                    "DATETIME" => VariableType::DateTime,
+                    "LIST" => VariableType::List,
+                    _ => VariableType::Unknown,
+                };
+                self.arguments(arguments, positional_type);
+            }
+            ast::InlineExpression::TermReference {
+                arguments: Some(arguments),
+                ..
+            } => self.arguments(arguments, VariableType::Unknown),
+            ast::InlineExpression::Placeable { expression } => self.expression(expression),
+            _ => {}
+        }
+    }
+
+    fn arguments(&mut self, arguments: &'r ast::CallArguments<&'r str>, positional: VariableType) {
+        // Named arguments can only be literals.
+        for argument in &arguments.positional {
+            self.inline(argument, positional);
+        }
+    }
+}
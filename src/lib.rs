@@ -1,144 +1,4272 @@
-use chrono::NaiveDate;
+use cache::{CachedBundle, CachedLocale, CachedResource, file_hash};
+use diff::{MessageDiff, ResourceDiff, diff};
 use fluent::FluentArgs;
-use fluent_bundle::FluentResource;
 use fluent_bundle::concurrent::FluentBundle;
-use miette::{LabeledSpan, miette};
-use pyo3::exceptions::{PyFileNotFoundError, PyTypeError, PyValueError};
+use fluent_bundle::resolver::ResolverError;
+use fluent_bundle::resolver::errors::ReferenceKind;
+use fluent_bundle::types::FluentNumberType;
+use fluent_bundle::{FluentError, FluentMessage, FluentResource};
+use fluent_langneg::NegotiationStrategy;
+use fluent_syntax::ast;
+use fluent_syntax::serializer::{Options as SerializeOptions, serialize, serialize_with_options};
+use functions::{is_function_name, python_function, take_function_error};
+use interchange::{i18next_string, pattern_source};
+use intl_pluralrules::PluralRuleType;
+use introspection::{
+    Junk, LoadedResource, Message, MessageGroup, MessageSource, VariableDoc, VariableType,
+    entry_comments, find_term, message_groups, message_pattern, message_tags,
+    pattern_variable_types, pattern_variables, variable_docs,
+};
+use lint::LintRules;
+use locale::{Available, LanguageTag, Locale, accepted_languages, negotiation_strategy};
+use merge::{MergeConflict, MergeResult, merge};
+use miette::{GraphicalReportHandler, GraphicalTheme, LabeledSpan, miette};
+use numbers::{Number, format_value, number_value};
+use parts::MessagePart;
+use pseudo::Pseudo;
+use pyo3::IntoPyObjectExt;
+use pyo3::exceptions::{PyException, PyKeyError, PyOSError, PyTypeError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::{PyDate, PyDict, PyInt, PyString};
+use pyo3::sync::PyOnceLock;
+use pyo3::types::{PyBytes, PyDict, PyString, PySuper, PyTuple, PyType};
+use rayon::prelude::*;
+use rewrite::{
+    SkeletonMode, format_source, is_valid_prefix, normalize, prefix_messages, rename_entries,
+    serialize_in_effect, skeleton,
+};
+use search::{SearchMatch, search, search_pattern};
+use stats::{MessageStats, Stats};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::io::{self, BufReader, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use suggestions::{did_you_mean, suggestions};
 use unic_langid::LanguageIdentifier;
+use validation::{
+    Baseline, ValidationError, ValidationRules, category_name, check_markup, check_placeholders,
+    check_severity, entry_references, find_formatting_cycle, find_unused, graph_dot, json_report,
+    plural_rules, referencing, to_json, validate,
+};
+use variables::{BoolPolicy, NonePolicy, VariableOptions, to_fluent_args, zone_info};
+
+mod cache;
+mod dates;
+mod diff;
+mod functions;
+mod gettext;
+mod interchange;
+mod introspection;
+mod lint;
+mod lists;
+mod locale;
+mod merge;
+mod numbers;
+mod parts;
+mod pseudo;
+mod rewrite;
+mod search;
+mod stats;
+mod suggestions;
+mod syntax;
+mod validation;
+mod variables;
+mod xliff;
 
 use pyo3::create_exception;
 
-create_exception!(rustfluent, ParserError, pyo3::exceptions::PyException);
+create_exception!(rustfluent, ParserError, pyo3::exceptions::PyException);
+create_exception!(rustfluent, CoverageError, pyo3::exceptions::PyException);
+
+create_exception!(rustfluent, BundleValidationError, ParserError);
+create_exception!(
+    rustfluent,
+    MissingPluralCategoryError,
+    BundleValidationError
+);
+create_exception!(rustfluent, UnknownMessageError, BundleValidationError);
+create_exception!(rustfluent, UnknownTermError, BundleValidationError);
+create_exception!(rustfluent, CyclicReferenceError, BundleValidationError);
+create_exception!(rustfluent, DuplicateMessageError, BundleValidationError);
+create_exception!(
+    rustfluent,
+    IgnoredPositionalArgumentError,
+    BundleValidationError
+);
+create_exception!(rustfluent, VariableDocMismatchError, BundleValidationError);
+create_exception!(rustfluent, PlaceholderMismatchError, BundleValidationError);
+create_exception!(rustfluent, MarkupMismatchError, BundleValidationError);
+create_exception!(rustfluent, LintError, BundleValidationError);
+
+create_exception!(rustfluent, FluentWarning, pyo3::exceptions::PyUserWarning);
+create_exception!(rustfluent, ParseWarning, FluentWarning);
+create_exception!(rustfluent, ValidationWarning, FluentWarning);
+create_exception!(rustfluent, MissingPluralCategoryWarning, ValidationWarning);
+create_exception!(rustfluent, UnknownMessageWarning, ValidationWarning);
+create_exception!(rustfluent, UnknownTermWarning, ValidationWarning);
+create_exception!(rustfluent, CyclicReferenceWarning, ValidationWarning);
+create_exception!(rustfluent, DuplicateMessageWarning, ValidationWarning);
+create_exception!(
+    rustfluent,
+    IgnoredPositionalArgumentWarning,
+    ValidationWarning
+);
+create_exception!(rustfluent, VariableDocMismatchWarning, ValidationWarning);
+create_exception!(rustfluent, PlaceholderMismatchWarning, ValidationWarning);
+create_exception!(rustfluent, MarkupMismatchWarning, ValidationWarning);
+create_exception!(rustfluent, LintWarning, ValidationWarning);
+
+static PARSER_ERROR_GROUP: PyOnceLock<Py<PyType>> = PyOnceLock::new();
+
+/// Return `ParserErrorGroup`, an `ExceptionGroup` which is also a `ParserError`,
+/// so that `except ParserError` still catches the errors of a strict bundle.
+fn parser_error_group(py: Python<'_>) -> PyResult<&Bound<'_, PyType>> {
+    PARSER_ERROR_GROUP
+        .get_or_try_init(py, || {
+            let exception_group = py.import("builtins")?.getattr("ExceptionGroup")?;
+            let namespace = PyDict::new(py);
+            namespace.set_item("__module__", "rustfluent")?;
+            namespace.set_item(
+                "__doc__",
+                "The syntax and validation errors of a strict bundle.",
+            )?;
+            let group = py.get_type::<PyType>().call1((
+                "ParserErrorGroup",
+                (exception_group, py.get_type::<ParserError>()),
+                namespace,
+            ))?;
+            Ok::<_, PyErr>(group.cast_into::<PyType>()?.unbind())
+        })
+        .map(|group| group.bind(py))
+}
+
+/// Check that a callable added to a bundle can be pickled, so that pickling the bundle raises
+/// a `PicklingError` saying which callable can't be, whatever pickling it raises.
+fn check_picklable(py: Python<'_>, description: &str, callable: &Py<PyAny>) -> PyResult<()> {
+    let pickle = py.import("pickle")?;
+    let Err(cause) = pickle.call_method1("dumps", (callable,)) else {
+        return Ok(());
+    };
+    let error = match pickle
+        .getattr("PicklingError")?
+        .call1((format!("{description} can't be pickled: {cause}"),))
+    {
+        Ok(error) => PyErr::from_value(error),
+        Err(error) => error,
+    };
+    error.set_cause(py, Some(cause));
+    Err(error)
+}
+
+/// Return a `ParserErrorGroup` of errors.
+fn error_group(py: Python<'_>, message: String, errors: Vec<PyErr>) -> PyErr {
+    let errors: Vec<_> = errors
+        .into_iter()
+        .map(|error| error.into_value(py))
+        .collect();
+    match parser_error_group(py).and_then(|group| group.call1((message, errors))) {
+        Ok(group) => PyErr::from_value(group),
+        Err(error) => error,
+    }
+}
+
+/// How the messages of exceptions for errors in FTL files are rendered, from the
+/// `diagnostic_style` argument.
+#[derive(Clone, Copy, PartialEq)]
+enum DiagnosticStyle {
+    /// A snippet of the source showing the errors, drawn with Unicode characters, in colour
+    /// unless `NO_COLOR` is set or stderr isn't a terminal.
+    Rich,
+    /// A snippet of the source showing the errors, drawn with ASCII characters without colour.
+    Plain,
+    /// A line for each error with its location, without a snippet.
+    None,
+}
+
+impl DiagnosticStyle {
+    fn new(diagnostic_style: &str) -> PyResult<Self> {
+        match diagnostic_style {
+            "rich" => Ok(Self::Rich),
+            "plain" => Ok(Self::Plain),
+            "none" => Ok(Self::None),
+            _ => Err(PyValueError::new_err(format!(
+                "diagnostic_style must be 'none', 'plain' or 'rich', not '{diagnostic_style}'."
+            ))),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Rich => "rich",
+            Self::Plain => "plain",
+            Self::None => "none",
+        }
+    }
+
+    /// The theme snippets are drawn with.
+    fn theme(self) -> GraphicalTheme {
+        // As at https://no-color.org, `NO_COLOR` disables colour if it's set and not empty.
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        match self {
+            Self::Rich if !no_color && std::io::stderr().is_terminal() => GraphicalTheme::unicode(),
+            Self::Rich => GraphicalTheme::unicode_nocolor(),
+            Self::Plain | Self::None => GraphicalTheme::none(),
+        }
+    }
+}
+
+/// Render the errors of a resource in a style, if it has any: a snippet of the resource
+/// showing where its syntax errors and validation errors are, or a line for each.
+fn render_errors(
+    loaded: &LoadedResource,
+    syntax_errors: &[fluent_syntax::parser::ParserError],
+    validation_errors: &[&ValidationError],
+    style: DiagnosticStyle,
+) -> Option<String> {
+    let heading = match (syntax_errors.is_empty(), validation_errors.is_empty()) {
+        (true, true) => return None,
+        (false, true) => "Error when parsing",
+        (true, false) => "Validation errors in",
+        (false, false) => "Errors in",
+    };
+    if style == DiagnosticStyle::None {
+        let mut lines = vec![format!("{heading} {}:", loaded.name)];
+        for error in syntax_errors {
+            let (line, column) = loaded.line_and_column(error.pos.start).unwrap_or((1, 1));
+            lines.push(format!("{}:{line}:{column}: {}", loaded.name, error.kind));
+        }
+        lines.extend(validation_errors.iter().map(ToString::to_string));
+        return Some(lines.join("\n"));
+    }
+    let mut labels = Vec::with_capacity(syntax_errors.len() + validation_errors.len());
+    for error in syntax_errors {
+        labels.push(LabeledSpan::at(
+            error.pos.clone(),
+            format!("{}", error.kind),
+        ));
+    }
+    for error in validation_errors {
+        if let Some(span) = error.span() {
+            labels.push(LabeledSpan::at(span, error.description()));
+        }
+    }
+    let report = miette!(labels = labels, "{heading} {}", loaded.name)
+        .with_source_code(loaded.resource.source().to_string());
+    // Lines aren't wrapped, as the width the message is shown at isn't known.
+    let handler = GraphicalReportHandler::new_themed(style.theme()).with_wrap_lines(false);
+    let mut rendered = String::new();
+    handler.render_report(&mut rendered, report.as_ref()).ok()?;
+    Some(rendered)
+}
+
+/// Return a `ParserError` for a syntax error, showing where it is in a style.
+fn syntax_exception(
+    loaded: &LoadedResource,
+    error: &fluent_syntax::parser::ParserError,
+    style: DiagnosticStyle,
+) -> PyErr {
+    let message = render_errors(loaded, std::slice::from_ref(error), &[], style)
+        .expect("a report is rendered for any error");
+    ParserError::new_err(message)
+}
+
+/// Return the exception for a validation error that fails a strict bundle, a subclass of
+/// `BundleValidationError` for its type with the `ValidationError` as its `error`.
+fn validation_exception(py: Python<'_>, error: &ValidationError) -> PyErr {
+    let message = error.to_string();
+    let exception = match error.error_type() {
+        "MissingPluralCategory" => MissingPluralCategoryError::new_err(message),
+        "UnknownMessage" => UnknownMessageError::new_err(message),
+        "UnknownTerm" => UnknownTermError::new_err(message),
+        "CyclicReference" => CyclicReferenceError::new_err(message),
+        "DuplicateMessageId" => DuplicateMessageError::new_err(message),
+        "IgnoredPositionalArgument" => IgnoredPositionalArgumentError::new_err(message),
+        "VariableDocMismatch" => VariableDocMismatchError::new_err(message),
+        "PlaceholderMismatch" => PlaceholderMismatchError::new_err(message),
+        "MarkupMismatch" => MarkupMismatchError::new_err(message),
+        "MaxLength" | "ForbiddenCharacter" | "QuoteStyle" | "TrailingWhitespace" | "IdNaming" => {
+            LintError::new_err(message)
+        }
+        _ => BundleValidationError::new_err(message),
+    };
+    match exception.value(py).setattr("error", error.clone()) {
+        Ok(()) => exception,
+        Err(setattr_error) => setattr_error,
+    }
+}
+
+/// Return the warning category for a type of validation error.
+fn warning_category<'py>(py: Python<'py>, error_type: &str) -> Bound<'py, PyType> {
+    match error_type {
+        "MissingPluralCategory" => py.get_type::<MissingPluralCategoryWarning>(),
+        "UnknownMessage" => py.get_type::<UnknownMessageWarning>(),
+        "UnknownTerm" => py.get_type::<UnknownTermWarning>(),
+        "CyclicReference" => py.get_type::<CyclicReferenceWarning>(),
+        "DuplicateMessageId" => py.get_type::<DuplicateMessageWarning>(),
+        "IgnoredPositionalArgument" => py.get_type::<IgnoredPositionalArgumentWarning>(),
+        "VariableDocMismatch" => py.get_type::<VariableDocMismatchWarning>(),
+        "PlaceholderMismatch" => py.get_type::<PlaceholderMismatchWarning>(),
+        "MarkupMismatch" => py.get_type::<MarkupMismatchWarning>(),
+        "MaxLength" | "ForbiddenCharacter" | "QuoteStyle" | "TrailingWhitespace" | "IdNaming" => {
+            py.get_type::<LintWarning>()
+        }
+        _ => py.get_type::<ValidationWarning>(),
+    }
+}
+
+/// What to do with errors formatting a message, from an `errors` argument.
+enum ErrorHandling {
+    /// Return Fluent's best-effort result.
+    Ignore,
+    /// Raise a `FormatError`.
+    Raise,
+    /// Log the errors to the `rustfluent` logger, and return the best-effort result.
+    Log,
+    /// Pass a `FormatError` to a callable, and return the best-effort result.
+    Report(Py<PyAny>),
+}
+
+impl ErrorHandling {
+    /// Whether formatting errors are raised, so variables of unsupported types are too.
+    fn raises(&self) -> bool {
+        matches!(self, Self::Raise)
+    }
+}
+
+/// Read an `errors` argument: `"ignore"`, `"raise"`, `"log"` or a callable.
+fn error_handling(errors: &Bound<'_, PyAny>) -> PyResult<ErrorHandling> {
+    if let Ok(name) = errors.cast::<PyString>() {
+        return match name.to_str()? {
+            "ignore" => Ok(ErrorHandling::Ignore),
+            "raise" => Ok(ErrorHandling::Raise),
+            "log" => Ok(ErrorHandling::Log),
+            name => Err(PyValueError::new_err(format!(
+                "errors must be 'ignore', 'raise', 'log' or a callable, not '{name}'."
+            ))),
+        };
+    }
+    if errors.is_callable() {
+        return Ok(ErrorHandling::Report(errors.clone().unbind()));
+    }
+    Err(PyTypeError::new_err(format!(
+        "errors must be 'ignore', 'raise', 'log' or a callable, not {}.",
+        errors.get_type().name()?
+    )))
+}
+
+/// The errors formatting a message, raised with `errors="raise"` or passed to an `errors`
+/// callable. It can be created, compared and pickled from Python, e.g. to test error handling.
+#[pyclass(extends = PyException, frozen, get_all, eq, hash, module = "rustfluent")]
+#[derive(PartialEq, Eq, Hash)]
+pub(crate) struct FormatError {
+    /// The description of the errors, which is the exception's message.
+    message: String,
+    /// The message (or `message.attribute`) that was formatted.
+    message_id: String,
+    /// The description of each error.
+    errors: Vec<String>,
+    /// The first variable that wasn't passed, if any.
+    variable_name: Option<String>,
+    /// The first message, term, attribute or function that couldn't be used, if any.
+    reference: Option<String>,
+    /// How serious the errors are. Errors formatting a message are always `error`s, but
+    /// the severity can be given when creating one, as for the other error classes.
+    severity: String,
+    /// The stable code of each error, e.g. `RF1001` for an unknown variable.
+    codes: Vec<String>,
+}
+
+#[pymethods]
+impl FormatError {
+    #[new]
+    #[pyo3(signature = (message, message_id, errors=vec![], variable_name=None, reference=None, severity="error", codes=vec![]))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        message: String,
+        message_id: String,
+        errors: Vec<String>,
+        variable_name: Option<String>,
+        reference: Option<String>,
+        severity: &str,
+        codes: Vec<String>,
+    ) -> PyResult<Self> {
+        check_severity(severity)?;
+        Ok(Self {
+            message,
+            message_id,
+            errors,
+            variable_name,
+            reference,
+            severity: severity.to_string(),
+            codes,
+        })
+    }
+
+    /// Initialize the exception with only the message as its `args`, so that it's shown as
+    /// the exception's message. The other arguments are used by `__new__`.
+    #[pyo3(signature = (message, *_args, **_kwargs))]
+    fn __init__(
+        slf: &Bound<'_, Self>,
+        message: &str,
+        _args: &Bound<'_, PyTuple>,
+        _kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<()> {
+        PySuper::new(&slf.py().get_type::<PyException>(), slf)?
+            .call_method1("__init__", (message,))?;
+        Ok(())
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn __reduce__<'py>(
+        slf: &Bound<'py, Self>,
+    ) -> (
+        Bound<'py, PyType>,
+        (
+            String,
+            String,
+            Vec<String>,
+            Option<String>,
+            Option<String>,
+            String,
+            Vec<String>,
+        ),
+    ) {
+        let this = slf.get();
+        (
+            slf.get_type(),
+            (
+                this.message.clone(),
+                this.message_id.clone(),
+                this.errors.clone(),
+                this.variable_name.clone(),
+                this.reference.clone(),
+                this.severity.clone(),
+                this.codes.clone(),
+            ),
+        )
+    }
+
+    /// Return the error's fields as a dict, e.g. to serialize as JSON.
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("message", &self.message)?;
+        dict.set_item("message_id", &self.message_id)?;
+        dict.set_item("errors", &self.errors)?;
+        dict.set_item("variable_name", &self.variable_name)?;
+        dict.set_item("reference", &self.reference)?;
+        dict.set_item("severity", &self.severity)?;
+        dict.set_item("codes", &self.codes)?;
+        Ok(dict)
+    }
+}
+
+/// The variables passed to format a message that don't match those it uses, with
+/// `strict_variables`, or the types in its variable schema.
+#[derive(Default)]
+struct VariableMismatch {
+    /// Variables the message uses, including through the messages it references, that weren't
+    /// passed.
+    missing: Vec<String>,
+    /// Variables that were passed but the message doesn't use.
+    unexpected: Vec<String>,
+    /// Variables passed with another type than the schema gives, with the expected and actual
+    /// types.
+    wrong_types: Vec<(String, VariableType, VariableType)>,
+}
+
+impl VariableMismatch {
+    fn new(required: &[String], args: Option<&FluentArgs>) -> Self {
+        let passed: Vec<&str> = args
+            .map(|args| args.iter().map(|(name, _)| name).collect())
+            .unwrap_or_default();
+        Self {
+            missing: required
+                .iter()
+                .filter(|name| !passed.contains(&name.as_str()))
+                .cloned()
+                .collect(),
+            unexpected: passed
+                .into_iter()
+                .filter(|name| !required.iter().any(|required| required == name))
+                .map(str::to_string)
+                .collect(),
+            wrong_types: vec![],
+        }
+    }
+
+    /// Record the variables passed with another type than `schema` gives them.
+    fn check_types(&mut self, schema: &HashMap<String, VariableType>, args: Option<&FluentArgs>) {
+        let Some(args) = args else {
+            return;
+        };
+        for (name, value) in args.iter() {
+            if let Some(&expected) = schema.get(name)
+                && let Some(actual) = VariableType::of(value)
+                && actual != expected
+            {
+                self.wrong_types.push((name.to_string(), expected, actual));
+            }
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.unexpected.is_empty() && self.wrong_types.is_empty()
+    }
+
+    /// Return the error type and description of each mismatched variable.
+    fn errors(&self) -> Vec<(&'static str, String)> {
+        let missing = self
+            .missing
+            .iter()
+            .map(|name| ("MissingVariable", format!("Missing variable: ${name}")));
+        let unexpected = self.unexpected.iter().map(|name| {
+            (
+                "UnexpectedVariable",
+                format!("Unexpected variable: ${name}"),
+            )
+        });
+        let wrong_types = self.wrong_types.iter().map(|(name, expected, actual)| {
+            (
+                "WrongVariableType",
+                format!(
+                    "Variable ${name} should be a {}, not a {}",
+                    expected.as_str(),
+                    actual.as_str()
+                ),
+            )
+        });
+        missing.chain(unexpected).chain(wrong_types).collect()
+    }
+}
+
+/// Return the `FormatError` for the errors formatting a message, with the first variable
+/// that wasn't passed (or was passed with the wrong type) as its `variable_name`, and the
+/// first message, term, attribute or function that couldn't be used as its `reference`. For a
+/// cycle, that's the first of its members formatting the message ran into again. Mismatched
+/// variables come before the other errors.
+fn format_error(
+    identifier: &str,
+    errors: &[FluentError],
+    mismatch: &VariableMismatch,
+    resources: &[LoadedResource],
+) -> PyErr {
+    let mut variable_name = mismatch
+        .missing
+        .first()
+        .or(mismatch.wrong_types.first().map(|(name, _, _)| name))
+        .cloned();
+    let mut reference = None;
+    for error in errors {
+        let FluentError::ResolverError(error) = error else {
+            continue;
+        };
+        let unresolved = match error {
+            ResolverError::Reference(ReferenceKind::Variable { id }) => {
+                variable_name.get_or_insert_with(|| id.clone());
+                continue;
+            }
+            ResolverError::Reference(ReferenceKind::Function { id }) => format!("{id}()"),
+            ResolverError::Reference(ReferenceKind::Message { id, attribute }) => match attribute {
+                Some(attribute) => format!("{id}.{attribute}"),
+                None => id.clone(),
+            },
+            ResolverError::Reference(ReferenceKind::Term { id, attribute }) => match attribute {
+                Some(attribute) => format!("-{id}.{attribute}"),
+                None => format!("-{id}"),
+            },
+            ResolverError::NoValue(id) => id.clone(),
+            // Fluent doesn't say where the cycle is, so it's found again from the references.
+            ResolverError::Cyclic if reference.is_none() => {
+                match find_formatting_cycle(resources, identifier) {
+                    Some(cycle) => cycle[0].clone(),
+                    None => continue,
+                }
+            }
+            _ => continue,
+        };
+        reference.get_or_insert(unresolved);
+    }
+    let (mut codes, mut messages): (Vec<&str>, Vec<String>) = mismatch
+        .errors()
+        .into_iter()
+        .map(|(error_type, message)| (format_code(error_type), message))
+        .unzip();
+    messages.extend(errors.iter().map(ToString::to_string));
+    codes.extend(errors.iter().map(format_error_code));
+    // This may run on a worker thread without the GIL, when formatting a batch.
+    Python::attach(|py| {
+        let described: Vec<String> = messages
+            .iter()
+            .zip(&codes)
+            .map(|(message, code)| format!("{message} ({code})"))
+            .collect();
+        let message = format!(
+            "Errors when formatting {identifier}: {}",
+            described.join("; ")
+        );
+        // The exception is created by calling its class, so that its `args` are set.
+        match py.get_type::<FormatError>().call1((
+            message,
+            identifier,
+            messages,
+            variable_name,
+            reference,
+            "error",
+            codes,
+        )) {
+            Ok(error) => PyErr::from_value(error),
+            Err(error) => error,
+        }
+    })
+}
+
+/// The kind of an error formatting a message, e.g. `UnknownVariable`.
+fn format_error_type(error: &FluentError) -> &'static str {
+    let FluentError::ResolverError(error) = error else {
+        return "FluentError";
+    };
+    match error {
+        ResolverError::Reference(ReferenceKind::Variable { .. }) => "UnknownVariable",
+        ResolverError::Reference(ReferenceKind::Function { .. }) => "UnknownFunction",
+        ResolverError::Reference(ReferenceKind::Message {
+            attribute: None, ..
+        }) => "UnknownMessage",
+        ResolverError::Reference(ReferenceKind::Term {
+            attribute: None, ..
+        }) => "UnknownTerm",
+        ResolverError::Reference(_) => "UnknownAttribute",
+        ResolverError::NoValue(_) => "NoValue",
+        ResolverError::MissingDefault => "MissingDefault",
+        ResolverError::Cyclic => "CyclicReference",
+        ResolverError::TooManyPlaceables => "TooManyPlaceables",
+    }
+}
+
+/// The stable code of each type of error formatting a message, which are numbered apart from
+/// the validation error codes, as `UnknownMessage` when formatting isn't the same problem as
+/// an `UnknownMessage` validation error.
+const FORMAT_ERROR_CODES: [(&str, &str); 13] = [
+    ("FluentError", "RF1000"),
+    ("UnknownVariable", "RF1001"),
+    ("UnknownFunction", "RF1002"),
+    ("UnknownMessage", "RF1003"),
+    ("UnknownTerm", "RF1004"),
+    ("UnknownAttribute", "RF1005"),
+    ("NoValue", "RF1006"),
+    ("MissingDefault", "RF1007"),
+    ("CyclicReference", "RF1008"),
+    ("TooManyPlaceables", "RF1009"),
+    ("MissingVariable", "RF1010"),
+    ("UnexpectedVariable", "RF1011"),
+    ("WrongVariableType", "RF1012"),
+];
+
+/// Return the code of a type of error formatting a message.
+fn format_code(error_type: &str) -> &'static str {
+    FORMAT_ERROR_CODES
+        .iter()
+        .find(|&&(other, _)| other == error_type)
+        .map_or("RF1000", |&(_, code)| code)
+}
+
+/// Return the code of an error formatting a message, e.g. `RF1001` for an unknown variable.
+fn format_error_code(error: &FluentError) -> &'static str {
+    format_code(format_error_type(error))
+}
+
+/// Log each of the errors formatting a message, after any mismatched variables, as a warning
+/// from the `rustfluent` logger, with its `message_id`, `locale`, `error_type` and `code` in
+/// the record.
+fn log_format_errors(
+    py: Python<'_>,
+    identifier: &str,
+    locale: &str,
+    errors: &[FluentError],
+    mismatch: &VariableMismatch,
+) -> PyResult<()> {
+    let logger = py
+        .import("logging")?
+        .call_method1("getLogger", ("rustfluent",))?;
+    let errors = mismatch.errors().into_iter().chain(
+        errors
+            .iter()
+            .map(|error| (format_error_type(error), error.to_string())),
+    );
+    for (error_type, message) in errors {
+        let extra = PyDict::new(py);
+        extra.set_item("message_id", identifier)?;
+        extra.set_item("locale", locale)?;
+        extra.set_item("error_type", error_type)?;
+        extra.set_item("code", format_code(error_type))?;
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("extra", extra)?;
+        logger.call_method(
+            "warning",
+            (
+                "Error when formatting %s for %s: %s",
+                identifier,
+                locale,
+                message,
+            ),
+            Some(&kwargs),
+        )?;
+    }
+    Ok(())
+}
+
+/// Return Python's `repr()` of a value, e.g. `['hello', 'bye']` for a list of strings.
+fn python_repr<'py>(py: Python<'py>, value: impl IntoPyObject<'py>) -> PyResult<String> {
+    Ok(value.into_bound_py_any(py)?.repr()?.to_string())
+}
+
+/// Return the ID of a message, or of a term with its `-`.
+fn entry_id<S: AsRef<str>>(entry: &ast::Entry<S>) -> Option<String> {
+    match entry {
+        ast::Entry::Message(message) => Some(message.id.name.as_ref().to_string()),
+        ast::Entry::Term(term) => Some(format!("-{}", term.id.name.as_ref())),
+        _ => None,
+    }
+}
+
+/// Collect the strings in a Python iterable, such as a list or a set.
+fn string_set(iterable: &Bound<'_, PyAny>) -> PyResult<HashSet<String>> {
+    iterable
+        .try_iter()?
+        .map(|item| item?.extract::<String>())
+        .collect()
+}
+
+/// An FTL resource passed to `Bundle`: a path to a file, a `(name, source)` pair, or the
+/// source as `bytes` or an open file.
+#[derive(FromPyObject)]
+enum FtlSource {
+    Source(String, String),
+    Contents(#[pyo3(from_py_with = read_contents)] (String, Contents)),
+    Path(PathBuf),
+}
+
+/// FTL source read from `bytes` or a file object, which is only decoded if it's binary.
+enum Contents {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+/// Read FTL source from `bytes`, or a file object such as `io.BytesIO` or a resource opened
+/// with `importlib.resources`, returning a name for it and its contents. The file is named
+/// after its `name` attribute, if it has one.
+fn read_contents(object: &Bound<'_, PyAny>) -> PyResult<(String, Contents)> {
+    if let Ok(bytes) = object.cast::<PyBytes>() {
+        return Ok((
+            "<bytes>".to_string(),
+            Contents::Bytes(bytes.as_bytes().to_vec()),
+        ));
+    }
+    if !object.hasattr("read")? {
+        return Err(PyTypeError::new_err("not bytes or a file object"));
+    }
+    let name = object
+        .getattr("name")
+        .and_then(|name| name.extract::<String>())
+        .unwrap_or_else(|_| "<file>".to_string());
+    let contents = object.call_method0("read")?;
+    let contents = if let Ok(bytes) = contents.cast::<PyBytes>() {
+        Contents::Bytes(bytes.as_bytes().to_vec())
+    } else {
+        Contents::Text(contents.extract()?)
+    };
+    Ok((name, contents))
+}
+
+impl FtlSource {
+    fn load(self, decoding: &Decoding) -> PyResult<SourceText> {
+        match self {
+            Self::Source(name, contents) | Self::Contents((name, Contents::Text(contents))) => {
+                Ok(SourceText {
+                    name,
+                    path: None,
+                    file_hash: None,
+                    contents,
+                })
+            }
+            Self::Contents((name, Contents::Bytes(bytes))) => Ok(SourceText {
+                contents: decoding.decode(&name, bytes)?,
+                name,
+                path: None,
+                file_hash: None,
+            }),
+            Self::Path(file_path) => {
+                let bytes = read_path(&file_path)?;
+                let name = file_path.to_string_lossy().into_owned();
+                Ok(SourceText {
+                    file_hash: Some(file_hash(&bytes)),
+                    contents: decoding.decode(&name, bytes)?,
+                    name,
+                    path: Some(file_path),
+                })
+            }
+        }
+    }
+
+    /// Read the sources in parallel, keeping their order.
+    fn load_all(sources: Vec<Self>, decoding: &Decoding) -> PyResult<Vec<SourceText>> {
+        let sources: Vec<_> = sources
+            .into_par_iter()
+            .map(|source| source.load(decoding))
+            .collect();
+        sources.into_iter().collect()
+    }
+}
+
+/// Read an FTL file, which may be a member of a zip archive, as in
+/// `translations.zip!/de/main.ftl`.
+fn read_path(path: &Path) -> PyResult<Vec<u8>> {
+    match archive_member(path) {
+        Some((archive, member)) => read_archive_member(path, archive, member),
+        None => fs::read(path).map_err(|error| io_exception(path, error)),
+    }
+}
+
+/// Split a path like `translations.zip!/de/main.ftl` into the archive and the path of the
+/// member in it, if the part before the `!` is a file.
+fn archive_member(path: &Path) -> Option<(&Path, &str)> {
+    let (archive, member) = path.to_str()?.split_once("!/")?;
+    let archive = Path::new(archive);
+    archive.is_file().then_some((archive, member))
+}
+
+/// Read a member of a zip archive, using Python's `zipfile`.
+fn read_archive_member(path: &Path, archive: &Path, member: &str) -> PyResult<Vec<u8>> {
+    Python::attach(|py| {
+        let zip_file = py
+            .import("zipfile")?
+            .getattr("ZipFile")?
+            .call1((archive,))?;
+        let contents = zip_file.call_method1("read", (member,));
+        zip_file.call_method0("close")?;
+        match contents {
+            Ok(contents) => Ok(contents.cast::<PyBytes>()?.as_bytes().to_vec()),
+            // A missing member raises a `KeyError`, but it's a missing file like any other.
+            Err(error) if error.is_instance_of::<PyKeyError>(py) => {
+                Err(io_exception(path, io::Error::from_raw_os_error(2)))
+            }
+            Err(error) => Err(error),
+        }
+    })
+}
+
+/// Return the `OSError` for a file that couldn't be read. Python picks the subclass for the
+/// error number, e.g. `PermissionError` or `IsADirectoryError`.
+fn io_exception(path: &Path, error: io::Error) -> PyErr {
+    match error.raw_os_error() {
+        Some(errno) => {
+            // Python shows the error number itself, as in `[Errno 13] Permission denied`.
+            let message = error.to_string();
+            let message = message
+                .strip_suffix(&format!(" (os error {errno})"))
+                .unwrap_or(&message);
+            PyOSError::new_err((errno, message.to_string(), path.to_path_buf()))
+        }
+        None => PyOSError::new_err(format!("{}: {error}", path.display())),
+    }
+}
+
+/// What to do with messages and terms defined again in a later FTL file of the same language,
+/// from the `on_duplicate` argument. Duplicates within a file are always validation errors.
+#[derive(Clone, Copy, PartialEq)]
+enum DuplicatePolicy {
+    /// The later definition is used, and is a `DuplicateMessageId` validation error.
+    Warn,
+    /// The later definition is used.
+    LastWins,
+    /// The first definition is used.
+    FirstWins,
+    /// The later definition is a `DuplicateMessageId` validation error, which always raises.
+    Error,
+}
+
+impl DuplicatePolicy {
+    fn new(on_duplicate: &str) -> PyResult<Self> {
+        match on_duplicate {
+            "warn" => Ok(Self::Warn),
+            "last_wins" => Ok(Self::LastWins),
+            "first_wins" => Ok(Self::FirstWins),
+            "error" => Ok(Self::Error),
+            _ => Err(PyValueError::new_err(format!(
+                "on_duplicate must be 'error', 'first_wins', 'last_wins' or 'warn', not '{on_duplicate}'."
+            ))),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Warn => "warn",
+            Self::LastWins => "last_wins",
+            Self::FirstWins => "first_wins",
+            Self::Error => "error",
+        }
+    }
+
+    /// Whether definitions in later files are reported as validation errors.
+    fn reports_overrides(self) -> bool {
+        matches!(self, Self::Warn | Self::Error)
+    }
+}
+
+/// How to decode FTL files, from the `encoding` and `errors` arguments, as for `open()`.
+#[derive(Clone)]
+struct Decoding {
+    encoding: String,
+    errors: String,
+}
+
+impl Decoding {
+    fn new(encoding: &str, errors: &str) -> Self {
+        Self {
+            encoding: encoding.to_string(),
+            errors: errors.to_string(),
+        }
+    }
+
+    /// Decode the contents of a file. Strict UTF-8 is decoded without the GIL; other encodings,
+    /// and invalid UTF-8, are decoded by Python, which raises a `UnicodeDecodeError` (or a
+    /// `LookupError` for an unknown encoding) noting the file.
+    fn decode(&self, name: &str, bytes: Vec<u8>) -> PyResult<String> {
+        let normalized = self.encoding.to_lowercase().replace(['-', '_'], "");
+        let bytes = if normalized == "utf8" && self.errors == "strict" {
+            match String::from_utf8(bytes) {
+                Ok(contents) => return Ok(contents),
+                Err(error) => error.into_bytes(),
+            }
+        } else {
+            bytes
+        };
+        Python::attach(|py| {
+            PyBytes::new(py, &bytes)
+                .call_method1("decode", (&self.encoding, &self.errors))
+                .and_then(|contents| contents.extract())
+                .map_err(|error| {
+                    let note = format!("while reading {name}");
+                    match error.value(py).call_method1("add_note", (note,)) {
+                        Ok(_) => error,
+                        Err(note_error) => note_error,
+                    }
+                })
+        })
+    }
+}
+
+/// The FTL source of a resource, along with where it came from.
+struct SourceText {
+    name: String,
+    /// The FTL file the source was read from, if it wasn't passed as a string.
+    path: Option<PathBuf>,
+    /// The `file_hash` of the file's bytes when they were read, to tell if it has changed since.
+    file_hash: Option<u64>,
+    contents: String,
+}
+
+impl SourceText {
+    /// Prefix the IDs of the source's messages, if a prefix is given for it.
+    fn prefixed(mut self, prefixes: &HashMap<String, String>) -> Self {
+        if let Some(prefix) = prefixes.get(&self.name) {
+            self.contents = prefix_messages(&self.contents, prefix);
+        }
+        self
+    }
+
+    /// Parse the source. Entries with syntax errors are left out of the resource,
+    /// and the errors are kept with it.
+    fn parse(self) -> LoadedResource {
+        let Self {
+            name,
+            path,
+            file_hash,
+            contents,
+        } = self;
+        let (resource, errors) = match FluentResource::try_new(contents) {
+            Ok(resource) => (resource, vec![]),
+            Err((resource, errors)) => (resource, errors),
+        };
+        LoadedResource {
+            name,
+            path,
+            file_hash,
+            resource: Arc::new(resource),
+            errors,
+        }
+    }
+}
+
+/// Why a message's pattern couldn't be found. The error for a missing message is only made
+/// when it's raised, since looking for similar IDs to suggest is slow for large bundles.
+enum LookupError {
+    NotFound(String),
+    Other(PyErr),
+}
+
+/// The bundle and FTL resources for one language in a `Bundle`'s fallback chain.
+struct LocaleBundle {
+    /// The bundle with Unicode isolation marks around placeables.
+    bundle: FluentBundle<Arc<FluentResource>>,
+    /// The same bundle without isolation marks. Keeping both configured up front
+    /// means formatting never has to mutate a bundle that may be shared between threads.
+    non_isolating: FluentBundle<Arc<FluentResource>>,
+    resources: Vec<LoadedResource>,
+}
+
+impl LocaleBundle {
+    fn new(langids: Vec<LanguageIdentifier>, sources: Vec<SourceText>) -> Self {
+        let new_bundle = |use_isolating| {
+            let mut bundle = FluentBundle::new_concurrent(langids.clone());
+            bundle
+                .add_builtins()
+                .and_then(|_| bundle.add_function("DATETIME", dates::datetime))
+                .and_then(|_| bundle.add_function("LIST", lists::list))
+                .expect("built-in functions are only registered once");
+            bundle.set_formatter(Some(format_value));
+            bundle.set_use_isolating(use_isolating);
+            bundle
+        };
+        let mut locale = Self {
+            bundle: new_bundle(true),
+            non_isolating: new_bundle(false),
+            resources: Vec::with_capacity(sources.len()),
+        };
+        // Parse the sources in parallel, then add them in order,
+        // so that later resources still override earlier ones.
+        let resources: Vec<_> = sources.into_par_iter().map(SourceText::parse).collect();
+        for loaded in resources {
+            locale.push_resource(loaded);
+        }
+        locale
+    }
+
+    /// Add a parsed resource to the bundle, overriding any existing messages.
+    fn push_resource(&mut self, loaded: LoadedResource) {
+        self.bundle
+            .add_resource_overriding(Arc::clone(&loaded.resource));
+        self.non_isolating
+            .add_resource_overriding(Arc::clone(&loaded.resource));
+        self.resources.push(loaded);
+    }
+
+    /// Return the sources of the resources, without the messages and terms for which `remove`
+    /// returns true, given the index of their resource and their ID (with a term's `-`).
+    fn sources_without(&self, remove: impl Fn(usize, &str) -> bool) -> Vec<SourceText> {
+        self.resources
+            .iter()
+            .enumerate()
+            .map(|(index, loaded)| {
+                let removed =
+                    |entry: &ast::Entry<&str>| entry_id(entry).is_some_and(|id| remove(index, &id));
+                let contents = if loaded.resource.entries().any(removed) {
+                    let mut resource = loaded.full_ast();
+                    resource.body.retain(|entry| !removed(entry));
+                    // Keep invalid entries, so that their syntax errors are still reported.
+                    serialize_with_options(&resource, SerializeOptions { with_junk: true })
+                } else {
+                    loaded.resource.source().to_string()
+                };
+                SourceText {
+                    name: loaded.name.clone(),
+                    path: loaded.path.clone(),
+                    file_hash: loaded.file_hash,
+                    contents,
+                }
+            })
+            .collect()
+    }
+
+    /// Serialize the resources back to FTL, in order, leaving out the messages and terms that
+    /// are overridden by a later definition.
+    fn to_ftl(&self, with_junk: bool) -> String {
+        let resources = self
+            .resources
+            .iter()
+            .map(LoadedResource::full_ast)
+            .collect();
+        serialize_in_effect(resources, with_junk)
+    }
+
+    /// Rebuild the bundle with only the first definition of each message and term that's
+    /// defined in several files, rather than the last.
+    fn keep_first_definitions(self) -> Self {
+        let mut first_files: HashMap<String, usize> = HashMap::new();
+        for (index, loaded) in self.resources.iter().enumerate() {
+            for id in loaded.resource.entries().filter_map(entry_id) {
+                first_files.entry(id).or_insert(index);
+            }
+        }
+        if !self.resources.iter().enumerate().any(|(index, loaded)| {
+            loaded
+                .resource
+                .entries()
+                .filter_map(entry_id)
+                .any(|id| first_files[&id] < index)
+        }) {
+            return self;
+        }
+        let sources = self.sources_without(|index, id| first_files[id] < index);
+        Self::new(self.bundle.locales.clone(), sources)
+    }
+
+    /// Apply a pseudo-localization transform to the text of both bundles' messages, if given.
+    fn set_pseudo(&mut self, pseudo: Option<Pseudo>) {
+        let transform = pseudo.map(Pseudo::transform);
+        self.bundle.set_transform(transform);
+        self.non_isolating.set_transform(transform);
+    }
+
+    /// Add a Python function to both bundles.
+    fn add_function(&mut self, name: &str, function: &Bound<'_, PyAny>) -> PyResult<()> {
+        for bundle in [&mut self.bundle, &mut self.non_isolating] {
+            bundle
+                .add_function(name, python_function(function.clone().unbind()))
+                .map_err(|_| {
+                    PyValueError::new_err(format!("Function '{name}' is already registered."))
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Return the IDs of the messages in this language, each followed by its attributes
+    /// (as `message.attribute`), in file order.
+    fn message_ids(&self) -> Vec<String> {
+        let mut seen = HashSet::new();
+        let mut message_ids = vec![];
+        for loaded in self.resources.iter() {
+            for entry in loaded.resource.entries() {
+                let ast::Entry::Message(message) = entry else {
+                    continue;
+                };
+                let message_id = message.id.name;
+                // The attributes are those of the message that overrides any others.
+                if seen.insert(message_id)
+                    && let Some(message) = self.bundle.get_message(message_id)
+                {
+                    message_ids.push(message_id.to_string());
+                    message_ids.extend(
+                        message
+                            .attributes()
+                            .map(|attribute| format!("{message_id}.{}", attribute.id())),
+                    );
+                }
+            }
+        }
+        message_ids
+    }
+
+    /// The bundle to format messages with.
+    fn formatter(&self, use_isolating: bool) -> &FluentBundle<Arc<FluentResource>> {
+        if use_isolating {
+            &self.bundle
+        } else {
+            &self.non_isolating
+        }
+    }
+}
+
+#[pymodule]
+mod rustfluent {
+    use super::*;
+    use pyo3::types::{PyDict, PyIterator, PyList};
+
+    #[pymodule_export]
+    use super::ParserError;
+
+    #[pymodule_export]
+    use super::FormatError;
+
+    #[pymodule_export]
+    use super::CoverageError;
+
+    #[pymodule_export]
+    use super::{
+        BundleValidationError, CyclicReferenceError, DuplicateMessageError,
+        IgnoredPositionalArgumentError, LintError, MarkupMismatchError, MissingPluralCategoryError,
+        PlaceholderMismatchError, UnknownMessageError, UnknownTermError, VariableDocMismatchError,
+    };
+
+    #[pymodule_init]
+    fn init(module: &Bound<'_, PyModule>) -> PyResult<()> {
+        module.add("ParserErrorGroup", parser_error_group(module.py())?)
+    }
+
+    #[pymodule_export]
+    use super::{
+        CyclicReferenceWarning, DuplicateMessageWarning, FluentWarning,
+        IgnoredPositionalArgumentWarning, LintWarning, MarkupMismatchWarning,
+        MissingPluralCategoryWarning, ParseWarning, PlaceholderMismatchWarning,
+        UnknownMessageWarning, UnknownTermWarning, ValidationWarning, VariableDocMismatchWarning,
+    };
+
+    #[pymodule_export]
+    use super::MessageGroup;
+
+    #[pymodule_export]
+    use super::MessageDiff;
+
+    #[pymodule_export]
+    use super::ResourceDiff;
+
+    #[pymodule_export]
+    use super::MergeResult;
+
+    #[pymodule_export]
+    use super::MergeConflict;
+
+    #[pymodule_export]
+    use super::Message;
+
+    #[pymodule_export]
+    use super::Junk;
+
+    #[pymodule_export]
+    use super::MessageSource;
+
+    #[pymodule_export]
+    use super::SearchMatch;
+
+    #[pymodule_export]
+    use super::VariableDoc;
+
+    #[pymodule_export]
+    use super::MessagePart;
+
+    #[pymodule_export]
+    use super::Locale;
+
+    #[pymodule_export]
+    use super::Number;
+
+    #[pymodule_export]
+    use super::MessageStats;
+
+    #[pymodule_export]
+    use super::ValidationError;
+
+    /// The languages passed to `Bundle`: a single language, or a fallback chain.
+    #[derive(FromPyObject)]
+    enum Languages {
+        One(LanguageTag),
+        Chain(Vec<LanguageTag>),
+    }
+
+    /// The identifier passed to `get_translation`: a single message, or a list of
+    /// messages to try in order.
+    #[derive(FromPyObject)]
+    enum Identifiers {
+        One(String),
+        Chain(Vec<String>),
+    }
+
+    /// The languages passed to `negotiate_languages`: an HTTP `Accept-Language` header, or a
+    /// list of languages in order of preference.
+    #[derive(FromPyObject)]
+    enum RequestedLanguages {
+        Header(String),
+        List(Vec<LanguageTag>),
+    }
+
+    /// The FTL passed to `format_ftl`: source as a `str`, or the path of a file.
+    #[derive(FromPyObject)]
+    enum SourceOrPath {
+        Source(String),
+        Path(PathBuf),
+    }
+
+    /// The FTL files passed to `Bundle`: one list shared by all the languages,
+    /// or a mapping of language to files.
+    #[derive(FromPyObject)]
+    enum FtlFilenames {
+        Shared(Vec<FtlSource>),
+        PerLanguage(HashMap<String, Vec<FtlSource>>),
+    }
+
+    /// A pickled bundle: its resources and settings in the format of a cache file, and its
+    /// functions, adapters and transform.
+    type PickleState = (
+        Vec<u8>,
+        Vec<(String, Py<PyAny>)>,
+        Vec<(Py<PyType>, Py<PyAny>)>,
+        Option<Py<PyAny>>,
+    );
+
+    /// How a bundle loads, checks and formats its messages: the options passed to `Bundle`
+    /// besides its languages and FTL files, and its variable schema.
+    struct BundleSettings {
+        variable_separator: String,
+        /// Whether FTL files added later raise a `ParserError` for syntax errors.
+        strict_parse: bool,
+        /// Whether reloading the bundle raises for validation errors.
+        strict_validation: bool,
+        /// Whether reloading the bundle warns about errors that don't raise.
+        warn: bool,
+        /// The validation checks to report, and which of them are warnings.
+        validation_rules: ValidationRules,
+        /// The language whose messages the other languages' placeables are checked against.
+        reference_language: Option<LanguageIdentifier>,
+        /// The style rules that messages are checked against.
+        lint: LintRules,
+        /// Validation errors which don't fail the bundle in strict mode.
+        baseline: Option<Baseline>,
+        /// How the bundle's FTL files are decoded when they're reloaded.
+        decoding: Decoding,
+        /// Which definition of a message in several files is used, and whether it's reported.
+        on_duplicate: DuplicatePolicy,
+        /// The prefixes for the IDs of the messages in FTL files, by file name, to add again
+        /// when the files are reloaded.
+        prefixes: HashMap<String, String>,
+        /// The pseudo-localization transform for formatted messages, if any.
+        pseudo: Option<Pseudo>,
+        /// Whether isolation marks are removed from formatted messages, unless a call says
+        /// otherwise.
+        strip_isolation: bool,
+        /// The `zoneinfo.ZoneInfo` to show timezone-aware datetime variables in, if any.
+        timezone: Option<Py<PyAny>>,
+        /// How `None` variables are passed to Fluent.
+        none_as: NonePolicy,
+        /// How `bool` variables are passed to Fluent.
+        bool_as: BoolPolicy,
+        /// How the messages of exceptions for errors in FTL files are rendered.
+        diagnostic_style: DiagnosticStyle,
+        /// The types of the variables of each message, set with `set_variable_schema`.
+        variable_schema: HashMap<String, HashMap<String, VariableType>>,
+    }
+
+    impl Default for BundleSettings {
+        fn default() -> Self {
+            Self {
+                variable_separator: "_".to_string(),
+                strict_parse: false,
+                strict_validation: false,
+                warn: false,
+                validation_rules: ValidationRules::default(),
+                reference_language: None,
+                lint: LintRules::default(),
+                baseline: None,
+                decoding: Decoding::new("utf-8", "strict"),
+                on_duplicate: DuplicatePolicy::Warn,
+                prefixes: HashMap::new(),
+                pseudo: None,
+                strip_isolation: false,
+                timezone: None,
+                none_as: NonePolicy::Missing,
+                bool_as: BoolPolicy::String,
+                diagnostic_style: DiagnosticStyle::Rich,
+                variable_schema: HashMap::new(),
+            }
+        }
+    }
+
+    impl BundleSettings {
+        fn clone_ref(&self, py: Python<'_>) -> Self {
+            Self {
+                variable_separator: self.variable_separator.clone(),
+                strict_parse: self.strict_parse,
+                strict_validation: self.strict_validation,
+                warn: self.warn,
+                validation_rules: self.validation_rules.clone(),
+                reference_language: self.reference_language.clone(),
+                lint: self.lint.clone_ref(py),
+                baseline: self.baseline.clone(),
+                decoding: self.decoding.clone(),
+                on_duplicate: self.on_duplicate,
+                prefixes: self.prefixes.clone(),
+                pseudo: self.pseudo,
+                strip_isolation: self.strip_isolation,
+                timezone: self
+                    .timezone
+                    .as_ref()
+                    .map(|timezone| timezone.clone_ref(py)),
+                none_as: self.none_as,
+                bool_as: self.bool_as,
+                diagnostic_style: self.diagnostic_style,
+                variable_schema: self.variable_schema.clone(),
+            }
+        }
+
+        /// The settings as a JSON object of the keyword arguments of `Bundle`, and the variable
+        /// schema, for cache files, which keep `variable_separator` and `strict` separately.
+        fn to_json(&self, py: Python<'_>) -> PyResult<String> {
+            let settings = PyDict::new(py);
+            let (rules, ignore, severity) = self.validation_rules.arguments();
+            settings.set_item("strict_validation", self.strict_validation)?;
+            settings.set_item("warn", self.warn)?;
+            settings.set_item("rules", rules)?;
+            settings.set_item("ignore", ignore)?;
+            settings.set_item("severity", severity)?;
+            settings.set_item(
+                "reference_language",
+                self.reference_language.as_ref().map(ToString::to_string),
+            )?;
+            settings.set_item("lint", self.lint.config(py)?)?;
+            settings.set_item(
+                "baseline",
+                self.baseline
+                    .as_ref()
+                    .map(|baseline| baseline.path.to_string_lossy()),
+            )?;
+            settings.set_item("encoding", &self.decoding.encoding)?;
+            settings.set_item("errors", &self.decoding.errors)?;
+            settings.set_item("on_duplicate", self.on_duplicate.as_str())?;
+            settings.set_item("prefixes", &self.prefixes)?;
+            settings.set_item("pseudo", self.pseudo.map(Pseudo::as_str))?;
+            settings.set_item("strip_isolation", self.strip_isolation)?;
+            // Timezones are only created from their names.
+            let timezone = self
+                .timezone
+                .as_ref()
+                .map(|timezone| timezone.getattr(py, "key"));
+            settings.set_item("timezone", timezone.transpose()?)?;
+            settings.set_item("none_as", self.none_as.as_str())?;
+            settings.set_item("bool_as", self.bool_as.as_str())?;
+            settings.set_item("diagnostic_style", self.diagnostic_style.as_str())?;
+            let variable_schema: HashMap<&str, HashMap<&str, &str>> = self
+                .variable_schema
+                .iter()
+                .map(|(id, types)| {
+                    let types = types
+                        .iter()
+                        .map(|(name, variable_type)| (name.as_str(), variable_type.as_str()))
+                        .collect();
+                    (id.as_str(), types)
+                })
+                .collect();
+            settings.set_item("variable_schema", variable_schema)?;
+            to_json(py, settings)
+        }
+
+        /// Read settings written by `to_json`, checking them as `Bundle` does. The baseline is
+        /// read again from its file.
+        fn from_json(
+            py: Python<'_>,
+            variable_separator: String,
+            strict_parse: bool,
+            json: &str,
+        ) -> PyResult<Self> {
+            let settings = py.import("json")?.call_method1("loads", (json,))?;
+            let get = |name: &str| settings.get_item(name);
+            let reference_language = get("reference_language")?
+                .extract::<Option<String>>()?
+                .map(|language| {
+                    language.parse().map_err(|_| {
+                        PyValueError::new_err(format!("Invalid language: '{language}'"))
+                    })
+                })
+                .transpose()?;
+            let rules: Option<Bound<'_, PyAny>> = get("rules")?.extract()?;
+            let baseline: Option<PathBuf> = get("baseline")?.extract()?;
+            let pseudo: Option<String> = get("pseudo")?.extract()?;
+            let timezone: Option<String> = get("timezone")?.extract()?;
+            let variable_schema: HashMap<String, HashMap<String, String>> =
+                get("variable_schema")?.extract()?;
+            Ok(Self {
+                variable_separator,
+                strict_parse,
+                strict_validation: get("strict_validation")?.extract()?,
+                warn: get("warn")?.extract()?,
+                validation_rules: ValidationRules::new(
+                    rules.map(|rules| string_set(&rules)).transpose()?,
+                    string_set(&get("ignore")?)?,
+                    get("severity")?.extract()?,
+                )?,
+                reference_language,
+                lint: LintRules::new(py, get("lint")?.extract()?)?,
+                baseline: baseline.map(|path| Baseline::load(py, path)).transpose()?,
+                decoding: Decoding::new(
+                    &get("encoding")?.extract::<String>()?,
+                    &get("errors")?.extract::<String>()?,
+                ),
+                on_duplicate: DuplicatePolicy::new(&get("on_duplicate")?.extract::<String>()?)?,
+                prefixes: get("prefixes")?.extract()?,
+                pseudo: pseudo.as_deref().map(Pseudo::new).transpose()?,
+                strip_isolation: get("strip_isolation")?.extract()?,
+                timezone: timezone.map(|name| zone_info(py, &name)).transpose()?,
+                none_as: NonePolicy::new(&get("none_as")?.extract::<String>()?)?,
+                bool_as: BoolPolicy::new(&get("bool_as")?.extract::<String>()?)?,
+                diagnostic_style: DiagnosticStyle::new(
+                    &get("diagnostic_style")?.extract::<String>()?,
+                )?,
+                variable_schema: variable_schema
+                    .into_iter()
+                    .map(|(id, types)| {
+                        let types = types
+                            .into_iter()
+                            .filter_map(|(name, type_name)| {
+                                Some((name, VariableType::from_name(&type_name)?))
+                            })
+                            .collect();
+                        (id, types)
+                    })
+                    .collect(),
+            })
+        }
+    }
+
+    #[pyclass]
+    struct Bundle {
+        /// The bundles to look messages up in, in order of preference.
+        locales: Vec<LocaleBundle>,
+        settings: BundleSettings,
+        /// The functions added with `add_function`, to add again when the bundle is reloaded.
+        functions: Vec<(String, Py<PyAny>)>,
+        /// How often each message has been formatted, once `enable_stats` is called.
+        stats: Option<Stats>,
+        /// The Python callable set with `set_transform`, which formatted messages are passed
+        /// through after any pseudo-localization.
+        transform: Option<Py<PyAny>>,
+        /// The converters added with `register_adapter`, for variables of each type.
+        adapters: Vec<(Py<PyType>, Py<PyAny>)>,
+        /// How many times the bundle's resources have changed, so that compiled messages
+        /// know to look their message up again.
+        generation: u64,
+    }
+
+    #[pymethods]
+    impl Bundle {
+        #[new]
+        #[pyo3(signature = (language, ftl_filenames, strict=false, variable_separator="_", rules=None, ignore=None, severity=None, baseline=None, strict_parse=None, strict_validation=None, warn=false, encoding="utf-8", errors="strict", on_duplicate="warn", prefixes=None, pseudo=None, strip_isolation=false, timezone=None, none_as="missing", bool_as="string", reference_language=None, lint=None, diagnostic_style="rich"))]
+        #[allow(clippy::too_many_arguments)]
+        fn new(
+            py: Python<'_>,
+            language: Languages,
+            ftl_filenames: FtlFilenames,
+            strict: bool,
+            variable_separator: &str,
+            rules: Option<&Bound<'_, PyAny>>,
+            ignore: Option<&Bound<'_, PyAny>>,
+            severity: Option<HashMap<String, String>>,
+            baseline: Option<PathBuf>,
+            strict_parse: Option<bool>,
+            strict_validation: Option<bool>,
+            warn: bool,
+            encoding: &str,
+            errors: &str,
+            on_duplicate: &str,
+            prefixes: Option<HashMap<PathBuf, String>>,
+            pseudo: Option<&str>,
+            strip_isolation: bool,
+            timezone: Option<&str>,
+            none_as: &str,
+            bool_as: &str,
+            reference_language: Option<LanguageTag>,
+            lint: Option<HashMap<String, Bound<'_, PyAny>>>,
+            diagnostic_style: &str,
+        ) -> PyResult<Self> {
+            // `strict` is shorthand for both kinds of strictness.
+            let strict_parse = strict_parse.unwrap_or(strict);
+            let strict_validation = strict_validation.unwrap_or(strict);
+            let validation_rules = ValidationRules::new(
+                rules.map(string_set).transpose()?,
+                ignore.map(string_set).transpose()?.unwrap_or_default(),
+                severity.unwrap_or_default(),
+            )?;
+            let lint = lint
+                .map(|lint| LintRules::new(py, lint))
+                .transpose()?
+                .unwrap_or_default();
+            let baseline = baseline.map(|path| Baseline::load(py, path)).transpose()?;
+            // Prefixes are given for paths or names, which are matched with the sources' names.
+            let prefixes: HashMap<String, String> = prefixes
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(name, prefix)| (name.to_string_lossy().into_owned(), prefix))
+                .collect();
+            if let Some(prefix) = prefixes.values().find(|prefix| !is_valid_prefix(prefix)) {
+                return Err(PyValueError::new_err(format!("Invalid prefix: '{prefix}'")));
+            }
+            let reference_language = reference_language
+                .map(|reference_language| {
+                    let reference_language = String::from(reference_language);
+                    reference_language.parse().map_err(|_| {
+                        PyValueError::new_err(format!(
+                            "'{reference_language}' is not one of the bundle's languages."
+                        ))
+                    })
+                })
+                .transpose()?;
+            let settings = BundleSettings {
+                variable_separator: variable_separator.to_string(),
+                strict_parse,
+                strict_validation,
+                warn,
+                validation_rules,
+                reference_language,
+                lint,
+                baseline,
+                decoding: Decoding::new(encoding, errors),
+                on_duplicate: DuplicatePolicy::new(on_duplicate)?,
+                prefixes,
+                pseudo: pseudo.map(Pseudo::new).transpose()?,
+                strip_isolation,
+                timezone: timezone.map(|name| zone_info(py, name)).transpose()?,
+                none_as: NonePolicy::new(none_as)?,
+                bool_as: BoolPolicy::new(bool_as)?,
+                diagnostic_style: DiagnosticStyle::new(diagnostic_style)?,
+                variable_schema: HashMap::new(),
+            };
+            let languages = match language {
+                Languages::One(language) => vec![language.into()],
+                Languages::Chain(languages) => languages.into_iter().map(String::from).collect(),
+            };
+            Self::load(py, languages, ftl_filenames, settings)
+        }
+
+        /// Report terms that aren't referenced by any message or term. Given the IDs of the
+        /// messages an application uses, also report messages that aren't used by it, directly
+        /// or through a reference from a used message.
+        #[pyo3(signature = (used_ids=None))]
+        fn find_unused(
+            &self,
+            used_ids: Option<&Bound<'_, PyAny>>,
+        ) -> PyResult<Vec<ValidationError>> {
+            // Accept any iterable of IDs, such as a set.
+            let used_ids = used_ids.map(string_set).transpose()?;
+            Ok(self
+                .locales
+                .iter()
+                .flat_map(|locale| find_unused(&locale.resources, used_ids.as_ref()))
+                .collect())
+        }
+
+        /// Check the bundle's messages for problems that Fluent doesn't report when parsing,
+        /// such as plural selectors missing some of the language's plural categories.
+        ///
+        /// Only the checks enabled by the bundle's `rules` and `ignore` are reported.
+        fn validation_errors(&self, py: Python<'_>) -> PyResult<Vec<ValidationError>> {
+            let mut errors = vec![];
+            for locale in self.locales.iter() {
+                errors.extend(self.locale_validation_errors(py, locale)?);
+            }
+            Ok(errors)
+        }
+
+        /// Run the bundle's validation checks, raising its validation errors as a strict bundle
+        /// does when it's created. Bundles only check themselves when they're created with
+        /// `strict_validation` or `warn`, so that other bundles load faster.
+        fn validate(&self, py: Python<'_>) -> PyResult<()> {
+            self.check(py, false, true, false)
+        }
+
+        /// Write the bundle's current validation errors to a baseline file, accepting them so
+        /// that only new errors fail the bundle in strict mode.
+        ///
+        /// Writes to the bundle's `baseline` file unless another path is given.
+        #[pyo3(signature = (path=None))]
+        fn write_baseline(&self, py: Python<'_>, path: Option<PathBuf>) -> PyResult<()> {
+            let path = path
+                .or_else(|| {
+                    self.settings
+                        .baseline
+                        .as_ref()
+                        .map(|baseline| baseline.path.clone())
+                })
+                .ok_or_else(|| {
+                    PyValueError::new_err("No path given, and the bundle has no baseline file.")
+                })?;
+            Baseline::write(py, &path, &self.validation_errors(py)?)
+        }
+
+        /// Return a report of the bundle's syntax errors (which are skipped outside strict
+        /// mode) and validation errors, with the file, location, type and message of each.
+        #[pyo3(signature = (format="json"))]
+        fn error_report(&self, py: Python<'_>, format: &str) -> PyResult<String> {
+            if format != "json" {
+                return Err(PyValueError::new_err(format!(
+                    "format must be 'json', not '{format}'."
+                )));
+            }
+            let resources = self.locales.iter().flat_map(|locale| &locale.resources);
+            let parse_severity = self.settings.validation_rules.severity("ParseError");
+            json_report(py, resources, parse_severity, &self.validation_errors(py)?)
+        }
+
+        /// Return the messages and terms of one of the bundle's languages (by default the
+        /// first), in file order, each with the IDs of the messages and terms it references, as
+        /// a dict, or in the DOT language of Graphviz with `format="dot"`.
+        #[pyo3(signature = (language=None, format="dict"))]
+        fn reference_graph<'py>(
+            &self,
+            py: Python<'py>,
+            language: Option<&str>,
+            format: &str,
+        ) -> PyResult<Bound<'py, PyAny>> {
+            let index = self.locale_index(language)?;
+            let graph = entry_references(&self.locales[index].resources);
+            match format {
+                "dict" => {
+                    let dict = PyDict::new(py);
+                    for (id, references) in graph {
+                        dict.set_item(id, references)?;
+                    }
+                    Ok(dict.into_any())
+                }
+                "dot" => Ok(PyString::new(py, &graph_dot(&graph)).into_any()),
+                _ => Err(PyValueError::new_err(format!(
+                    "format must be 'dict' or 'dot', not '{format}'."
+                ))),
+            }
+        }
+
+        /// Return the messages and terms that reference a message, or a term if the ID starts
+        /// with `-`, in file order. With `transitive`, those that reference it through other
+        /// messages and terms are included too.
+        #[pyo3(signature = (id, transitive=false, language=None))]
+        fn referencing(
+            &self,
+            id: &str,
+            transitive: bool,
+            language: Option<&str>,
+        ) -> PyResult<Vec<String>> {
+            let index = self.locale_index(language)?;
+            let graph = entry_references(&self.locales[index].resources);
+            if !graph.iter().any(|(entry, _)| entry == id) {
+                return Err(self.not_found(id));
+            }
+            Ok(referencing(&graph, id, transitive))
+        }
+
+        /// Return a copy of the bundle, with the same resources, functions and settings, which
+        /// can have resources and functions added separately.
+        fn copy(&self, py: Python<'_>) -> PyResult<Self> {
+            let sources = self
+                .locales
+                .iter()
+                .map(|locale| {
+                    locale
+                        .resources
+                        .iter()
+                        .map(|loaded| SourceText {
+                            name: loaded.name.clone(),
+                            path: loaded.path.clone(),
+                            file_hash: loaded.file_hash,
+                            contents: loaded.resource.source().to_string(),
+                        })
+                        .collect()
+                })
+                .collect();
+            self.with_sources(py, sources)
+        }
+
+        /// Return a copy of the bundle with only the messages whose IDs start with `prefix`,
+        /// and for which `predicate` returns true. Terms and comments are all kept.
+        #[pyo3(signature = (prefix=None, predicate=None))]
+        fn subset(
+            &self,
+            py: Python<'_>,
+            prefix: Option<&str>,
+            predicate: Option<&Bound<'_, PyAny>>,
+        ) -> PyResult<Self> {
+            if prefix.is_none() && predicate.is_none() {
+                return Err(PyTypeError::new_err(
+                    "subset() needs a prefix or a predicate.",
+                ));
+            }
+            let keep = |message_id: &str| -> PyResult<bool> {
+                if prefix.is_some_and(|prefix| !message_id.starts_with(prefix)) {
+                    return Ok(false);
+                }
+                match predicate {
+                    Some(predicate) => predicate.call1((message_id,))?.is_truthy(),
+                    None => Ok(true),
+                }
+            };
+            let mut sources = Vec::with_capacity(self.locales.len());
+            for locale in self.locales.iter() {
+                let mut locale_sources = Vec::with_capacity(locale.resources.len());
+                for loaded in locale.resources.iter() {
+                    let mut resource = loaded.full_ast();
+                    let mut body = Vec::with_capacity(resource.body.len());
+                    for entry in resource.body {
+                        let included = match &entry {
+                            ast::Entry::Message(message) => keep(message.id.name)?,
+                            ast::Entry::Junk { .. } => false,
+                            _ => true,
+                        };
+                        if included {
+                            body.push(entry);
+                        }
+                    }
+                    resource.body = body;
+                    // The subset isn't the file's contents, so isn't reloaded from it.
+                    locale_sources.push(SourceText {
+                        name: loaded.name.clone(),
+                        path: None,
+                        file_hash: None,
+                        contents: serialize(&resource),
+                    });
+                }
+                sources.push(locale_sources);
+            }
+            self.with_sources(py, sources)
+        }
+
+        /// Read the bundle's FTL files again, and rebuild it with their current contents.
+        /// Resources that weren't read from files, such as those added with `add_ftl_string`,
+        /// are kept. The bundle is only replaced if the files are read, and pass the checks
+        /// the bundle was created with. Returns the new validation errors.
+        fn reload(slf: &Bound<'_, Self>) -> PyResult<Vec<ValidationError>> {
+            let py = slf.py();
+            let (sources, decoding, on_duplicate, prefixes) = {
+                let this = slf.borrow();
+                let sources: Vec<_> = this
+                    .locales
+                    .iter()
+                    .map(|locale| {
+                        let sources: Vec<_> = locale
+                            .resources
+                            .iter()
+                            .map(|loaded| match &loaded.path {
+                                Some(path) => FtlSource::Path(path.clone()),
+                                None => FtlSource::Source(
+                                    loaded.name.clone(),
+                                    loaded.resource.source().to_string(),
+                                ),
+                            })
+                            .collect();
+                        (locale.bundle.locales.clone(), sources)
+                    })
+                    .collect();
+                let prefixes = this.settings.prefixes.clone();
+                (
+                    sources,
+                    this.settings.decoding.clone(),
+                    this.settings.on_duplicate,
+                    prefixes,
+                )
+            };
+            // The bundle can still be used while the files are read and parsed.
+            let mut locales = py.detach(|| {
+                sources
+                    .into_iter()
+                    .map(|(langids, sources)| {
+                        // The other sources already have their prefixes.
+                        let sources = FtlSource::load_all(sources, &decoding)?
+                            .into_iter()
+                            .map(|source| match source.path {
+                                Some(_) => source.prefixed(&prefixes),
+                                None => source,
+                            })
+                            .collect();
+                        let locale = LocaleBundle::new(langids, sources);
+                        Ok(if on_duplicate == DuplicatePolicy::FirstWins {
+                            locale.keep_first_definitions()
+                        } else {
+                            locale
+                        })
+                    })
+                    .collect::<PyResult<Vec<_>>>()
+            })?;
+
+            let mut this = slf.borrow_mut();
+            for locale in locales.iter_mut() {
+                locale.set_pseudo(this.settings.pseudo);
+                for (name, function) in this.functions.iter() {
+                    locale.add_function(name, function.bind(py))?;
+                }
+            }
+            let previous = std::mem::replace(&mut this.locales, locales);
+            if let Err(error) = this.check(
+                py,
+                this.settings.strict_parse,
+                this.settings.strict_validation,
+                this.settings.warn,
+            ) {
+                this.locales = previous;
+                return Err(error);
+            }
+            this.generation += 1;
+            this.validation_errors(py)
+        }
+
+        /// Add FTL source to the bundle, overriding any existing messages.
+        ///
+        /// The resource is added to the preferred language, unless another is given.
+        #[pyo3(signature = (name, contents, language=None))]
+        fn add_ftl_string(
+            &mut self,
+            py: Python<'_>,
+            name: &str,
+            contents: &str,
+            language: Option<&str>,
+        ) -> PyResult<()> {
+            let strict = self.settings.strict_parse
+                && self.settings.validation_rules.severity("ParseError") == "error";
+            let index = self.locale_index(language)?;
+            let locale = &mut self.locales[index];
+            let source = SourceText {
+                name: name.to_string(),
+                path: None,
+                file_hash: None,
+                contents: contents.to_string(),
+            };
+            let loaded = source.parse();
+            if strict && !loaded.errors.is_empty() {
+                let errors = loaded
+                    .errors
+                    .iter()
+                    .map(|error| syntax_exception(&loaded, error, self.settings.diagnostic_style))
+                    .collect();
+                let message =
+                    render_errors(&loaded, &loaded.errors, &[], self.settings.diagnostic_style)
+                        .unwrap_or_default();
+                return Err(error_group(py, message, errors));
+            }
+            locale.push_resource(loaded);
+            self.generation += 1;
+            Ok(())
+        }
+
+        /// Replace a message (or term) with one defined by an FTL snippet, e.g. to fix a message
+        /// without deploying new FTL files. The message is replaced in the preferred language,
+        /// unless another is given, and added if it isn't already there.
+        #[pyo3(signature = (id, ftl_snippet, language=None))]
+        fn override_message(
+            &mut self,
+            py: Python<'_>,
+            id: &str,
+            ftl_snippet: &str,
+            language: Option<&str>,
+        ) -> PyResult<()> {
+            let index = self.locale_index(language)?;
+            let loaded = SourceText {
+                name: format!("<override of {id}>"),
+                path: None,
+                file_hash: None,
+                contents: ftl_snippet.to_string(),
+            }
+            .parse();
+            if !loaded.errors.is_empty() {
+                let errors = loaded
+                    .errors
+                    .iter()
+                    .map(|error| syntax_exception(&loaded, error, self.settings.diagnostic_style))
+                    .collect();
+                let message =
+                    render_errors(&loaded, &loaded.errors, &[], self.settings.diagnostic_style)
+                        .unwrap_or_default();
+                return Err(error_group(py, message, errors));
+            }
+            let ids: Vec<String> = loaded.resource.entries().filter_map(entry_id).collect();
+            if ids != [id] {
+                return Err(PyValueError::new_err(format!(
+                    "The FTL snippet must define '{id}' and nothing else."
+                )));
+            }
+            self.remove_entry(py, index, id)?;
+            self.locales[index].push_resource(loaded);
+            self.generation += 1;
+            Ok(())
+        }
+
+        /// Remove a message (or term) from all of the bundle's languages.
+        fn remove_message(&mut self, py: Python<'_>, id: &str) -> PyResult<()> {
+            let mut found = false;
+            for index in 0..self.locales.len() {
+                found |= self.remove_entry(py, index, id)?;
+            }
+            if !found {
+                return Err(self.not_found(id));
+            }
+            Ok(())
+        }
+
+        /// Rename a message, or a term if the IDs start with `-`, in all of the bundle's
+        /// languages, along with every reference to it. Returns the updated FTL of each
+        /// resource that changed, by name, so the files can be written back.
+        fn rename<'py>(
+            &mut self,
+            py: Python<'py>,
+            old_id: &str,
+            new_id: &str,
+        ) -> PyResult<Bound<'py, PyDict>> {
+            let name = new_id.strip_prefix('-').unwrap_or(new_id);
+            if old_id.starts_with('-') != new_id.starts_with('-') || !is_valid_prefix(name) {
+                return Err(PyValueError::new_err(format!(
+                    "Invalid ID '{new_id}' to rename '{old_id}' to."
+                )));
+            }
+            let defines = |id: &str| {
+                self.locales.iter().any(|locale| {
+                    locale.resources.iter().any(|loaded| {
+                        loaded
+                            .resource
+                            .entries()
+                            .any(|entry| entry_id(entry).as_deref() == Some(id))
+                    })
+                })
+            };
+            if !defines(old_id) {
+                return Err(self.not_found(old_id));
+            }
+            if defines(new_id) {
+                return Err(PyValueError::new_err(format!("{new_id} already exists.")));
+            }
+
+            let renames = HashMap::from([(old_id, new_id.to_string())]);
+            let updated = PyDict::new(py);
+            for index in 0..self.locales.len() {
+                let locale = &self.locales[index];
+                let mut changed = false;
+                let mut sources = vec![];
+                for loaded in &locale.resources {
+                    let source = loaded.resource.source();
+                    let contents = match rename_entries(source, &renames) {
+                        Some(renamed) => {
+                            updated.set_item(&loaded.name, &renamed)?;
+                            changed = true;
+                            renamed
+                        }
+                        None => source.to_string(),
+                    };
+                    sources.push(SourceText {
+                        name: loaded.name.clone(),
+                        path: loaded.path.clone(),
+                        file_hash: loaded.file_hash,
+                        contents,
+                    });
+                }
+                if changed {
+                    self.rebuild_locale(py, index, sources)?;
+                }
+            }
+            Ok(updated)
+        }
+
+        /// Save the bundle's resources and settings to a binary cache file, for
+        /// `Bundle.from_cache`.
+        fn save_cache(&self, py: Python<'_>, path: PathBuf) -> PyResult<()> {
+            let cached = self.cached(py)?;
+            let mut writer = BufWriter::new(fs::File::create(path)?);
+            cached.write(&mut writer)?;
+            writer.flush()?;
+            Ok(())
+        }
+
+        /// Load a bundle saved with `Bundle.save_cache`.
+        ///
+        /// With `verify`, raise a `ValueError` if any of the FTL files have changed since.
+        #[staticmethod]
+        #[pyo3(signature = (path, verify=true))]
+        fn from_cache(py: Python<'_>, path: PathBuf, verify: bool) -> PyResult<Self> {
+            let cached = CachedBundle::read(&mut BufReader::new(fs::File::open(&path)?)).map_err(
+                |error| match error.kind() {
+                    io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof => {
+                        PyValueError::new_err(format!(
+                            "Invalid bundle cache {}: {error}",
+                            path.display()
+                        ))
+                    }
+                    _ => error.into(),
+                },
+            )?;
+            Self::from_cached(py, cached, verify)
+        }
+
+        /// Pickle the bundle as its resources and settings, in the cache format, and the
+        /// functions, adapters and transform added to it, which must be picklable themselves.
+        fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, PickleState)> {
+            let py = slf.py();
+            let this = slf.borrow();
+            let mut state = vec![];
+            this.cached(py)?.write(&mut state)?;
+            let mut functions = Vec::with_capacity(this.functions.len());
+            for (name, function) in this.functions.iter() {
+                check_picklable(py, &format!("Function '{name}'"), function)?;
+                functions.push((name.clone(), function.clone_ref(py)));
+            }
+            let mut adapters = Vec::with_capacity(this.adapters.len());
+            for (python_type, adapter) in this.adapters.iter() {
+                let description = format!("Adapter for '{}'", python_type.bind(py).name()?);
+                check_picklable(py, &description, adapter)?;
+                adapters.push((python_type.clone_ref(py), adapter.clone_ref(py)));
+            }
+            let transform = this
+                .transform
+                .as_ref()
+                .map(|transform| transform.clone_ref(py));
+            if let Some(transform) = &transform {
+                check_picklable(py, "Transform", transform)?;
+            }
+            Ok((
+                slf.get_type().getattr("_unpickle")?,
+                (state, functions, adapters, transform),
+            ))
+        }
+
+        /// Recreate a pickled bundle.
+        #[staticmethod]
+        #[pyo3(signature = (state, functions, adapters=vec![], transform=None))]
+        fn _unpickle(
+            py: Python<'_>,
+            state: &[u8],
+            functions: Vec<(String, Py<PyAny>)>,
+            adapters: Vec<(Py<PyType>, Py<PyAny>)>,
+            transform: Option<Py<PyAny>>,
+        ) -> PyResult<Self> {
+            let cached = CachedBundle::read(&mut &state[..]).map_err(|error| {
+                PyValueError::new_err(format!("Invalid pickled bundle: {error}"))
+            })?;
+            let mut bundle = Self::from_cached(py, cached, false)?;
+            for (name, function) in functions {
+                bundle.add_function(&name, function.bind(py))?;
+            }
+            bundle.adapters = adapters;
+            bundle.transform = transform;
+            Ok(bundle)
+        }
+
+        /// Set a Python callable to pass every formatted message through before it's returned,
+        /// e.g. `str.upper`, or remove it with `None`.
+        fn set_transform(&mut self, transform: Option<&Bound<'_, PyAny>>) -> PyResult<()> {
+            if let Some(transform) = transform
+                && !transform.is_callable()
+            {
+                return Err(PyTypeError::new_err(format!(
+                    "Transform is not callable, got {transform}."
+                )));
+            }
+            self.transform = transform.map(|transform| transform.clone().unbind());
+            Ok(())
+        }
+
+        /// Register a Python callable to convert variables of a type, e.g. `Money`, into values
+        /// Fluent supports. Adapters are tried in the order they're registered, and registering
+        /// one for a type again replaces it.
+        fn register_adapter(
+            &mut self,
+            python_type: &Bound<'_, PyType>,
+            adapter: &Bound<'_, PyAny>,
+        ) -> PyResult<()> {
+            if !adapter.is_callable() {
+                return Err(PyTypeError::new_err(format!(
+                    "Adapter for '{}' is not callable, got {adapter}.",
+                    python_type.name()?
+                )));
+            }
+            let adapter = adapter.clone().unbind();
+            match self
+                .adapters
+                .iter_mut()
+                .find(|(registered, _)| registered.is(python_type))
+            {
+                Some((_, registered)) => *registered = adapter,
+                None => self.adapters.push((python_type.clone().unbind(), adapter)),
+            }
+            Ok(())
+        }
+
+        /// Register a Python callable as a Fluent function, e.g. `PLATFORM()`.
+        fn add_function(&mut self, name: &str, function: &Bound<'_, PyAny>) -> PyResult<()> {
+            if !function.is_callable() {
+                return Err(PyTypeError::new_err(format!(
+                    "Function '{name}' is not callable, got {function}."
+                )));
+            }
+            if !is_function_name(name) {
+                return Err(PyValueError::new_err(format!(
+                    "Invalid function name: '{name}'"
+                )));
+            }
+            for locale in self.locales.iter_mut() {
+                locale.add_function(name, function)?;
+            }
+            self.functions
+                .push((name.to_string(), function.clone().unbind()));
+            Ok(())
+        }
+
+        /// Return the source that was skipped because of syntax errors, in load order, so that
+        /// broken entries aren't silently dropped outside strict mode.
+        fn get_junk(&self) -> Vec<Junk> {
+            self.locales
+                .iter()
+                .flat_map(|locale| &locale.resources)
+                .flat_map(|loaded| {
+                    loaded.junk(self.settings.validation_rules.severity("ParseError"))
+                })
+                .collect()
+        }
+
+        /// Return the syntax errors (as the `Junk` skipped because of them) and validation errors
+        /// of each of the bundle's files which has any, by file name, in load order. Validation
+        /// errors that aren't in a file are under `None`.
+        fn get_errors_by_file<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+            let resources: Vec<&LoadedResource> = self
+                .locales
+                .iter()
+                .flat_map(|locale| &locale.resources)
+                .collect();
+            let validation_errors = self.validation_errors(py)?;
+            let mut names: Vec<Option<&str>> = vec![];
+            for name in resources
+                .iter()
+                .map(|loaded| Some(loaded.name.as_str()))
+                .chain(validation_errors.iter().map(ValidationError::filename))
+            {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+            let errors_by_file = PyDict::new(py);
+            for name in names {
+                let junk: Vec<Junk> = resources
+                    .iter()
+                    .filter(|loaded| Some(loaded.name.as_str()) == name)
+                    .flat_map(|loaded| {
+                        loaded.junk(self.settings.validation_rules.severity("ParseError"))
+                    })
+                    .collect();
+                let file_errors: Vec<ValidationError> = validation_errors
+                    .iter()
+                    .filter(|error| error.filename() == name)
+                    .cloned()
+                    .collect();
+                if junk.is_empty() && file_errors.is_empty() {
+                    continue;
+                }
+                let errors = PyDict::new(py);
+                errors.set_item("parse", junk)?;
+                errors.set_item("validation", file_errors)?;
+                errors_by_file.set_item(name, errors)?;
+            }
+            Ok(errors_by_file)
+        }
+
+        /// Serialize the resources of one of the bundle's languages (by default the first) back
+        /// to FTL, with their comments. Messages and terms overridden by a later definition are
+        /// left out.
+        #[pyo3(signature = (language=None, with_junk=false))]
+        fn to_ftl(&self, language: Option<&str>, with_junk: bool) -> PyResult<String> {
+            let index = self.locale_index(language)?;
+            Ok(self.locales[index].to_ftl(with_junk))
+        }
+
+        /// Export the bundle's messages as a flat JSON object mapping each ID to its value, in
+        /// file order, for JavaScript clients. With the `"placeholder"` mode, values are
+        /// formatted with i18next-style `{{name}}` placeholders for variables and the default
+        /// variant of select expressions; with `"raw"`, they're FTL source. With
+        /// `include_attributes`, attributes are included as `message.attribute`.
+        #[pyo3(signature = (variables_mode="placeholder", include_attributes=false))]
+        fn to_json(
+            &self,
+            py: Python<'_>,
+            variables_mode: &str,
+            include_attributes: bool,
+        ) -> PyResult<String> {
+            let raw = match variables_mode {
+                "placeholder" => false,
+                "raw" => true,
+                _ => {
+                    return Err(PyValueError::new_err(format!(
+                        "variables_mode must be 'placeholder' or 'raw', not '{variables_mode}'."
+                    )));
+                }
+            };
+            let messages = PyDict::new(py);
+            for (locale, message_id, message) in self.messages() {
+                let value = |pattern| self.json_value(locale, pattern, raw);
+                if let Some(pattern) = message.value() {
+                    messages.set_item(message_id, value(pattern))?;
+                }
+                if include_attributes {
+                    for attribute in message.attributes() {
+                        messages.set_item(
+                            format!("{message_id}.{}", attribute.id()),
+                            value(attribute.value()),
+                        )?;
+                    }
+                }
+            }
+            to_json(py, messages)
+        }
+
+        /// Start counting how many times each message is formatted, and how many of those had
+        /// errors. Counting again after it's started has no effect.
+        fn enable_stats(&mut self) {
+            self.stats.get_or_insert_with(Stats::default);
+        }
+
+        /// Return the counts since `enable_stats` as a dict mapping each message (and any
+        /// `message.attribute` that's been formatted) to its `MessageStats`, in file order,
+        /// including messages that haven't been formatted. With `reset`, counting starts again.
+        #[pyo3(signature = (reset=false))]
+        fn stats<'py>(&self, py: Python<'py>, reset: bool) -> PyResult<Bound<'py, PyDict>> {
+            let stats = self.stats.as_ref().ok_or_else(|| {
+                PyValueError::new_err("Stats aren't enabled; call enable_stats() first.")
+            })?;
+            let dict = PyDict::new(py);
+            for (identifier, message_stats) in stats.snapshot(&self.message_ids(false), reset) {
+                dict.set_item(identifier, message_stats)?;
+            }
+            Ok(dict)
+        }
+
+        /// Return the IDs of the messages tagged with a tag, in file order. Messages are tagged
+        /// by `tags:` lines in their comment, their group's comment, or their file's comments.
+        fn messages_with_tag(&self, tag: &str) -> Vec<String> {
+            let tags: Vec<_> = self
+                .locales
+                .iter()
+                .map(|locale| message_tags(&locale.resources))
+                .collect();
+            self.messages()
+                .into_iter()
+                .filter(|(locale, message_id, _)| {
+                    tags[*locale]
+                        .get(*message_id)
+                        .is_some_and(|tags| tags.iter().any(|other| other == tag))
+                })
+                .map(|(_, message_id, _)| message_id.to_string())
+                .collect()
+        }
+
+        /// Return the `##` message groups from the FTL files, in load order.
+        fn groups(&self) -> Vec<MessageGroup> {
+            self.locales
+                .iter()
+                .flat_map(|locale| message_groups(&locale.resources))
+                .collect()
+        }
+
+        /// Look up a message (or `message.attribute`) once, returning a callable
+        /// that formats it without repeating the lookup.
+        fn compile(slf: &Bound<'_, Self>, identifier: &str) -> PyResult<CompiledMessage> {
+            let this = slf.borrow();
+            let (message_id, attribute_id) = match identifier.split_once('.') {
+                Some((message_id, attribute_id)) => (message_id, Some(attribute_id)),
+                None => (identifier, None),
+            };
+
+            let (locale, loaded, entry) = this
+                .find_message_entry(message_id)
+                .ok_or_else(|| this.not_found(message_id))?;
+            let resource = &loaded.resource;
+            let Some(ast::Entry::Message(message)) = resource.get_entry(entry) else {
+                unreachable!("entry was found as a message");
+            };
+
+            let attribute = match attribute_id {
+                Some(attribute_id) => Some(
+                    message
+                        .attributes
+                        .iter()
+                        .position(|attribute| attribute.id.name == attribute_id)
+                        .ok_or_else(|| {
+                            PyValueError::new_err(format!(
+                                "{identifier} - Attribute '{attribute_id}' not found on message '{message_id}'."
+                            ))
+                        })?,
+                ),
+                None => None,
+            };
+            let pattern = resource
+                .get_entry(entry)
+                .and_then(|entry| message_pattern(entry, attribute))
+                .ok_or_else(|| {
+                    PyValueError::new_err(format!("{identifier} - Message has no value."))
+                })?;
+
+            Ok(CompiledMessage {
+                bundle: slf.clone().unbind(),
+                generation: this.generation,
+                locale,
+                identifier: identifier.to_string(),
+                resource: Arc::clone(resource),
+                entry,
+                attribute,
+                variables: pattern_variables(pattern, |id| {
+                    this.locales[locale].bundle.get_message(id)
+                }),
+            })
+        }
+
+        /// Return the names of the variables needed to format a message (or `message.attribute`),
+        /// including those used by the messages it references.
+        fn get_required_variables(&self, identifier: &str) -> PyResult<Vec<String>> {
+            let (locale, pattern) = self.find_pattern(identifier)?;
+            Ok(pattern_variables(pattern, |id| {
+                self.locales[locale].bundle.get_message(id)
+            }))
+        }
+
+        /// Return the variables needed by every message and attribute in the bundle, as a dict
+        /// mapping each identifier (`message` or `message.attribute`) to its variables.
+        fn get_required_variables_all<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+            let variables = PyDict::new(py);
+            for (locale, message_id, message) in self.messages() {
+                let get_message = |id: &str| self.locales[locale].bundle.get_message(id);
+                if let Some(pattern) = message.value() {
+                    variables.set_item(message_id, pattern_variables(pattern, get_message))?;
+                }
+                for attribute in message.attributes() {
+                    variables.set_item(
+                        format!("{message_id}.{}", attribute.id()),
+                        pattern_variables(attribute.value(), get_message),
+                    )?;
+                }
+            }
+            Ok(variables)
+        }
+
+        /// Search the text of the bundle's messages and terms, in every language, for some text
+        /// (matching any whitespace between its words) or a regular expression, returning where
+        /// each match is, in file order.
+        #[pyo3(signature = (pattern, regex=false, in_attributes=true))]
+        fn search(
+            &self,
+            py: Python<'_>,
+            pattern: &str,
+            regex: bool,
+            in_attributes: bool,
+        ) -> PyResult<Vec<SearchMatch>> {
+            let regex = search_pattern(py, pattern, regex)?;
+            let mut matches = vec![];
+            for locale in self.locales.iter() {
+                matches.extend(search(&regex, &locale.resources, in_attributes)?);
+            }
+            Ok(matches)
+        }
+
+        /// Return the IDs of the messages that use a variable (with or without its `$`), directly
+        /// or through the messages they reference, in file order. With `include_attributes`,
+        /// each message's value and attributes are listed separately, as `message.attribute`.
+        #[pyo3(signature = (name, include_attributes=false))]
+        fn messages_using_variable(&self, name: &str, include_attributes: bool) -> Vec<String> {
+            let name = name.strip_prefix('$').unwrap_or(name);
+            let mut message_ids = vec![];
+            for (locale, message_id, message) in self.messages() {
+                let get_message = |id: &str| self.locales[locale].bundle.get_message(id);
+                let uses = |pattern| {
+                    pattern_variables(pattern, get_message)
+                        .iter()
+                        .any(|variable| variable == name)
+                };
+                let value = message.value().is_some_and(uses);
+                let attributes: Vec<String> = message
+                    .attributes()
+                    .filter(|attribute| uses(attribute.value()))
+                    .map(|attribute| format!("{message_id}.{}", attribute.id()))
+                    .collect();
+                if include_attributes {
+                    if value {
+                        message_ids.push(message_id.to_string());
+                    }
+                    message_ids.extend(attributes);
+                } else if value || !attributes.is_empty() {
+                    message_ids.push(message_id.to_string());
+                }
+            }
+            message_ids
+        }
+
+        /// Return the variables needed to format a message, as for `get_required_variables`,
+        /// mapped to the type each is expected to have: "number", "string", "datetime" or
+        /// "unknown".
+        fn get_variable_types<'py>(
+            &self,
+            py: Python<'py>,
+            identifier: &str,
+        ) -> PyResult<Bound<'py, PyDict>> {
+            let (locale, pattern) = self.find_pattern(identifier)?;
+            let types = PyDict::new(py);
+            for (name, variable_type) in
+                pattern_variable_types(pattern, |id| self.locales[locale].bundle.get_message(id))
+            {
+                types.set_item(name, variable_type.as_str())?;
+            }
+            Ok(types)
+        }
+
+        #[pyo3(signature = (identifier, variables=None, use_isolating=true, strict_types=false, errors=ErrorHandling::Ignore, default=None, strip_isolation=None, strict_variables=false))]
+        #[allow(clippy::too_many_arguments)]
+        pub fn get_translation(
+            &self,
+            identifier: Identifiers,
+            variables: Option<&Bound<'_, PyAny>>,
+            use_isolating: bool,
+            strict_types: bool,
+            #[pyo3(from_py_with = error_handling)] errors: ErrorHandling,
+            default: Option<String>,
+            strip_isolation: Option<bool>,
+            strict_variables: bool,
+        ) -> PyResult<String> {
+            let identifiers = match &identifier {
+                Identifiers::One(identifier) => std::slice::from_ref(identifier),
+                Identifiers::Chain(identifiers) => identifiers.as_slice(),
+            };
+            let (identifier, locale, pattern) =
+                match (self.find_first_pattern(identifiers), default) {
+                    (Ok(found), _) => found,
+                    (Err(_), Some(default)) => return Ok(default),
+                    (Err(error), None) => return Err(self.lookup_error(error)),
+                };
+            let args = self.fluent_args(variables, strict_types || errors.raises())?;
+            let mismatch = self.variable_mismatch(
+                identifier,
+                locale,
+                pattern,
+                args.as_ref(),
+                strict_variables,
+            );
+            self.format(
+                identifier,
+                locale,
+                pattern,
+                args.as_ref(),
+                use_isolating,
+                strip_isolation,
+                &errors,
+                &mismatch,
+            )
+        }
+
+        /// Format a message (or `message.attribute`) as a list of parts: the literal text from
+        /// the FTL source, and each placeable with the variable it formats. Placeables aren't
+        /// wrapped in isolation marks, so frontends can wrap them in their own elements.
+        #[pyo3(signature = (identifier, variables=None, strict_types=false, errors=ErrorHandling::Ignore))]
+        fn get_translation_parts(
+            &self,
+            identifier: &str,
+            variables: Option<&Bound<'_, PyAny>>,
+            strict_types: bool,
+            #[pyo3(from_py_with = error_handling)] errors: ErrorHandling,
+        ) -> PyResult<Vec<MessagePart>> {
+            let (locale, pattern) = self.find_pattern(identifier)?;
+            let args = self.fluent_args(variables, strict_types || errors.raises())?;
+            let formatter = self.locales[locale].formatter(false);
+            let mut format_errors = vec![];
+            let values: Vec<String> = pattern
+                .elements
+                .iter()
+                .map(|element| {
+                    // Each element is formatted as a pattern of its own, so any transform is
+                    // applied to its text as it would be in the whole message.
+                    let element_pattern = ast::Pattern {
+                        elements: vec![element.clone()],
+                    };
+                    formatter
+                        .format_pattern(&element_pattern, args.as_ref(), &mut format_errors)
+                        .into_owned()
+                })
+                .collect();
+            let mismatch =
+                self.variable_mismatch(identifier, locale, pattern, args.as_ref(), false);
+            self.handle_format_errors(identifier, locale, &format_errors, &errors, &mismatch)?;
+            pattern
+                .elements
+                .iter()
+                .zip(values)
+                .map(|(element, value)| Ok(MessagePart::new(element, self.transformed(&value)?)))
+                .collect()
+        }
+
+        /// Return whether the bundle has a translation for a message (or `message.attribute`),
+        /// i.e. whether `get_translation` would find it.
+        fn has_message(&self, identifier: &str) -> bool {
+            let (message_id, attribute_id) = match identifier.split_once('.') {
+                Some((message_id, attribute_id)) => (message_id, Some(attribute_id)),
+                None => (identifier, None),
+            };
+            let message = self
+                .locales
+                .iter()
+                .find_map(|locale| locale.bundle.get_message(message_id));
+            match (message, attribute_id) {
+                (Some(message), Some(attribute_id)) => {
+                    message.get_attribute(attribute_id).is_some()
+                }
+                (Some(message), None) => message.value().is_some(),
+                (None, _) => false,
+            }
+        }
+
+        fn __contains__(&self, identifier: &str) -> bool {
+            self.has_message(identifier)
+        }
+
+        /// Return the IDs of the messages in the bundle, in file order. With `include_attributes`,
+        /// each message is followed by its attributes, as `message.attribute`.
+        #[pyo3(signature = (include_attributes=false))]
+        fn message_ids(&self, include_attributes: bool) -> Vec<String> {
+            let mut message_ids = vec![];
+            for (_, message_id, message) in self.messages() {
+                message_ids.push(message_id.to_string());
+                if include_attributes {
+                    message_ids.extend(
+                        message
+                            .attributes()
+                            .map(|attribute| format!("{message_id}.{}", attribute.id())),
+                    );
+                }
+            }
+            message_ids
+        }
+
+        fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyIterator>> {
+            PyList::new(py, self.message_ids(false))?.try_iter()
+        }
+
+        fn __len__(&self) -> usize {
+            self.messages().len()
+        }
+
+        /// Return the comment directly above a message, or a term if the ID starts with `-`.
+        fn get_comment(&self, id: &str) -> PyResult<Option<String>> {
+            let loaded = match id.strip_prefix('-') {
+                Some(term_id) => self
+                    .locales
+                    .iter()
+                    .find_map(|locale| find_term(&locale.resources, term_id))
+                    .map(|(loaded, _)| loaded),
+                None => self.find_message_entry(id).map(|(_, loaded, _)| loaded),
+            }
+            .ok_or_else(|| self.not_found(id))?;
+            Ok(entry_comments(loaded, id).and_then(|comments| comments.comment))
+        }
+
+        /// Return the variables documented in the comment above a message, from lines like
+        /// `$user (String) - the user's display name`, in the order they're documented.
+        fn get_variable_docs(&self, id: &str) -> PyResult<Vec<VariableDoc>> {
+            let (_, loaded, _) = self
+                .find_message_entry(id)
+                .ok_or_else(|| self.not_found(id))?;
+            let comment = entry_comments(loaded, id).and_then(|comments| comments.comment);
+            Ok(comment
+                .map(|comment| variable_docs(&comment))
+                .unwrap_or_default())
+        }
+
+        /// Set the types of the variables of messages, e.g. `{"cart-total": {"amount": "number"}}`,
+        /// which are checked when the messages are formatted. With `from_comments`, the types
+        /// documented in the messages' comments are used too, unless the schema gives others.
+        /// Calling it again replaces the schema.
+        #[pyo3(signature = (schema=None, from_comments=false))]
+        fn set_variable_schema(
+            &mut self,
+            schema: Option<HashMap<String, HashMap<String, String>>>,
+            from_comments: bool,
+        ) -> PyResult<()> {
+            let mut variable_schema: HashMap<String, HashMap<String, VariableType>> =
+                HashMap::new();
+            if from_comments {
+                for (_, message_id, _) in self.messages() {
+                    let Some((_, loaded, _)) = self.find_message_entry(message_id) else {
+                        continue;
+                    };
+                    let Some(comment) =
+                        entry_comments(loaded, message_id).and_then(|comments| comments.comment)
+                    else {
+                        continue;
+                    };
+                    for doc in variable_docs(&comment) {
+                        // Types that aren't ones Fluent has, e.g. `Money`, aren't checked.
+                        if let Some(variable_type) = doc
+                            .variable_type
+                            .as_deref()
+                            .and_then(VariableType::from_name)
+                        {
+                            variable_schema
+                                .entry(message_id.to_string())
+                                .or_default()
+                                .insert(doc.name, variable_type);
+                        }
+                    }
+                }
+            }
+            for (message_id, variables) in schema.unwrap_or_default() {
+                for (name, type_name) in variables {
+                    let variable_type = VariableType::from_name(&type_name).ok_or_else(|| {
+                        PyValueError::new_err(format!(
+                            "Unknown type '{type_name}' for ${name} in {message_id}. Expected \
+                             'string', 'number', 'datetime' or 'list'."
+                        ))
+                    })?;
+                    let name = name.strip_prefix('$').unwrap_or(&name).to_string();
+                    variable_schema
+                        .entry(message_id.clone())
+                        .or_default()
+                        .insert(name, variable_type);
+                }
+            }
+            self.settings.variable_schema = variable_schema;
+            Ok(())
+        }
+
+        /// Return the IDs of the terms in the bundle, in file order, with their leading `-`.
+        fn term_ids(&self) -> Vec<String> {
+            let mut term_ids: Vec<String> = vec![];
+            for locale in self.locales.iter() {
+                for loaded in locale.resources.iter() {
+                    for entry in loaded.resource.entries() {
+                        if let ast::Entry::Term(term) = entry {
+                            let term_id = format!("-{}", term.id.name);
+                            if !term_ids.contains(&term_id) {
+                                term_ids.push(term_id);
+                            }
+                        }
+                    }
+                }
+            }
+            term_ids
+        }
+
+        /// Return a term's value and attributes, formatted without any arguments.
+        /// The leading `-` of the ID is optional.
+        fn get_term(&self, py: Python<'_>, id: &str) -> PyResult<Term> {
+            let name = id.strip_prefix('-').unwrap_or(id);
+            let (locale, (_, term)) = self
+                .locales
+                .iter()
+                .enumerate()
+                .find_map(|(index, locale)| Some((index, find_term(&locale.resources, name)?)))
+                .ok_or_else(|| self.not_found(&format!("-{name}")))?;
 
-#[pymodule]
-mod rustfluent {
-    use super::*;
+            let bundle = self.locales[locale].formatter(false);
+            let format = |pattern| {
+                let mut errors = vec![];
+                bundle
+                    .format_pattern(pattern, None, &mut errors)
+                    .into_owned()
+            };
+            let attributes = PyDict::new(py);
+            for attribute in term.attributes.iter() {
+                attributes.set_item(attribute.id.name, format(&attribute.value))?;
+            }
+            Ok(Term {
+                id: format!("-{name}"),
+                value: format(&term.value),
+                attributes: attributes.unbind(),
+            })
+        }
 
-    #[pymodule_export]
-    use super::ParserError;
+        /// Return a message's attributes, comment and variables.
+        fn get_message(&self, id: &str) -> PyResult<Message> {
+            let (locale, loaded, entry) = self
+                .find_message_entry(id)
+                .ok_or_else(|| self.not_found(id))?;
+            let Some(ast::Entry::Message(message)) = loaded.resource.get_entry(entry) else {
+                unreachable!("entry was found as a message");
+            };
+            let comments = entry_comments(loaded, id).expect("message was found in the resource");
+            Ok(Message::new(message, comments, |id| {
+                self.locales[locale].bundle.get_message(id)
+            }))
+        }
 
-    #[pyclass]
-    struct Bundle {
-        bundle: FluentBundle<FluentResource>,
+        /// Return the FTL source of a message, or a term if the ID starts with `-`, with the
+        /// file and position it's at, from the first language that has it.
+        fn get_message_source(&self, id: &str) -> PyResult<MessageSource> {
+            let source = match id.strip_prefix('-') {
+                Some(term_id) => self
+                    .locales
+                    .iter()
+                    .find_map(|locale| find_term(&locale.resources, term_id))
+                    .and_then(|(loaded, term)| loaded.entry_source(id, term.id.name)),
+                None => self.find_message_entry(id).and_then(|(_, loaded, entry)| {
+                    match loaded.resource.get_entry(entry)? {
+                        ast::Entry::Message(message) => loaded.entry_source(id, message.id.name),
+                        _ => None,
+                    }
+                }),
+            };
+            source.ok_or_else(|| self.not_found(id))
+        }
+
+        /// Format a message's value and all of its attributes with the same variables,
+        /// returning `{"value": ..., "attributes": {...}}`.
+        #[pyo3(signature = (identifier, variables=None, use_isolating=true, strict_types=false, errors=ErrorHandling::Ignore, strip_isolation=None))]
+        #[allow(clippy::too_many_arguments)]
+        fn get_translation_with_attributes<'py>(
+            &self,
+            py: Python<'py>,
+            identifier: &str,
+            variables: Option<&Bound<'_, PyAny>>,
+            use_isolating: bool,
+            strict_types: bool,
+            #[pyo3(from_py_with = error_handling)] errors: ErrorHandling,
+            strip_isolation: Option<bool>,
+        ) -> PyResult<Bound<'py, PyDict>> {
+            let (locale, message) = self
+                .locales
+                .iter()
+                .enumerate()
+                .find_map(|(index, locale)| Some((index, locale.bundle.get_message(identifier)?)))
+                .ok_or_else(|| self.not_found(identifier))?;
+            let args = self.fluent_args(variables, strict_types || errors.raises())?;
+            let format = |identifier: &str, pattern| {
+                self.format(
+                    identifier,
+                    locale,
+                    pattern,
+                    args.as_ref(),
+                    use_isolating,
+                    strip_isolation,
+                    &errors,
+                    &self.variable_mismatch(identifier, locale, pattern, args.as_ref(), false),
+                )
+            };
+
+            let value = message
+                .value()
+                .map(|pattern| format(identifier, pattern))
+                .transpose()?;
+            let attributes = PyDict::new(py);
+            for attribute in message.attributes() {
+                let attribute_identifier = format!("{identifier}.{}", attribute.id());
+                attributes.set_item(
+                    attribute.id(),
+                    format(&attribute_identifier, attribute.value())?,
+                )?;
+            }
+            let translation = PyDict::new(py);
+            translation.set_item("value", value)?;
+            translation.set_item("attributes", attributes)?;
+            Ok(translation)
+        }
+
+        /// Format many messages at once, in parallel and without holding the GIL.
+        ///
+        /// Takes `(identifier, variables)` pairs and returns the translations in the same order.
+        #[pyo3(signature = (requests, use_isolating=true, strict_types=false, errors=ErrorHandling::Ignore, default=None, strip_isolation=None))]
+        #[allow(clippy::too_many_arguments)]
+        fn get_translations(
+            &self,
+            py: Python<'_>,
+            requests: Vec<(String, Option<Bound<'_, PyAny>>)>,
+            use_isolating: bool,
+            strict_types: bool,
+            #[pyo3(from_py_with = error_handling)] errors: ErrorHandling,
+            default: Option<String>,
+            strip_isolation: Option<bool>,
+        ) -> PyResult<Vec<String>> {
+            // Python variables have to be converted while holding the GIL.
+            let requests = requests
+                .into_iter()
+                .map(|(identifier, variables)| {
+                    Ok((
+                        identifier,
+                        self.fluent_args(variables.as_ref(), strict_types || errors.raises())?,
+                    ))
+                })
+                .collect::<PyResult<Vec<_>>>()?;
+
+            let results: Vec<PyResult<String>> = py.detach(|| {
+                requests
+                    .into_par_iter()
+                    .map(|(identifier, args)| {
+                        let (locale, pattern) = match (self.lookup_pattern(&identifier), &default) {
+                            (Ok(found), _) => found,
+                            (Err(_), Some(default)) => return Ok(default.clone()),
+                            (Err(error), None) => return Err(self.lookup_error(error)),
+                        };
+                        self.format(
+                            &identifier,
+                            locale,
+                            pattern,
+                            args.as_ref(),
+                            use_isolating,
+                            strip_isolation,
+                            &errors,
+                            &self.variable_mismatch(
+                                &identifier,
+                                locale,
+                                pattern,
+                                args.as_ref(),
+                                false,
+                            ),
+                        )
+                    })
+                    .collect()
+            });
+            results.into_iter().collect()
+        }
     }
 
-    #[pymethods]
     impl Bundle {
-        #[new]
-        #[pyo3(signature = (language, ftl_filenames, strict=false))]
-        fn new(language: &str, ftl_filenames: Vec<PathBuf>, strict: bool) -> PyResult<Self> {
-            let langid: LanguageIdentifier = match language.parse() {
-                Ok(langid) => langid,
-                Err(_) => {
-                    return Err(PyValueError::new_err(format!(
-                        "Invalid language: '{language}'"
-                    )));
+        /// Create a bundle for languages, in order of preference, from their FTL files, and
+        /// check it as its settings say.
+        fn load(
+            py: Python<'_>,
+            languages: Vec<String>,
+            ftl_filenames: FtlFilenames,
+            settings: BundleSettings,
+        ) -> PyResult<Self> {
+            if languages.is_empty() {
+                return Err(PyValueError::new_err("At least one language is required."));
+            }
+            let mut langids = Vec::with_capacity(languages.len());
+            for language in languages.iter() {
+                let langid: LanguageIdentifier = match language.parse() {
+                    Ok(langid) => langid,
+                    Err(_) => {
+                        return Err(PyValueError::new_err(format!(
+                            "Invalid language: '{language}'"
+                        )));
+                    }
+                };
+                langids.push(langid);
+            }
+            if let Some(reference_language) = &settings.reference_language
+                && !langids.contains(reference_language)
+            {
+                return Err(PyValueError::new_err(format!(
+                    "'{reference_language}' is not one of the bundle's languages."
+                )));
+            }
+
+            let prefixes = &settings.prefixes;
+            let decoding = &settings.decoding;
+            let prefixed = |sources: Vec<SourceText>| -> Vec<SourceText> {
+                sources
+                    .into_iter()
+                    .map(|source| source.prefixed(prefixes))
+                    .collect()
+            };
+            // Reading and parsing the files doesn't need the GIL.
+            let locales = py.detach(|| match ftl_filenames {
+                FtlFilenames::Shared(ftl_filenames) => Ok(vec![LocaleBundle::new(
+                    langids,
+                    prefixed(FtlSource::load_all(ftl_filenames, decoding)?),
+                )]),
+                FtlFilenames::PerLanguage(mut ftl_filenames) => {
+                    if let Some(unknown) = ftl_filenames
+                        .keys()
+                        .find(|language| !languages.contains(language))
+                    {
+                        return Err(PyValueError::new_err(format!(
+                            "FTL files given for '{unknown}', which is not one of the bundle's languages."
+                        )));
+                    }
+                    // Each language gets its own bundle, so that messages which fall back
+                    // to a later language are formatted with that language's rules.
+                    let mut locales = Vec::with_capacity(languages.len());
+                    for (index, language) in languages.iter().enumerate() {
+                        let ftl_filenames = ftl_filenames.remove(language).unwrap_or_default();
+                        locales.push(LocaleBundle::new(
+                            langids[index..].to_vec(),
+                            prefixed(FtlSource::load_all(ftl_filenames, decoding)?),
+                        ));
+                    }
+                    Ok(locales)
                 }
+            })?;
+            if let Some(unknown) = prefixes.keys().find(|name| {
+                !locales
+                    .iter()
+                    .flat_map(|locale| &locale.resources)
+                    .any(|loaded| &&loaded.name == name)
+            }) {
+                return Err(PyValueError::new_err(format!(
+                    "Prefix given for '{unknown}', which is not one of the FTL files."
+                )));
+            }
+            let locales = if settings.on_duplicate == DuplicatePolicy::FirstWins {
+                locales
+                    .into_iter()
+                    .map(LocaleBundle::keep_first_definitions)
+                    .collect()
+            } else {
+                locales
+            };
+
+            let bundle = Self::from_locales(locales, settings);
+            let settings = &bundle.settings;
+            bundle.check(
+                py,
+                settings.strict_parse,
+                settings.strict_validation,
+                settings.warn,
+            )?;
+            Ok(bundle)
+        }
+
+        /// Create a bundle from its languages' resources, without any functions.
+        fn from_locales(mut locales: Vec<LocaleBundle>, settings: BundleSettings) -> Self {
+            for locale in locales.iter_mut() {
+                locale.set_pseudo(settings.pseudo);
+            }
+            Self {
+                locales,
+                settings,
+                functions: vec![],
+                stats: None,
+                transform: None,
+                adapters: vec![],
+                generation: 0,
+            }
+        }
+
+        /// Return the variables passed to format a message (or `message.attribute`) that don't
+        /// match those it uses, with `strict_variables`, or the types in its variable schema. An
+        /// attribute has the variable schema of its message, unless it has one of its own.
+        fn variable_mismatch(
+            &self,
+            identifier: &str,
+            locale: usize,
+            pattern: &ast::Pattern<&str>,
+            args: Option<&FluentArgs>,
+            strict_variables: bool,
+        ) -> VariableMismatch {
+            let mut mismatch = if strict_variables {
+                let required =
+                    pattern_variables(pattern, |id| self.locales[locale].bundle.get_message(id));
+                VariableMismatch::new(&required, args)
+            } else {
+                VariableMismatch::default()
+            };
+            let schema = self.settings.variable_schema.get(identifier).or_else(|| {
+                let (message_id, _) = identifier.split_once('.')?;
+                self.settings.variable_schema.get(message_id)
+            });
+            if let Some(schema) = schema {
+                mismatch.check_types(schema, args);
+            }
+            mismatch
+        }
+
+        /// Return the index of one of the bundle's languages, or of the preferred language.
+        fn locale_index(&self, language: Option<&str>) -> PyResult<usize> {
+            let Some(language) = language else {
+                return Ok(0);
+            };
+            let langid: LanguageIdentifier = language
+                .parse()
+                .map_err(|_| PyValueError::new_err(format!("Invalid language: '{language}'")))?;
+            self.locales
+                .iter()
+                .position(|locale| locale.bundle.locales.first() == Some(&langid))
+                .ok_or_else(|| {
+                    PyValueError::new_err(format!(
+                        "'{language}' is not one of the bundle's languages."
+                    ))
+                })
+        }
+
+        /// Rebuild a language's bundle without the definitions of a message or term, returning
+        /// whether it had any. The resources they're removed from keep their paths, so reloading
+        /// the bundle restores them.
+        fn remove_entry(&mut self, py: Python<'_>, index: usize, id: &str) -> PyResult<bool> {
+            let locale = &self.locales[index];
+            let defines = |loaded: &LoadedResource| {
+                loaded
+                    .resource
+                    .entries()
+                    .any(|entry| entry_id(entry).as_deref() == Some(id))
             };
-            let mut bundle = FluentBundle::new_concurrent(vec![langid]);
+            if !locale.resources.iter().any(defines) {
+                return Ok(false);
+            }
+            let sources = locale.sources_without(|_, entry| entry == id);
+            self.rebuild_locale(py, index, sources)?;
+            Ok(true)
+        }
 
-            for file_path in ftl_filenames.iter() {
-                let contents = fs::read_to_string(file_path)
-                    .map_err(|_| PyFileNotFoundError::new_err(file_path.clone()))?;
+        /// Replace the resources of one of the bundle's languages with new sources, keeping its
+        /// functions and settings.
+        fn rebuild_locale(
+            &mut self,
+            py: Python<'_>,
+            index: usize,
+            sources: Vec<SourceText>,
+        ) -> PyResult<()> {
+            let locale = &self.locales[index];
+            let mut rebuilt = LocaleBundle::new(locale.bundle.locales.clone(), sources);
+            rebuilt.set_pseudo(self.settings.pseudo);
+            for (name, function) in self.functions.iter() {
+                rebuilt.add_function(name, function.bind(py))?;
+            }
+            self.locales[index] = rebuilt;
+            self.generation += 1;
+            Ok(())
+        }
+
+        /// Create a bundle with the same languages, functions and settings as this one, from
+        /// new sources for each of its languages.
+        fn with_sources(&self, py: Python<'_>, sources: Vec<Vec<SourceText>>) -> PyResult<Self> {
+            let mut locales = Vec::with_capacity(self.locales.len());
+            for (locale, sources) in self.locales.iter().zip(sources) {
+                let mut copy = LocaleBundle::new(locale.bundle.locales.clone(), sources);
+                copy.set_pseudo(self.settings.pseudo);
+                for (name, function) in self.functions.iter() {
+                    copy.add_function(name, function.bind(py))?;
+                }
+                locales.push(copy);
+            }
+            Ok(Self {
+                locales,
+                settings: self.settings.clone_ref(py),
+                functions: self
+                    .functions
+                    .iter()
+                    .map(|(name, function)| (name.clone(), function.clone_ref(py)))
+                    .collect(),
+                stats: None,
+                transform: self
+                    .transform
+                    .as_ref()
+                    .map(|transform| transform.clone_ref(py)),
+                adapters: self
+                    .adapters
+                    .iter()
+                    .map(|(python_type, adapter)| {
+                        (python_type.clone_ref(py), adapter.clone_ref(py))
+                    })
+                    .collect(),
+                generation: 0,
+            })
+        }
+
+        /// The bundle's resources and settings, in the format of a cache file.
+        fn cached(&self, py: Python<'_>) -> PyResult<CachedBundle> {
+            Ok(CachedBundle {
+                variable_separator: self.settings.variable_separator.clone(),
+                strict: self.settings.strict_parse,
+                settings: self.settings.to_json(py)?,
+                locales: self
+                    .locales
+                    .iter()
+                    .map(|locale| CachedLocale {
+                        languages: locale
+                            .bundle
+                            .locales
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect(),
+                        resources: locale
+                            .resources
+                            .iter()
+                            .map(|loaded| CachedResource {
+                                name: loaded.name.clone(),
+                                path: loaded
+                                    .path
+                                    .as_ref()
+                                    .map(|path| path.to_string_lossy().into_owned()),
+                                file_hash: loaded.file_hash.unwrap_or_default(),
+                                source: loaded.resource.source().to_string(),
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            })
+        }
 
-                let resource = match FluentResource::try_new(contents) {
-                    Ok(resource) => resource,
-                    Err((resource, errors)) if strict => {
-                        let mut labels = Vec::with_capacity(errors.len());
-                        for error in errors {
-                            labels.push(LabeledSpan::at(error.pos, format!("{}", error.kind)))
+        /// Create a bundle from a cache file's resources and settings. With `verify`, raise a `ValueError`
+        /// if any of the FTL files have changed since the cache was saved.
+        fn from_cached(py: Python<'_>, cached: CachedBundle, verify: bool) -> PyResult<Self> {
+            let settings = BundleSettings::from_json(
+                py,
+                cached.variable_separator,
+                cached.strict,
+                &cached.settings,
+            )?;
+            let mut locales = Vec::with_capacity(cached.locales.len());
+            for cached_locale in cached.locales {
+                let mut langids = Vec::with_capacity(cached_locale.languages.len());
+                for language in cached_locale.languages.iter() {
+                    langids.push(language.parse().map_err(|_| {
+                        PyValueError::new_err(format!("Invalid language: '{language}'"))
+                    })?);
+                }
+                let mut sources = Vec::with_capacity(cached_locale.resources.len());
+                for resource in cached_locale.resources {
+                    if verify && let Some(file_path) = &resource.path {
+                        // The file's bytes are compared, since the source may have been
+                        // decoded, prefixed or rewritten when it was loaded.
+                        let current = read_path(Path::new(file_path)).ok();
+                        if current.as_deref().map(file_hash) != Some(resource.file_hash) {
+                            return Err(PyValueError::new_err(format!(
+                                "Bundle cache is stale: '{file_path}' has changed."
+                            )));
                         }
-                        let error = miette!(
-                            labels = labels,
-                            "Error when parsing {}",
-                            file_path.to_string_lossy()
-                        )
-                        .with_source_code(resource.source().to_string());
-                        return Err(ParserError::new_err(format!("{error:?}")));
                     }
-                    Err((resource, _errors)) => resource,
+                    sources.push(SourceText {
+                        name: resource.name,
+                        file_hash: resource.path.is_some().then_some(resource.file_hash),
+                        path: resource.path.map(PathBuf::from),
+                        contents: resource.source,
+                    });
+                }
+                // The resources were checked when the bundle was created,
+                // so don't spend time rendering strict mode errors again.
+                locales.push(LocaleBundle::new(langids, sources));
+            }
+
+            Ok(Self::from_locales(locales, settings))
+        }
+
+        fn locale_validation_errors(
+            &self,
+            py: Python<'_>,
+            locale: &LocaleBundle,
+        ) -> PyResult<Vec<ValidationError>> {
+            let language = &locale.bundle.locales[0];
+            let mut errors = validate(
+                &locale.resources,
+                Some(language),
+                self.settings.on_duplicate.reports_overrides(),
+            );
+            // Each language is checked against the reference language's own messages.
+            let reference = self
+                .settings
+                .reference_language
+                .as_ref()
+                .filter(|reference_language| *reference_language != language)
+                .and_then(|reference_language| {
+                    self.locales
+                        .iter()
+                        .find(|other| &other.bundle.locales[0] == reference_language)
+                        .map(|other| (other.resources.as_slice(), reference_language))
+                });
+            if let Some((resources, reference_language)) = reference {
+                errors.extend(check_placeholders(
+                    &locale.resources,
+                    resources,
+                    reference_language,
+                ));
+            }
+            if self.settings.validation_rules.enabled("MarkupMismatch") {
+                errors.extend(check_markup(&locale.resources, reference));
+            }
+            errors.extend(self.settings.lint.check(py, &locale.resources)?);
+            Ok(self.settings.validation_rules.apply(errors))
+        }
+
+        /// Raise the bundle's syntax errors (with `strict_parse`) and validation errors (with
+        /// `strict_validation`) together, as a `ParserErrorGroup` whose message shows where
+        /// the errors are in each file. With `warn`, emit warnings for the errors that aren't
+        /// raised. Errors in the baseline are neither raised nor warned about.
+        fn check(
+            &self,
+            py: Python<'_>,
+            strict_parse: bool,
+            strict_validation: bool,
+            warn: bool,
+        ) -> PyResult<()> {
+            let mut failures = vec![];
+            let mut reports = vec![];
+            let mut unlocated = vec![];
+            let mut warnings = vec![];
+            let parse_errors_fail =
+                self.settings.validation_rules.severity("ParseError") == "error";
+            for locale in self.locales.iter() {
+                let raise_duplicates = self.settings.on_duplicate == DuplicatePolicy::Error;
+                let errors = if strict_validation || warn || raise_duplicates {
+                    self.locale_validation_errors(py, locale)?
+                } else {
+                    vec![]
                 };
-                bundle.add_resource_overriding(resource);
+                // Only errors with an error severity fail the bundle.
+                let (failing, passing): (Vec<_>, Vec<_>) = errors
+                    .into_iter()
+                    .filter(|error| {
+                        !self
+                            .settings
+                            .baseline
+                            .as_ref()
+                            .is_some_and(|baseline| baseline.contains(error))
+                    })
+                    .partition(|error| {
+                        (strict_validation && error.is_error())
+                            || (raise_duplicates && error.error_type() == "DuplicateMessageId")
+                    });
+                warnings.extend(passing.into_iter().filter(|error| !error.is_info()));
+
+                for loaded in locale.resources.iter() {
+                    let syntax_errors: &[_] = if strict_parse && parse_errors_fail {
+                        &loaded.errors
+                    } else {
+                        &[]
+                    };
+                    let validation_errors: Vec<&ValidationError> = failing
+                        .iter()
+                        .filter(|error| error.filename() == Some(loaded.name.as_str()))
+                        .collect();
+                    reports.extend(render_errors(
+                        loaded,
+                        syntax_errors,
+                        &validation_errors,
+                        self.settings.diagnostic_style,
+                    ));
+                    failures.extend(syntax_errors.iter().map(|error| {
+                        syntax_exception(loaded, error, self.settings.diagnostic_style)
+                    }));
+                }
+                for error in failing.iter() {
+                    if error.filename().is_none() {
+                        unlocated.push(error.to_string());
+                    }
+                    failures.push(validation_exception(py, error));
+                }
             }
 
-            Ok(Self { bundle })
+            if !unlocated.is_empty() {
+                reports.push(format!(
+                    "Validation errors in bundle:\n{}",
+                    unlocated.join("\n")
+                ));
+            }
+            if !failures.is_empty() {
+                return Err(error_group(py, reports.join("\n"), failures));
+            }
+            if warn {
+                self.warn(py, &warnings)?;
+            }
+            Ok(())
         }
 
-        #[pyo3(signature = (identifier, variables=None, use_isolating=true))]
-        pub fn get_translation(
-            &mut self,
-            identifier: &str,
-            variables: Option<&Bound<'_, PyDict>>,
-            use_isolating: bool,
-        ) -> PyResult<String> {
-            self.bundle.set_use_isolating(use_isolating);
+        /// Emit a Python warning for each syntax error that was skipped (unless their severity
+        /// is `info`), and each of the given validation errors, so that warning filters can
+        /// report or raise them.
+        fn warn(&self, py: Python<'_>, errors: &[ValidationError]) -> PyResult<()> {
+            let warnings = py.import("warnings")?;
+            let parse_errors_warn = self.settings.validation_rules.severity("ParseError") != "info";
+            let resources = self.locales.iter().flat_map(|locale| &locale.resources);
+            for loaded in resources.filter(|_| parse_errors_warn) {
+                for error in loaded.errors.iter() {
+                    let (line, column) = loaded.line_and_column(error.pos.start).unwrap_or((1, 1));
+                    let message = format!(
+                        "Error when parsing {}:{line}:{column}: {}",
+                        loaded.name, error.kind
+                    );
+                    warnings.call_method1("warn", (message, py.get_type::<ParseWarning>()))?;
+                }
+            }
+            for error in errors {
+                let category = warning_category(py, error.error_type());
+                warnings.call_method1("warn", (error.to_string(), category))?;
+            }
+            Ok(())
+        }
+
+        /// Find the entry for a message in the first language with the message, returning the
+        /// index of the language, the resource and the index of the entry within it.
+        fn find_message_entry(&self, message_id: &str) -> Option<(usize, &LoadedResource, usize)> {
+            // Within a language, later resources override earlier ones,
+            // as do later entries within a resource.
+            self.locales
+                .iter()
+                .enumerate()
+                .find_map(|(locale, locale_bundle)| {
+                    locale_bundle.resources.iter().rev().find_map(|loaded| {
+                        loaded
+                            .resource
+                            .entries()
+                            .enumerate()
+                            .filter(|(_, entry)| {
+                                matches!(entry, ast::Entry::Message(message) if message.id.name == message_id)
+                            })
+                            .last()
+                            .map(|(index, _)| (locale, loaded, index))
+                    })
+                })
+        }
+
+        /// Return the messages in the bundle, in file order, each from the first language with
+        /// the message (as when formatting) along with the index of that language and the ID.
+        fn messages(&self) -> Vec<(usize, &str, FluentMessage<'_>)> {
+            let mut seen = HashSet::new();
+            let mut messages = vec![];
+            for locale in self.locales.iter() {
+                for loaded in locale.resources.iter() {
+                    for entry in loaded.resource.entries() {
+                        if let ast::Entry::Message(message) = entry
+                            && seen.insert(message.id.name)
+                        {
+                            messages.extend(self.locales.iter().enumerate().find_map(
+                                |(index, locale)| {
+                                    let id = message.id.name;
+                                    Some((index, id, locale.bundle.get_message(id)?))
+                                },
+                            ));
+                        }
+                    }
+                }
+            }
+            messages
+        }
+
+        /// Return the value of a pattern for `to_json`: its FTL source if `raw`, otherwise
+        /// formatted with an i18next placeholder for each variable.
+        fn json_value<'a>(
+            &'a self,
+            locale: usize,
+            pattern: &'a ast::Pattern<&'a str>,
+            raw: bool,
+        ) -> String {
+            if raw {
+                return pattern_source(pattern);
+            }
+            let mut args = FluentArgs::new();
+            for name in pattern_variables(pattern, |id| self.locales[locale].bundle.get_message(id))
+            {
+                let placeholder = format!("{{{{{name}}}}}");
+                args.set(name, placeholder);
+            }
+            let formatter = self.locales[locale].formatter(false);
+            i18next_string(pattern, &mut |pattern| {
+                formatter
+                    .format_pattern(pattern, Some(&args), &mut vec![])
+                    .into_owned()
+            })
+        }
+
+        /// Find the pattern for the first of the identifiers with a translation, raising the
+        /// error for the first identifier if none of them have one.
+        fn find_first_pattern<'a>(
+            &'a self,
+            identifiers: &'a [String],
+        ) -> Result<(&'a str, usize, &'a ast::Pattern<&'a str>), LookupError> {
+            let mut first_error = None;
+            for identifier in identifiers {
+                match self.lookup_pattern(identifier) {
+                    Ok((locale, pattern)) => return Ok((identifier, locale, pattern)),
+                    Err(error) => {
+                        first_error.get_or_insert(error);
+                    }
+                }
+            }
+            Err(first_error.unwrap_or_else(|| {
+                LookupError::Other(PyValueError::new_err(
+                    "At least one identifier is required.",
+                ))
+            }))
+        }
+
+        /// Find the pattern for a message (or `message.attribute`), in the first language
+        /// with the message.
+        fn find_pattern(&self, identifier: &str) -> PyResult<(usize, &ast::Pattern<&str>)> {
+            self.lookup_pattern(identifier)
+                .map_err(|error| self.lookup_error(error))
+        }
 
+        /// Find the pattern for a message (or `message.attribute`) as `find_pattern` does,
+        /// leaving the error to be made if it's raised, rather than replaced with a default.
+        fn lookup_pattern(
+            &self,
+            identifier: &str,
+        ) -> Result<(usize, &ast::Pattern<&str>), LookupError> {
             let get_message = |id: &str| {
-                self.bundle
-                    .get_message(id)
-                    .ok_or_else(|| PyValueError::new_err(format!("{id} not found")))
+                self.locales
+                    .iter()
+                    .enumerate()
+                    .find_map(|(locale, locale_bundle)| {
+                        Some((locale, locale_bundle.bundle.get_message(id)?))
+                    })
+                    .ok_or_else(|| LookupError::NotFound(id.to_string()))
             };
 
-            let pattern = match identifier.split_once('.') {
-                Some((message_id, attribute_id)) => get_message(message_id)?
+            match identifier.split_once('.') {
+                Some((message_id, attribute_id)) => {
+                    let (locale, message) = get_message(message_id)?;
+                    let pattern = message
                     .get_attribute(attribute_id)
                     .ok_or_else(|| {
-                        PyValueError::new_err(format!(
+                        LookupError::Other(PyValueError::new_err(format!(
                             "{identifier} - Attribute '{attribute_id}' not found on message '{message_id}'."
-                        ))
+                        )))
                     })?
-                    .value(),
+                    .value();
                     // Note: attribute.value() returns &Pattern directly (not Option)
                     // because attributes always have values, unlike messages
-                None => get_message(identifier)?
-                    .value()
-                    .ok_or_else(|| {
-                        PyValueError::new_err(format!("{identifier} - Message has no value."))
-                    })?
+                    Ok((locale, pattern))
+                }
+                None => {
+                    let (locale, message) = get_message(identifier)?;
+                    let pattern = message.value().ok_or_else(|| {
+                        LookupError::Other(PyValueError::new_err(format!(
+                            "{identifier} - Message has no value."
+                        )))
+                    })?;
+                    Ok((locale, pattern))
+                }
+            }
+        }
+
+        /// Make the error to raise for a failed `lookup_pattern`.
+        fn lookup_error(&self, error: LookupError) -> PyErr {
+            match error {
+                LookupError::NotFound(id) => self.not_found(&id),
+                LookupError::Other(error) => error,
+            }
+        }
+
+        /// The error for a message (or a term, with a leading `-`) which isn't in the bundle,
+        /// suggesting the closest IDs which are, which it also has as its `suggestions`.
+        fn not_found(&self, id: &str) -> PyErr {
+            let ids = if id.starts_with('-') {
+                self.term_ids()
+            } else {
+                self.message_ids(false)
             };
+            let suggestions = suggestions(id, ids.iter().map(String::as_str));
+            let message = match did_you_mean(&suggestions) {
+                Some(hint) => format!("{id} not found. {hint}"),
+                None => format!("{id} not found"),
+            };
+            let error = PyValueError::new_err(message);
+            Python::attach(
+                |py| match error.value(py).setattr("suggestions", suggestions) {
+                    Ok(()) => error,
+                    Err(setattr_error) => setattr_error,
+                },
+            )
+        }
 
-            let mut args = FluentArgs::new();
+        fn fluent_args(
+            &self,
+            variables: Option<&Bound<'_, PyAny>>,
+            strict_types: bool,
+        ) -> PyResult<Option<FluentArgs<'static>>> {
+            variables
+                .map(|variables| {
+                    let timezone = self
+                        .settings
+                        .timezone
+                        .as_ref()
+                        .map(|timezone| timezone.bind(variables.py()));
+                    to_fluent_args(
+                        variables,
+                        &VariableOptions {
+                            strict_types,
+                            separator: &self.settings.variable_separator,
+                            timezone,
+                            none_as: self.settings.none_as,
+                            bool_as: self.settings.bool_as,
+                            adapters: &self.adapters,
+                            adapt: true,
+                        },
+                    )
+                })
+                .transpose()
+        }
+
+        /// Format a message's pattern, raising a `FormatError` for any errors, logging them or
+        /// passing the `FormatError` to a callback, as `error_handling` says. Otherwise,
+        /// Fluent's best-effort result is returned. Isolation marks are removed afterwards with
+        /// `strip_isolation`, or if it's `None` and the bundle strips them.
+        #[allow(clippy::too_many_arguments)]
+        fn format(
+            &self,
+            identifier: &str,
+            locale: usize,
+            pattern: &ast::Pattern<&str>,
+            args: Option<&FluentArgs>,
+            use_isolating: bool,
+            strip_isolation: Option<bool>,
+            error_handling: &ErrorHandling,
+            mismatch: &VariableMismatch,
+        ) -> PyResult<String> {
+            let mut errors = vec![];
+            let value = self.locales[locale]
+                .formatter(use_isolating)
+                .format_pattern(pattern, args, &mut errors);
+            self.handle_format_errors(identifier, locale, &errors, error_handling, mismatch)?;
+            if strip_isolation.unwrap_or(self.settings.strip_isolation) {
+                self.transformed(&value.replace(['\u{2068}', '\u{2069}'], ""))
+            } else {
+                self.transformed(&value)
+            }
+        }
+
+        /// Count a message being formatted, then raise a `FormatError` for any errors, log them
+        /// or pass the `FormatError` to a callback, as `error_handling` says. Mismatched
+        /// variables are errors even if `error_handling` ignores errors, so they raise.
+        fn handle_format_errors(
+            &self,
+            identifier: &str,
+            locale: usize,
+            errors: &[FluentError],
+            error_handling: &ErrorHandling,
+            mismatch: &VariableMismatch,
+        ) -> PyResult<()> {
+            // Python functions stash their exceptions on the thread that formatted the message.
+            let function_error = take_function_error();
+            if let Some(stats) = &self.stats {
+                let failed = !errors.is_empty() || !mismatch.is_empty();
+                stats.record(identifier, function_error.is_some() || failed);
+            }
+            if let Some(error) = function_error {
+                return Err(error);
+            }
+            if errors.is_empty() && mismatch.is_empty() {
+                return Ok(());
+            }
+            // Variables that weren't passed are already reported as missing.
+            let errors: Vec<FluentError> = errors
+                .iter()
+                .filter(|error| {
+                    mismatch.missing.is_empty()
+                        || !matches!(
+                            error,
+                            FluentError::ResolverError(ResolverError::Reference(
+                                ReferenceKind::Variable { .. }
+                            ))
+                        )
+                })
+                .cloned()
+                .collect();
+            let errors = errors.as_slice();
+            let resources = &self.locales[locale].resources;
+            match error_handling {
+                ErrorHandling::Ignore if mismatch.is_empty() => {}
+                ErrorHandling::Ignore | ErrorHandling::Raise => {
+                    return Err(format_error(identifier, errors, mismatch, resources));
+                }
+                ErrorHandling::Log => {
+                    let language = self.locales[locale].bundle.locales[0].to_string();
+                    Python::attach(|py| {
+                        log_format_errors(py, identifier, &language, errors, mismatch)
+                    })?;
+                }
+                ErrorHandling::Report(callback) => {
+                    let error = format_error(identifier, errors, mismatch, resources);
+                    // This may run on a worker thread without the GIL, when formatting a batch.
+                    Python::attach(|py| callback.call1(py, (error.into_value(py),)))?;
+                }
+            }
+            Ok(())
+        }
+
+        /// Pass a formatted message through the transform set with `set_transform`, if any.
+        fn transformed(&self, value: &str) -> PyResult<String> {
+            let Some(transform) = &self.transform else {
+                return Ok(value.to_string());
+            };
+            // This may run on a worker thread without the GIL, when formatting a batch.
+            Python::attach(|py| {
+                let result = transform.bind(py).call1((value,))?;
+                match result.cast::<PyString>() {
+                    Ok(transformed) => Ok(transformed.to_str()?.to_string()),
+                    Err(_) => Err(PyTypeError::new_err(format!(
+                        "The transform must return a str, got {result}."
+                    ))),
+                }
+            })
+        }
+    }
+
+    /// A term's value and attributes, as returned by `Bundle.get_term`.
+    #[pyclass(frozen, get_all)]
+    struct Term {
+        /// The term's ID, including the leading `-`.
+        id: String,
+        value: String,
+        /// The term's attributes, mapped to their values.
+        attributes: Py<PyDict>,
+    }
+
+    #[pymethods]
+    impl Term {
+        fn __repr__(&self) -> String {
+            format!("Term(id={:?}, value={:?})", self.id, self.value)
+        }
+    }
+
+    /// A message looked up ahead of time by `Bundle.compile`.
+    #[pyclass(frozen)]
+    struct CompiledMessage {
+        bundle: Py<Bundle>,
+        /// The bundle's `generation` when the message was looked up.
+        generation: u64,
+        /// The index of the language the message was found in.
+        locale: usize,
+        #[pyo3(get)]
+        identifier: String,
+        resource: Arc<FluentResource>,
+        entry: usize,
+        attribute: Option<usize>,
+        /// The variables the message uses, including through the messages it references.
+        #[pyo3(get)]
+        variables: Vec<String>,
+    }
+
+    #[pymethods]
+    impl CompiledMessage {
+        #[pyo3(signature = (variables=None, use_isolating=true, strict_types=false, errors=ErrorHandling::Ignore, strip_isolation=None))]
+        fn __call__(
+            &self,
+            py: Python<'_>,
+            variables: Option<&Bound<'_, PyAny>>,
+            use_isolating: bool,
+            strict_types: bool,
+            #[pyo3(from_py_with = error_handling)] errors: ErrorHandling,
+            strip_isolation: Option<bool>,
+        ) -> PyResult<String> {
+            let bundle = self.bundle.borrow(py);
+            let (locale, pattern) = if bundle.generation == self.generation {
+                let pattern = self
+                    .resource
+                    .get_entry(self.entry)
+                    .and_then(|entry| message_pattern(entry, self.attribute))
+                    .expect("pattern was resolved when compiled");
+                (self.locale, pattern)
+            } else {
+                // The message may have been overridden or removed since it was compiled.
+                bundle.find_pattern(&self.identifier)?
+            };
+            let args = bundle.fluent_args(variables, strict_types || errors.raises())?;
+            bundle.format(
+                &self.identifier,
+                locale,
+                pattern,
+                args.as_ref(),
+                use_isolating,
+                strip_isolation,
+                &errors,
+                &bundle.variable_mismatch(&self.identifier, locale, pattern, args.as_ref(), false),
+            )
+        }
+
+        fn __repr__(&self) -> String {
+            format!("CompiledMessage({:?})", self.identifier)
+        }
+    }
+
+    /// Where `Localization` finds the FTL file for a locale and resource ID.
+    #[derive(FromPyObject)]
+    enum ResourceLoader<'py> {
+        /// A directory containing a `{locale}` placeholder, e.g. `l10n/{locale}`.
+        Root(PathBuf),
+        /// A callable taking the locale and resource ID, and returning a path or `None`.
+        Callable(Bound<'py, PyAny>),
+    }
 
-            if let Some(variables) = variables {
-                for (python_key, python_value) in variables {
-                    // Make sure the variable key is a Python string,
-                    // raising a TypeError if not.
-                    if !python_key.is_instance_of::<PyString>() {
+    impl ResourceLoader<'_> {
+        /// Find the FTL file for a resource in a locale, if there is one.
+        fn path(&self, locale: &str, resource_id: &str) -> PyResult<Option<PathBuf>> {
+            match self {
+                Self::Root(root) => {
+                    let root = root.to_string_lossy().replace("{locale}", locale);
+                    let path = PathBuf::from(root).join(resource_id);
+                    Ok(path.is_file().then_some(path))
+                }
+                Self::Callable(loader) => {
+                    if !loader.is_callable() {
                         return Err(PyTypeError::new_err(format!(
-                            "Variable key not a str, got {python_key}."
+                            "Resource loader must be a path or a callable, got {loader}."
                         )));
                     }
-                    let key = python_key.to_string();
-                    // Set the variable value as a string or integer,
-                    // raising a TypeError if not.
-                    if python_value.is_instance_of::<PyString>() {
-                        args.set(key, python_value.to_string());
-                    } else if python_value.is_instance_of::<PyInt>()
-                        && let Ok(int_value) = python_value.extract::<i32>()
-                    {
-                        args.set(key, int_value);
-                    } else if python_value.is_instance_of::<PyDate>()
-                        && let Ok(chrono_date) = python_value.extract::<NaiveDate>()
-                    {
-                        args.set(key, chrono_date.format("%Y-%m-%d").to_string());
-                    } else {
-                        // The variable value was of an unsupported type.
-                        // Fall back to displaying the variable key as its value.
-                        let fallback_value = key.clone();
-                        args.set(key, fallback_value);
-                    }
+                    loader.call1((locale, resource_id))?.extract()
                 }
             }
+        }
+    }
 
-            let mut errors = vec![];
-            let value = self
-                .bundle
-                .format_pattern(pattern, Some(&args), &mut errors);
-            Ok(value.to_string())
+    /// Formats messages for an ordered list of locales, using the first locale that
+    /// has each message, like `fluent-fallback`.
+    #[pyclass]
+    struct Localization {
+        bundle: Bundle,
+        #[pyo3(get)]
+        locales: Vec<String>,
+    }
+
+    #[pymethods]
+    impl Localization {
+        #[new]
+        #[pyo3(signature = (locales, resource_ids, loader, strict=false))]
+        fn new(
+            py: Python<'_>,
+            locales: Vec<LanguageTag>,
+            resource_ids: Vec<String>,
+            loader: ResourceLoader<'_>,
+            strict: bool,
+        ) -> PyResult<Self> {
+            let locales: Vec<String> = locales.into_iter().map(String::from).collect();
+            let mut ftl_filenames = HashMap::with_capacity(locales.len());
+            for locale in locales.iter() {
+                let mut paths = Vec::with_capacity(resource_ids.len());
+                for resource_id in resource_ids.iter() {
+                    paths.extend(loader.path(locale, resource_id)?.map(FtlSource::Path));
+                }
+                ftl_filenames.insert(locale.clone(), paths);
+            }
+            let settings = BundleSettings {
+                strict_parse: strict,
+                strict_validation: strict,
+                ..BundleSettings::default()
+            };
+            let bundle = Bundle::load(
+                py,
+                locales.clone(),
+                FtlFilenames::PerLanguage(ftl_filenames),
+                settings,
+            )?;
+            Ok(Self { bundle, locales })
+        }
+
+        /// Format a message using the first locale that has it.
+        #[pyo3(signature = (identifier, variables=None))]
+        fn format_value(
+            &self,
+            identifier: &str,
+            variables: Option<&Bound<'_, PyAny>>,
+        ) -> PyResult<String> {
+            self.bundle.get_translation(
+                Identifiers::One(identifier.to_string()),
+                variables,
+                true,
+                false,
+                ErrorHandling::Ignore,
+                None,
+                None,
+                false,
+            )
+        }
+
+        fn __repr__(&self) -> String {
+            format!("Localization({:?})", self.locales)
+        }
+    }
+
+    /// The result of `compare_bundles`.
+    #[pyclass(frozen, get_all)]
+    struct BundleComparison {
+        /// Messages and attributes in the reference bundle but not the target.
+        missing: Vec<String>,
+        /// Messages and attributes in the target bundle but not the reference.
+        obsolete: Vec<String>,
+    }
+
+    #[pymethods]
+    impl BundleComparison {
+        /// Whether the target has exactly the reference's messages and attributes.
+        fn __bool__(&self) -> bool {
+            self.missing.is_empty() && self.obsolete.is_empty()
+        }
+
+        fn __repr__(&self) -> String {
+            format!(
+                "BundleComparison(missing={:?}, obsolete={:?})",
+                self.missing, self.obsolete
+            )
+        }
+    }
+
+    /// Compare the messages (and attributes) of the first language of two bundles, e.g. to
+    /// check that a translation is complete.
+    #[pyfunction]
+    fn compare_bundles(reference: &Bundle, target: &Bundle) -> BundleComparison {
+        let reference_ids = reference.locales[0].message_ids();
+        let target_ids = target.locales[0].message_ids();
+        let difference = |ids: &[String], other: &[String]| {
+            let other: HashSet<_> = other.iter().collect();
+            ids.iter()
+                .filter(|id| !other.contains(id))
+                .cloned()
+                .collect()
+        };
+        BundleComparison {
+            missing: difference(&reference_ids, &target_ids),
+            obsolete: difference(&target_ids, &reference_ids),
+        }
+    }
+
+    /// The result of `coverage`.
+    #[pyclass(frozen, get_all)]
+    struct CoverageReport {
+        /// The first language of the target bundle.
+        language: String,
+        /// The number of messages in the reference bundle.
+        total: usize,
+        /// The number of those messages which the target bundle has.
+        translated: usize,
+        /// The percentage of the messages which are translated, or 100 if there are none.
+        percentage: f64,
+        /// The messages in the reference bundle but not the target, in file order.
+        untranslated: Vec<String>,
+    }
+
+    #[pymethods]
+    impl CoverageReport {
+        fn __repr__(&self) -> String {
+            format!(
+                "CoverageReport(language={:?}, translated={}, total={}, percentage={:.1})",
+                self.language, self.translated, self.total, self.percentage
+            )
+        }
+    }
+
+    /// Count how many of the messages of the reference bundle's first language the target
+    /// bundle's first language has. With a `threshold`, raise a `CoverageError` with the
+    /// report if the percentage translated is below it, to stop incomplete languages from
+    /// being released.
+    #[pyfunction]
+    #[pyo3(signature = (reference, target, threshold=None))]
+    fn coverage(
+        py: Python<'_>,
+        reference: &Bundle,
+        target: &Bundle,
+        threshold: Option<f64>,
+    ) -> PyResult<CoverageReport> {
+        if let Some(threshold) = threshold
+            && !(0.0..=100.0).contains(&threshold)
+        {
+            return Err(PyValueError::new_err(format!(
+                "threshold must be between 0 and 100, not {threshold}."
+            )));
+        }
+        // Attributes are part of their message, so only the IDs of messages are counted.
+        let message_ids = |bundle: &Bundle| -> Vec<String> {
+            bundle.locales[0]
+                .message_ids()
+                .into_iter()
+                .filter(|id| !id.contains('.'))
+                .collect()
+        };
+        let reference_ids = message_ids(reference);
+        let target_ids: HashSet<String> = message_ids(target).into_iter().collect();
+        let untranslated: Vec<String> = reference_ids
+            .iter()
+            .filter(|id| !target_ids.contains(*id))
+            .cloned()
+            .collect();
+        let total = reference_ids.len();
+        let translated = total - untranslated.len();
+        let percentage = if total == 0 {
+            100.0
+        } else {
+            translated as f64 * 100.0 / total as f64
+        };
+        let report = CoverageReport {
+            language: target.locales[0].bundle.locales[0].to_string(),
+            total,
+            translated,
+            percentage,
+            untranslated,
+        };
+        if let Some(threshold) = threshold
+            && percentage < threshold
+        {
+            let error = CoverageError::new_err(format!(
+                "{} is {percentage:.1}% translated, below the threshold of {threshold}%: {} of \
+                 {total} messages are untranslated.",
+                report.language,
+                report.untranslated.len(),
+            ));
+            error.value(py).setattr("report", report)?;
+            return Err(error);
+        }
+        Ok(report)
+    }
+
+    /// Return the CLDR plural category of a number in a locale, e.g. "few" for 3 in Polish,
+    /// to make the same choice as a selector on the number would. The type defaults to the
+    /// number's own, which is ordinal for `Number(value, type="ordinal")`.
+    #[pyfunction]
+    #[pyo3(signature = (locale, value, r#type=None))]
+    fn plural_category(
+        locale: &str,
+        value: &Bound<'_, PyAny>,
+        r#type: Option<&str>,
+    ) -> PyResult<&'static str> {
+        let language = locale
+            .parse::<LanguageIdentifier>()
+            .map_err(|_| PyValueError::new_err(format!("Invalid language: '{locale}'")))?;
+        let Some(number) = number_value(value)? else {
+            return Err(PyTypeError::new_err(format!(
+                "Value must be a number, got {value}."
+            )));
+        };
+        let rule_type = match r#type {
+            Some("cardinal") => PluralRuleType::CARDINAL,
+            Some("ordinal") => PluralRuleType::ORDINAL,
+            Some(other) => {
+                return Err(PyValueError::new_err(format!(
+                    "type must be 'cardinal' or 'ordinal', not '{other}'."
+                )));
+            }
+            None => match number.options.r#type {
+                FluentNumberType::Cardinal => PluralRuleType::CARDINAL,
+                FluentNumberType::Ordinal => PluralRuleType::ORDINAL,
+            },
+        };
+        let category = plural_rules(&language, rule_type)
+            .select(&number)
+            .map_err(|error| PyValueError::new_err(error.to_string()))?;
+        Ok(category_name(category))
+    }
+
+    /// Parse FTL source and serialize it again in canonical form, keeping its comments.
+    /// Invalid entries are dropped unless `with_junk` is set.
+    #[pyfunction]
+    #[pyo3(signature = (source, with_junk=false))]
+    fn serialize_resource(source: &str, with_junk: bool) -> String {
+        normalize(source, with_junk)
+    }
+
+    /// Parse FTL source into its syntax tree as nested dicts and lists, for linters and
+    /// codemods written in Python. Comments are kept, and invalid entries are `Junk` nodes.
+    #[pyfunction]
+    fn parse<'py>(py: Python<'py>, source: &str) -> PyResult<Bound<'py, PyDict>> {
+        syntax::syntax_tree(py, source)
+    }
+
+    /// Format FTL source, or a file, in canonical form with `indent` spaces of indentation,
+    /// e.g. to check the formatting of FTL files in a pre-commit hook. Comments and invalid
+    /// entries are kept. With `sort`, the messages and terms between comments are sorted by ID.
+    #[pyfunction]
+    #[pyo3(signature = (source_or_path, indent=4, sort=false, encoding="utf-8", errors="strict"))]
+    fn format_ftl(
+        py: Python<'_>,
+        source_or_path: SourceOrPath,
+        indent: usize,
+        sort: bool,
+        encoding: &str,
+        errors: &str,
+    ) -> PyResult<String> {
+        if indent == 0 {
+            return Err(PyValueError::new_err("indent must be at least 1."));
+        }
+        let source = match source_or_path {
+            SourceOrPath::Source(source) => source,
+            SourceOrPath::Path(path) => {
+                FtlSource::Path(path)
+                    .load(&Decoding::new(encoding, errors))?
+                    .contents
+            }
+        };
+        Ok(py.detach(|| format_source(&source, indent, sort)))
+    }
+
+    /// Generate the FTL file for a new locale from the reference locale's files, with every
+    /// message, term and attribute, and values that are empty, copied, or pseudo-localized.
+    #[pyfunction]
+    #[pyo3(signature = (reference_paths, mode="empty", encoding="utf-8", errors="strict"))]
+    fn generate_skeleton(
+        py: Python<'_>,
+        reference_paths: Vec<FtlSource>,
+        mode: &str,
+        encoding: &str,
+        errors: &str,
+    ) -> PyResult<String> {
+        let mode = SkeletonMode::new(mode)?;
+        py.detach(|| {
+            let sources = FtlSource::load_all(reference_paths, &Decoding::new(encoding, errors))?;
+            let sources: Vec<&str> = sources.iter().map(|source| &*source.contents).collect();
+            Ok(skeleton(&sources, mode))
+        })
+    }
+
+    /// Compare two versions of an FTL file, returning the messages and terms that were added,
+    /// removed, or have a different value or attributes, e.g. to create translation jobs for a
+    /// release.
+    #[pyfunction]
+    #[pyo3(signature = (old_path, new_path, encoding="utf-8", errors="strict"))]
+    fn diff_resources(
+        py: Python<'_>,
+        old_path: FtlSource,
+        new_path: FtlSource,
+        encoding: &str,
+        errors: &str,
+    ) -> PyResult<ResourceDiff> {
+        let decoding = Decoding::new(encoding, errors);
+        py.detach(|| {
+            let mut loaded = FtlSource::load_all(vec![old_path, new_path], &decoding)?
+                .into_iter()
+                .map(SourceText::parse);
+            let old = loaded.next().expect("the old file is loaded");
+            let new = loaded.next().expect("the new file is loaded");
+            Ok(diff(&old, &new))
+        })
+    }
+
+    /// Merge the changes made to an FTL file in two versions, `ours` and `theirs`, since their
+    /// common `base` version, message by message rather than line by line. Conflicting changes
+    /// are resolved with our version, and reported.
+    #[pyfunction]
+    #[pyo3(signature = (base, ours, theirs, encoding="utf-8", errors="strict"))]
+    fn merge_resources(
+        py: Python<'_>,
+        base: FtlSource,
+        ours: FtlSource,
+        theirs: FtlSource,
+        encoding: &str,
+        errors: &str,
+    ) -> PyResult<MergeResult> {
+        let decoding = Decoding::new(encoding, errors);
+        py.detach(|| {
+            let sources = FtlSource::load_all(vec![base, ours, theirs], &decoding)?;
+            let [base, ours, theirs] = [0, 1, 2].map(|index| sources[index].contents.as_str());
+            Ok(merge(base, ours, theirs))
+        })
+    }
+
+    /// Convert a gettext PO file to FTL, e.g. to migrate from gettext or to import the
+    /// translations of a PO file exported with `ftl_to_po`. Plurals become select expressions
+    /// on the plural categories of `locale`, or of the file's `Language`.
+    #[pyfunction]
+    #[pyo3(signature = (
+        path, locale=None, include_untranslated=false, encoding="utf-8", errors="strict"
+    ))]
+    fn po_to_ftl(
+        py: Python<'_>,
+        path: FtlSource,
+        locale: Option<&str>,
+        include_untranslated: bool,
+        encoding: &str,
+        errors: &str,
+    ) -> PyResult<String> {
+        py.detach(|| {
+            let source = path.load(&Decoding::new(encoding, errors))?;
+            gettext::po_to_ftl(&source.name, &source.contents, locale, include_untranslated)
+        })
+    }
+
+    /// Export an FTL file as a gettext PO file, for translation tools that only support PO
+    /// files. Without a `reference_path`, it's a template of the file's messages; with one,
+    /// it's the file's translations of the reference's messages.
+    #[pyfunction]
+    #[pyo3(signature = (path, reference_path=None, locale=None, encoding="utf-8", errors="strict"))]
+    fn ftl_to_po(
+        py: Python<'_>,
+        path: FtlSource,
+        reference_path: Option<FtlSource>,
+        locale: Option<&str>,
+        encoding: &str,
+        errors: &str,
+    ) -> PyResult<String> {
+        let decoding = Decoding::new(encoding, errors);
+        py.detach(|| {
+            let sources = FtlSource::load_all(
+                [Some(path), reference_path].into_iter().flatten().collect(),
+                &decoding,
+            )?;
+            let mut loaded = sources.into_iter().map(SourceText::parse);
+            let resource = loaded.next().expect("the file is loaded");
+            let reference = loaded.next();
+            gettext::ftl_to_po(&resource, reference.as_ref(), locale)
+        })
+    }
+
+    /// Export the messages of the first language of a reference bundle as an XLIFF 2.0 file,
+    /// with their translations from the first language of a target bundle, e.g. to send them to
+    /// a translation vendor.
+    #[pyfunction]
+    fn export_xliff(
+        reference_bundle: &Bundle,
+        target_bundle: &Bundle,
+        path: PathBuf,
+    ) -> PyResult<()> {
+        let reference = &reference_bundle.locales[0];
+        let target = &target_bundle.locales[0];
+        let xliff = xliff::export(
+            &reference.to_ftl(false),
+            &target.to_ftl(false),
+            &reference.bundle.locales[0].to_string(),
+            &target.bundle.locales[0].to_string(),
+        );
+        fs::write(&path, xliff).map_err(|error| io_exception(&path, error))
+    }
+
+    /// Import the translations of an XLIFF 2.0 file, as exported by `export_xliff`, as FTL.
+    #[pyfunction]
+    fn import_xliff(py: Python<'_>, path: PathBuf) -> PyResult<String> {
+        xliff::import(py, &path)
+    }
+
+    /// Parse an HTTP `Accept-Language` header into language tags, most preferred first.
+    #[pyfunction]
+    fn parse_accept_language(header: &str) -> Vec<String> {
+        accepted_languages(header)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    /// Return the available languages which best match the requested ones, in order of
+    /// preference, e.g. to choose the languages of a bundle for a web request.
+    #[pyfunction]
+    #[pyo3(signature = (requested, available, default=None, strategy="filtering"))]
+    fn negotiate_languages(
+        requested: RequestedLanguages,
+        available: Vec<LanguageTag>,
+        default: Option<LanguageTag>,
+        strategy: &str,
+    ) -> PyResult<Vec<String>> {
+        let strategy = negotiation_strategy(strategy)?;
+        let requested = match requested {
+            RequestedLanguages::Header(header) => accepted_languages(&header),
+            // Languages requested by users which aren't valid don't match anything.
+            RequestedLanguages::List(languages) => languages
+                .into_iter()
+                .filter_map(|language| String::from(language).parse().ok())
+                .collect(),
+        };
+        let available = available
+            .into_iter()
+            .map(|language| Available::new(language.into()))
+            .collect::<PyResult<Vec<_>>>()?;
+        let default = default
+            .map(|language| Available::new(language.into()))
+            .transpose()?;
+        if strategy == NegotiationStrategy::Lookup && default.is_none() {
+            return Err(PyValueError::new_err(
+                "A default is required with strategy='lookup'.",
+            ));
         }
+        let negotiated =
+            fluent_langneg::negotiate_languages(&requested, &available, default.as_ref(), strategy);
+        Ok(negotiated
+            .into_iter()
+            .map(|language| language.tag.clone())
+            .collect())
+    }
+
+    /// Check FTL files for validation errors, without building a bundle to format messages
+    /// with, e.g. in a pre-commit hook. Syntax errors don't raise, and the entries with them
+    /// are skipped.
+    ///
+    /// Plural selectors are only checked if the files' locale is given.
+    #[pyfunction(name = "validate")]
+    #[pyo3(signature = (ftl_filenames, locale=None, rules=None, ignore=None, severity=None, encoding="utf-8", errors="strict", on_duplicate="warn", lint=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn validate_files(
+        py: Python<'_>,
+        ftl_filenames: Vec<FtlSource>,
+        locale: Option<&str>,
+        rules: Option<&Bound<'_, PyAny>>,
+        ignore: Option<&Bound<'_, PyAny>>,
+        severity: Option<HashMap<String, String>>,
+        encoding: &str,
+        errors: &str,
+        on_duplicate: &str,
+        lint: Option<HashMap<String, Bound<'_, PyAny>>>,
+    ) -> PyResult<Vec<ValidationError>> {
+        let on_duplicate = DuplicatePolicy::new(on_duplicate)?;
+        let lint = lint
+            .map(|lint| LintRules::new(py, lint))
+            .transpose()?
+            .unwrap_or_default();
+        let validation_rules = ValidationRules::new(
+            rules.map(string_set).transpose()?,
+            ignore.map(string_set).transpose()?.unwrap_or_default(),
+            severity.unwrap_or_default(),
+        )?;
+        let langid = locale
+            .map(|locale| {
+                locale
+                    .parse::<LanguageIdentifier>()
+                    .map_err(|_| PyValueError::new_err(format!("Invalid language: '{locale}'")))
+            })
+            .transpose()?;
+        let (resources, mut errors) = py.detach(|| {
+            let sources = FtlSource::load_all(ftl_filenames, &Decoding::new(encoding, errors))?;
+            let resources: Vec<_> = sources.into_par_iter().map(SourceText::parse).collect();
+            let mut errors = validate(
+                &resources,
+                langid.as_ref(),
+                on_duplicate.reports_overrides(),
+            );
+            if validation_rules.enabled("MarkupMismatch") {
+                errors.extend(check_markup(&resources, None));
+            }
+            Ok::<_, PyErr>((resources, errors))
+        })?;
+        // Lint rules can use Python regular expressions, so need the GIL.
+        errors.extend(lint.check(py, &resources)?);
+        Ok(validation_rules.apply(errors))
     }
 }
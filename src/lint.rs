@@ -0,0 +1,296 @@
+use crate::introspection::LoadedResource;
+use crate::validation::{ValidationError, effective_entries};
+use fluent_syntax::ast;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::collections::HashMap;
+
+/// Which quotes the text of messages should use, for the `QuoteStyle` lint rule.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum QuoteStyle {
+    /// `'` and `"`.
+    Straight,
+    /// `‘`, `’`, `“` and `”`.
+    Curly,
+}
+
+impl QuoteStyle {
+    fn new(quotes: &str) -> PyResult<Self> {
+        match quotes {
+            "straight" => Ok(Self::Straight),
+            "curly" => Ok(Self::Curly),
+            _ => Err(PyValueError::new_err(format!(
+                "quotes must be 'straight' or 'curly', not '{quotes}'."
+            ))),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Straight => "straight",
+            Self::Curly => "curly",
+        }
+    }
+
+    /// Describe a quote that isn't in this style, if it's a quote.
+    fn wrong_quote(self, c: char) -> Option<String> {
+        match (self, c) {
+            (Self::Curly, '\'' | '"') => Some(format!("Straight quote {c} instead of a curly one")),
+            (Self::Straight, '‘' | '’' | '“' | '”') => {
+                Some(format!("Curly quote {c} instead of a straight one"))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The style rules for the messages of a bundle, from the `lint` argument. Each rule is only
+/// checked if it's given.
+#[derive(Default)]
+pub(crate) struct LintRules {
+    /// The longest a message's value or attribute may be, in characters.
+    max_length: Option<usize>,
+    /// Characters that the text of messages mustn't contain.
+    forbidden_characters: Vec<char>,
+    /// Which quotes the text of messages should use.
+    quotes: Option<QuoteStyle>,
+    /// Whether lines of messages and terms mustn't end with whitespace.
+    trailing_whitespace: bool,
+    /// The Python regular expression that the IDs of messages and terms must match, without
+    /// a term's `-`, with the pattern it was compiled from.
+    id_pattern: Option<(String, Py<PyAny>)>,
+}
+
+impl LintRules {
+    pub(crate) fn new(py: Python<'_>, config: HashMap<String, Bound<'_, PyAny>>) -> PyResult<Self> {
+        let mut rules = Self::default();
+        for (name, value) in config {
+            let invalid = |expected: &str| {
+                PyTypeError::new_err(format!("The {name} lint rule must be {expected}."))
+            };
+            match name.as_str() {
+                "max_length" => {
+                    rules.max_length = Some(value.extract().map_err(|_| invalid("an int"))?);
+                }
+                "forbidden_characters" => {
+                    let characters: String = value.extract().map_err(|_| invalid("a str"))?;
+                    rules.forbidden_characters = characters.chars().collect();
+                }
+                "quotes" => {
+                    let quotes: String = value.extract().map_err(|_| invalid("a str"))?;
+                    rules.quotes = Some(QuoteStyle::new(&quotes)?);
+                }
+                "trailing_whitespace" => {
+                    rules.trailing_whitespace = value.extract().map_err(|_| invalid("a bool"))?;
+                }
+                "id_pattern" => {
+                    let pattern: String = value.extract().map_err(|_| invalid("a str"))?;
+                    let regex = py.import("re")?.call_method1("compile", (&pattern,))?;
+                    rules.id_pattern = Some((pattern, regex.unbind()));
+                }
+                _ => {
+                    return Err(PyValueError::new_err(format!(
+                        "Unknown lint rule: '{name}'"
+                    )));
+                }
+            }
+        }
+        Ok(rules)
+    }
+
+    pub(crate) fn clone_ref(&self, py: Python<'_>) -> Self {
+        Self {
+            max_length: self.max_length,
+            forbidden_characters: self.forbidden_characters.clone(),
+            quotes: self.quotes,
+            trailing_whitespace: self.trailing_whitespace,
+            id_pattern: self
+                .id_pattern
+                .as_ref()
+                .map(|(pattern, regex)| (pattern.clone(), regex.clone_ref(py))),
+        }
+    }
+
+    /// The rules as the `lint` argument they can be created from.
+    pub(crate) fn config<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let config = PyDict::new(py);
+        if let Some(max_length) = self.max_length {
+            config.set_item("max_length", max_length)?;
+        }
+        if !self.forbidden_characters.is_empty() {
+            let characters: String = self.forbidden_characters.iter().collect();
+            config.set_item("forbidden_characters", characters)?;
+        }
+        if let Some(quotes) = self.quotes {
+            config.set_item("quotes", quotes.as_str())?;
+        }
+        if self.trailing_whitespace {
+            config.set_item("trailing_whitespace", true)?;
+        }
+        if let Some((pattern, _)) = &self.id_pattern {
+            config.set_item("id_pattern", pattern)?;
+        }
+        Ok(config)
+    }
+
+    /// Check the messages and terms of one language's resources against the rules, in order.
+    /// Messages and terms overridden by a later definition aren't checked.
+    pub(crate) fn check(
+        &self,
+        py: Python<'_>,
+        resources: &[LoadedResource],
+    ) -> PyResult<Vec<ValidationError>> {
+        let mut errors = vec![];
+        for entry in effective_entries(resources) {
+            if let Some((pattern, regex)) = &self.id_pattern {
+                let name = entry.id.strip_prefix('-').unwrap_or(&entry.id);
+                if regex.bind(py).call_method1("fullmatch", (name,))?.is_none() {
+                    errors.push(
+                        ValidationError::new(
+                            "IdNaming",
+                            entry.id.clone(),
+                            format!("ID doesn't match the naming convention {pattern}"),
+                        )
+                        .at(entry.loaded, entry.range()),
+                    );
+                }
+            }
+            if self.trailing_whitespace
+                && let Some(range) = entry.range()
+            {
+                for offset in trailing_whitespace(entry.loaded.resource.source(), range.start) {
+                    errors.push(
+                        ValidationError::new(
+                            "TrailingWhitespace",
+                            entry.id.clone(),
+                            "Line ends with whitespace".to_string(),
+                        )
+                        .at(entry.loaded, Some(offset)),
+                    );
+                }
+            }
+            // Terms are only checked for their names and whitespace, as their text is often a
+            // brand name or a parameter that doesn't follow the style of messages.
+            if entry.id.starts_with('-') {
+                continue;
+            }
+            let mut reported = vec![];
+            for &(attribute, pattern) in &entry.patterns {
+                if let Some(max_length) = self.max_length {
+                    let length = max_length_of(pattern);
+                    if length > max_length {
+                        let what = match attribute {
+                            Some(attribute) => format!(".{attribute}"),
+                            None => "Value".to_string(),
+                        };
+                        errors.push(
+                            ValidationError::new(
+                                "MaxLength",
+                                entry.id.clone(),
+                                format!(
+                                    "{what} is {length} characters long, longer than the \
+                                     maximum of {max_length}"
+                                ),
+                            )
+                            .at(entry.loaded, entry.range()),
+                        );
+                    }
+                }
+                let mut texts = vec![];
+                text_elements(pattern, &mut texts);
+                for text in texts {
+                    for (index, c) in text.char_indices() {
+                        // Each character is only reported once for each message.
+                        if reported.contains(&c) {
+                            continue;
+                        }
+                        let (error_type, message) = if self.forbidden_characters.contains(&c) {
+                            (
+                                "ForbiddenCharacter",
+                                format!("Forbidden character {c} (U+{:04X})", u32::from(c)),
+                            )
+                        } else if let Some(message) =
+                            self.quotes.and_then(|quotes| quotes.wrong_quote(c))
+                        {
+                            ("QuoteStyle", message)
+                        } else {
+                            continue;
+                        };
+                        reported.push(c);
+                        let range = entry
+                            .loaded
+                            .source_range(text, text)
+                            .map(|range| range.start + index..range.start + index + c.len_utf8());
+                        errors.push(
+                            ValidationError::new(error_type, entry.id.clone(), message)
+                                .at(entry.loaded, range),
+                        );
+                    }
+                }
+            }
+        }
+        Ok(errors)
+    }
+}
+
+/// Return the length of the longest text a pattern can be formatted as, in characters, taking
+/// the longest variant of each select. Other placeables aren't counted, as their length isn't
+/// known until they're formatted.
+fn max_length_of(pattern: &ast::Pattern<&str>) -> usize {
+    pattern
+        .elements
+        .iter()
+        .map(|element| match element {
+            ast::PatternElement::TextElement { value } => value.chars().count(),
+            ast::PatternElement::Placeable {
+                expression: ast::Expression::Select { variants, .. },
+            } => variants
+                .iter()
+                .map(|variant| max_length_of(&variant.value))
+                .max()
+                .unwrap_or(0),
+            ast::PatternElement::Placeable { .. } => 0,
+        })
+        .sum()
+}
+
+/// Collect the text of a pattern, including the text of the variants of its selects.
+fn text_elements<'s>(pattern: &ast::Pattern<&'s str>, texts: &mut Vec<&'s str>) {
+    for element in &pattern.elements {
+        match element {
+            ast::PatternElement::TextElement { value } => texts.push(value),
+            ast::PatternElement::Placeable {
+                expression: ast::Expression::Select { variants, .. },
+            } => {
+                for variant in variants {
+                    text_elements(&variant.value, texts);
+                }
+            }
+            ast::PatternElement::Placeable { .. } => {}
+        }
+    }
+}
+
+/// Return the ranges of whitespace at the ends of the lines of the entry starting at `start`,
+/// which ends before the next line that isn't indented or blank.
+fn trailing_whitespace(source: &str, start: usize) -> Vec<std::ops::Range<usize>> {
+    let mut ranges = vec![];
+    let mut line_start = start;
+    loop {
+        let line_end = source[line_start..]
+            .find('\n')
+            .map_or(source.len(), |newline| line_start + newline);
+        let line = source[line_start..line_end].trim_end_matches('\r');
+        let trimmed = line.trim_end();
+        if trimmed.len() < line.len() {
+            ranges.push(line_start + trimmed.len()..line_start + line.len());
+        }
+        line_start = line_end + 1;
+        if line_start >= source.len() || !source[line_start..].starts_with([' ', '\t', '\r', '\n'])
+        {
+            break;
+        }
+    }
+    ranges
+}
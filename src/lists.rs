@@ -0,0 +1,199 @@
+use fluent::{FluentArgs, FluentValue};
+use fluent_bundle::types::FluentType;
+use intl_memoizer::Memoizable;
+use std::borrow::Cow;
+use std::convert::Infallible;
+use unic_langid::LanguageIdentifier;
+
+/// A list of strings, joined using the bundle's locale.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FluentList {
+    pub items: Vec<String>,
+    list_type: ListType,
+}
+
+impl FluentList {
+    pub fn new(items: Vec<String>) -> Self {
+        Self {
+            items,
+            list_type: ListType::Conjunction,
+        }
+    }
+}
+
+impl FluentType for FluentList {
+    fn duplicate(&self) -> Box<dyn FluentType + Send> {
+        Box::new(self.clone())
+    }
+
+    fn as_string(&self, intls: &intl_memoizer::IntlLangMemoizer) -> Cow<'static, str> {
+        intls
+            .with_try_get::<ListFormatter, _, _>((), |formatter| formatter.format(self))
+            .unwrap_or_else(|never| match never {})
+            .into()
+    }
+
+    fn as_string_threadsafe(
+        &self,
+        intls: &intl_memoizer::concurrent::IntlLangMemoizer,
+    ) -> Cow<'static, str> {
+        intls
+            .with_try_get::<ListFormatter, _, _>((), |formatter| formatter.format(self))
+            .unwrap_or_else(|never| match never {})
+            .into()
+    }
+}
+
+/// The `LIST()` Fluent function, applying formatting options to a list variable.
+pub(crate) fn list<'a>(positional: &[FluentValue<'a>], named: &FluentArgs) -> FluentValue<'a> {
+    let Some(FluentValue::Custom(value)) = positional.first() else {
+        return FluentValue::Error;
+    };
+    let Some(list) = value.as_any().downcast_ref::<FluentList>() else {
+        return FluentValue::Error;
+    };
+    let mut list = list.clone();
+    if let Some(FluentValue::String(list_type)) = named.get("type")
+        && let Some(list_type) = ListType::parse(list_type)
+    {
+        list.list_type = list_type;
+    }
+    FluentValue::Custom(Box::new(list))
+}
+
+/// How the items of a list are joined, as in `Intl.ListFormat`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ListType {
+    /// "a, b and c"
+    Conjunction,
+    /// "a, b or c"
+    Disjunction,
+    /// "a, b, c"
+    Unit,
+}
+
+impl ListType {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "conjunction" => Some(Self::Conjunction),
+            "disjunction" => Some(Self::Disjunction),
+            "unit" => Some(Self::Unit),
+            _ => None,
+        }
+    }
+}
+
+/// The words used to join lists in a locale, taken from the CLDR.
+struct ListSymbols {
+    /// Joins two items, e.g. " and ".
+    and: &'static str,
+    /// Joins the last two of three or more items, e.g. ", and ".
+    and_last: &'static str,
+    or: &'static str,
+    or_last: &'static str,
+}
+
+const EN: ListSymbols = ListSymbols {
+    and: " and ",
+    and_last: ", and ",
+    or: " or ",
+    or_last: ", or ",
+};
+
+/// English outside the US, without the serial comma.
+const EN_GB: ListSymbols = ListSymbols {
+    and: " and ",
+    and_last: " and ",
+    or: " or ",
+    or_last: " or ",
+};
+
+const DE: ListSymbols = ListSymbols {
+    and: " und ",
+    and_last: " und ",
+    or: " oder ",
+    or_last: " oder ",
+};
+
+const FR: ListSymbols = ListSymbols {
+    and: " et ",
+    and_last: " et ",
+    or: " ou ",
+    or_last: " ou ",
+};
+
+const ES: ListSymbols = ListSymbols {
+    and: " y ",
+    and_last: " y ",
+    or: " o ",
+    or_last: " o ",
+};
+
+const IT: ListSymbols = ListSymbols {
+    and: " e ",
+    and_last: " e ",
+    or: " o ",
+    or_last: " o ",
+};
+
+const NL: ListSymbols = ListSymbols {
+    and: " en ",
+    and_last: " en ",
+    or: " of ",
+    or_last: " of ",
+};
+
+const PT: ListSymbols = ListSymbols {
+    and: " e ",
+    and_last: " e ",
+    or: " ou ",
+    or_last: " ou ",
+};
+
+impl ListSymbols {
+    fn for_locale(locale: &LanguageIdentifier) -> &'static Self {
+        let region = locale.region.as_ref().map(|region| region.as_str());
+        match (locale.language.as_str(), region) {
+            ("en", Some("AU" | "GB" | "IE" | "IN" | "NZ")) => &EN_GB,
+            ("de", _) => &DE,
+            ("fr", _) => &FR,
+            ("es", _) => &ES,
+            ("it", _) => &IT,
+            ("nl", _) => &NL,
+            ("pt", _) => &PT,
+            _ => &EN,
+        }
+    }
+}
+
+/// Formats lists according to the bundle's locale.
+pub(crate) struct ListFormatter {
+    symbols: &'static ListSymbols,
+}
+
+impl Memoizable for ListFormatter {
+    type Args = ();
+    type Error = Infallible;
+
+    fn construct(lang: LanguageIdentifier, _args: Self::Args) -> Result<Self, Self::Error> {
+        Ok(Self {
+            symbols: ListSymbols::for_locale(&lang),
+        })
+    }
+}
+
+impl ListFormatter {
+    fn format(&self, list: &FluentList) -> String {
+        let (pair, last) = match list.list_type {
+            ListType::Conjunction => (self.symbols.and, self.symbols.and_last),
+            ListType::Disjunction => (self.symbols.or, self.symbols.or_last),
+            ListType::Unit => (", ", ", "),
+        };
+        match list.items.as_slice() {
+            [] => String::new(),
+            [item] => item.clone(),
+            [first, second] => format!("{first}{pair}{second}"),
+            [rest @ .., last_item] => format!("{}{last}{last_item}", rest.join(", ")),
+        }
+    }
+}
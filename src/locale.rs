@@ -0,0 +1,165 @@
+use fluent_langneg::NegotiationStrategy;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use unic_langid::LanguageIdentifier;
+
+/// A BCP 47 language tag, e.g. `en-US`, parsed the same way as the languages given to
+/// `Bundle`.
+#[pyclass(frozen, eq, hash, from_py_object, module = "rustfluent")]
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Locale {
+    langid: LanguageIdentifier,
+}
+
+#[pymethods]
+impl Locale {
+    #[new]
+    fn new(tag: &str) -> PyResult<Self> {
+        let langid = tag
+            .parse()
+            .map_err(|_| PyValueError::new_err(format!("Invalid language: '{tag}'")))?;
+        Ok(Self { langid })
+    }
+
+    /// Return a language tag in its canonical form, e.g. `en-US` for `EN_us`.
+    #[staticmethod]
+    fn canonicalize(tag: &str) -> PyResult<String> {
+        Ok(Self::new(tag)?.langid.to_string())
+    }
+
+    #[getter]
+    fn language(&self) -> &str {
+        self.langid.language.as_str()
+    }
+
+    #[getter]
+    fn script(&self) -> Option<&str> {
+        self.langid.script.as_ref().map(|script| script.as_str())
+    }
+
+    #[getter]
+    fn region(&self) -> Option<&str> {
+        self.langid.region.as_ref().map(|region| region.as_str())
+    }
+
+    #[getter]
+    fn variants(&self) -> Vec<&str> {
+        self.langid
+            .variants()
+            .map(|variant| variant.as_str())
+            .collect()
+    }
+
+    /// Return the locale with its likely script and region added, e.g. `zh-Hans-CN` for `zh`.
+    fn maximize(&self) -> Self {
+        let mut langid = self.langid.clone();
+        langid.maximize();
+        Self { langid }
+    }
+
+    /// Return the locale without the script and region that `maximize` would add, e.g. `zh`
+    /// for `zh-Hans-CN`.
+    fn minimize(&self) -> Self {
+        let mut langid = self.langid.clone();
+        langid.minimize();
+        Self { langid }
+    }
+
+    fn __str__(&self) -> String {
+        self.langid.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Locale('{}')", self.langid)
+    }
+}
+
+/// A language given as a `str` or a `Locale`.
+#[derive(FromPyObject)]
+pub(crate) enum LanguageTag {
+    Str(String),
+    Locale(Locale),
+}
+
+impl From<LanguageTag> for String {
+    fn from(tag: LanguageTag) -> Self {
+        match tag {
+            LanguageTag::Str(tag) => tag,
+            LanguageTag::Locale(locale) => locale.langid.to_string(),
+        }
+    }
+}
+
+/// Parse an HTTP `Accept-Language` header into the languages it accepts, most preferred
+/// first. Entries with an invalid language or quality value are skipped, as are the
+/// wildcard `*` and entries with a quality of 0, which mark languages as not acceptable.
+pub(crate) fn accepted_languages(header: &str) -> Vec<LanguageIdentifier> {
+    let mut languages: Vec<(LanguageIdentifier, f32)> = vec![];
+    for entry in header.split(',') {
+        let mut parts = entry.split(';').map(str::trim);
+        let tag = parts.next().unwrap_or_default();
+        let mut quality = Some(1.0);
+        for parameter in parts {
+            if let Some((name, value)) = parameter.split_once('=')
+                && name.trim().eq_ignore_ascii_case("q")
+            {
+                quality = value
+                    .trim()
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|quality| (0.0..=1.0).contains(quality));
+            }
+        }
+        let Some(quality) = quality.filter(|quality| *quality > 0.0) else {
+            continue;
+        };
+        if tag == "*" {
+            continue;
+        }
+        let Ok(langid) = tag.parse::<LanguageIdentifier>() else {
+            continue;
+        };
+        if !languages.iter().any(|(other, _)| *other == langid) {
+            languages.push((langid, quality));
+        }
+    }
+    // A stable sort, so languages with the same quality stay in the header's order.
+    languages.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    languages.into_iter().map(|(langid, _)| langid).collect()
+}
+
+/// An available language to negotiate, keeping the tag it was given as so it can be used to
+/// look up e.g. the FTL files for the language.
+#[derive(PartialEq)]
+pub(crate) struct Available {
+    langid: LanguageIdentifier,
+    pub tag: String,
+}
+
+impl Available {
+    pub(crate) fn new(tag: String) -> PyResult<Self> {
+        let langid = tag
+            .parse()
+            .map_err(|_| PyValueError::new_err(format!("Invalid language: '{tag}'")))?;
+        Ok(Self { langid, tag })
+    }
+}
+
+impl AsRef<LanguageIdentifier> for Available {
+    fn as_ref(&self) -> &LanguageIdentifier {
+        &self.langid
+    }
+}
+
+/// How `negotiate_languages` matches requested languages to available ones, as in
+/// `fluent-langneg`.
+pub(crate) fn negotiation_strategy(strategy: &str) -> PyResult<NegotiationStrategy> {
+    match strategy {
+        "filtering" => Ok(NegotiationStrategy::Filtering),
+        "matching" => Ok(NegotiationStrategy::Matching),
+        "lookup" => Ok(NegotiationStrategy::Lookup),
+        _ => Err(PyValueError::new_err(format!(
+            "strategy must be 'filtering', 'lookup' or 'matching', not '{strategy}'."
+        ))),
+    }
+}
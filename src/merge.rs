@@ -0,0 +1,198 @@
+use crate::rewrite::parse_all;
+use crate::{entry_id, python_repr};
+use fluent_syntax::ast;
+use fluent_syntax::serializer::{Options, serialize_with_options};
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+/// The result of `merge_resources`: the merged FTL, and the messages that were changed in
+/// conflicting ways.
+#[pyclass(frozen, get_all, module = "rustfluent")]
+pub(crate) struct MergeResult {
+    /// The merged FTL source. Conflicts are resolved with our version.
+    source: String,
+    /// The messages (and terms) that couldn't be merged, in our file's order, then theirs.
+    conflicts: Vec<MergeConflict>,
+}
+
+#[pymethods]
+impl MergeResult {
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        let ids: Vec<&str> = self
+            .conflicts
+            .iter()
+            .map(|conflict| conflict.message_id.as_str())
+            .collect();
+        Ok(format!("MergeResult(conflicts={})", python_repr(py, ids)?))
+    }
+}
+
+/// A message (or term) that was changed differently in both versions being merged.
+#[pyclass(frozen, get_all, skip_from_py_object, module = "rustfluent")]
+#[derive(Clone)]
+pub(crate) struct MergeConflict {
+    /// The ID of the message, or of the term with its leading `-`.
+    message_id: String,
+    /// How the versions conflict: `changed_in_both`, `added_in_both`, `deleted_in_ours` or
+    /// `deleted_in_theirs`.
+    kind: String,
+    /// The FTL of the message in each version, or `None` where it isn't defined.
+    base: Option<String>,
+    ours: Option<String>,
+    theirs: Option<String>,
+}
+
+#[pymethods]
+impl MergeConflict {
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!(
+            "MergeConflict(message_id={}, kind={})",
+            python_repr(py, &self.message_id)?,
+            python_repr(py, &self.kind)?
+        ))
+    }
+}
+
+type Entry = ast::Entry<String>;
+
+/// Identifies an entry across the versions being merged: messages and terms by ID, and
+/// comments and invalid entries by their content, so they're only ever added or removed.
+/// Entries with the same key in one version are told apart by how many came before.
+type Key = (String, usize);
+
+/// Parse a version of FTL source into its entries, with their keys.
+fn keyed_entries(source: &str) -> Vec<(Key, Entry)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    parse_all(source.to_string())
+        .body
+        .into_iter()
+        .map(|entry| {
+            let key = match &entry {
+                ast::Entry::Comment(comment) => format!("#{}", comment.content.join("\n")),
+                ast::Entry::GroupComment(comment) => format!("##{}", comment.content.join("\n")),
+                ast::Entry::ResourceComment(comment) => {
+                    format!("###{}", comment.content.join("\n"))
+                }
+                ast::Entry::Junk { content } => format!("!{content}"),
+                entry => entry_id(entry).unwrap_or_default(),
+            };
+            let count = counts.entry(key.clone()).or_default();
+            *count += 1;
+            ((key, *count - 1), entry)
+        })
+        .collect()
+}
+
+/// Serialize a single entry.
+fn serialize_entry(entry: &Entry) -> String {
+    let resource = ast::Resource {
+        body: vec![entry.clone()],
+    };
+    serialize_with_options(&resource, Options { with_junk: true })
+}
+
+impl MergeConflict {
+    fn new(kind: &str, base: Option<&Entry>, ours: Option<&Entry>, theirs: Option<&Entry>) -> Self {
+        let message_id = [ours, theirs, base]
+            .into_iter()
+            .flatten()
+            .find_map(entry_id)
+            .unwrap_or_default();
+        Self {
+            message_id,
+            kind: kind.to_string(),
+            base: base.map(serialize_entry),
+            ours: ours.map(serialize_entry),
+            theirs: theirs.map(serialize_entry),
+        }
+    }
+}
+
+/// Merge the changes from a common base version of FTL source to two others, message by
+/// message, like a three-way merge in version control. Messages changed in only one version
+/// take that version's change, and comments added or removed in either are added or removed.
+/// Where both versions changed a message differently, our version is kept and the conflict is
+/// reported.
+pub(crate) fn merge(base: &str, ours: &str, theirs: &str) -> MergeResult {
+    let base: HashMap<Key, Entry> = keyed_entries(base).into_iter().collect();
+    let ours = keyed_entries(ours);
+    let theirs = keyed_entries(theirs);
+    let their_entries: HashMap<&Key, &Entry> =
+        theirs.iter().map(|(key, entry)| (key, entry)).collect();
+    let our_entries: HashMap<&Key, &Entry> = ours.iter().map(|(key, entry)| (key, entry)).collect();
+
+    let mut merged: Vec<(&Key, &Entry)> = Vec::with_capacity(ours.len());
+    let mut conflicts = vec![];
+    for (key, our_entry) in ours.iter() {
+        let base_entry = base.get(key);
+        let their_entry = their_entries.get(key).copied();
+        match (base_entry, their_entry) {
+            // Added in ours.
+            (None, None) => merged.push((key, our_entry)),
+            (_, Some(their_entry)) if their_entry == our_entry => merged.push((key, our_entry)),
+            // Only changed in theirs.
+            (Some(base_entry), Some(their_entry)) if base_entry == our_entry => {
+                merged.push((key, their_entry));
+            }
+            // Only changed in ours.
+            (Some(base_entry), Some(their_entry)) if base_entry == their_entry => {
+                merged.push((key, our_entry));
+            }
+            // Deleted in theirs.
+            (Some(base_entry), None) if base_entry == our_entry => {}
+            (base_entry, their_entry) => {
+                let kind = match (base_entry, their_entry) {
+                    (Some(_), Some(_)) => "changed_in_both",
+                    (None, Some(_)) => "added_in_both",
+                    _ => "deleted_in_theirs",
+                };
+                conflicts.push(MergeConflict::new(
+                    kind,
+                    base_entry,
+                    Some(our_entry),
+                    their_entry,
+                ));
+                merged.push((key, our_entry));
+            }
+        }
+    }
+
+    for (index, (key, their_entry)) in theirs.iter().enumerate() {
+        if our_entries.contains_key(key) {
+            continue;
+        }
+        match base.get(key) {
+            // Deleted in ours.
+            Some(base_entry) if base_entry == their_entry => {}
+            Some(base_entry) => {
+                conflicts.push(MergeConflict::new(
+                    "deleted_in_ours",
+                    Some(base_entry),
+                    None,
+                    Some(their_entry),
+                ));
+            }
+            // Added in theirs, so it goes after the entry it follows there.
+            None => {
+                let position = theirs[..index]
+                    .iter()
+                    .rev()
+                    .find_map(|(previous, _)| {
+                        merged
+                            .iter()
+                            .position(|(merged_key, _)| *merged_key == previous)
+                    })
+                    .map_or(0, |position| position + 1);
+                merged.insert(position, (key, their_entry));
+            }
+        }
+    }
+
+    let resource = ast::Resource {
+        body: merged.into_iter().map(|(_, entry)| entry.clone()).collect(),
+    };
+    MergeResult {
+        source: serialize_with_options(&resource, Options { with_junk: true }),
+        conflicts,
+    }
+}
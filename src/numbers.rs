@@ -0,0 +1,398 @@
+use crate::variables::{decimal_value, int_value};
+use fluent::FluentValue;
+use fluent_bundle::types::{
+    FluentNumber, FluentNumberCurrencyDisplayStyle, FluentNumberOptions, FluentNumberStyle,
+    FluentNumberType,
+};
+use intl_memoizer::Memoizable;
+use intl_memoizer::concurrent::IntlLangMemoizer;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyFloat, PyInt};
+use std::convert::Infallible;
+use std::str::FromStr;
+use unic_langid::LanguageIdentifier;
+
+/// A number with formatting options, named as in `NUMBER()`, for a variable to be formatted
+/// the same way wherever it's used. It's still a number for plural rules, and options given
+/// to `NUMBER()` in the message take precedence.
+#[pyclass(frozen, module = "rustfluent")]
+pub(crate) struct Number {
+    number: FluentNumber,
+}
+
+#[pymethods]
+impl Number {
+    #[new]
+    #[pyo3(signature = (value, *, style="decimal", currency=None, currency_display="symbol", use_grouping=true, minimum_integer_digits=None, minimum_fraction_digits=None, maximum_fraction_digits=None, minimum_significant_digits=None, maximum_significant_digits=None, r#type="cardinal"))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        value: &Bound<'_, PyAny>,
+        style: &str,
+        currency: Option<String>,
+        currency_display: &str,
+        use_grouping: bool,
+        minimum_integer_digits: Option<usize>,
+        minimum_fraction_digits: Option<usize>,
+        maximum_fraction_digits: Option<usize>,
+        minimum_significant_digits: Option<usize>,
+        maximum_significant_digits: Option<usize>,
+        r#type: &str,
+    ) -> PyResult<Self> {
+        // Decimals keep their trailing zeros, unless `minimum_fraction_digits` is given.
+        let mut number = match decimal_value(value)? {
+            Some(FluentValue::Number(number)) => number,
+            _ => FluentNumber::new(value.extract()?, FluentNumberOptions::default()),
+        };
+        let options = &mut number.options;
+        options.style = match style {
+            "decimal" => FluentNumberStyle::Decimal,
+            "currency" => FluentNumberStyle::Currency,
+            "percent" => FluentNumberStyle::Percent,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "style must be 'currency', 'decimal' or 'percent', not '{style}'."
+                )));
+            }
+        };
+        if options.style == FluentNumberStyle::Currency && currency.is_none() {
+            return Err(PyValueError::new_err(
+                "currency is required with style='currency'.",
+            ));
+        }
+        options.currency = currency;
+        options.currency_display = match currency_display {
+            "symbol" => FluentNumberCurrencyDisplayStyle::Symbol,
+            "code" => FluentNumberCurrencyDisplayStyle::Code,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "currency_display must be 'code' or 'symbol', not '{currency_display}'."
+                )));
+            }
+        };
+        options.r#type = match r#type {
+            "cardinal" => FluentNumberType::Cardinal,
+            "ordinal" => FluentNumberType::Ordinal,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "type must be 'cardinal' or 'ordinal', not '{}'.",
+                    r#type
+                )));
+            }
+        };
+        options.use_grouping = use_grouping;
+        options.minimum_integer_digits = minimum_integer_digits;
+        options.minimum_fraction_digits = match (minimum_fraction_digits, maximum_fraction_digits) {
+            (Some(minimum), _) => Some(minimum),
+            // A decimal's own digits don't override the maximum.
+            (None, Some(maximum)) => options
+                .minimum_fraction_digits
+                .map(|digits| digits.min(maximum)),
+            (None, None) => options.minimum_fraction_digits,
+        };
+        options.maximum_fraction_digits = maximum_fraction_digits;
+        options.minimum_significant_digits = minimum_significant_digits;
+        options.maximum_significant_digits = maximum_significant_digits;
+        Ok(Self { number })
+    }
+
+    #[getter]
+    fn value(&self) -> f64 {
+        self.number.value
+    }
+
+    fn __repr__(&self) -> String {
+        let options = &self.number.options;
+        let mut repr = format!("Number({:?}", self.number.value);
+        match options.style {
+            FluentNumberStyle::Decimal => {}
+            FluentNumberStyle::Currency => repr.push_str(", style='currency'"),
+            FluentNumberStyle::Percent => repr.push_str(", style='percent'"),
+        }
+        if let Some(currency) = &options.currency {
+            repr.push_str(&format!(", currency='{currency}'"));
+        }
+        let digits = [
+            ("minimum_integer_digits", options.minimum_integer_digits),
+            ("minimum_fraction_digits", options.minimum_fraction_digits),
+            ("maximum_fraction_digits", options.maximum_fraction_digits),
+            (
+                "minimum_significant_digits",
+                options.minimum_significant_digits,
+            ),
+            (
+                "maximum_significant_digits",
+                options.maximum_significant_digits,
+            ),
+        ];
+        for (name, value) in digits {
+            if let Some(value) = value {
+                repr.push_str(&format!(", {name}={value}"));
+            }
+        }
+        repr.push(')');
+        repr
+    }
+}
+
+impl Number {
+    /// The Fluent number to pass to a message.
+    pub(crate) fn fluent_number(&self) -> FluentNumber {
+        self.number.clone()
+    }
+}
+
+/// Convert a Python number into the Fluent number it would be as a variable, e.g. to find its
+/// plural category. Returns `None` for values which aren't numbers.
+pub(crate) fn number_value(value: &Bound<'_, PyAny>) -> PyResult<Option<FluentNumber>> {
+    let number = if value.is_instance_of::<PyBool>() {
+        return Ok(None);
+    } else if value.is_instance_of::<PyInt>() {
+        int_value(value)
+    } else if let Some(number) = decimal_value(value)? {
+        number
+    } else if let Ok(number) = value.cast::<Number>() {
+        return Ok(Some(number.get().fluent_number()));
+    } else if value.is_instance_of::<PyFloat>() {
+        return Ok(Some(FluentNumber::from(value.extract::<f64>()?)));
+    } else {
+        return Ok(None);
+    };
+    Ok(match number {
+        FluentValue::Number(number) => Some(number),
+        // Integers too large for an `f64`, and NaN decimals.
+        FluentValue::String(digits) => FluentNumber::from_str(&digits).ok(),
+        _ => None,
+    })
+}
+
+/// Where the currency symbol goes relative to the number.
+#[derive(Clone, Copy)]
+enum CurrencyPosition {
+    /// `$1.00`
+    Prefix,
+    /// `€ 1,00`
+    PrefixSpaced,
+    /// `1,00 €`
+    Suffix,
+}
+
+/// The symbols used to format numbers in a locale, taken from the CLDR.
+#[derive(Clone, Copy)]
+struct NumberSymbols {
+    group: &'static str,
+    decimal: &'static str,
+    /// The number of digits needed before the first group separator is used.
+    minimum_grouping_digits: usize,
+    percent_suffix: &'static str,
+    currency_position: CurrencyPosition,
+}
+
+const DEFAULT_SYMBOLS: NumberSymbols = NumberSymbols {
+    group: ",",
+    decimal: ".",
+    minimum_grouping_digits: 1,
+    percent_suffix: "%",
+    currency_position: CurrencyPosition::Prefix,
+};
+
+impl NumberSymbols {
+    fn for_locale(locale: &LanguageIdentifier) -> Self {
+        use CurrencyPosition::*;
+
+        let symbols =
+            |group, decimal, minimum_grouping_digits, percent_suffix, currency_position| Self {
+                group,
+                decimal,
+                minimum_grouping_digits,
+                percent_suffix,
+                currency_position,
+            };
+        let region = locale.region.as_ref().map(|region| region.as_str());
+        match (locale.language.as_str(), region) {
+            ("de", Some("CH" | "LI")) => symbols("’", ".", 1, "%", Suffix),
+            ("de", Some("AT")) => symbols("\u{a0}", ",", 1, "\u{a0}%", Suffix),
+            ("de", _) => symbols(".", ",", 1, "\u{a0}%", Suffix),
+            ("fr", _) => symbols("\u{202f}", ",", 1, "\u{202f}%", Suffix),
+            ("es", _) => symbols(".", ",", 2, "\u{a0}%", Suffix),
+            ("it", _) => symbols(".", ",", 1, "%", Suffix),
+            ("nl", _) => symbols(".", ",", 1, "%", PrefixSpaced),
+            ("pt", Some("PT")) => symbols("\u{a0}", ",", 2, "%", Suffix),
+            ("pt", _) => symbols(".", ",", 1, "%", PrefixSpaced),
+            ("pl", _) => symbols("\u{a0}", ",", 2, "%", Suffix),
+            ("da", _) => symbols(".", ",", 1, "\u{a0}%", Suffix),
+            ("cs" | "fi" | "nb" | "nn" | "no" | "ru" | "sk" | "sv" | "uk", _) => {
+                symbols("\u{a0}", ",", 1, "\u{a0}%", Suffix)
+            }
+            _ => DEFAULT_SYMBOLS,
+        }
+    }
+}
+
+/// Formats Fluent numbers according to the bundle's locale.
+pub(crate) struct NumberFormatter {
+    symbols: NumberSymbols,
+}
+
+impl Memoizable for NumberFormatter {
+    type Args = ();
+    type Error = Infallible;
+
+    fn construct(lang: LanguageIdentifier, _args: Self::Args) -> Result<Self, Self::Error> {
+        Ok(Self {
+            symbols: NumberSymbols::for_locale(&lang),
+        })
+    }
+}
+
+/// A `FluentBundle` formatter which formats numbers using the bundle's locale,
+/// leaving other values to the default formatting.
+pub(crate) fn format_value(value: &FluentValue<'_>, intls: &IntlLangMemoizer) -> Option<String> {
+    let FluentValue::Number(number) = value else {
+        return None;
+    };
+    intls
+        .with_try_get::<NumberFormatter, _, _>((), |formatter| formatter.format(number))
+        .ok()
+}
+
+impl NumberFormatter {
+    fn format(&self, number: &FluentNumber) -> String {
+        let options = &number.options;
+        if !number.value.is_finite() {
+            return number.as_string().into_owned();
+        }
+
+        let value = match options.style {
+            FluentNumberStyle::Percent => number.value * 100.0,
+            _ => number.value,
+        };
+        let currency = match (&options.style, &options.currency) {
+            (FluentNumberStyle::Currency, Some(currency)) => Some(currency.to_uppercase()),
+            _ => None,
+        };
+        // The defaults used by `Intl.NumberFormat`.
+        let (default_minimum, default_maximum) = match (&options.style, currency.as_deref()) {
+            (FluentNumberStyle::Currency, Some(currency)) => {
+                (currency_digits(currency), currency_digits(currency))
+            }
+            (FluentNumberStyle::Percent, _) => (0, 0),
+            _ => (0, 3),
+        };
+
+        let digits = match options.maximum_significant_digits {
+            Some(maximum) => significant_digits(
+                value.abs(),
+                options.minimum_significant_digits.unwrap_or(1),
+                maximum,
+            ),
+            None => {
+                let minimum = options.minimum_fraction_digits.unwrap_or(default_minimum);
+                let maximum = options
+                    .maximum_fraction_digits
+                    .unwrap_or(default_maximum)
+                    .max(minimum);
+                fraction_digits(value.abs(), minimum, maximum)
+            }
+        };
+        let (integer, fraction) = digits.split_once('.').unwrap_or((&digits, ""));
+
+        let mut formatted = String::new();
+        let minimum_integer_digits = options.minimum_integer_digits.unwrap_or(1);
+        let integer = format!("{integer:0>minimum_integer_digits$}");
+        if options.use_grouping && integer.len() >= 3 + self.symbols.minimum_grouping_digits {
+            for (index, digit) in integer.chars().enumerate() {
+                if index > 0 && (integer.len() - index) % 3 == 0 {
+                    formatted.push_str(self.symbols.group);
+                }
+                formatted.push(digit);
+            }
+        } else {
+            formatted.push_str(&integer);
+        }
+        if !fraction.is_empty() {
+            formatted.push_str(self.symbols.decimal);
+            formatted.push_str(fraction);
+        }
+
+        let sign = if value < 0.0 && digits.chars().any(|digit| matches!(digit, '1'..='9')) {
+            "-"
+        } else {
+            ""
+        };
+        match (&options.style, currency) {
+            (FluentNumberStyle::Percent, _) => {
+                format!("{sign}{formatted}{}", self.symbols.percent_suffix)
+            }
+            (FluentNumberStyle::Currency, Some(currency)) => {
+                let symbol = match options.currency_display {
+                    FluentNumberCurrencyDisplayStyle::Symbol => currency_symbol(&currency),
+                    _ => &currency,
+                };
+                match self.symbols.currency_position {
+                    CurrencyPosition::Prefix => format!("{sign}{symbol}{formatted}"),
+                    CurrencyPosition::PrefixSpaced => format!("{symbol}\u{a0}{sign}{formatted}"),
+                    CurrencyPosition::Suffix => format!("{sign}{formatted}\u{a0}{symbol}"),
+                }
+            }
+            _ => format!("{sign}{formatted}"),
+        }
+    }
+}
+
+/// Render a non-negative value with between `minimum` and `maximum` fraction digits.
+fn fraction_digits(value: f64, minimum: usize, maximum: usize) -> String {
+    let mut digits = format!("{value:.maximum$}");
+    if let Some(point) = digits.find('.') {
+        while digits.len() - point - 1 > minimum && digits.ends_with('0') {
+            digits.pop();
+        }
+        if digits.ends_with('.') {
+            digits.pop();
+        }
+    }
+    digits
+}
+
+/// Render a non-negative value rounded to at most `maximum` significant digits,
+/// keeping at least `minimum` of them.
+fn significant_digits(value: f64, minimum: usize, maximum: usize) -> String {
+    let maximum = maximum.max(1);
+    let rounded: f64 = format!("{value:.*e}", maximum - 1).parse().unwrap_or(value);
+    let exponent = if rounded == 0.0 {
+        0
+    } else {
+        rounded.log10().floor() as i64
+    };
+    let fraction_digit_count =
+        |significant: usize| (significant as i64 - 1 - exponent).max(0) as usize;
+    fraction_digits(
+        rounded,
+        fraction_digit_count(minimum.min(maximum)),
+        fraction_digit_count(maximum),
+    )
+}
+
+/// The number of fraction digits conventionally used for a currency.
+fn currency_digits(currency: &str) -> usize {
+    match currency {
+        "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF" | "UGX"
+        | "VND" | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+        "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+        _ => 2,
+    }
+}
+
+fn currency_symbol(currency: &str) -> &str {
+    match currency {
+        "EUR" => "€",
+        "GBP" => "£",
+        "USD" => "$",
+        "JPY" => "¥",
+        "INR" => "₹",
+        "KRW" => "₩",
+        "AUD" => "A$",
+        "CAD" => "CA$",
+        _ => currency,
+    }
+}
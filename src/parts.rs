@@ -0,0 +1,67 @@
+use fluent_syntax::ast;
+use pyo3::prelude::*;
+
+/// A segment of a formatted message, as returned by `Bundle.get_translation_parts`, so
+/// frontends can render placeables differently from the surrounding text.
+#[pyclass(frozen, get_all, module = "rustfluent")]
+pub(crate) struct MessagePart {
+    /// "literal" for text from the FTL source, or "placeable" for a `{ ... }` expression.
+    r#type: String,
+    /// The formatted text of the part.
+    value: String,
+    /// For a placeable, the variable it formats, e.g. `name` for `{ $name }` or
+    /// `{ NUMBER($name) }`.
+    name: Option<String>,
+}
+
+impl MessagePart {
+    /// Make the part for an element of a message's pattern, formatted as `value`.
+    pub(crate) fn new(element: &ast::PatternElement<&str>, value: String) -> Self {
+        match element {
+            ast::PatternElement::TextElement { .. } => Self {
+                r#type: "literal".to_string(),
+                value,
+                name: None,
+            },
+            ast::PatternElement::Placeable { expression } => Self {
+                r#type: "placeable".to_string(),
+                value,
+                name: placeable_variable(expression).map(str::to_string),
+            },
+        }
+    }
+}
+
+#[pymethods]
+impl MessagePart {
+    fn __repr__(&self) -> String {
+        match &self.name {
+            Some(name) => format!(
+                "MessagePart(type={:?}, value={:?}, name={name:?})",
+                self.r#type, self.value
+            ),
+            None => format!(
+                "MessagePart(type={:?}, value={:?})",
+                self.r#type, self.value
+            ),
+        }
+    }
+}
+
+/// Return the variable a placeable formats, either directly or as the first argument of a
+/// function.
+fn placeable_variable<'s>(expression: &ast::Expression<&'s str>) -> Option<&'s str> {
+    let ast::Expression::Inline(inline) = expression else {
+        return None;
+    };
+    let variable = match inline {
+        ast::InlineExpression::FunctionReference { arguments, .. } => {
+            arguments.positional.first()?
+        }
+        inline => inline,
+    };
+    match variable {
+        ast::InlineExpression::VariableReference { id } => Some(id.name),
+        _ => None,
+    }
+}
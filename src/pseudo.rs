@@ -0,0 +1,116 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::borrow::Cow;
+
+/// Accented versions of `a` to `z` and `A` to `Z`, which are still readable.
+const ACCENTED: [char; 52] = [
+    'ȧ', 'ƀ', 'ƈ', 'ḓ', 'ḗ', 'ƒ', 'ɠ', 'ħ', 'ī', 'ĵ', 'ķ', 'ŀ', 'ḿ', 'ƞ', 'ǿ', 'ƥ', 'ɋ', 'ř', 'ş',
+    'ŧ', 'ŭ', 'ṽ', 'ẇ', 'ẋ', 'ẏ', 'ẑ', 'Ȧ', 'Ɓ', 'Ƈ', 'Ḓ', 'Ḗ', 'Ƒ', 'Ɠ', 'Ħ', 'Ī', 'Ĵ', 'Ķ', 'Ŀ',
+    'Ḿ', 'Ƞ', 'Ǿ', 'Ƥ', 'Ɋ', 'Ř', 'Ş', 'Ŧ', 'Ŭ', 'Ṽ', 'Ẇ', 'Ẋ', 'Ẏ', 'Ẑ',
+];
+
+/// Upside-down versions of `a` to `z` and `A` to `Z`, to read right to left.
+const FLIPPED: [char; 52] = [
+    'ɐ', 'q', 'ɔ', 'p', 'ǝ', 'ɟ', 'ƃ', 'ɥ', 'ı', 'ɾ', 'ʞ', 'ʅ', 'ɯ', 'u', 'o', 'd', 'b', 'ɹ', 's',
+    'ʇ', 'n', 'ʌ', 'ʍ', 'x', 'ʎ', 'z', '∀', 'Ԑ', 'Ↄ', 'ᗡ', 'Ǝ', 'Ⅎ', '⅁', 'H', 'I', 'ſ', 'Ӽ', '⅂',
+    'W', 'N', 'O', 'Ԁ', 'Ò', 'ᴚ', 'S', '⊥', '∩', 'Ʌ', 'Ｍ', 'X', 'ʎ', 'Z',
+];
+
+/// A pseudo-localization transform for the text of messages, from the `pseudo` argument,
+/// so that untranslated strings and layout problems stand out without fake FTL files.
+/// Only the text of the FTL source is transformed, not variables.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Pseudo {
+    /// Accent every letter.
+    Accented,
+    /// Accent every letter, and double vowels, to make text about a third longer.
+    Expanded,
+    /// Flip every letter, and mark each word as right-to-left.
+    Bidi,
+}
+
+impl Pseudo {
+    pub(crate) fn new(pseudo: &str) -> PyResult<Self> {
+        match pseudo {
+            "accented" => Ok(Self::Accented),
+            "expanded" => Ok(Self::Expanded),
+            "bidi" => Ok(Self::Bidi),
+            _ => Err(PyValueError::new_err(format!(
+                "pseudo must be 'accented', 'bidi' or 'expanded', not '{pseudo}'."
+            ))),
+        }
+    }
+
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Accented => "accented",
+            Self::Expanded => "expanded",
+            Self::Bidi => "bidi",
+        }
+    }
+
+    /// The transform for Fluent to apply to text.
+    pub(crate) fn transform(self) -> fn(&str) -> Cow<'_, str> {
+        match self {
+            Self::Accented => accented,
+            Self::Expanded => expanded,
+            Self::Bidi => bidi,
+        }
+    }
+}
+
+/// Return the character in `map` for an ASCII letter.
+fn replace(c: char, map: &[char; 52]) -> Option<char> {
+    match c {
+        'a'..='z' => Some(map[c as usize - 'a' as usize]),
+        'A'..='Z' => Some(map[26 + c as usize - 'A' as usize]),
+        _ => None,
+    }
+}
+
+fn accented(text: &str) -> Cow<'_, str> {
+    text.chars()
+        .map(|c| replace(c, &ACCENTED).unwrap_or(c))
+        .collect::<String>()
+        .into()
+}
+
+fn expanded(text: &str) -> Cow<'_, str> {
+    let mut transformed = String::with_capacity(text.len() * 2);
+    for c in text.chars() {
+        let accented = replace(c, &ACCENTED).unwrap_or(c);
+        transformed.push(accented);
+        if "aeiouAEIOU".contains(c) {
+            transformed.push(accented);
+        }
+    }
+    transformed.into()
+}
+
+fn bidi(text: &str) -> Cow<'_, str> {
+    // Each word is wrapped in RIGHT-TO-LEFT OVERRIDE and POP DIRECTIONAL FORMATTING.
+    let mut transformed = String::with_capacity(text.len() * 3);
+    let mut in_word = false;
+    for c in text.chars() {
+        match replace(c, &FLIPPED) {
+            Some(flipped) => {
+                if !in_word {
+                    transformed.push('\u{202e}');
+                    in_word = true;
+                }
+                transformed.push(flipped);
+            }
+            None => {
+                if in_word {
+                    transformed.push('\u{202c}');
+                    in_word = false;
+                }
+                transformed.push(c);
+            }
+        }
+    }
+    if in_word {
+        transformed.push('\u{202c}');
+    }
+    transformed.into()
+}
@@ -0,0 +1,404 @@
+use crate::entry_id;
+use crate::pseudo::Pseudo;
+use fluent_syntax::ast;
+use fluent_syntax::parser::{Slice, parse};
+use fluent_syntax::serializer::{Options, serialize_with_options};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::iter::repeat_n;
+
+/// Whether a name is a Fluent identifier, as used for the IDs of messages (and terms, after
+/// their `-`), attributes and variables: a letter followed by letters, digits, `_` and `-`.
+pub(crate) fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Whether a prefix can be put in front of message IDs, keeping them identifiers.
+pub(crate) fn is_valid_prefix(prefix: &str) -> bool {
+    is_identifier(prefix)
+}
+
+/// Parse FTL source and serialize it again, normalizing its formatting.
+pub(crate) fn normalize(source: &str, with_junk: bool) -> String {
+    let resource = match parse(source) {
+        Ok(resource) => resource,
+        Err((resource, _errors)) => resource,
+    };
+    serialize_with_options(&resource, Options { with_junk })
+}
+
+/// Format FTL source in canonical form, keeping invalid entries as they are, with `indent`
+/// spaces for each level of indentation. With `sort`, the messages and terms between each
+/// comment are sorted by ID.
+pub(crate) fn format_source(source: &str, indent: usize, sort: bool) -> String {
+    let mut resource = parse_all(source.to_string());
+    // The serializer indents by 4 spaces, so spaces at the start of lines of text and invalid
+    // entries are marked with a character that isn't in the source, to keep them as they are
+    // while the serializer's indentation is changed.
+    let marker = ('\u{E000}'..='\u{F8FF}')
+        .find(|marker| !source.contains(*marker))
+        .filter(|_| indent != 4);
+    if let Some(marker) = marker {
+        for_each_text(&mut resource, &mut |text, at_line_start| {
+            *text = mark_indentation(text, at_line_start, marker);
+        });
+    }
+    if sort {
+        for section in resource.body.split_mut(|entry| entry_id(entry).is_none()) {
+            section.sort_by_key(entry_id);
+        }
+    }
+    let serialized = serialize_with_options(&resource, Options { with_junk: true });
+    match marker {
+        Some(marker) => reindent(&serialized, indent, marker),
+        None => serialized,
+    }
+}
+
+/// Parse FTL source, including any invalid entries.
+pub(crate) fn parse_all<'s, S: Slice<'s>>(source: S) -> ast::Resource<S> {
+    match parse(source) {
+        Ok(resource) => resource,
+        Err((resource, _errors)) => resource,
+    }
+}
+
+/// Serialize resources in order, leaving out the messages and terms that are overridden by a
+/// later definition.
+pub(crate) fn serialize_in_effect<'s, S: Slice<'s>>(
+    resources: Vec<ast::Resource<S>>,
+    with_junk: bool,
+) -> String {
+    // Where the definition of each message and term that's in effect is.
+    let mut definitions = HashMap::new();
+    for (index, resource) in resources.iter().enumerate() {
+        for (position, entry) in resource.body.iter().enumerate() {
+            if let Some(id) = entry_id(entry) {
+                definitions.insert(id, (index, position));
+            }
+        }
+    }
+    let mut sources = vec![];
+    for (index, mut resource) in resources.into_iter().enumerate() {
+        let mut position = 0;
+        resource.body.retain(|entry| {
+            let in_effect = entry_id(entry).is_none_or(|id| definitions[&id] == (index, position));
+            position += 1;
+            in_effect
+        });
+        let source = serialize_with_options(&resource, Options { with_junk });
+        if !source.is_empty() {
+            sources.push(source);
+        }
+    }
+    sources.join("\n")
+}
+
+/// How `generate_skeleton` fills in the values of messages for a new locale.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum SkeletonMode {
+    /// An empty string, `{ "" }`, as FTL doesn't allow empty values.
+    Empty,
+    /// The reference's values.
+    Copy,
+    /// The reference's values, with the text pseudo-localized.
+    Pseudo,
+}
+
+impl SkeletonMode {
+    pub(crate) fn new(mode: &str) -> PyResult<Self> {
+        match mode {
+            "empty" => Ok(Self::Empty),
+            "copy" => Ok(Self::Copy),
+            "pseudo" => Ok(Self::Pseudo),
+            _ => Err(PyValueError::new_err(format!(
+                "mode must be 'copy', 'empty' or 'pseudo', not '{mode}'."
+            ))),
+        }
+    }
+}
+
+/// Make the FTL for a new locale from reference FTL sources, with their messages, terms,
+/// attributes and comments, and values filled in according to `mode`. Invalid entries are
+/// left out, and messages and terms defined in several sources are only included once.
+pub(crate) fn skeleton(sources: &[&str], mode: SkeletonMode) -> String {
+    let mut resources = Vec::with_capacity(sources.len());
+    for source in sources {
+        let mut resource = parse_all(source.to_string());
+        resource
+            .body
+            .retain(|entry| !matches!(entry, ast::Entry::Junk { .. }));
+        match mode {
+            SkeletonMode::Empty => {
+                for pattern in patterns(&mut resource) {
+                    *pattern = empty_pattern();
+                }
+            }
+            SkeletonMode::Copy => {}
+            SkeletonMode::Pseudo => {
+                let transform = Pseudo::Accented.transform();
+                for_each_text(&mut resource, &mut |text, _| {
+                    *text = transform(text).into_owned();
+                });
+            }
+        }
+        resources.push(resource);
+    }
+    serialize_in_effect(resources, false)
+}
+
+/// Return the values and attributes of the messages and terms in a resource.
+fn patterns<S>(resource: &mut ast::Resource<S>) -> Vec<&mut ast::Pattern<S>> {
+    let mut patterns = vec![];
+    for entry in resource.body.iter_mut() {
+        let (value, attributes) = match entry {
+            ast::Entry::Message(message) => (message.value.as_mut(), &mut message.attributes),
+            ast::Entry::Term(term) => (Some(&mut term.value), &mut term.attributes),
+            _ => continue,
+        };
+        patterns.extend(value);
+        patterns.extend(attributes.iter_mut().map(|attribute| &mut attribute.value));
+    }
+    patterns
+}
+
+/// A pattern for an empty string, `{ "" }`.
+pub(crate) fn empty_pattern() -> ast::Pattern<String> {
+    ast::Pattern {
+        elements: vec![ast::PatternElement::Placeable {
+            expression: ast::Expression::Inline(ast::InlineExpression::StringLiteral {
+                value: String::new(),
+            }),
+        }],
+    }
+}
+
+/// Replace the spaces at the start of each line of some text with a marker.
+fn mark_indentation(text: &str, mut at_line_start: bool, marker: char) -> String {
+    text.chars()
+        .map(|c| {
+            let indented = at_line_start && c == ' ';
+            at_line_start = indented || c == '\n';
+            if indented { marker } else { c }
+        })
+        .collect()
+}
+
+/// Change serialized FTL from 4 spaces for each level of indentation to `indent`, and replace
+/// the markers of the spaces that are part of the source's text with spaces again.
+fn reindent(serialized: &str, indent: usize, marker: char) -> String {
+    let mut reindented = String::with_capacity(serialized.len());
+    for line in serialized.split_inclusive('\n') {
+        let content = line.trim_start_matches(' ');
+        let spaces = line.len() - content.len();
+        // Default variants have a `*` in place of their last space of indentation.
+        let star = usize::from(content.starts_with('*'));
+        let level = (spaces + star) / 4;
+        let extra = (spaces + star) % 4;
+        reindented.extend(repeat_n(' ', level * indent + extra - star));
+        reindented.push_str(content);
+    }
+    reindented.replace(marker, " ")
+}
+
+/// Call `f` on each invalid entry, and on the text in the source of each message and term,
+/// along with whether the text starts at the start of a line.
+fn for_each_text(resource: &mut ast::Resource<String>, f: &mut impl FnMut(&mut String, bool)) {
+    for entry in resource.body.iter_mut() {
+        if let ast::Entry::Junk { content } = entry {
+            f(content, true);
+        }
+    }
+    for pattern in patterns(resource) {
+        text_in_pattern(pattern, f);
+    }
+}
+
+fn text_in_pattern(pattern: &mut ast::Pattern<String>, f: &mut impl FnMut(&mut String, bool)) {
+    let mut at_line_start = false;
+    for element in pattern.elements.iter_mut() {
+        match element {
+            ast::PatternElement::TextElement { value } => {
+                f(value, at_line_start);
+                at_line_start = value.ends_with('\n');
+            }
+            ast::PatternElement::Placeable { expression } => {
+                text_in_expression(expression, f);
+                at_line_start = false;
+            }
+        }
+    }
+}
+
+fn text_in_expression(
+    expression: &mut ast::Expression<String>,
+    f: &mut impl FnMut(&mut String, bool),
+) {
+    match expression {
+        ast::Expression::Select { selector, variants } => {
+            text_in_inline_expression(selector, f);
+            for variant in variants.iter_mut() {
+                text_in_pattern(&mut variant.value, f);
+            }
+        }
+        ast::Expression::Inline(expression) => text_in_inline_expression(expression, f),
+    }
+}
+
+fn text_in_inline_expression(
+    expression: &mut ast::InlineExpression<String>,
+    f: &mut impl FnMut(&mut String, bool),
+) {
+    let arguments = match expression {
+        ast::InlineExpression::FunctionReference { arguments, .. } => arguments,
+        ast::InlineExpression::TermReference {
+            arguments: Some(arguments),
+            ..
+        } => arguments,
+        ast::InlineExpression::Placeable { expression } => {
+            return text_in_expression(expression, f);
+        }
+        _ => return,
+    };
+    for argument in arguments.positional.iter_mut() {
+        text_in_inline_expression(argument, f);
+    }
+    for argument in arguments.named.iter_mut() {
+        text_in_inline_expression(&mut argument.value, f);
+    }
+}
+
+/// Put a prefix in front of the IDs of the messages in FTL source, and of the references to
+/// them within it. Terms, and references to messages in other files, are left as they are.
+pub(crate) fn prefix_messages(source: &str, prefix: &str) -> String {
+    let message_ids = match parse(source) {
+        Ok(resource) => resource,
+        Err((resource, _errors)) => resource,
+    }
+    .body
+    .into_iter()
+    .filter_map(|entry| match entry {
+        ast::Entry::Message(message) => Some(message.id.name),
+        _ => None,
+    });
+    let renames: HashMap<&str, String> = message_ids
+        .map(|id| (id, format!("{prefix}{id}")))
+        .collect();
+    rename_entries(source, &renames).unwrap_or_else(|| normalize(source, true))
+}
+
+/// Rename messages and terms (with their leading `-`) in FTL source, both where they're
+/// defined and where they're referenced. The source is serialized again, so its formatting
+/// is normalized, but invalid entries are kept as they are. Returns `None` if none of them
+/// are defined or referenced in the source.
+pub(crate) fn rename_entries<'s>(
+    source: &'s str,
+    renames: &'s HashMap<&str, String>,
+) -> Option<String> {
+    let mut resource = match parse(source) {
+        Ok(resource) => resource,
+        Err((resource, _errors)) => resource,
+    };
+    let renamed = Cell::new(false);
+    let rename = |id: &mut ast::Identifier<&'s str>, is_term: bool| {
+        let new_id = if is_term {
+            renames.get(format!("-{}", id.name).as_str())
+        } else {
+            renames.get(id.name)
+        };
+        if let Some(new_id) = new_id {
+            id.name = new_id.strip_prefix('-').unwrap_or(new_id);
+            renamed.set(true);
+        }
+    };
+    for entry in resource.body.iter_mut() {
+        match entry {
+            ast::Entry::Message(message) => {
+                rename(&mut message.id, false);
+                if let Some(value) = &mut message.value {
+                    rename_in_pattern(value, &rename);
+                }
+                for attribute in message.attributes.iter_mut() {
+                    rename_in_pattern(&mut attribute.value, &rename);
+                }
+            }
+            ast::Entry::Term(term) => {
+                rename(&mut term.id, true);
+                rename_in_pattern(&mut term.value, &rename);
+                for attribute in term.attributes.iter_mut() {
+                    rename_in_pattern(&mut attribute.value, &rename);
+                }
+            }
+            _ => {}
+        }
+    }
+    renamed
+        .get()
+        .then(|| serialize_with_options(&resource, Options { with_junk: true }))
+}
+
+fn rename_in_pattern<'s>(
+    pattern: &mut ast::Pattern<&'s str>,
+    rename: &impl Fn(&mut ast::Identifier<&'s str>, bool),
+) {
+    for element in pattern.elements.iter_mut() {
+        if let ast::PatternElement::Placeable { expression } = element {
+            rename_in_expression(expression, rename);
+        }
+    }
+}
+
+fn rename_in_expression<'s>(
+    expression: &mut ast::Expression<&'s str>,
+    rename: &impl Fn(&mut ast::Identifier<&'s str>, bool),
+) {
+    match expression {
+        ast::Expression::Select { selector, variants } => {
+            rename_in_inline_expression(selector, rename);
+            for variant in variants.iter_mut() {
+                rename_in_pattern(&mut variant.value, rename);
+            }
+        }
+        ast::Expression::Inline(expression) => rename_in_inline_expression(expression, rename),
+    }
+}
+
+fn rename_in_inline_expression<'s>(
+    expression: &mut ast::InlineExpression<&'s str>,
+    rename: &impl Fn(&mut ast::Identifier<&'s str>, bool),
+) {
+    match expression {
+        ast::InlineExpression::MessageReference { id, .. } => rename(id, false),
+        ast::InlineExpression::FunctionReference { arguments, .. } => {
+            rename_in_arguments(arguments, rename);
+        }
+        ast::InlineExpression::TermReference { id, arguments, .. } => {
+            rename(id, true);
+            if let Some(arguments) = arguments {
+                rename_in_arguments(arguments, rename);
+            }
+        }
+        ast::InlineExpression::Placeable { expression } => {
+            rename_in_expression(expression, rename);
+        }
+        _ => {}
+    }
+}
+
+fn rename_in_arguments<'s>(
+    arguments: &mut ast::CallArguments<&'s str>,
+    rename: &impl Fn(&mut ast::Identifier<&'s str>, bool),
+) {
+    for argument in arguments.positional.iter_mut() {
+        rename_in_inline_expression(argument, rename);
+    }
+    for argument in arguments.named.iter_mut() {
+        rename_in_inline_expression(&mut argument.value, rename);
+    }
+}
@@ -1,15 +1,508 @@
+from collections.abc import Callable, Iterable, Iterator, Mapping
 from datetime import date
+from decimal import Decimal
 from pathlib import Path
+from typing import IO, Any, Literal, TypedDict, TypeVar, overload
 
-Variable = str | int | date
+T = TypeVar("T")
+
+Variable = (
+    str
+    | int
+    | bool
+    | Decimal
+    | Number
+    | date
+    | list[str]
+    | tuple[str, ...]
+    | Mapping[str, "Variable"]
+    | None
+)
+FtlSource = str | Path | tuple[str, str] | bytes | IO[str] | IO[bytes]
+ErrorHandling = Literal["ignore", "raise", "log"] | Callable[[FormatError], object]
+
+class ParserError(Exception): ...
+class ParserErrorGroup(ExceptionGroup[ParserError], ParserError): ...
+
+class BundleValidationError(ParserError):
+    error: ValidationError
+
+class MissingPluralCategoryError(BundleValidationError): ...
+class UnknownMessageError(BundleValidationError): ...
+class UnknownTermError(BundleValidationError): ...
+class CyclicReferenceError(BundleValidationError): ...
+class DuplicateMessageError(BundleValidationError): ...
+class IgnoredPositionalArgumentError(BundleValidationError): ...
+class VariableDocMismatchError(BundleValidationError): ...
+class PlaceholderMismatchError(BundleValidationError): ...
+class MarkupMismatchError(BundleValidationError): ...
+class LintError(BundleValidationError): ...
+
+class FormatError(Exception):
+    message: str
+    errors: list[str]
+    message_id: str
+    variable_name: str | None
+    reference: str | None
+    severity: Literal["error", "warning", "info"]
+    codes: list[str]
+    def __init__(
+        self,
+        message: str,
+        message_id: str,
+        errors: list[str] = ...,
+        variable_name: str | None = None,
+        reference: str | None = None,
+        severity: Literal["error", "warning", "info"] = "error",
+        codes: list[str] = ...,
+    ) -> None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+
+class FluentWarning(UserWarning): ...
+class ParseWarning(FluentWarning): ...
+class ValidationWarning(FluentWarning): ...
+class MissingPluralCategoryWarning(ValidationWarning): ...
+class UnknownMessageWarning(ValidationWarning): ...
+class UnknownTermWarning(ValidationWarning): ...
+class CyclicReferenceWarning(ValidationWarning): ...
+class DuplicateMessageWarning(ValidationWarning): ...
+class IgnoredPositionalArgumentWarning(ValidationWarning): ...
+class VariableDocMismatchWarning(ValidationWarning): ...
+class PlaceholderMismatchWarning(ValidationWarning): ...
+class MarkupMismatchWarning(ValidationWarning): ...
+class LintWarning(ValidationWarning): ...
+
+class LintConfig(TypedDict, total=False):
+    max_length: int
+    forbidden_characters: str
+    quotes: Literal["straight", "curly"]
+    trailing_whitespace: bool
+    id_pattern: str
+
+class FileErrors(TypedDict):
+    parse: list[Junk]
+    validation: list[ValidationError]
+
+class TranslationWithAttributes(TypedDict):
+    value: str | None
+    attributes: dict[str, str]
+
+class MessageGroup:
+    name: str
+    comment: str
+    message_ids: list[str]
+    filename: str
+
+class ValidationError:
+    error_type: str
+    message_id: str
+    message: str
+    severity: Literal["error", "warning", "info"]
+    filename: str | None
+    line: int | None
+    column: int | None
+    byte_start: int | None
+    byte_end: int | None
+    suggestions: list[str]
+    @property
+    def code(self) -> str | None: ...
+    def __init__(
+        self,
+        error_type: str,
+        message_id: str,
+        message: str,
+        severity: Literal["error", "warning", "info"] = "error",
+        filename: str | None = None,
+        line: int | None = None,
+        column: int | None = None,
+        byte_start: int | None = None,
+        byte_end: int | None = None,
+        suggestions: list[str] = ...,
+    ) -> None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+
+class Message:
+    id: str
+    attributes: list[str]
+    has_value: bool
+    comment: str | None
+    group_comment: str | None
+    resource_comment: str | None
+    variables: list[str]
+    tags: list[str]
+
+class Junk:
+    content: str
+    filename: str
+    line: int
+    column: int
+    byte_start: int
+    byte_end: int
+    errors: list[str]
+    severity: Literal["error", "warning", "info"]
+    @property
+    def code(self) -> str: ...
+    def __init__(
+        self,
+        content: str,
+        filename: str,
+        line: int,
+        column: int,
+        byte_start: int,
+        byte_end: int,
+        errors: list[str] = ...,
+        severity: Literal["error", "warning", "info"] = "error",
+    ) -> None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+
+class MessageSource:
+    id: str
+    source: str
+    filename: str
+    line: int
+    column: int
+    byte_start: int
+    byte_end: int
+
+class SearchMatch:
+    id: str
+    text: str
+    start: int
+    end: int
+    filename: str
+    line: int
+    column: int
+
+class VariableDoc:
+    name: str
+    type: str | None
+    description: str | None
+
+class MessageStats:
+    formatted: int
+    errors: int
+
+class MessagePart:
+    type: Literal["literal", "placeable"]
+    value: str
+    name: str | None
+
+class Number:
+    def __init__(
+        self,
+        value: int | float | Decimal,
+        *,
+        style: Literal["decimal", "currency", "percent"] = "decimal",
+        currency: str | None = None,
+        currency_display: Literal["symbol", "code"] = "symbol",
+        use_grouping: bool = True,
+        minimum_integer_digits: int | None = None,
+        minimum_fraction_digits: int | None = None,
+        maximum_fraction_digits: int | None = None,
+        minimum_significant_digits: int | None = None,
+        maximum_significant_digits: int | None = None,
+        type: Literal["cardinal", "ordinal"] = "cardinal",
+    ) -> None: ...
+    @property
+    def value(self) -> float: ...
+
+class Locale:
+    def __init__(self, tag: str) -> None: ...
+    @staticmethod
+    def canonicalize(tag: str) -> str: ...
+    @property
+    def language(self) -> str: ...
+    @property
+    def script(self) -> str | None: ...
+    @property
+    def region(self) -> str | None: ...
+    @property
+    def variants(self) -> list[str]: ...
+    def maximize(self) -> Locale: ...
+    def minimize(self) -> Locale: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+
+class Term:
+    id: str
+    value: str
+    attributes: dict[str, str]
+
+class CompiledMessage:
+    identifier: str
+    variables: list[str]
+    def __call__(
+        self,
+        variables: Mapping[str, Variable] | Iterable[tuple[str, Variable]] | None = None,
+        use_isolating: bool = True,
+        strict_types: bool = False,
+        errors: ErrorHandling = "ignore",
+        strip_isolation: bool | None = None,
+    ) -> str: ...
 
 class Bundle:
     def __init__(
-        self, language: str, ftl_filenames: list[str | Path], strict: bool = False
+        self,
+        language: str | Locale | list[str | Locale],
+        ftl_filenames: list[FtlSource] | Mapping[str, list[FtlSource]],
+        strict: bool = False,
+        variable_separator: str = "_",
+        rules: Iterable[str] | None = None,
+        ignore: Iterable[str] | None = None,
+        severity: Mapping[str, Literal["error", "warning", "info"]] | None = None,
+        baseline: str | Path | None = None,
+        strict_parse: bool | None = None,
+        strict_validation: bool | None = None,
+        warn: bool = False,
+        encoding: str = "utf-8",
+        errors: str = "strict",
+        on_duplicate: Literal["warn", "last_wins", "first_wins", "error"] = "warn",
+        prefixes: Mapping[str | Path, str] | None = None,
+        pseudo: Literal["accented", "expanded", "bidi"] | None = None,
+        strip_isolation: bool = False,
+        timezone: str | None = None,
+        none_as: Literal["missing", "empty"] = "missing",
+        bool_as: Literal["string", "number"] = "string",
+        reference_language: str | Locale | None = None,
+        lint: LintConfig | None = None,
+        diagnostic_style: Literal["rich", "plain", "none"] = "rich",
     ) -> None: ...
+    def save_cache(self, path: str | Path) -> None: ...
+    def __reduce__(self) -> tuple[object, ...]: ...
+    @staticmethod
+    def from_cache(path: str | Path, verify: bool = True) -> Bundle: ...
+    def validate(self) -> None: ...
+    def reload(self) -> list[ValidationError]: ...
+    def copy(self) -> Bundle: ...
+    def subset(
+        self, prefix: str | None = None, predicate: Callable[[str], bool] | None = None
+    ) -> Bundle: ...
+    def write_baseline(self, path: str | Path | None = None) -> None: ...
+    def error_report(self, format: Literal["json"] = "json") -> str: ...
+    def add_ftl_string(self, name: str, contents: str, language: str | None = None) -> None: ...
+    def override_message(self, id: str, ftl_snippet: str, language: str | None = None) -> None: ...
+    def remove_message(self, id: str) -> None: ...
+    def rename(self, old_id: str, new_id: str) -> dict[str, str]: ...
+    def add_function(self, name: str, function: Callable[..., object]) -> None: ...
+    def register_adapter(self, type: type[T], adapter: Callable[[T], object]) -> None: ...
+    def set_transform(self, transform: Callable[[str], str] | None) -> None: ...
+    def groups(self) -> list[MessageGroup]: ...
+    def messages_with_tag(self, tag: str) -> list[str]: ...
+    def get_junk(self) -> list[Junk]: ...
+    def get_errors_by_file(self) -> dict[str | None, FileErrors]: ...
+    def to_ftl(self, language: str | None = None, with_junk: bool = False) -> str: ...
+    def to_json(
+        self,
+        variables_mode: Literal["placeholder", "raw"] = "placeholder",
+        include_attributes: bool = False,
+    ) -> str: ...
+    def enable_stats(self) -> None: ...
+    def stats(self, reset: bool = False) -> dict[str, MessageStats]: ...
+    def compile(self, identifier: str) -> CompiledMessage: ...
+    def get_required_variables(self, identifier: str) -> list[str]: ...
+    def get_required_variables_all(self) -> dict[str, list[str]]: ...
+    def search(
+        self, pattern: str, regex: bool = False, in_attributes: bool = True
+    ) -> list[SearchMatch]: ...
+    def messages_using_variable(
+        self, name: str, include_attributes: bool = False
+    ) -> list[str]: ...
+    def get_variable_types(
+        self, identifier: str
+    ) -> dict[str, Literal["number", "string", "datetime", "list", "unknown"]]: ...
     def get_translation(
+        self,
+        identifier: str | list[str],
+        variables: Mapping[str, Variable] | Iterable[tuple[str, Variable]] | None = None,
+        use_isolating: bool = True,
+        strict_types: bool = False,
+        errors: ErrorHandling = "ignore",
+        default: str | None = None,
+        strip_isolation: bool | None = None,
+        strict_variables: bool = False,
+    ) -> str: ...
+    def get_translation_with_attributes(
+        self,
+        identifier: str,
+        variables: Mapping[str, Variable] | Iterable[tuple[str, Variable]] | None = None,
+        use_isolating: bool = True,
+        strict_types: bool = False,
+        errors: ErrorHandling = "ignore",
+        strip_isolation: bool | None = None,
+    ) -> TranslationWithAttributes: ...
+    def get_translation_parts(
         self,
         identifier: str,
-        variables: dict[str, Variable] | None = None,
+        variables: Mapping[str, Variable] | Iterable[tuple[str, Variable]] | None = None,
+        strict_types: bool = False,
+        errors: ErrorHandling = "ignore",
+    ) -> list[MessagePart]: ...
+    def has_message(self, identifier: str) -> bool: ...
+    def __contains__(self, identifier: str) -> bool: ...
+    def message_ids(self, include_attributes: bool = False) -> list[str]: ...
+    def __iter__(self) -> Iterator[str]: ...
+    def __len__(self) -> int: ...
+    def validation_errors(self) -> list[ValidationError]: ...
+    def find_unused(self, used_ids: Iterable[str] | None = None) -> list[ValidationError]: ...
+    @overload
+    def reference_graph(
+        self, language: str | None = None, format: Literal["dict"] = "dict"
+    ) -> dict[str, list[str]]: ...
+    @overload
+    def reference_graph(self, language: str | None = None, *, format: Literal["dot"]) -> str: ...
+    @overload
+    def reference_graph(self, language: str | None, format: Literal["dot"]) -> str: ...
+    def referencing(
+        self, id: str, transitive: bool = False, language: str | None = None
+    ) -> list[str]: ...
+    def get_message(self, id: str) -> Message: ...
+    def get_comment(self, id: str) -> str | None: ...
+    def get_variable_docs(self, id: str) -> list[VariableDoc]: ...
+    def set_variable_schema(
+        self,
+        schema: Mapping[str, Mapping[str, str]] | None = None,
+        from_comments: bool = False,
+    ) -> None: ...
+    def get_message_source(self, id: str) -> MessageSource: ...
+    def term_ids(self) -> list[str]: ...
+    def get_term(self, id: str) -> Term: ...
+    def get_translations(
+        self,
+        requests: Iterable[
+            tuple[str, Mapping[str, Variable] | Iterable[tuple[str, Variable]] | None]
+        ],
         use_isolating: bool = True,
+        strict_types: bool = False,
+        errors: ErrorHandling = "ignore",
+        default: str | None = None,
+        strip_isolation: bool | None = None,
+    ) -> list[str]: ...
+
+class MessageDiff:
+    message_id: str
+    value_changed: bool
+    attributes: list[str]
+    filename: str
+    line: int
+    column: int
+    byte_start: int
+    byte_end: int
+
+class ResourceDiff:
+    added: list[MessageDiff]
+    removed: list[MessageDiff]
+    changed: list[MessageDiff]
+
+class MergeConflict:
+    message_id: str
+    kind: Literal["changed_in_both", "added_in_both", "deleted_in_ours", "deleted_in_theirs"]
+    base: str | None
+    ours: str | None
+    theirs: str | None
+
+class MergeResult:
+    source: str
+    conflicts: list[MergeConflict]
+
+class BundleComparison:
+    missing: list[str]
+    obsolete: list[str]
+    def __bool__(self) -> bool: ...
+
+def compare_bundles(reference: Bundle, target: Bundle) -> BundleComparison: ...
+
+class CoverageReport:
+    language: str
+    total: int
+    translated: int
+    percentage: float
+    untranslated: list[str]
+
+class CoverageError(Exception):
+    report: CoverageReport
+
+def coverage(
+    reference: Bundle, target: Bundle, threshold: float | None = None
+) -> CoverageReport: ...
+def plural_category(
+    locale: str,
+    value: int | float | Decimal | Number,
+    type: Literal["cardinal", "ordinal"] | None = None,
+) -> Literal["zero", "one", "two", "few", "many", "other"]: ...
+def serialize_resource(source: str, with_junk: bool = False) -> str: ...
+def parse(source: str) -> dict[str, Any]: ...
+def format_ftl(
+    source_or_path: str | Path,
+    indent: int = 4,
+    sort: bool = False,
+    encoding: str = "utf-8",
+    errors: str = "strict",
+) -> str: ...
+def generate_skeleton(
+    reference_paths: list[FtlSource],
+    mode: Literal["empty", "copy", "pseudo"] = "empty",
+    encoding: str = "utf-8",
+    errors: str = "strict",
+) -> str: ...
+def diff_resources(
+    old_path: FtlSource,
+    new_path: FtlSource,
+    encoding: str = "utf-8",
+    errors: str = "strict",
+) -> ResourceDiff: ...
+def merge_resources(
+    base: FtlSource,
+    ours: FtlSource,
+    theirs: FtlSource,
+    encoding: str = "utf-8",
+    errors: str = "strict",
+) -> MergeResult: ...
+def po_to_ftl(
+    path: FtlSource,
+    locale: str | None = None,
+    include_untranslated: bool = False,
+    encoding: str = "utf-8",
+    errors: str = "strict",
+) -> str: ...
+def ftl_to_po(
+    path: FtlSource,
+    reference_path: FtlSource | None = None,
+    locale: str | None = None,
+    encoding: str = "utf-8",
+    errors: str = "strict",
+) -> str: ...
+def export_xliff(reference_bundle: Bundle, target_bundle: Bundle, path: str | Path) -> None: ...
+def import_xliff(path: str | Path) -> str: ...
+def parse_accept_language(header: str) -> list[str]: ...
+def negotiate_languages(
+    requested: str | list[str | Locale],
+    available: list[str | Locale],
+    default: str | Locale | None = None,
+    strategy: Literal["filtering", "matching", "lookup"] = "filtering",
+) -> list[str]: ...
+def validate(
+    ftl_filenames: list[FtlSource],
+    locale: str | None = None,
+    rules: Iterable[str] | None = None,
+    ignore: Iterable[str] | None = None,
+    severity: Mapping[str, Literal["error", "warning", "info"]] | None = None,
+    encoding: str = "utf-8",
+    errors: str = "strict",
+    on_duplicate: Literal["warn", "last_wins", "first_wins", "error"] = "warn",
+    lint: LintConfig | None = None,
+) -> list[ValidationError]: ...
+
+class Localization:
+    locales: list[str]
+    def __init__(
+        self,
+        locales: list[str | Locale],
+        resource_ids: list[str],
+        loader: str | Path | Callable[[str, str], str | Path | None],
+        strict: bool = False,
+    ) -> None: ...
+    def format_value(
+        self,
+        identifier: str,
+        variables: Mapping[str, Variable] | Iterable[tuple[str, Variable]] | None = None,
     ) -> str: ...
@@ -0,0 +1,157 @@
+use crate::interchange::pattern_source;
+use crate::introspection::LoadedResource;
+use crate::validation::effective_entries;
+use fluent_syntax::ast;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// Where some text was found by `Bundle.search`.
+#[pyclass(frozen, get_all, module = "rustfluent")]
+pub(crate) struct SearchMatch {
+    /// The ID of the message (or term, with its leading `-`), and attribute after a `.`.
+    id: String,
+    /// The text of the pattern that was searched, with placeables as their FTL source.
+    text: String,
+    /// The offsets of the match in `text`, in characters.
+    start: usize,
+    end: usize,
+    /// The name of the file the message is in.
+    filename: String,
+    /// Where the match starts in the file, counting from 1.
+    line: usize,
+    column: usize,
+}
+
+#[pymethods]
+impl SearchMatch {
+    fn __repr__(&self) -> String {
+        let matched: String = self
+            .text
+            .chars()
+            .skip(self.start)
+            .take(self.end - self.start)
+            .collect();
+        format!(
+            "SearchMatch(id={:?}, matched={matched:?}, filename={:?}, line={})",
+            self.id, self.filename, self.line
+        )
+    }
+}
+
+/// Compile the Python regular expression for a search. Plain text matches any whitespace
+/// between its words, so sentences split over the lines of a multiline pattern are found.
+pub(crate) fn search_pattern<'py>(
+    py: Python<'py>,
+    pattern: &str,
+    regex: bool,
+) -> PyResult<Bound<'py, PyAny>> {
+    let re = py.import("re")?;
+    if regex {
+        return re.call_method1("compile", (pattern,));
+    }
+    let words = pattern
+        .split_whitespace()
+        .map(|word| re.call_method1("escape", (word,))?.extract())
+        .collect::<PyResult<Vec<String>>>()?;
+    if words.is_empty() {
+        return Err(PyValueError::new_err("pattern must not be empty."));
+    }
+    re.call_method1("compile", (words.join(r"\s+"),))
+}
+
+/// The text of a pattern, with where each piece of it came from in the source, if known.
+struct PatternText {
+    text: String,
+    /// The byte offset of each piece in the text, and of its start in the source.
+    pieces: Vec<(usize, Option<usize>)>,
+}
+
+impl PatternText {
+    fn new(loaded: &LoadedResource, pattern: &ast::Pattern<&str>) -> Self {
+        let mut text = String::new();
+        let mut pieces = vec![];
+        for element in &pattern.elements {
+            match element {
+                ast::PatternElement::TextElement { value } => {
+                    let source = loaded.source_range(value, value).map(|range| range.start);
+                    pieces.push((text.len(), source));
+                    text.push_str(value);
+                }
+                ast::PatternElement::Placeable { .. } => {
+                    pieces.push((text.len(), None));
+                    text.push_str(&pattern_source(&ast::Pattern {
+                        elements: vec![element.clone()],
+                    }));
+                }
+            }
+        }
+        Self { text, pieces }
+    }
+
+    /// Return the offset in the source of a byte offset in the text. Placeables aren't
+    /// written as they are in the source, so offsets in them are at the end of the text
+    /// before them.
+    fn source_offset(&self, offset: usize) -> Option<usize> {
+        let index = self
+            .pieces
+            .iter()
+            .rposition(|(start, _)| *start <= offset)?;
+        if let (start, Some(source)) = self.pieces[index] {
+            return Some(source + offset - start);
+        }
+        let previous = self.pieces[..index]
+            .iter()
+            .rposition(|(_, source)| source.is_some())?;
+        let (start, source) = self.pieces[previous];
+        let end = self.pieces[previous + 1].0;
+        Some(source? + end - start)
+    }
+}
+
+/// Search the text of the messages and terms of the resources (and their attributes, with
+/// `in_attributes`) with a compiled Python regular expression, in file order. Messages and
+/// terms overridden by a later definition are left out.
+pub(crate) fn search(
+    regex: &Bound<'_, PyAny>,
+    resources: &[LoadedResource],
+    in_attributes: bool,
+) -> PyResult<Vec<SearchMatch>> {
+    let mut matches = vec![];
+    for entry in effective_entries(resources) {
+        for &(attribute, pattern) in &entry.patterns {
+            if attribute.is_some() && !in_attributes {
+                continue;
+            }
+            let id = match attribute {
+                Some(attribute) => format!("{}.{attribute}", entry.id),
+                None => entry.id.clone(),
+            };
+            let text = PatternText::new(entry.loaded, pattern);
+            for found in regex.call_method1("finditer", (&text.text,))?.try_iter()? {
+                let found = found?;
+                let start: usize = found.call_method0("start")?.extract()?;
+                let end: usize = found.call_method0("end")?.extract()?;
+                let byte_start = text
+                    .text
+                    .char_indices()
+                    .nth(start)
+                    .map_or(text.text.len(), |(index, _)| index);
+                let position = text
+                    .source_offset(byte_start)
+                    .or_else(|| Some(entry.range()?.start))
+                    .and_then(|offset| entry.loaded.line_and_column(offset));
+                let (line, column) = position.unwrap_or((1, 1));
+                matches.push(SearchMatch {
+                    id: id.clone(),
+                    text: text.text.clone(),
+                    start,
+                    end,
+                    filename: entry.loaded.name.clone(),
+                    line,
+                    column,
+                });
+            }
+        }
+    }
+    Ok(matches)
+}
@@ -0,0 +1,74 @@
+use pyo3::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, PoisonError};
+
+/// How many times a message (or `message.attribute`) has been formatted, as returned by
+/// `Bundle.stats`.
+#[pyclass(frozen, get_all, skip_from_py_object, module = "rustfluent")]
+#[derive(Clone, Copy, Default)]
+pub(crate) struct MessageStats {
+    /// The number of times the message was formatted.
+    formatted: u64,
+    /// The number of those times there were errors formatting it.
+    errors: u64,
+}
+
+#[pymethods]
+impl MessageStats {
+    fn __repr__(&self) -> String {
+        format!(
+            "MessageStats(formatted={}, errors={})",
+            self.formatted, self.errors
+        )
+    }
+}
+
+/// The counts for each message a bundle has formatted since `Bundle.enable_stats`. Messages
+/// may be formatted on several threads at once, when formatting a batch.
+#[derive(Default)]
+pub(crate) struct Stats {
+    counts: Mutex<HashMap<String, MessageStats>>,
+}
+
+impl Stats {
+    /// Count a message (or `message.attribute`) being formatted.
+    pub(crate) fn record(&self, identifier: &str, had_errors: bool) {
+        let mut counts = self.counts.lock().unwrap_or_else(PoisonError::into_inner);
+        // Only copy the identifier the first time the message is formatted.
+        if !counts.contains_key(identifier) {
+            counts.insert(identifier.to_string(), MessageStats::default());
+        }
+        let stats = counts
+            .get_mut(identifier)
+            .expect("the message was just counted");
+        stats.formatted += 1;
+        stats.errors += u64::from(had_errors);
+    }
+
+    /// Return the counts for the given messages, which are zero for any that haven't been
+    /// formatted, followed by any other identifiers that have been, such as attributes.
+    /// With `reset`, counting starts again from zero.
+    pub(crate) fn snapshot(
+        &self,
+        message_ids: &[String],
+        reset: bool,
+    ) -> Vec<(String, MessageStats)> {
+        let mut counts = self.counts.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut snapshot: Vec<(String, MessageStats)> = message_ids
+            .iter()
+            .map(|id| (id.clone(), counts.get(id).copied().unwrap_or_default()))
+            .collect();
+        let listed: HashSet<&String> = message_ids.iter().collect();
+        let mut others: Vec<(String, MessageStats)> = counts
+            .iter()
+            .filter(|(id, _)| !listed.contains(id))
+            .map(|(id, stats)| (id.clone(), *stats))
+            .collect();
+        others.sort_by(|(a, _), (b, _)| a.cmp(b));
+        snapshot.extend(others);
+        if reset {
+            counts.clear();
+        }
+        snapshot
+    }
+}
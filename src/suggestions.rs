@@ -0,0 +1,62 @@
+/// The most suggestions to give for an unknown ID.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Return the IDs closest to an unknown one, for a "Did you mean" hint. Only IDs within a
+/// third of its length in edits (and at least one) are suggested, closest first.
+pub(crate) fn suggestions<'c>(
+    id: &str,
+    candidates: impl IntoIterator<Item = &'c str>,
+) -> Vec<String> {
+    let max_distance = (id.chars().count() / 3).max(1);
+    let mut close: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .filter(|candidate| *candidate != id)
+        .filter_map(|candidate| {
+            let distance = edit_distance(id, candidate);
+            (distance <= max_distance).then_some((distance, candidate))
+        })
+        .collect();
+    close.sort();
+    close.dedup();
+    close
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+/// Describe suggestions for an unknown ID, to add to the end of an error message.
+pub(crate) fn did_you_mean(suggestions: &[String]) -> Option<String> {
+    let quoted: Vec<String> = suggestions.iter().map(|id| format!("'{id}'")).collect();
+    match quoted.as_slice() {
+        [] => None,
+        [suggestion] => Some(format!("Did you mean {suggestion}?")),
+        [rest @ .., last] => Some(format!("Did you mean {} or {last}?", rest.join(", "))),
+    }
+}
+
+/// The Damerau-Levenshtein (optimal string alignment) distance between two strings, so
+/// swapping two adjacent characters counts as one edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // Three rows of the distance matrix: two rows back, the previous one and the current one.
+    let mut before: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current: Vec<usize> = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let substitution = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + substitution);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut before, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
@@ -0,0 +1,401 @@
+use fluent_syntax::ast;
+use fluent_syntax::parser::{ParserError, parse};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use std::ops::Range;
+
+/// A node of the syntax tree as a dict, with the byte range of the source it was parsed from.
+type Node<'py> = (Bound<'py, PyDict>, Option<Range<usize>>);
+
+/// Parse FTL source into its syntax tree as nested dicts and lists, in the JSON format of the
+/// python-fluent and fluent.js syntax trees. Each node has a `type`, and a `span` with the
+/// byte offsets of its source, or `None` if they aren't known. Invalid entries are `Junk`,
+/// with an `Annotation` for each syntax error in them.
+pub(crate) fn syntax_tree<'py>(py: Python<'py>, source: &str) -> PyResult<Bound<'py, PyDict>> {
+    let (resource, errors) = match parse(source) {
+        Ok(resource) => (resource, vec![]),
+        Err((resource, errors)) => (resource, errors),
+    };
+    let tree = Tree { py, source, errors };
+    let body = PyList::empty(py);
+    for entry in &resource.body {
+        body.append(tree.entry(entry)?.0)?;
+    }
+    let resource = tree.node("Resource")?;
+    resource.set_item("body", body)?;
+    Ok(tree.spanned(resource, Some(0..source.len()))?.0)
+}
+
+/// Converts the nodes of a resource parsed from `source`. The AST doesn't record where its
+/// nodes are, but its slices are of the source, so their spans are found from where those
+/// slices are, and the punctuation around them.
+struct Tree<'py, 's> {
+    py: Python<'py>,
+    source: &'s str,
+    errors: Vec<ParserError>,
+}
+
+impl<'py> Tree<'py, '_> {
+    fn node(&self, kind: &str) -> PyResult<Bound<'py, PyDict>> {
+        let node = PyDict::new(self.py);
+        node.set_item("type", kind)?;
+        Ok(node)
+    }
+
+    fn spanned(&self, node: Bound<'py, PyDict>, span: Option<Range<usize>>) -> PyResult<Node<'py>> {
+        let span_node = match &span {
+            Some(span) => {
+                let span_node = self.node("Span")?;
+                span_node.set_item("start", span.start)?;
+                span_node.set_item("end", span.end)?;
+                Some(span_node)
+            }
+            None => None,
+        };
+        node.set_item("span", span_node)?;
+        Ok((node, span))
+    }
+
+    /// Return where a slice of the source is.
+    fn range(&self, slice: &str) -> Option<Range<usize>> {
+        let offset = (slice.as_ptr() as usize)
+            .checked_sub(self.source.as_ptr() as usize)
+            .filter(|offset| offset + slice.len() <= self.source.len())?;
+        Some(offset..offset + slice.len())
+    }
+
+    /// Return where the `opening` character is before an offset, skipping whitespace.
+    fn opening(&self, start: usize, opening: char) -> Option<usize> {
+        let before = self.source.get(..start)?.trim_end();
+        before
+            .ends_with(opening)
+            .then(|| before.len() - opening.len_utf8())
+    }
+
+    /// Return the offset after the `closing` character after an offset, skipping whitespace
+    /// and the trailing comma of call arguments.
+    fn closing(&self, end: usize, closing: char) -> Option<usize> {
+        let after = self
+            .source
+            .get(end..)?
+            .trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        after
+            .starts_with(closing)
+            .then(|| self.source.len() - after.len() + closing.len_utf8())
+    }
+
+    fn entry(&self, entry: &ast::Entry<&str>) -> PyResult<Node<'py>> {
+        match entry {
+            ast::Entry::Message(message) => self.message(
+                "Message",
+                &message.id,
+                message.value.as_ref(),
+                &message.attributes,
+                message.comment.as_ref(),
+            ),
+            ast::Entry::Term(term) => self.message(
+                "Term",
+                &term.id,
+                Some(&term.value),
+                &term.attributes,
+                term.comment.as_ref(),
+            ),
+            ast::Entry::Comment(comment) => self.comment("Comment", comment),
+            ast::Entry::GroupComment(comment) => self.comment("GroupComment", comment),
+            ast::Entry::ResourceComment(comment) => self.comment("ResourceComment", comment),
+            ast::Entry::Junk { content } => {
+                let span = self.range(content);
+                let annotations = PyList::empty(self.py);
+                for error in &self.errors {
+                    if span
+                        .as_ref()
+                        .is_some_and(|span| span.contains(&error.pos.start))
+                    {
+                        let annotation = self.node("Annotation")?;
+                        annotation.set_item("message", error.kind.to_string())?;
+                        annotations.append(self.spanned(annotation, Some(error.pos.clone()))?.0)?;
+                    }
+                }
+                let junk = self.node("Junk")?;
+                junk.set_item("content", content)?;
+                junk.set_item("annotations", annotations)?;
+                self.spanned(junk, span)
+            }
+        }
+    }
+
+    /// Convert a message or term. A term's span includes the `-` before its ID.
+    fn message(
+        &self,
+        kind: &str,
+        id: &ast::Identifier<&str>,
+        value: Option<&ast::Pattern<&str>>,
+        attributes: &[ast::Attribute<&str>],
+        comment: Option<&ast::Comment<&str>>,
+    ) -> PyResult<Node<'py>> {
+        let (id, id_span) = self.identifier(id)?;
+        let start = id_span
+            .as_ref()
+            .map(|span| span.start - usize::from(kind == "Term"));
+        let mut end = id_span.map(|span| span.end);
+        let node = self.node(kind)?;
+        node.set_item("id", id)?;
+        match value {
+            Some(value) => {
+                let (value, span) = self.pattern(value)?;
+                end = span.map(|span| span.end);
+                node.set_item("value", value)?;
+            }
+            None => node.set_item("value", None::<()>)?,
+        }
+        let attribute_nodes = PyList::empty(self.py);
+        for attribute in attributes {
+            let (id, id_span) = self.identifier(&attribute.id)?;
+            let (value, span) = self.pattern(&attribute.value)?;
+            end = span.map(|span| span.end);
+            let attribute_node = self.node("Attribute")?;
+            attribute_node.set_item("id", id)?;
+            attribute_node.set_item("value", value)?;
+            // The `.` is right before the attribute's ID.
+            let start = id_span.map(|span| span.start - 1);
+            attribute_nodes.append(self.spanned(attribute_node, start.zip(end).map(between))?.0)?;
+        }
+        node.set_item("attributes", attribute_nodes)?;
+        match comment {
+            Some(comment) => node.set_item("comment", self.comment("Comment", comment)?.0)?,
+            None => node.set_item("comment", None::<()>)?,
+        }
+        self.spanned(node, start.zip(end).map(between))
+    }
+
+    /// Convert a comment, with the lines of its content joined. Its span starts at the `#`s
+    /// of its first line.
+    fn comment(&self, kind: &str, comment: &ast::Comment<&str>) -> PyResult<Node<'py>> {
+        let first = comment.content.first().and_then(|line| self.range(line));
+        let last = comment.content.last().and_then(|line| self.range(line));
+        let start = first.map(|first| {
+            self.source[..first.start]
+                .rfind('\n')
+                .map_or(0, |index| index + 1)
+        });
+        let node = self.node(kind)?;
+        node.set_item("content", comment.content.join("\n"))?;
+        self.spanned(node, start.zip(last.map(|last| last.end)).map(between))
+    }
+
+    fn identifier(&self, id: &ast::Identifier<&str>) -> PyResult<Node<'py>> {
+        let node = self.node("Identifier")?;
+        node.set_item("name", id.name)?;
+        self.spanned(node, self.range(id.name))
+    }
+
+    fn pattern(&self, pattern: &ast::Pattern<&str>) -> PyResult<Node<'py>> {
+        let elements = PyList::empty(self.py);
+        let mut spans = vec![];
+        for element in &pattern.elements {
+            let (element, span) = match element {
+                ast::PatternElement::TextElement { value } => {
+                    let node = self.node("TextElement")?;
+                    node.set_item("value", value)?;
+                    self.spanned(node, self.range(value))?
+                }
+                ast::PatternElement::Placeable { expression } => {
+                    let expression = self.expression(expression)?;
+                    self.placeable(expression)?
+                }
+            };
+            elements.append(element)?;
+            spans.push(span);
+        }
+        let start = spans.first().cloned().flatten().map(|span| span.start);
+        let end = spans.last().cloned().flatten().map(|span| span.end);
+        let node = self.node("Pattern")?;
+        node.set_item("elements", elements)?;
+        self.spanned(node, start.zip(end).map(between))
+    }
+
+    /// Wrap an expression in a placeable, whose span includes its braces.
+    fn placeable(&self, (expression, expression_span): Node<'py>) -> PyResult<Node<'py>> {
+        let span = expression_span.and_then(|expression_span| {
+            Some(
+                self.opening(expression_span.start, '{')?
+                    ..self.closing(expression_span.end, '}')?,
+            )
+        });
+        let node = self.node("Placeable")?;
+        node.set_item("expression", expression)?;
+        self.spanned(node, span)
+    }
+
+    fn expression(&self, expression: &ast::Expression<&str>) -> PyResult<Node<'py>> {
+        let (selector, variants) = match expression {
+            ast::Expression::Select { selector, variants } => (selector, variants),
+            ast::Expression::Inline(expression) => return self.inline_expression(expression),
+        };
+        let (selector, selector_span) = self.inline_expression(selector)?;
+        let variant_nodes = PyList::empty(self.py);
+        let mut end = None;
+        for variant in variants {
+            let (key, key_span) = match &variant.key {
+                ast::VariantKey::Identifier { name } => {
+                    let node = self.node("Identifier")?;
+                    node.set_item("name", name)?;
+                    self.spanned(node, self.range(name))?
+                }
+                ast::VariantKey::NumberLiteral { value } => {
+                    let node = self.node("NumberLiteral")?;
+                    node.set_item("value", value)?;
+                    self.spanned(node, self.range(value))?
+                }
+            };
+            let (value, value_span) = self.pattern(&variant.value)?;
+            end = value_span.map(|span| span.end);
+            // The key is in brackets, after a `*` for the default variant.
+            let start = key_span
+                .and_then(|key_span| self.opening(key_span.start, '['))
+                .map(|start| {
+                    if variant.default && self.source[..start].ends_with('*') {
+                        start - 1
+                    } else {
+                        start
+                    }
+                });
+            let node = self.node("Variant")?;
+            node.set_item("key", key)?;
+            node.set_item("value", value)?;
+            node.set_item("default", variant.default)?;
+            variant_nodes.append(self.spanned(node, start.zip(end).map(between))?.0)?;
+        }
+        let node = self.node("SelectExpression")?;
+        node.set_item("selector", selector)?;
+        node.set_item("variants", variant_nodes)?;
+        let start = selector_span.map(|span| span.start);
+        self.spanned(node, start.zip(end).map(between))
+    }
+
+    fn inline_expression(&self, expression: &ast::InlineExpression<&str>) -> PyResult<Node<'py>> {
+        match expression {
+            ast::InlineExpression::StringLiteral { value } => {
+                let node = self.node("StringLiteral")?;
+                node.set_item("value", value)?;
+                // The value doesn't include the quotes.
+                let span = self
+                    .range(value)
+                    .and_then(|range| Some(range.start.checked_sub(1)?..range.end + 1));
+                self.spanned(node, span)
+            }
+            ast::InlineExpression::NumberLiteral { value } => {
+                let node = self.node("NumberLiteral")?;
+                node.set_item("value", value)?;
+                self.spanned(node, self.range(value))
+            }
+            ast::InlineExpression::FunctionReference { id, arguments } => {
+                let (id, id_span) = self.identifier(id)?;
+                let (arguments, arguments_span) =
+                    self.arguments(arguments, id_span.as_ref().map(|span| span.end))?;
+                let node = self.node("FunctionReference")?;
+                node.set_item("id", id)?;
+                node.set_item("arguments", arguments)?;
+                let start = id_span.map(|span| span.start);
+                self.spanned(
+                    node,
+                    start.zip(arguments_span).map(|(start, end)| start..end.end),
+                )
+            }
+            ast::InlineExpression::MessageReference { id, attribute } => {
+                let (id, id_span) = self.identifier(id)?;
+                let start = id_span.as_ref().map(|span| span.start);
+                let mut end = id_span.map(|span| span.end);
+                let node = self.node("MessageReference")?;
+                node.set_item("id", id)?;
+                match attribute {
+                    Some(attribute) => {
+                        let (attribute, span) = self.identifier(attribute)?;
+                        end = span.map(|span| span.end);
+                        node.set_item("attribute", attribute)?;
+                    }
+                    None => node.set_item("attribute", None::<()>)?,
+                }
+                self.spanned(node, start.zip(end).map(between))
+            }
+            ast::InlineExpression::TermReference {
+                id,
+                attribute,
+                arguments,
+            } => {
+                let (id, id_span) = self.identifier(id)?;
+                // The `-` is right before the term's ID.
+                let start = id_span.as_ref().map(|span| span.start - 1);
+                let mut end = id_span.map(|span| span.end);
+                let node = self.node("TermReference")?;
+                node.set_item("id", id)?;
+                match attribute {
+                    Some(attribute) => {
+                        let (attribute, span) = self.identifier(attribute)?;
+                        end = span.map(|span| span.end);
+                        node.set_item("attribute", attribute)?;
+                    }
+                    None => node.set_item("attribute", None::<()>)?,
+                }
+                match arguments {
+                    Some(arguments) => {
+                        let (arguments, span) = self.arguments(arguments, end)?;
+                        end = span.map(|span| span.end);
+                        node.set_item("arguments", arguments)?;
+                    }
+                    None => node.set_item("arguments", None::<()>)?,
+                }
+                self.spanned(node, start.zip(end).map(between))
+            }
+            ast::InlineExpression::VariableReference { id } => {
+                let (id, id_span) = self.identifier(id)?;
+                let node = self.node("VariableReference")?;
+                node.set_item("id", id)?;
+                // The `$` is right before the variable's name.
+                self.spanned(node, id_span.map(|span| span.start - 1..span.end))
+            }
+            ast::InlineExpression::Placeable { expression } => {
+                let expression = self.expression(expression)?;
+                self.placeable(expression)
+            }
+        }
+    }
+
+    /// Convert the arguments of a call, whose span is from the parentheses after `callee_end`.
+    fn arguments(
+        &self,
+        arguments: &ast::CallArguments<&str>,
+        callee_end: Option<usize>,
+    ) -> PyResult<Node<'py>> {
+        let start = callee_end
+            .and_then(|callee_end| self.closing(callee_end, '('))
+            .map(|after| after - 1);
+        let mut end = start.map(|start| start + 1);
+        let positional = PyList::empty(self.py);
+        for argument in &arguments.positional {
+            let (argument, span) = self.inline_expression(argument)?;
+            end = span.map(|span| span.end);
+            positional.append(argument)?;
+        }
+        let named = PyList::empty(self.py);
+        for argument in &arguments.named {
+            let (name, name_span) = self.identifier(&argument.name)?;
+            let (value, value_span) = self.inline_expression(&argument.value)?;
+            end = value_span.map(|span| span.end);
+            let node = self.node("NamedArgument")?;
+            node.set_item("name", name)?;
+            node.set_item("value", value)?;
+            let start = name_span.map(|span| span.start);
+            named.append(self.spanned(node, start.zip(end).map(between))?.0)?;
+        }
+        let node = self.node("CallArguments")?;
+        node.set_item("positional", positional)?;
+        node.set_item("named", named)?;
+        let end = end.and_then(|end| self.closing(end, ')'));
+        self.spanned(node, start.zip(end).map(between))
+    }
+}
+
+fn between((start, end): (usize, usize)) -> Range<usize> {
+    start..end
+}
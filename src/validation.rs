@@ -0,0 +1,1676 @@
+use crate::introspection::{LoadedResource, pattern_variables, variable_docs};
+use crate::suggestions::{did_you_mean, suggestions};
+use fluent_bundle::FluentMessage;
+use fluent_syntax::ast;
+use intl_pluralrules::{PluralCategory, PluralRuleType, PluralRules};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyType};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use unic_langid::LanguageIdentifier;
+
+/// A problem found in a bundle's messages which Fluent doesn't report when parsing,
+/// as returned by `Bundle.validation_errors`.
+#[pyclass(frozen, get_all, eq, hash, skip_from_py_object, module = "rustfluent")]
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ValidationError {
+    /// The kind of problem, e.g. `MissingPluralCategory`.
+    error_type: String,
+    /// The message (or term, with a leading `-`) with the problem.
+    message_id: String,
+    /// A description of the problem.
+    message: String,
+    /// How serious the problem is: `error`, `warning` or `info`, from the bundle's `severity`.
+    severity: String,
+    /// The name of the FTL file (or source) with the problem.
+    filename: Option<String>,
+    /// The line of the problem, counting from 1.
+    line: Option<usize>,
+    /// The column of the problem, in characters counting from 1.
+    column: Option<usize>,
+    /// The byte offset of the start of the problem in the source.
+    byte_start: Option<usize>,
+    /// The byte offset of the end of the problem in the source.
+    byte_end: Option<usize>,
+    /// For an unknown message or term, the defined IDs closest to it, closest first.
+    suggestions: Vec<String>,
+}
+
+#[pymethods]
+impl ValidationError {
+    #[new]
+    #[pyo3(signature = (
+        error_type,
+        message_id,
+        message,
+        severity="error",
+        filename=None,
+        line=None,
+        column=None,
+        byte_start=None,
+        byte_end=None,
+        suggestions=vec![],
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        error_type: String,
+        message_id: String,
+        message: String,
+        severity: &str,
+        filename: Option<String>,
+        line: Option<usize>,
+        column: Option<usize>,
+        byte_start: Option<usize>,
+        byte_end: Option<usize>,
+        suggestions: Vec<String>,
+    ) -> PyResult<Self> {
+        check_severity(severity)?;
+        Ok(Self {
+            error_type,
+            message_id,
+            message,
+            severity: severity.to_string(),
+            filename,
+            line,
+            column,
+            byte_start,
+            byte_end,
+            suggestions,
+        })
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn __reduce__<'py>(
+        slf: &Bound<'py, Self>,
+    ) -> (
+        Bound<'py, PyType>,
+        (
+            String,
+            String,
+            String,
+            String,
+            Option<String>,
+            Option<usize>,
+            Option<usize>,
+            Option<usize>,
+            Option<usize>,
+            Vec<String>,
+        ),
+    ) {
+        let this = slf.get().clone();
+        (
+            slf.get_type(),
+            (
+                this.error_type,
+                this.message_id,
+                this.message,
+                this.severity,
+                this.filename,
+                this.line,
+                this.column,
+                this.byte_start,
+                this.byte_end,
+                this.suggestions,
+            ),
+        )
+    }
+
+    /// The stable code of the error's type, e.g. `RF0003` for `UnknownTerm`, or `None` for a
+    /// type that isn't one of the bundle's.
+    #[getter]
+    pub(crate) fn code(&self) -> Option<&'static str> {
+        error_code(&self.error_type)
+    }
+
+    /// Return the error's fields as a dict, as in the `json` report of `Bundle.validate`.
+    pub(crate) fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("filename", &self.filename)?;
+        dict.set_item("line", self.line)?;
+        dict.set_item("column", self.column)?;
+        dict.set_item("byte_start", self.byte_start)?;
+        dict.set_item("byte_end", self.byte_end)?;
+        dict.set_item("code", self.code())?;
+        dict.set_item("error_type", &self.error_type)?;
+        dict.set_item("message_id", &self.message_id)?;
+        dict.set_item("message", &self.message)?;
+        dict.set_item("severity", &self.severity)?;
+        dict.set_item("suggestions", &self.suggestions)?;
+        Ok(dict)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ValidationError(error_type={:?}, message_id={:?}, message={:?})",
+            self.error_type, self.message_id, self.message
+        )
+    }
+
+    fn __str__(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let (Some(filename), Some(line), Some(column)) = (&self.filename, self.line, self.column)
+        {
+            write!(f, "{filename}:{line}:{column}: ")?;
+        }
+        write!(f, "{}", self.description())
+    }
+}
+
+impl ValidationError {
+    pub(crate) fn new(error_type: &str, message_id: String, message: String) -> Self {
+        Self {
+            error_type: error_type.to_string(),
+            message_id,
+            message,
+            severity: "error".to_string(),
+            filename: None,
+            line: None,
+            column: None,
+            byte_start: None,
+            byte_end: None,
+            suggestions: vec![],
+        }
+    }
+
+    pub(crate) fn error_type(&self) -> &str {
+        &self.error_type
+    }
+
+    pub(crate) fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+
+    /// The byte range of the problem in its file's source, if it's known.
+    pub(crate) fn span(&self) -> Option<Range<usize>> {
+        Some(self.byte_start?..self.byte_end?)
+    }
+
+    /// Describe the problem, without its location.
+    pub(crate) fn description(&self) -> String {
+        let error_type = match self.code() {
+            Some(code) => format!("{code} {}", self.error_type),
+            None => self.error_type.clone(),
+        };
+        let description = format!("{}: {} ({error_type})", self.message_id, self.message);
+        match did_you_mean(&self.suggestions) {
+            Some(hint) => format!("{description} {hint}"),
+            None => description,
+        }
+    }
+
+    /// Whether the problem should fail a strict bundle, rather than being a warning.
+    pub(crate) fn is_error(&self) -> bool {
+        self.severity == "error"
+    }
+
+    /// Whether the problem is only for information, and isn't emitted as a Python warning.
+    pub(crate) fn is_info(&self) -> bool {
+        self.severity == "info"
+    }
+
+    /// Record where in a resource's source the problem is, if it's known.
+    pub(crate) fn at(mut self, loaded: &LoadedResource, range: Option<Range<usize>>) -> Self {
+        if let Some(range) = range
+            && let Some((line, column)) = loaded.line_and_column(range.start)
+        {
+            self.filename = Some(loaded.name.clone());
+            self.line = Some(line);
+            self.column = Some(column);
+            self.byte_start = Some(range.start);
+            self.byte_end = Some(range.end);
+        }
+        self
+    }
+}
+
+/// The types of problem `validate` checks for.
+const ERROR_TYPES: [&str; 14] = [
+    "MissingPluralCategory",
+    "UnknownMessage",
+    "UnknownTerm",
+    "CyclicReference",
+    "DuplicateMessageId",
+    "IgnoredPositionalArgument",
+    "VariableDocMismatch",
+    "PlaceholderMismatch",
+    "MarkupMismatch",
+    "MaxLength",
+    "ForbiddenCharacter",
+    "QuoteStyle",
+    "TrailingWhitespace",
+    "IdNaming",
+];
+
+/// The stable code of each type of problem, which suppression lists, baselines and
+/// documentation can refer to. Codes are never renumbered or reused.
+const ERROR_CODES: [(&str, &str); 17] = [
+    ("MissingPluralCategory", "RF0001"),
+    ("UnknownMessage", "RF0002"),
+    ("UnknownTerm", "RF0003"),
+    ("CyclicReference", "RF0004"),
+    ("DuplicateMessageId", "RF0005"),
+    ("IgnoredPositionalArgument", "RF0006"),
+    ("VariableDocMismatch", "RF0007"),
+    ("PlaceholderMismatch", "RF0008"),
+    ("MarkupMismatch", "RF0009"),
+    ("MaxLength", "RF0010"),
+    ("ForbiddenCharacter", "RF0011"),
+    ("QuoteStyle", "RF0012"),
+    ("TrailingWhitespace", "RF0013"),
+    ("IdNaming", "RF0014"),
+    ("UnusedMessage", "RF0015"),
+    ("UnusedTerm", "RF0016"),
+    ("ParseError", "RF0017"),
+];
+
+/// Return the code of a type of problem, if it's one of the bundle's.
+pub(crate) fn error_code(error_type: &str) -> Option<&'static str> {
+    ERROR_CODES
+        .iter()
+        .find(|&&(other, _)| other == error_type)
+        .map(|&(_, code)| code)
+}
+
+/// Return the type of problem named by a type or its code, e.g. `UnknownTerm` for `RF0003`.
+fn error_type_named(name: &str) -> Option<&'static str> {
+    ERROR_CODES
+        .iter()
+        .find(|&&(error_type, code)| error_type == name || code == name)
+        .map(|&(error_type, _)| error_type)
+}
+
+/// The types of problem that are only checked for if they're in a bundle's `rules`.
+const OPTIONAL_ERROR_TYPES: [&str; 1] = ["MarkupMismatch"];
+
+/// The severity of the types of problem that aren't errors unless a bundle's `severity` says
+/// so. Positional arguments to terms are ignored rather than breaking the message.
+const DEFAULT_SEVERITIES: [(&str, &str); 1] = [("IgnoredPositionalArgument", "warning")];
+
+/// Check that a severity is one of `error`, `warning` and `info`.
+pub(crate) fn check_severity(severity: &str) -> PyResult<()> {
+    match severity {
+        "error" | "warning" | "info" => Ok(()),
+        _ => Err(PyValueError::new_err(format!(
+            "severity must be 'error', 'warning' or 'info', not '{severity}'."
+        ))),
+    }
+}
+
+/// Which of the validation checks a bundle reports, and how serious each is.
+#[derive(Clone)]
+pub(crate) struct ValidationRules {
+    /// The error types to report, or `None` for all of them but the optional ones.
+    rules: Option<HashSet<String>>,
+    /// Error types not to report, even if they're in `rules`.
+    ignore: HashSet<String>,
+    /// The severity of error types (and of syntax errors, as `ParseError`) that aren't
+    /// errors. Only errors fail a strict bundle.
+    severities: HashMap<String, String>,
+}
+
+impl Default for ValidationRules {
+    fn default() -> Self {
+        Self {
+            rules: None,
+            ignore: HashSet::new(),
+            severities: DEFAULT_SEVERITIES
+                .iter()
+                .map(|&(error_type, severity)| (error_type.to_string(), severity.to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl ValidationRules {
+    pub(crate) fn new(
+        rules: Option<HashSet<String>>,
+        ignore: HashSet<String>,
+        severity: HashMap<String, String>,
+    ) -> PyResult<Self> {
+        // Error types can be given by name or code. `ParseError` only has a severity.
+        let error_type = |name: &str, parse_error: bool| match error_type_named(name) {
+            Some(error_type)
+                if ERROR_TYPES.contains(&error_type)
+                    || (parse_error && error_type == "ParseError") =>
+            {
+                Ok(error_type.to_string())
+            }
+            _ => Err(PyValueError::new_err(format!(
+                "Unknown validation rule: '{name}'"
+            ))),
+        };
+        let rules = rules
+            .map(|rules| rules.iter().map(|name| error_type(name, false)).collect())
+            .transpose()?;
+        let ignore = ignore
+            .iter()
+            .map(|name| error_type(name, false))
+            .collect::<PyResult<_>>()?;
+        let mut severities = Self::default().severities;
+        for (name, severity) in severity {
+            let error_type = error_type(&name, true)?;
+            check_severity(&severity)?;
+            severities.insert(error_type, severity);
+        }
+        Ok(Self {
+            rules,
+            ignore,
+            severities,
+        })
+    }
+
+    /// The rules as the `rules`, `ignore` and `severity` arguments they can be created from.
+    pub(crate) fn arguments(&self) -> (Option<Vec<&str>>, Vec<&str>, &HashMap<String, String>) {
+        (
+            self.rules.as_ref().map(sorted),
+            sorted(&self.ignore),
+            &self.severities,
+        )
+    }
+
+    /// The severity of errors of a type, or of syntax errors for `ParseError`.
+    pub(crate) fn severity(&self, error_type: &str) -> &str {
+        self.severities
+            .get(error_type)
+            .map_or("error", String::as_str)
+    }
+
+    /// Whether errors of a type are reported.
+    pub(crate) fn enabled(&self, error_type: &str) -> bool {
+        let enabled = match &self.rules {
+            Some(rules) => rules.contains(error_type),
+            None => !OPTIONAL_ERROR_TYPES.contains(&error_type),
+        };
+        enabled && !self.ignore.contains(error_type)
+    }
+
+    /// Drop the errors of disabled rules, and set the severity of the others.
+    pub(crate) fn apply(&self, errors: Vec<ValidationError>) -> Vec<ValidationError> {
+        errors
+            .into_iter()
+            .filter(|error| self.enabled(&error.error_type))
+            .map(|mut error| {
+                error.severity = self.severity(&error.error_type).to_string();
+                error
+            })
+            .collect()
+    }
+}
+
+/// Error types in order, so that they're written the same way every time.
+fn sorted(error_types: &HashSet<String>) -> Vec<&str> {
+    let mut error_types: Vec<&str> = error_types.iter().map(String::as_str).collect();
+    error_types.sort_unstable();
+    error_types
+}
+
+/// Validation errors accepted in a JSON baseline file, which don't fail a strict bundle, so that
+/// strict mode can be adopted before every existing error is fixed.
+///
+/// The file is a list of objects with the `code` (or `error_type`), `message_id` and `message`
+/// of each error. Locations aren't compared, so that errors stay accepted as the files around
+/// them change.
+#[derive(Clone)]
+pub(crate) struct Baseline {
+    pub path: PathBuf,
+    accepted: HashSet<(String, String, String)>,
+}
+
+impl Baseline {
+    /// Load a baseline file. A missing file accepts no errors, so it can be written later.
+    pub(crate) fn load(py: Python<'_>, path: PathBuf) -> PyResult<Self> {
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(Self {
+                    path,
+                    accepted: HashSet::new(),
+                });
+            }
+            Err(error) => return Err(error.into()),
+        };
+        let invalid = |error: PyErr| {
+            PyValueError::new_err(format!("Invalid baseline file {}: {error}", path.display()))
+        };
+        let entries: Vec<HashMap<String, String>> = py
+            .import("json")?
+            .call_method1("loads", (contents,))
+            .and_then(|entries| entries.extract())
+            .map_err(invalid)?;
+        let mut accepted = HashSet::with_capacity(entries.len());
+        for mut entry in entries {
+            let mut field = |name: &str| {
+                entry.remove(name).ok_or_else(|| {
+                    PyValueError::new_err(format!(
+                        "Invalid baseline file {}: an error has no '{name}'",
+                        path.display()
+                    ))
+                })
+            };
+            // Baselines written before errors had codes only have the error type.
+            let code = field("code");
+            let error_type = match code {
+                Ok(code) => error_type_named(&code).map_or(code, str::to_string),
+                Err(error) => field("error_type").map_err(|_| error)?,
+            };
+            accepted.insert((error_type, field("message_id")?, field("message")?));
+        }
+        Ok(Self { path, accepted })
+    }
+
+    /// Whether the baseline accepts an error.
+    pub(crate) fn contains(&self, error: &ValidationError) -> bool {
+        self.accepted.contains(&(
+            error.error_type.clone(),
+            error.message_id.clone(),
+            error.message.clone(),
+        ))
+    }
+
+    /// Write a baseline file accepting the given errors.
+    pub(crate) fn write(py: Python<'_>, path: &Path, errors: &[ValidationError]) -> PyResult<()> {
+        let entries = errors
+            .iter()
+            .map(|error| {
+                let entry = PyDict::new(py);
+                entry.set_item("code", error.code())?;
+                entry.set_item("error_type", &error.error_type)?;
+                entry.set_item("message_id", &error.message_id)?;
+                entry.set_item("message", &error.message)?;
+                Ok(entry)
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        fs::write(path, to_json(py, entries)? + "\n")?;
+        Ok(())
+    }
+}
+
+/// Return a JSON report of a bundle's syntax errors and validation errors, with the file,
+/// location, type and description of each, for tools such as CI pipelines to annotate.
+///
+/// Syntax errors have the `error_type` `ParseError`, the given severity, no `message_id` and
+/// no `suggestions`.
+pub(crate) fn json_report<'r>(
+    py: Python<'_>,
+    resources: impl Iterator<Item = &'r LoadedResource>,
+    parse_severity: &str,
+    errors: &[ValidationError],
+) -> PyResult<String> {
+    let mut entries = vec![];
+    for loaded in resources {
+        for error in loaded.errors.iter() {
+            let (line, column) = loaded
+                .line_and_column(error.pos.start)
+                .map_or((None, None), |(line, column)| (Some(line), Some(column)));
+            let entry = PyDict::new(py);
+            entry.set_item("filename", &loaded.name)?;
+            entry.set_item("line", line)?;
+            entry.set_item("column", column)?;
+            entry.set_item("byte_start", error.pos.start)?;
+            entry.set_item("byte_end", error.pos.end)?;
+            entry.set_item("code", error_code("ParseError"))?;
+            entry.set_item("error_type", "ParseError")?;
+            entry.set_item("message_id", py.None())?;
+            entry.set_item("message", error.kind.to_string())?;
+            entry.set_item("severity", parse_severity)?;
+            entry.set_item("suggestions", Vec::<String>::new())?;
+            entries.push(entry);
+        }
+    }
+    for error in errors {
+        entries.push(error.to_dict(py)?);
+    }
+    to_json(py, entries)
+}
+
+pub(crate) fn to_json<'py>(py: Python<'py>, value: impl IntoPyObject<'py>) -> PyResult<String> {
+    let kwargs = PyDict::new(py);
+    kwargs.set_item("indent", 2)?;
+    kwargs.set_item("ensure_ascii", false)?;
+    py.import("json")?
+        .call_method("dumps", (value,), Some(&kwargs))?
+        .extract()
+}
+
+/// Check the messages and terms of one language's resources. Plural selectors are only
+/// checked if the language is known. Messages and terms defined again in a later file are
+/// only reported with `report_overrides`, but duplicates within a file always are.
+pub(crate) fn validate(
+    resources: &[LoadedResource],
+    language: Option<&LanguageIdentifier>,
+    report_overrides: bool,
+) -> Vec<ValidationError> {
+    let plural_categories = language.map(|language| (language, plural_categories(language)));
+    // References are checked against every message and term in the language, rather than
+    // those defined so far, so that messages can reference ones in later files.
+    let defined: HashSet<String> = entries(resources).map(|entry| entry.id).collect();
+    let mut errors = vec![];
+    let mut definitions: HashMap<String, Vec<&LoadedResource>> = HashMap::new();
+    for entry in entries(resources) {
+        let files = definitions.entry(entry.id.clone()).or_default();
+        let previous = if files.iter().any(|file| std::ptr::eq(*file, entry.loaded)) {
+            Some(entry.loaded)
+        } else if report_overrides {
+            files.first().copied()
+        } else {
+            None
+        };
+        if let Some(previous) = previous {
+            let kind = if entry.id.starts_with('-') {
+                "Term"
+            } else {
+                "Message"
+            };
+            errors.push(
+                ValidationError::new(
+                    "DuplicateMessageId",
+                    entry.id.clone(),
+                    format!("{kind} is already defined in {}", previous.name),
+                )
+                .at(entry.loaded, entry.range()),
+            );
+        }
+        files.push(entry.loaded);
+
+        let mut unknown: Vec<Reference> = vec![];
+        for &(_, pattern) in &entry.patterns {
+            if let Some((language, categories)) = &plural_categories {
+                check_plural_categories(pattern, &entry, language, categories, &mut errors);
+            }
+            for reference in pattern_references(pattern) {
+                if reference.has_positional_arguments {
+                    errors.push(
+                        ValidationError::new(
+                            "IgnoredPositionalArgument",
+                            entry.id.clone(),
+                            format!(
+                                "Positional arguments to {} are ignored, only named arguments \
+                                 are passed to terms",
+                                reference.id
+                            ),
+                        )
+                        .at(entry.loaded, reference.range(entry.loaded)),
+                    );
+                }
+                if !defined.contains(entry_id(&reference.id))
+                    && !unknown.iter().any(|other| other.id == reference.id)
+                {
+                    unknown.push(reference);
+                }
+            }
+        }
+        for reference in unknown {
+            let id = &reference.id;
+            let (error_type, message) = match id.strip_prefix('-') {
+                Some(_) => ("UnknownTerm", format!("Unknown term: {id}")),
+                None => ("UnknownMessage", format!("Unknown message: {id}")),
+            };
+            let is_term = id.starts_with('-');
+            let candidates = defined
+                .iter()
+                .filter(|other| other.starts_with('-') == is_term)
+                .map(String::as_str);
+            let mut error = ValidationError::new(error_type, entry.id.clone(), message)
+                .at(entry.loaded, reference.range(entry.loaded));
+            error.suggestions = suggestions(id, candidates);
+            errors.push(error);
+        }
+    }
+    check_cycles(resources, &mut errors);
+    check_variable_docs(resources, &mut errors);
+    errors
+}
+
+/// Report the placeables of a language's messages that don't match those of the same messages
+/// in the reference language: variables and message and term references that the translation
+/// is missing, or that the reference doesn't have. Variables only used as selectors count, but
+/// the selects themselves may differ, as languages have different plural categories.
+pub(crate) fn check_placeholders(
+    resources: &[LoadedResource],
+    reference: &[LoadedResource],
+    reference_language: &LanguageIdentifier,
+) -> Vec<ValidationError> {
+    reference_mismatches(
+        resources,
+        reference,
+        reference_language,
+        "PlaceholderMismatch",
+        |pattern| {
+            placeholders(pattern)
+                .into_iter()
+                .map(|placeholder| format!("{{ {placeholder} }}"))
+                .collect()
+        },
+    )
+}
+
+/// Report HTML-like tags in the text of a language's messages that aren't closed, or close a
+/// tag that isn't open. Given the resources of a reference language, also report tags that the
+/// translation of a message is missing, or that the reference doesn't have. Void elements like
+/// `<br>` and self-closing tags like `<br/>` don't need closing, and the variants of a select
+/// are checked separately, so a tag can't be opened before a select and closed in it.
+pub(crate) fn check_markup(
+    resources: &[LoadedResource],
+    reference: Option<(&[LoadedResource], &LanguageIdentifier)>,
+) -> Vec<ValidationError> {
+    let mut errors = vec![];
+    for entry in effective_entries(resources) {
+        if entry.id.starts_with('-') {
+            continue;
+        }
+        for &(attribute, pattern) in &entry.patterns {
+            let in_attribute = attribute
+                .map(|attribute| format!(" in .{attribute}"))
+                .unwrap_or_default();
+            let mut texts = vec![];
+            markup_texts(pattern, &mut texts);
+            for text in texts {
+                let mut open: Vec<String> = vec![];
+                let mut problems = vec![];
+                for tag in tags(&text) {
+                    if tag.self_closing {
+                        continue;
+                    }
+                    if !tag.closing {
+                        open.push(tag.name);
+                    } else if let Some(index) = open.iter().rposition(|name| *name == tag.name) {
+                        for name in open.drain(index..).skip(1) {
+                            problems.push(format!("<{name}> isn't closed{in_attribute}"));
+                        }
+                    } else {
+                        problems.push(format!(
+                            "</{}> doesn't close an open tag{in_attribute}",
+                            tag.name
+                        ));
+                    }
+                }
+                for name in open {
+                    problems.push(format!("<{name}> isn't closed{in_attribute}"));
+                }
+                for problem in problems {
+                    errors.push(
+                        ValidationError::new("MarkupMismatch", entry.id.clone(), problem)
+                            .at(entry.loaded, entry.range()),
+                    );
+                }
+            }
+        }
+    }
+    if let Some((reference, reference_language)) = reference {
+        errors.extend(reference_mismatches(
+            resources,
+            reference,
+            reference_language,
+            "MarkupMismatch",
+            |pattern| {
+                let mut texts = vec![];
+                markup_texts(pattern, &mut texts);
+                let mut names: Vec<String> = vec![];
+                for tag in texts.iter().flat_map(|text| tags(text)) {
+                    let name = format!("<{}>", tag.name);
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+                names
+            },
+        ));
+    }
+    errors
+}
+
+/// Report the differences between what `items` finds in each pattern of a language's messages
+/// and in the same pattern of the reference language, such as placeables the translation is
+/// missing or adds. Messages and attributes missing from either language aren't reported.
+fn reference_mismatches(
+    resources: &[LoadedResource],
+    reference: &[LoadedResource],
+    reference_language: &LanguageIdentifier,
+    error_type: &str,
+    items: impl Fn(&ast::Pattern<&str>) -> Vec<String>,
+) -> Vec<ValidationError> {
+    let reference_entries = effective_entries(reference);
+    let reference_patterns: HashMap<(&str, Option<&str>), &ast::Pattern<&str>> = reference_entries
+        .iter()
+        .filter(|entry| !entry.id.starts_with('-'))
+        .flat_map(|entry| {
+            entry
+                .patterns
+                .iter()
+                .map(|&(attribute, pattern)| ((entry.id.as_str(), attribute), pattern))
+        })
+        .collect();
+    let mut errors = vec![];
+    for entry in effective_entries(resources) {
+        if entry.id.starts_with('-') {
+            continue;
+        }
+        for &(attribute, pattern) in &entry.patterns {
+            let Some(reference_pattern) = reference_patterns.get(&(entry.id.as_str(), attribute))
+            else {
+                continue;
+            };
+            let translation = match attribute {
+                Some(attribute) => format!("Translation of .{attribute}"),
+                None => "Translation".to_string(),
+            };
+            let expected = items(reference_pattern);
+            let found = items(pattern);
+            let mismatches = expected
+                .iter()
+                .filter(|item| !found.contains(item))
+                .map(|item| {
+                    format!(
+                        "{translation} is missing {item}, which is in the {reference_language} \
+                         message"
+                    )
+                })
+                .chain(
+                    found
+                        .iter()
+                        .filter(|item| !expected.contains(item))
+                        .map(|item| {
+                            format!(
+                                "{translation} has {item}, which isn't in the \
+                                 {reference_language} message"
+                            )
+                        }),
+                );
+            for message in mismatches {
+                errors.push(
+                    ValidationError::new(error_type, entry.id.clone(), message)
+                        .at(entry.loaded, entry.range()),
+                );
+            }
+        }
+    }
+    errors
+}
+
+/// Return the variables (with their `$`) and the message and term references of a pattern,
+/// without following the references, in order of first appearance.
+fn placeholders(pattern: &ast::Pattern<&str>) -> Vec<String> {
+    let mut placeholders: Vec<String> = pattern_variables(pattern, |_| None)
+        .into_iter()
+        .map(|name| format!("${name}"))
+        .collect();
+    for reference in pattern_references(pattern) {
+        if !placeholders.contains(&reference.id) {
+            placeholders.push(reference.id);
+        }
+    }
+    placeholders
+}
+
+/// HTML elements which have no closing tag.
+const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// An HTML-like tag in the text of a message.
+struct Tag {
+    /// The tag's name, in lowercase.
+    name: String,
+    /// Whether it's a closing tag, like `</strong>`.
+    closing: bool,
+    /// Whether it doesn't need closing, like `<br/>` or `<br>`.
+    self_closing: bool,
+}
+
+/// Return the texts to check the markup of in a pattern: its own text, with placeables
+/// replaced by U+FFFC (so that tags can have placeables in their attributes), and the texts of
+/// the variants of its selects.
+fn markup_texts(pattern: &ast::Pattern<&str>, texts: &mut Vec<String>) {
+    let mut text = String::new();
+    for element in &pattern.elements {
+        match element {
+            ast::PatternElement::TextElement { value } => text.push_str(value),
+            ast::PatternElement::Placeable {
+                expression: ast::Expression::Select { variants, .. },
+            } => {
+                for variant in variants {
+                    markup_texts(&variant.value, texts);
+                }
+            }
+            ast::PatternElement::Placeable { .. } => text.push('\u{FFFC}'),
+        }
+    }
+    texts.push(text);
+}
+
+/// Return the tags in some text, in order. A `<` that isn't followed by a tag name and a `>`,
+/// as in `1 < 2`, isn't a tag.
+fn tags(text: &str) -> Vec<Tag> {
+    let mut tags = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let (closing, tag) = match rest.strip_prefix('/') {
+            Some(tag) => (true, tag),
+            None => (false, rest),
+        };
+        let name_end = tag
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+            .unwrap_or(tag.len());
+        let (name, after) = tag.split_at(name_end);
+        if !name.starts_with(|c: char| c.is_ascii_alphabetic())
+            || !(after.starts_with('>') || after.starts_with([' ', '\t', '\n', '/']))
+        {
+            continue;
+        }
+        let Some(end) = after.find('>') else {
+            break;
+        };
+        let name = name.to_ascii_lowercase();
+        tags.push(Tag {
+            self_closing: after[..end].ends_with('/') || VOID_ELEMENTS.contains(&name.as_str()),
+            name,
+            closing,
+        });
+        rest = &after[end + 1..];
+    }
+    tags
+}
+
+/// Report variables documented in the comments of messages, like `# $user (String) - the
+/// user's name`, that formatting the message doesn't use, and variables it uses that aren't
+/// documented, if any are. Variables used by referenced messages count as used.
+fn check_variable_docs(resources: &[LoadedResource], errors: &mut Vec<ValidationError>) {
+    let entries = effective_entries(resources);
+    let mut messages = HashMap::new();
+    for loaded in resources {
+        for entry in loaded.resource.entries() {
+            if let ast::Entry::Message(message) = entry {
+                messages.insert(message.id.name, message);
+            }
+        }
+    }
+    let get_message = |id: &str| {
+        messages
+            .get(id)
+            .map(|message| FluentMessage::from(*message))
+    };
+
+    let entries_by_id: HashMap<&str, &Entry> = entries
+        .iter()
+        .map(|entry| (entry.id.as_str(), entry))
+        .collect();
+
+    for loaded in resources {
+        // Comments aren't in the runtime AST, so only parse the resources that might have
+        // documented variables again.
+        let source = loaded.resource.source();
+        if !source
+            .lines()
+            .any(|line| line.starts_with('#') && line.contains('$'))
+        {
+            continue;
+        }
+        let resource = loaded.full_ast();
+        for message in resource.body.iter().filter_map(|entry| match entry {
+            ast::Entry::Message(message) => Some(message),
+            _ => None,
+        }) {
+            let Some(comment) = &message.comment else {
+                continue;
+            };
+            // Only check the definition that's used.
+            let Some(entry) = entries_by_id
+                .get(message.id.name)
+                .filter(|entry| std::ptr::eq(entry.loaded, loaded))
+            else {
+                continue;
+            };
+            let docs = variable_docs(&comment.content.join("\n"));
+            if docs.is_empty() {
+                continue;
+            }
+            let mut used: Vec<String> = vec![];
+            for &(_, pattern) in &entry.patterns {
+                for name in pattern_variables(pattern, get_message) {
+                    if !used.contains(&name) {
+                        used.push(name);
+                    }
+                }
+            }
+            for doc in &docs {
+                if !used.contains(&doc.name) {
+                    let line = comment.content.iter().find(|line| {
+                        variable_docs(line)
+                            .first()
+                            .is_some_and(|other| other.name == doc.name)
+                    });
+                    errors.push(
+                        ValidationError::new(
+                            "VariableDocMismatch",
+                            entry.id.clone(),
+                            format!(
+                                "Documented variable ${} isn't used by the message",
+                                doc.name
+                            ),
+                        )
+                        .at(
+                            entry.loaded,
+                            line.and_then(|line| entry.loaded.source_range(line, line)),
+                        ),
+                    );
+                }
+            }
+            for name in used {
+                if !docs.iter().any(|doc| doc.name == name) {
+                    errors.push(
+                        ValidationError::new(
+                            "VariableDocMismatch",
+                            entry.id.clone(),
+                            format!("Variable ${name} isn't documented in the message's comment"),
+                        )
+                        .at(entry.loaded, entry.range()),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Report terms which no other message or term references, and, given the IDs of the
+/// messages an application uses, messages which neither it nor those messages use.
+pub(crate) fn find_unused(
+    resources: &[LoadedResource],
+    used_ids: Option<&HashSet<String>>,
+) -> Vec<ValidationError> {
+    let entries = effective_entries(resources);
+    let mut entries_by_id = HashMap::new();
+    for entry in &entries {
+        let references = entry
+            .patterns
+            .iter()
+            .flat_map(|(_, pattern)| pattern_references(pattern))
+            .map(|reference| entry_id(&reference.id).to_string())
+            .collect::<Vec<_>>();
+        entries_by_id.insert(entry.id.clone(), references);
+    }
+
+    let mut referenced_terms = HashSet::new();
+    for (id, references) in entries_by_id.iter() {
+        referenced_terms.extend(
+            references
+                .iter()
+                .filter(|reference| *reference != id && reference.starts_with('-')),
+        );
+    }
+
+    // Messages used by the application, along with everything they reference.
+    let mut used_messages = HashSet::new();
+    if let Some(used_ids) = used_ids {
+        let mut pending: Vec<String> = used_ids
+            .iter()
+            .map(|id| {
+                id.split_once('.')
+                    .map_or(id.as_str(), |(id, _)| id)
+                    .to_string()
+            })
+            .collect();
+        while let Some(id) = pending.pop() {
+            if let Some(references) = entries_by_id.get(&id)
+                && used_messages.insert(id)
+            {
+                pending.extend(references.iter().cloned());
+            }
+        }
+    }
+
+    let mut errors = vec![];
+    for entry in &entries {
+        let id = &entry.id;
+        let error = if id.starts_with('-') {
+            if referenced_terms.contains(id) {
+                continue;
+            }
+            ValidationError::new(
+                "UnusedTerm",
+                id.clone(),
+                "Term isn't referenced by any message or term".to_string(),
+            )
+        } else if used_ids.is_some() && !used_messages.contains(id) {
+            ValidationError::new(
+                "UnusedMessage",
+                id.clone(),
+                "Message isn't used, or referenced by a used message".to_string(),
+            )
+        } else {
+            continue;
+        };
+        errors.push(error.at(entry.loaded, entry.range()));
+    }
+    errors
+}
+
+/// A message or term, with the patterns of its value and attributes.
+pub(crate) struct Entry<'r> {
+    /// The ID of the message, or of the term with a leading `-`.
+    pub(crate) id: String,
+    /// The message or term's identifier in the source, without the leading `-` of a term.
+    name: &'r str,
+    /// The resource the entry is defined in.
+    pub(crate) loaded: &'r LoadedResource,
+    /// The patterns of the value (with no attribute name) and of each attribute.
+    pub(crate) patterns: Vec<(Option<&'r str>, &'r ast::Pattern<&'r str>)>,
+}
+
+/// Return the messages and terms of the resources, in order.
+fn entries(resources: &[LoadedResource]) -> impl Iterator<Item = Entry<'_>> {
+    resources.iter().flat_map(|loaded| {
+        loaded.resource.entries().filter_map(|entry| match entry {
+            ast::Entry::Message(message) => Some(Entry {
+                id: message.id.name.to_string(),
+                name: message.id.name,
+                loaded,
+                patterns: message
+                    .value
+                    .iter()
+                    .map(|value| (None, value))
+                    .chain(attribute_patterns(&message.attributes))
+                    .collect(),
+            }),
+            ast::Entry::Term(term) => Some(Entry {
+                id: format!("-{}", term.id.name),
+                name: term.id.name,
+                loaded,
+                patterns: std::iter::once((None, &term.value))
+                    .chain(attribute_patterns(&term.attributes))
+                    .collect(),
+            }),
+            _ => None,
+        })
+    })
+}
+
+impl Entry<'_> {
+    /// Return where the entry's ID is in the resource's source, including any leading `-`.
+    pub(crate) fn range(&self) -> Option<Range<usize>> {
+        let range = self.loaded.source_range(self.name, self.name)?;
+        let sigil = usize::from(self.id.starts_with('-'));
+        Some(range.start - sigil..range.end)
+    }
+}
+
+fn attribute_patterns<'r>(
+    attributes: &'r [ast::Attribute<&'r str>],
+) -> impl Iterator<Item = (Option<&'r str>, &'r ast::Pattern<&'r str>)> {
+    attributes
+        .iter()
+        .map(|attribute| (Some(attribute.id.name), &attribute.value))
+}
+
+/// Return the messages and terms of the resources, with later definitions overriding earlier
+/// ones as they do in the bundle, in the order they're first defined.
+pub(crate) fn effective_entries(resources: &[LoadedResource]) -> Vec<Entry<'_>> {
+    let mut effective: Vec<Entry> = vec![];
+    let mut indexes = HashMap::new();
+    for entry in entries(resources) {
+        match indexes.get(&entry.id) {
+            Some(&index) => effective[index] = entry,
+            None => {
+                indexes.insert(entry.id.clone(), effective.len());
+                effective.push(entry);
+            }
+        }
+    }
+    effective
+}
+
+/// Return the messages and terms of the resources, in the order they're first defined, each
+/// with the IDs of the messages and terms it references (without attributes), in the order
+/// they're first referenced.
+pub(crate) fn entry_references(resources: &[LoadedResource]) -> Vec<(String, Vec<String>)> {
+    effective_entries(resources)
+        .iter()
+        .map(|entry| {
+            let mut references: Vec<String> = vec![];
+            for reference in entry
+                .patterns
+                .iter()
+                .flat_map(|(_, pattern)| pattern_references(pattern))
+            {
+                let id = entry_id(&reference.id);
+                if !references.iter().any(|other| other == id) {
+                    references.push(id.to_string());
+                }
+            }
+            (entry.id.clone(), references)
+        })
+        .collect()
+}
+
+/// Return the entries of a graph from `entry_references` that reference an ID, in order.
+/// With `transitive`, entries that reference it through others are included too.
+pub(crate) fn referencing(
+    graph: &[(String, Vec<String>)],
+    id: &str,
+    transitive: bool,
+) -> Vec<String> {
+    let mut found = HashSet::new();
+    let mut pending = vec![id];
+    while let Some(target) = pending.pop() {
+        for (entry, references) in graph {
+            if references.iter().any(|reference| reference == target)
+                && found.insert(entry.as_str())
+                && transitive
+            {
+                pending.push(entry);
+            }
+        }
+    }
+    graph
+        .iter()
+        .map(|(entry, _)| entry)
+        .filter(|entry| *entry != id && found.contains(entry.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// Write a graph from `entry_references` in the DOT language of Graphviz, with an edge from
+/// each message or term to each one it references.
+pub(crate) fn graph_dot(graph: &[(String, Vec<String>)]) -> String {
+    let mut dot = String::from("digraph {\n");
+    for (entry, references) in graph {
+        if references.is_empty() {
+            dot.push_str(&format!("    \"{entry}\";\n"));
+        }
+        for reference in references {
+            dot.push_str(&format!("    \"{entry}\" -> \"{reference}\";\n"));
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Return the message or term ID of a reference, without any attribute.
+fn entry_id(reference: &str) -> &str {
+    reference
+        .split_once('.')
+        .map_or(reference, |(id, _attribute)| id)
+}
+
+/// The values and attributes of messages and terms, with the entry they belong to and the
+/// references in their patterns.
+type Graph<'r> = HashMap<String, (&'r Entry<'r>, Vec<String>)>;
+
+/// Report messages and terms which reference themselves, directly or through other messages
+/// and terms in any of the resources.
+///
+/// Each loop is reported once, for the first of its members by ID, with the path of references
+/// from that member back to itself, every member of the loop, and the resources they're in.
+fn check_cycles(resources: &[LoadedResource], errors: &mut Vec<ValidationError>) {
+    let entries = effective_entries(resources);
+    let (nodes, graph) = reference_graph(&entries);
+
+    let positions: HashMap<&str, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(position, node)| (node.as_str(), position))
+        .collect();
+    let mut components = cyclic_components(&graph, &nodes);
+    for component in &mut components {
+        component.sort_by_key(|node| positions[node]);
+    }
+    components.sort_by_key(|component| positions[component[0]]);
+
+    for component in components {
+        let start = *component.iter().min().expect("components aren't empty");
+        let cycle =
+            find_cycle(&graph, start).expect("every node of a cyclic component is in a cycle");
+        let mut members: Vec<&str> = component
+            .iter()
+            .map(|node| graph[*node].0.id.as_str())
+            .collect();
+        members.sort();
+        members.dedup();
+        let mut files: Vec<&str> = vec![];
+        for node in &component {
+            let resource = graph[*node].0.loaded.name.as_str();
+            if !files.contains(&resource) {
+                files.push(resource);
+            }
+        }
+        let entry = graph[start].0;
+        errors.push(
+            ValidationError::new(
+                "CyclicReference",
+                entry.id.clone(),
+                format!(
+                    "Cyclic reference between {}: {} (in {})",
+                    members.join(", "),
+                    cycle.join(" -> "),
+                    files.join(", ")
+                ),
+            )
+            .at(entry.loaded, entry.range()),
+        );
+    }
+}
+
+/// Return the graph of references between the values and attributes of the entries, along
+/// with its nodes in order.
+fn reference_graph<'r>(entries: &'r [Entry<'r>]) -> (Vec<String>, Graph<'r>) {
+    // Each value and attribute is a node, so a message may reference its own attributes.
+    let mut nodes: Vec<String> = vec![];
+    let mut graph: Graph = HashMap::new();
+    for entry in entries {
+        for &(attribute, pattern) in &entry.patterns {
+            let node = match attribute {
+                Some(attribute) => format!("{}.{attribute}", entry.id),
+                None => entry.id.clone(),
+            };
+            nodes.push(node.clone());
+            let references = pattern_references(pattern)
+                .into_iter()
+                .map(|reference| reference.id)
+                .collect();
+            graph.insert(node, (entry, references));
+        }
+    }
+    (nodes, graph)
+}
+
+/// Return the first cycle of references that formatting a message (or `message.attribute`)
+/// runs into, following its references in order as Fluent does, e.g. `["-a", "-b", "-a"]`.
+pub(crate) fn find_formatting_cycle(
+    resources: &[LoadedResource],
+    identifier: &str,
+) -> Option<Vec<String>> {
+    fn visit<'g>(
+        graph: &'g Graph,
+        node: &'g str,
+        path: &mut Vec<&'g str>,
+        visited: &mut HashSet<&'g str>,
+    ) -> Option<Vec<String>> {
+        if let Some(position) = path.iter().position(|other| *other == node) {
+            let mut cycle: Vec<String> = path[position..].iter().map(|n| n.to_string()).collect();
+            cycle.push(node.to_string());
+            return Some(cycle);
+        }
+        // A node visited before, but not on the path, has no cycles after it.
+        if !visited.insert(node) {
+            return None;
+        }
+        path.push(node);
+        for reference in &graph[node].1 {
+            if let Some((next, _)) = graph.get_key_value(reference)
+                && let Some(cycle) = visit(graph, next, path, visited)
+            {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        None
+    }
+
+    let entries = effective_entries(resources);
+    let (_, graph) = reference_graph(&entries);
+    let (start, _) = graph.get_key_value(identifier)?;
+    visit(&graph, start, &mut vec![], &mut HashSet::new())
+}
+
+/// Return the strongly-connected components of the graph which contain a cycle, i.e. those with
+/// several nodes, or a single node which references itself, using Tarjan's algorithm.
+fn cyclic_components<'g>(graph: &'g Graph, nodes: &'g [String]) -> Vec<Vec<&'g str>> {
+    struct Search<'g, 'r> {
+        graph: &'g Graph<'r>,
+        indexes: HashMap<&'g str, usize>,
+        low_links: HashMap<&'g str, usize>,
+        stack: Vec<&'g str>,
+        on_stack: HashSet<&'g str>,
+        components: Vec<Vec<&'g str>>,
+    }
+
+    impl<'g> Search<'g, '_> {
+        fn visit(&mut self, node: &'g str) {
+            let index = self.indexes.len();
+            self.indexes.insert(node, index);
+            self.low_links.insert(node, index);
+            self.stack.push(node);
+            self.on_stack.insert(node);
+
+            let graph = self.graph;
+            for reference in &graph[node].1 {
+                let Some((reference, _)) = graph.get_key_value(reference) else {
+                    continue;
+                };
+                let reference = reference.as_str();
+                let low_link = if !self.indexes.contains_key(reference) {
+                    self.visit(reference);
+                    self.low_links[reference]
+                } else if self.on_stack.contains(reference) {
+                    self.indexes[reference]
+                } else {
+                    continue;
+                };
+                if low_link < self.low_links[node] {
+                    self.low_links.insert(node, low_link);
+                }
+            }
+
+            if self.low_links[node] == index {
+                let mut component = vec![];
+                loop {
+                    let member = self.stack.pop().expect("the node is on the stack");
+                    self.on_stack.remove(member);
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                if component.len() > 1 || graph[node].1.iter().any(|reference| reference == node) {
+                    self.components.push(component);
+                }
+            }
+        }
+    }
+
+    let mut search = Search {
+        graph,
+        indexes: HashMap::new(),
+        low_links: HashMap::new(),
+        stack: vec![],
+        on_stack: HashSet::new(),
+        components: vec![],
+    };
+    for node in nodes {
+        if !search.indexes.contains_key(node.as_str()) {
+            search.visit(node);
+        }
+    }
+    search.components
+}
+
+/// Return a path of references from a node back to itself, if there is one.
+fn find_cycle<'g>(graph: &'g Graph, start: &'g str) -> Option<Vec<&'g str>> {
+    fn visit<'g>(
+        graph: &'g Graph,
+        start: &str,
+        path: &mut Vec<&'g str>,
+        visited: &mut HashSet<&'g str>,
+    ) -> bool {
+        let current = path[path.len() - 1];
+        for reference in &graph[current].1 {
+            let Some((node, _)) = graph.get_key_value(reference) else {
+                continue;
+            };
+            path.push(node);
+            if node == start || (visited.insert(node) && visit(graph, start, path, visited)) {
+                return true;
+            }
+            path.pop();
+        }
+        false
+    }
+
+    let mut path = vec![start];
+    let mut visited = HashSet::new();
+    visit(graph, start, &mut path, &mut visited).then_some(path)
+}
+
+/// A reference to a message or term in a pattern.
+struct Reference<'s> {
+    /// The ID of the message, or of the term with a leading `-`, followed by the attribute name
+    /// if it references an attribute, e.g. `-brand.gender`.
+    id: String,
+    /// The referenced ID and attribute names in the source.
+    names: (&'s str, &'s str),
+    /// Whether the reference is to a term with positional arguments, which Fluent ignores.
+    has_positional_arguments: bool,
+}
+
+impl Reference<'_> {
+    /// Return where the reference is in the resource's source, including any leading `-`.
+    fn range(&self, loaded: &LoadedResource) -> Option<Range<usize>> {
+        let range = loaded.source_range(self.names.0, self.names.1)?;
+        let sigil = usize::from(self.id.starts_with('-'));
+        Some(range.start - sigil..range.end)
+    }
+}
+
+/// Return the references to messages and terms in a pattern, in order.
+fn pattern_references<'s>(pattern: &ast::Pattern<&'s str>) -> Vec<Reference<'s>> {
+    let mut references = vec![];
+    collect_pattern_references(pattern, &mut references);
+    references
+}
+
+fn collect_pattern_references<'s>(
+    pattern: &ast::Pattern<&'s str>,
+    references: &mut Vec<Reference<'s>>,
+) {
+    for element in &pattern.elements {
+        if let ast::PatternElement::Placeable { expression } = element {
+            collect_expression_references(expression, references);
+        }
+    }
+}
+
+fn collect_expression_references<'s>(
+    expression: &ast::Expression<&'s str>,
+    references: &mut Vec<Reference<'s>>,
+) {
+    match expression {
+        ast::Expression::Select { selector, variants } => {
+            collect_inline_references(selector, references);
+            for variant in variants {
+                collect_pattern_references(&variant.value, references);
+            }
+        }
+        ast::Expression::Inline(expression) => collect_inline_references(expression, references),
+    }
+}
+
+fn collect_inline_references<'s>(
+    expression: &ast::InlineExpression<&'s str>,
+    references: &mut Vec<Reference<'s>>,
+) {
+    match expression {
+        ast::InlineExpression::MessageReference { id, attribute } => {
+            references.push(reference(id.name.to_string(), id, attribute.as_ref()));
+        }
+        ast::InlineExpression::TermReference {
+            id,
+            attribute,
+            arguments,
+        } => {
+            let mut term = reference(format!("-{}", id.name), id, attribute.as_ref());
+            term.has_positional_arguments = arguments
+                .as_ref()
+                .is_some_and(|arguments| !arguments.positional.is_empty());
+            references.push(term);
+            if let Some(arguments) = arguments {
+                collect_argument_references(arguments, references);
+            }
+        }
+        ast::InlineExpression::FunctionReference { arguments, .. } => {
+            collect_argument_references(arguments, references);
+        }
+        ast::InlineExpression::Placeable { expression } => {
+            collect_expression_references(expression, references);
+        }
+        _ => {}
+    }
+}
+
+fn reference<'s>(
+    id: String,
+    name: &ast::Identifier<&'s str>,
+    attribute: Option<&ast::Identifier<&'s str>>,
+) -> Reference<'s> {
+    match attribute {
+        Some(attribute) => Reference {
+            id: format!("{id}.{}", attribute.name),
+            names: (name.name, attribute.name),
+            has_positional_arguments: false,
+        },
+        None => Reference {
+            id,
+            names: (name.name, name.name),
+            has_positional_arguments: false,
+        },
+    }
+}
+
+fn collect_argument_references<'s>(
+    arguments: &ast::CallArguments<&'s str>,
+    references: &mut Vec<Reference<'s>>,
+) {
+    for argument in &arguments.positional {
+        collect_inline_references(argument, references);
+    }
+    for argument in &arguments.named {
+        collect_inline_references(&argument.value, references);
+    }
+}
+
+const PLURAL_CATEGORY_NAMES: [&str; 6] = ["zero", "one", "two", "few", "many", "other"];
+
+/// Report plural selectors whose variants don't cover every plural category of the language,
+/// e.g. a Russian message with `[one]` and `*[other]`, but not `[few]` and `[many]`.
+///
+/// A select expression counts as a plural selector if its selector is a variable or `NUMBER()`,
+/// and its variant keys are all numbers or plural categories, including one other than `other`.
+fn check_plural_categories(
+    pattern: &ast::Pattern<&str>,
+    entry: &Entry,
+    language: &LanguageIdentifier,
+    plural_categories: &[&str],
+    errors: &mut Vec<ValidationError>,
+) {
+    for element in &pattern.elements {
+        let ast::PatternElement::Placeable { expression } = element else {
+            continue;
+        };
+        let mut expression = expression;
+        // Unwrap nested placeables, e.g. `{ { $count -> ... } }`.
+        while let ast::Expression::Inline(ast::InlineExpression::Placeable { expression: inner }) =
+            expression
+        {
+            expression = inner;
+        }
+        let ast::Expression::Select { selector, variants } = expression else {
+            continue;
+        };
+
+        // Where the selector's variable (with its `$`) or function name is.
+        let (is_numeric_selector, selector_range) = match selector {
+            ast::InlineExpression::VariableReference { id } => (
+                true,
+                entry
+                    .loaded
+                    .source_range(id.name, id.name)
+                    .map(|range| range.start - 1..range.end),
+            ),
+            ast::InlineExpression::FunctionReference { id, .. } if id.name == "NUMBER" => {
+                (true, entry.loaded.source_range(id.name, id.name))
+            }
+            _ => (false, None),
+        };
+        let keys: Vec<Option<&str>> = variants
+            .iter()
+            .map(|variant| match &variant.key {
+                ast::VariantKey::Identifier { name } => Some(*name),
+                ast::VariantKey::NumberLiteral { .. } => None,
+            })
+            .collect();
+        let is_plural = keys
+            .iter()
+            .all(|key| key.is_none_or(|key| PLURAL_CATEGORY_NAMES.contains(&key)))
+            && keys.iter().any(|key| key.is_some_and(|key| key != "other"));
+        if is_numeric_selector && is_plural {
+            let missing: Vec<&str> = plural_categories
+                .iter()
+                .filter(|category| !keys.contains(&Some(**category)))
+                .copied()
+                .collect();
+            if !missing.is_empty() {
+                errors.push(
+                    ValidationError::new(
+                        "MissingPluralCategory",
+                        entry.id.clone(),
+                        format!(
+                            "Plural variants for '{language}' don't cover: {}",
+                            missing.join(", ")
+                        ),
+                    )
+                    .at(entry.loaded, selector_range),
+                );
+            }
+        }
+
+        for variant in variants {
+            check_plural_categories(&variant.value, entry, language, plural_categories, errors);
+        }
+    }
+}
+
+/// Return the CLDR plural rules for a language, falling back to the language without its
+/// region, then to English, as Fluent does when selecting a variant.
+pub(crate) fn plural_rules(
+    language: &LanguageIdentifier,
+    rule_type: PluralRuleType,
+) -> PluralRules {
+    PluralRules::create(language.clone(), rule_type)
+        .or_else(|_| {
+            let language_only = LanguageIdentifier::from_parts(language.language, None, None, &[]);
+            PluralRules::create(language_only, rule_type)
+        })
+        .or_else(|_| {
+            let english: LanguageIdentifier = "en".parse().expect("valid language");
+            PluralRules::create(english, rule_type)
+        })
+        .expect("English has plural rules")
+}
+
+/// The name of a plural category, as used for variant keys.
+pub(crate) fn category_name(category: PluralCategory) -> &'static str {
+    match category {
+        PluralCategory::ZERO => "zero",
+        PluralCategory::ONE => "one",
+        PluralCategory::TWO => "two",
+        PluralCategory::FEW => "few",
+        PluralCategory::MANY => "many",
+        PluralCategory::OTHER => "other",
+    }
+}
+
+/// Return the CLDR cardinal plural categories used by a language, e.g. `one`, `few`, `many`
+/// and `other` for Russian.
+pub(crate) fn plural_categories(language: &LanguageIdentifier) -> Vec<&'static str> {
+    let rules = plural_rules(language, PluralRuleType::CARDINAL);
+
+    // The plural rules can't list their categories, so find them from a sample of numbers
+    // which reaches every category in the CLDR data.
+    let integers = (0..=200)
+        .chain([1_000, 1_000_000])
+        .map(|number| number.to_string());
+    let decimals = (0..30).map(|tenths| format!("{}.{}", tenths / 10, tenths % 10));
+    let mut categories = vec![];
+    for number in integers.chain(decimals) {
+        if let Ok(category) = rules.select(number.as_str()) {
+            let name = category_name(category);
+            if !categories.contains(&name) {
+                categories.push(name);
+            }
+        }
+    }
+    // In the conventional order, rather than the order they were found in.
+    PLURAL_CATEGORY_NAMES
+        .into_iter()
+        .filter(|name| categories.contains(name))
+        .collect()
+}
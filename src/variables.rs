@@ -0,0 +1,320 @@
+use crate::dates::FluentDateTime;
+use crate::lists::FluentList;
+use crate::numbers::Number;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use fluent::{FluentArgs, FluentValue};
+use fluent_bundle::types::FluentNumber;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::sync::PyOnceLock;
+use pyo3::types::{
+    PyBool, PyDate, PyDateTime, PyDict, PyInt, PyList, PyMapping, PyString, PyTuple, PyType,
+};
+use std::str::FromStr;
+
+/// The largest integer magnitude that Fluent numbers, which are backed by an `f64`,
+/// can represent exactly.
+const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+static DECIMAL: PyOnceLock<Py<PyType>> = PyOnceLock::new();
+
+/// Options controlling how Python variables are converted into Fluent arguments.
+#[derive(Clone, Copy)]
+pub(crate) struct VariableOptions<'a> {
+    /// Raise a `TypeError` for unsupported values instead of falling back to the key.
+    pub strict_types: bool,
+    /// Joins the keys of nested mappings into a single variable name.
+    pub separator: &'a str,
+    /// The timezone to convert timezone-aware datetimes to, if any.
+    pub timezone: Option<&'a Bound<'a, PyAny>>,
+    /// How `None` values are passed to Fluent.
+    pub none_as: NonePolicy,
+    /// How `bool` values are passed to Fluent.
+    pub bool_as: BoolPolicy,
+    /// The converters registered with `Bundle.register_adapter`, for values of each type.
+    pub adapters: &'a [(Py<PyType>, Py<PyAny>)],
+    /// Whether values are passed to adapters or their `__fluent_value__` method. Values
+    /// returned by them aren't, so they can't be adapted forever.
+    pub adapt: bool,
+}
+
+impl VariableOptions<'_> {
+    /// The same options for a value returned by an adapter.
+    fn without_adapters(&self) -> Self {
+        Self {
+            adapt: false,
+            ..*self
+        }
+    }
+}
+
+/// How `None` variables are passed to Fluent, from the `none_as` argument.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum NonePolicy {
+    /// The variable is left out, so it's formatted as an unknown variable.
+    Missing,
+    /// The variable is an empty string.
+    Empty,
+}
+
+impl NonePolicy {
+    pub(crate) fn new(none_as: &str) -> PyResult<Self> {
+        match none_as {
+            "missing" => Ok(Self::Missing),
+            "empty" => Ok(Self::Empty),
+            _ => Err(PyValueError::new_err(format!(
+                "none_as must be 'empty' or 'missing', not '{none_as}'."
+            ))),
+        }
+    }
+
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Missing => "missing",
+            Self::Empty => "empty",
+        }
+    }
+}
+
+/// How `bool` variables are passed to Fluent, from the `bool_as` argument.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum BoolPolicy {
+    /// The strings `"true"` and `"false"`, which can be matched by `[true]` and `[false]`
+    /// variants.
+    String,
+    /// The numbers 1 and 0.
+    Number,
+}
+
+impl BoolPolicy {
+    pub(crate) fn new(bool_as: &str) -> PyResult<Self> {
+        match bool_as {
+            "string" => Ok(Self::String),
+            "number" => Ok(Self::Number),
+            _ => Err(PyValueError::new_err(format!(
+                "bool_as must be 'number' or 'string', not '{bool_as}'."
+            ))),
+        }
+    }
+
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Number => "number",
+        }
+    }
+}
+
+/// Look up a timezone by its IANA name, e.g. "Europe/London", using Python's `zoneinfo`.
+pub(crate) fn zone_info(py: Python<'_>, name: &str) -> PyResult<Py<PyAny>> {
+    let zone_info = py.import("zoneinfo")?.getattr("ZoneInfo")?;
+    match zone_info.call1((name,)) {
+        Ok(timezone) => Ok(timezone.unbind()),
+        Err(_) => Err(PyValueError::new_err(format!("Invalid timezone: '{name}'"))),
+    }
+}
+
+/// Convert the `variables` passed from Python into Fluent arguments.
+pub(crate) fn to_fluent_args(
+    variables: &Bound<'_, PyAny>,
+    options: &VariableOptions,
+) -> PyResult<FluentArgs<'static>> {
+    let mut args = FluentArgs::new();
+    for (python_key, python_value) in variable_items(variables)? {
+        set_variable(&mut args, None, &python_key, &python_value, options)?;
+    }
+    Ok(args)
+}
+
+fn set_variable(
+    args: &mut FluentArgs<'static>,
+    prefix: Option<&str>,
+    python_key: &Bound<'_, PyAny>,
+    python_value: &Bound<'_, PyAny>,
+    options: &VariableOptions,
+) -> PyResult<()> {
+    // Make sure the variable key is a Python string,
+    // raising a TypeError if not.
+    if !python_key.is_instance_of::<PyString>() {
+        return Err(PyTypeError::new_err(format!(
+            "Variable key not a str, got {python_key}."
+        )));
+    }
+    // Registered adapters take precedence over the built-in conversions.
+    if options.adapt {
+        let py = python_value.py();
+        for (python_type, adapter) in options.adapters {
+            if python_value.is_instance(python_type.bind(py))? {
+                let adapted = adapter.bind(py).call1((python_value,))?;
+                let options = options.without_adapters();
+                return set_variable(args, prefix, python_key, &adapted, &options);
+            }
+        }
+    }
+    let key = match prefix {
+        Some(prefix) => format!("{prefix}{}{python_key}", options.separator),
+        None => python_key.to_string(),
+    };
+    // Set the variable value as a string or integer,
+    // raising a TypeError if not.
+    if python_value.is_none() {
+        if options.none_as == NonePolicy::Empty {
+            args.set(key, "");
+        }
+    } else if let Ok(value) = python_value.cast::<PyBool>() {
+        // Checked before `int`, which `bool` is a subclass of.
+        match options.bool_as {
+            BoolPolicy::String => args.set(key, if value.is_true() { "true" } else { "false" }),
+            BoolPolicy::Number => args.set(key, i64::from(value.is_true())),
+        }
+    } else if python_value.is_instance_of::<PyString>() {
+        args.set(key, python_value.to_string());
+    } else if python_value.is_instance_of::<PyInt>() {
+        args.set(key, int_value(python_value));
+    } else if let Some(number) = decimal_value(python_value)? {
+        args.set(key, number);
+    } else if let Ok(number) = python_value.cast::<Number>() {
+        args.set(key, number.get().fluent_number());
+    } else if let Some(date) = date_value(&in_timezone(python_value, options.timezone)?)? {
+        args.set(key, date);
+    } else if let Some(list) = list_value(python_value)? {
+        args.set(key, list);
+    } else if python_value.cast::<PyMapping>().is_ok() {
+        // Flatten nested mappings, e.g. {"user": {"name": ...}} becomes `user_name`.
+        for (nested_key, nested_value) in variable_items(python_value)? {
+            set_variable(args, Some(&key), &nested_key, &nested_value, options)?;
+        }
+    } else if options.adapt
+        && let Some(fluent_value) = python_value.getattr_opt("__fluent_value__")?
+    {
+        let adapted = fluent_value.call0()?;
+        set_variable(
+            args,
+            prefix,
+            python_key,
+            &adapted,
+            &options.without_adapters(),
+        )?;
+    } else if options.strict_types {
+        return Err(PyTypeError::new_err(format!(
+            "Variable '{key}' has an unsupported value, got {python_value}."
+        )));
+    } else {
+        // The variable value was of an unsupported type.
+        // Fall back to displaying the variable key as its value.
+        let fallback_value = key.clone();
+        args.set(key, fallback_value);
+    }
+    Ok(())
+}
+
+/// Convert a Python integer into a Fluent number, or into its decimal string
+/// if it is too large to be represented exactly.
+pub(crate) fn int_value(value: &Bound<'_, PyAny>) -> FluentValue<'static> {
+    match value.extract::<i64>() {
+        Ok(int) if (-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(&int) => int.into(),
+        _ => value.to_string().into(),
+    }
+}
+
+/// Convert a Python `Decimal` into a Fluent number from its digits rather than through a
+/// `float`, so trailing zeros are kept: `Decimal("1.50")` is shown as `1.50`, and selects
+/// the plural form for `1.50` rather than `1.5`.
+pub(crate) fn decimal_value(value: &Bound<'_, PyAny>) -> PyResult<Option<FluentValue<'static>>> {
+    let decimal = DECIMAL.import(value.py(), "decimal", "Decimal")?;
+    if !value.is_instance(decimal.as_any())? {
+        return Ok(None);
+    }
+    // Fluent takes the digits after the point as the number's minimum fraction digits,
+    // so the value must be in fixed-point notation, e.g. `0.00015` rather than `1.5E-4`.
+    let digits = value.call_method1("__format__", ("f",))?.to_string();
+    Ok(Some(match FluentNumber::from_str(&digits) {
+        Ok(number) => number.into(),
+        // A signalling NaN.
+        Err(_) => digits.into(),
+    }))
+}
+
+/// Convert a Python `date` or `datetime` into a Fluent date, to be formatted using
+/// the bundle's locale. Timezone-aware datetimes keep their local time.
+pub(crate) fn date_value(value: &Bound<'_, PyAny>) -> PyResult<Option<FluentValue<'static>>> {
+    let datetime = if value.is_instance_of::<PyDateTime>() {
+        let py = value.py();
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("tzinfo", py.None())?;
+        let naive = value.call_method("replace", (), Some(&kwargs))?;
+        FluentDateTime::new(naive.extract::<NaiveDateTime>()?, true)
+    } else if value.is_instance_of::<PyDate>() {
+        let date = value.extract::<NaiveDate>()?;
+        FluentDateTime::new(date.and_time(NaiveTime::MIN), false)
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(FluentValue::Custom(Box::new(datetime))))
+}
+
+/// Convert a Python `list` or `tuple` of strings into a Fluent list, to be joined using the
+/// bundle's locale. Lists of anything else aren't supported.
+pub(crate) fn list_value(value: &Bound<'_, PyAny>) -> PyResult<Option<FluentValue<'static>>> {
+    if !value.is_instance_of::<PyList>() && !value.is_instance_of::<PyTuple>() {
+        return Ok(None);
+    }
+    let mut items = vec![];
+    for item in value.try_iter()? {
+        let item = item?;
+        if !item.is_instance_of::<PyString>() {
+            return Ok(None);
+        }
+        items.push(item.to_string());
+    }
+    Ok(Some(FluentValue::Custom(Box::new(FluentList::new(items)))))
+}
+
+/// Convert a timezone-aware `datetime` to `timezone`, if given. Naive datetimes are
+/// assumed to be in the right timezone already, and are left as they are, like any other value.
+fn in_timezone<'py>(
+    value: &Bound<'py, PyAny>,
+    timezone: Option<&Bound<'py, PyAny>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    match timezone {
+        Some(timezone)
+            if value.is_instance_of::<PyDateTime>()
+                && !value.call_method0("utcoffset")?.is_none() =>
+        {
+            value.call_method1("astimezone", (timezone,))
+        }
+        _ => Ok(value.clone()),
+    }
+}
+
+/// Collect the key/value pairs from a `dict`, any other `collections.abc.Mapping`,
+/// or an iterable of `(key, value)` pairs.
+fn variable_items<'py>(
+    variables: &Bound<'py, PyAny>,
+) -> PyResult<Vec<(Bound<'py, PyAny>, Bound<'py, PyAny>)>> {
+    if let Ok(dict) = variables.cast_exact::<PyDict>() {
+        return Ok(dict.iter().collect());
+    }
+    // Go through `items()` for mapping subclasses, as they may not store
+    // their values the same way they present them (e.g. Django's `QueryDict`).
+    let pairs = match variables.cast::<PyMapping>() {
+        Ok(mapping) => mapping.items()?.into_any(),
+        Err(_) => variables.clone(),
+    };
+    let not_pairs = || {
+        PyTypeError::new_err(format!(
+            "Variables must be a mapping or an iterable of (key, value) pairs, got {variables}."
+        ))
+    };
+    let mut items = vec![];
+    for pair in pairs.try_iter().map_err(|_| not_pairs())? {
+        let pair = pair?;
+        match pair.cast::<PyTuple>() {
+            Ok(tuple) if tuple.len() == 2 => {
+                items.push((tuple.get_item(0)?, tuple.get_item(1)?));
+            }
+            _ => return Err(not_pairs()),
+        }
+    }
+    Ok(items)
+}
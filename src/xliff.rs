@@ -0,0 +1,275 @@
+use crate::interchange::{Definitions, Piece, ftl_pattern, pattern_source, pieces_pattern, units};
+use crate::rewrite::parse_all;
+use fluent_syntax::ast;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+
+const XLIFF_NAMESPACE: &str = "urn:oasis:names:tc:xliff:document:2.0";
+
+/// Escape text for the content or an attribute of an XML element.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The original data of a unit: the FTL source of the placeables in its source and target,
+/// which are `<ph>` codes in their content, so translation tools keep them as they are.
+#[derive(Default)]
+struct OriginalData {
+    data: Vec<String>,
+}
+
+impl OriginalData {
+    /// Write a pattern as the content of a `<source>` or `<target>`: its text, with a `<ph>`
+    /// for each placeable. Patterns with select expressions are written as FTL source, as the
+    /// text of their variants is inside the placeable.
+    fn content(&mut self, pattern: &ast::Pattern<&str>) -> String {
+        let has_select = pattern.elements.iter().any(|element| {
+            matches!(
+                element,
+                ast::PatternElement::Placeable {
+                    expression: ast::Expression::Select { .. }
+                }
+            )
+        });
+        if has_select {
+            return escape(&pattern_source(pattern));
+        }
+        let mut content = String::new();
+        // Codes for the same placeable are told apart by how many came before.
+        let mut occurrences: HashMap<usize, usize> = HashMap::new();
+        for element in &pattern.elements {
+            match element {
+                ast::PatternElement::TextElement { value } => content.push_str(&escape(value)),
+                ast::PatternElement::Placeable { .. } => {
+                    let source = pattern_source(&ast::Pattern {
+                        elements: vec![element.clone()],
+                    });
+                    let index = match self.data.iter().position(|data| *data == source) {
+                        Some(index) => index,
+                        None => {
+                            self.data.push(source);
+                            self.data.len() - 1
+                        }
+                    };
+                    let occurrence = occurrences.entry(index).or_default();
+                    *occurrence += 1;
+                    let id = match *occurrence {
+                        1 => format!("{}", index + 1),
+                        occurrence => format!("{}-{occurrence}", index + 1),
+                    };
+                    let _ = write!(content, "<ph id=\"{id}\" dataRef=\"d{}\"/>", index + 1);
+                }
+            }
+        }
+        content
+    }
+}
+
+/// Export the messages, terms and attributes of reference FTL as the units of an XLIFF 2.0
+/// file, with their translations from the target FTL. Each unit's ID is that of its message
+/// or term (with its leading `-`), with the attribute's name after a `.`, and its notes are
+/// the message's comment.
+pub(crate) fn export(
+    reference: &str,
+    target: &str,
+    source_language: &str,
+    target_language: &str,
+) -> String {
+    let reference = parse_all(reference);
+    let target = parse_all(target);
+    let translations: HashMap<String, &ast::Pattern<&str>> = units(&target)
+        .into_iter()
+        .map(|unit| (unit.context, unit.value))
+        .collect();
+
+    let mut xliff = String::new();
+    let _ = writeln!(xliff, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(
+        xliff,
+        r#"<xliff xmlns="{XLIFF_NAMESPACE}" version="2.0" srcLang="{}" trgLang="{}">"#,
+        escape(source_language),
+        escape(target_language)
+    );
+    let _ = writeln!(xliff, r#"  <file id="f1">"#);
+    for unit in units(&reference) {
+        let mut data = OriginalData::default();
+        let source = data.content(unit.value);
+        let target = translations
+            .get(&unit.context)
+            .map(|pattern| data.content(pattern));
+        let _ = writeln!(xliff, r#"    <unit id="{}">"#, escape(&unit.context));
+        if let Some(comment) = &unit.comment {
+            let _ = writeln!(xliff, "      <notes>");
+            let _ = writeln!(
+                xliff,
+                r#"        <note category="comment">{}</note>"#,
+                escape(comment)
+            );
+            let _ = writeln!(xliff, "      </notes>");
+        }
+        if !data.data.is_empty() {
+            let _ = writeln!(xliff, "      <originalData>");
+            for (index, data) in data.data.iter().enumerate() {
+                let _ = writeln!(
+                    xliff,
+                    r#"        <data id="d{}">{}</data>"#,
+                    index + 1,
+                    escape(data)
+                );
+            }
+            let _ = writeln!(xliff, "      </originalData>");
+        }
+        let state = if target.is_some() {
+            "translated"
+        } else {
+            "initial"
+        };
+        let _ = writeln!(xliff, r#"      <segment state="{state}">"#);
+        let _ = writeln!(xliff, "        <source>{source}</source>");
+        if let Some(target) = target {
+            let _ = writeln!(xliff, "        <target>{target}</target>");
+        }
+        let _ = writeln!(xliff, "      </segment>");
+        let _ = writeln!(xliff, "    </unit>");
+    }
+    let _ = writeln!(xliff, "  </file>");
+    let _ = writeln!(xliff, "</xliff>");
+    xliff
+}
+
+/// A part of the content of a `<source>` or `<target>`: text, or the FTL source of a code.
+enum Part {
+    Text(String),
+    Code(String),
+}
+
+/// Collect the parts of the content of an element. Codes are looked up in the unit's original
+/// data, and the content of paired codes and annotations, like `<pc>` and `<mrk>`, is kept.
+fn content_parts(
+    element: &Bound<'_, PyAny>,
+    data: &HashMap<String, String>,
+    parts: &mut Vec<Part>,
+) -> PyResult<()> {
+    if let Some(text) = element.getattr("text")?.extract::<Option<String>>()? {
+        parts.push(Part::Text(text));
+    }
+    for child in element.try_iter()? {
+        let child = child?;
+        let tag: String = child.getattr("tag")?.extract()?;
+        match tag.rsplit('}').next().unwrap_or_default() {
+            "ph" | "sc" | "ec" => {
+                let data_ref: Option<String> =
+                    child.call_method1("get", ("dataRef",))?.extract()?;
+                let equiv: Option<String> = child.call_method1("get", ("equiv",))?.extract()?;
+                let code = data_ref
+                    .and_then(|data_ref| data.get(&data_ref).cloned())
+                    .or(equiv)
+                    .unwrap_or_default();
+                parts.push(Part::Code(code));
+            }
+            _ => content_parts(&child, data, parts)?,
+        }
+        if let Some(tail) = child.getattr("tail")?.extract::<Option<String>>()? {
+            parts.push(Part::Text(tail));
+        }
+    }
+    Ok(())
+}
+
+/// Make a pattern from the parts of a translation. With codes, the text around them is
+/// text; without, it's the FTL source of the pattern, as for select expressions. If that
+/// isn't valid FTL, the whole translation is text.
+fn parts_pattern(parts: Vec<Part>) -> ast::Pattern<String> {
+    let has_codes = parts.iter().any(|part| matches!(part, Part::Code(_)));
+    let mut source = String::new();
+    for part in &parts {
+        match part {
+            Part::Text(text) if has_codes => {
+                for c in text.chars() {
+                    match c {
+                        '{' => source.push_str(r#"{ "{" }"#),
+                        '}' => source.push_str(r#"{ "}" }"#),
+                        c => source.push(c),
+                    }
+                }
+            }
+            Part::Text(text) | Part::Code(text) => source.push_str(text),
+        }
+    }
+    ftl_pattern(&source).unwrap_or_else(|| {
+        let pieces = parts.into_iter().map(|part| match part {
+            Part::Text(text) | Part::Code(text) => Piece::Text(text),
+        });
+        pieces_pattern(pieces.collect())
+    })
+}
+
+/// Import the translations of an XLIFF 2.0 file as FTL, with each unit's ID as the ID of its
+/// message or term, and an attribute after a `.`, as exported by `export`. The notes of each
+/// unit are comments. Units without a translation are left out.
+pub(crate) fn import(py: Python<'_>, path: &Path) -> PyResult<String> {
+    let root = py
+        .import("xml.etree.ElementTree")?
+        .call_method1("parse", (path,))?
+        .call_method0("getroot")?;
+    let tag = |name: &str| format!("{{{XLIFF_NAMESPACE}}}{name}");
+    let mut definitions = Definitions::default();
+    for unit in root.call_method1("iter", (tag("unit"),))?.try_iter()? {
+        let unit = unit?;
+        let id: Option<String> = unit.call_method1("get", ("id",))?.extract()?;
+        let mut comments = vec![];
+        for note in unit.call_method1("iter", (tag("note"),))?.try_iter()? {
+            if let Some(text) = note?.getattr("text")?.extract::<Option<String>>()? {
+                comments.extend(text.lines().map(str::to_string));
+            }
+        }
+        let mut data = HashMap::new();
+        for element in unit.call_method1("iter", (tag("data"),))?.try_iter()? {
+            let element = element?;
+            let data_id: Option<String> = element.call_method1("get", ("id",))?.extract()?;
+            let text: Option<String> = element.getattr("text")?.extract()?;
+            if let Some(data_id) = data_id {
+                data.insert(data_id, text.unwrap_or_default());
+            }
+        }
+
+        // The content is in the unit's segments, and the whitespace between them in its
+        // `<ignorable>`s, which may not be translated.
+        let mut parts = vec![];
+        let mut translated = true;
+        for child in unit.try_iter()? {
+            let child = child?;
+            let name: String = child.getattr("tag")?.extract()?;
+            let is_segment = name == tag("segment");
+            if !is_segment && name != tag("ignorable") {
+                continue;
+            }
+            let target = child.call_method1("find", (tag("target"),))?;
+            let content = if target.is_none() && !is_segment {
+                child.call_method1("find", (tag("source"),))?
+            } else {
+                target
+            };
+            if content.is_none() {
+                translated = false;
+                continue;
+            }
+            content_parts(&content, &data, &mut parts)?;
+        }
+        let empty = parts.iter().all(|part| match part {
+            Part::Text(text) => text.trim().is_empty(),
+            Part::Code(_) => false,
+        });
+        if !translated || empty {
+            continue;
+        }
+        let text = id.clone().unwrap_or_default();
+        definitions.add(id.as_deref(), &text, comments, parts_pattern(parts));
+    }
+    Ok(definitions.serialize())
+}
@@ -0,0 +1,7 @@
+plain-date = { DATETIME($date, year: "numeric", month: "numeric", day: "numeric") }
+long-month = { DATETIME($date, month: "long", year: "numeric") }
+full-date = { DATETIME($date, dateStyle: "full") }
+short-date = { DATETIME($date, month: "short", day: "numeric") }
+time = { DATETIME($date, hour: "numeric", minute: "2-digit") }
+date-and-time = { DATETIME($date, dateStyle: "medium", timeStyle: "short") }
+next-day = { DATETIME(NEXT_DAY($date), month: "long", day: "numeric") }
@@ -9,3 +9,6 @@ with-selector = { $number ->
     [1] One
     *[other] Something else
 }
+
+nested-user = Hello, { $user_name } from { $user_address_city }.
+nested-user-dashed = Hello, { $user-name }.
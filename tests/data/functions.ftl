@@ -0,0 +1,3 @@
+platform-message = Running on { PLATFORM() }.
+shout-message = { SHOUT($name, suffix: "!") }
+double-message = { DOUBLE($count) }
@@ -0,0 +1,19 @@
+### Resource comment describing the whole file.
+
+ungrouped-message = Not in a group
+
+## Checkout
+## Owned by the payments team.
+
+checkout-title = Checkout
+checkout-pay = Pay now
+
+## Emails
+
+email-subject = Your order
+-brand = Kraken
+email-footer = Thanks from { -brand }
+
+##
+
+after-closed-group = Not in a group either
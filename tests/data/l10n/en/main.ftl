@@ -0,0 +1,3 @@
+hello-world = Hello World
+hello-user = Hello, { $user }
+goodbye = Goodbye
@@ -0,0 +1,6 @@
+plain-number = { $number }
+fraction-digits = { NUMBER($number, minimumFractionDigits: 2) }
+percent = { NUMBER($ratio, style: "percent") }
+no-grouping = { NUMBER($number, useGrouping: "false") }
+price = { NUMBER($amount, style: "currency", currency: "EUR") }
+price-code = { NUMBER($amount, style: "currency", currency: "USD", currencyDisplay: "code") }
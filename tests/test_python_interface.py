@@ -1,6 +1,19 @@
 #!/usr/bin/env python
+import collections
+import concurrent.futures
+import enum
+import io
+import json
 import pathlib
-from datetime import datetime
+import pickle
+import re
+import types
+import uuid
+import warnings
+import zipfile
+from datetime import date, datetime, timedelta, timezone
+from decimal import Decimal
+from xml.etree import ElementTree
 
 import pytest
 
@@ -57,11 +70,17 @@ def test_en_with_variables_use_isolating_off():
     (
         ("String", "hello-user", {"user": "Bob"}, f"Hello, {BIDI_OPEN}Bob{BIDI_CLOSE}"),
         ("Integer", "apples", {"numberOfApples": 10}, f"{BIDI_OPEN}10{BIDI_CLOSE} apples"),
+        (
+            "Date",
+            "date-message",
+            {"date": date(2020, 1, 5)},
+            f"The date is {BIDI_OPEN}1/5/2020{BIDI_CLOSE}.",
+        ),
         (
             "Naive datetime",
             "date-message",
-            {"date": datetime(2020, 1, 5)},
-            f"The date is {BIDI_OPEN}2020-01-05{BIDI_CLOSE}.",
+            {"date": datetime(2020, 1, 5, 15, 4, 5)},
+            f"The date is {BIDI_OPEN}1/5/2020, 3:04:05\u202fPM{BIDI_CLOSE}.",
         ),
     ),
 )
@@ -73,6 +92,70 @@ def test_variables_of_different_types(description, identifier, variables, expect
     assert result == expected
 
 
+@pytest.mark.parametrize(
+    "variables",
+    (
+        collections.ChainMap({"user": "Bob"}, {"user": "Alice"}),
+        types.MappingProxyType({"user": "Bob"}),
+        collections.OrderedDict(user="Bob"),
+        [("user", "Bob")],
+        (pair for pair in [("user", "Bob")]),
+    ),
+)
+def test_variables_accept_mappings_and_pairs(variables):
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    result = bundle.get_translation("hello-user", variables=variables)
+
+    assert result == f"Hello, {BIDI_OPEN}Bob{BIDI_CLOSE}"
+
+
+@pytest.mark.parametrize(
+    "variables",
+    (
+        10,
+        ["user"],
+        [("user", "Bob", "extra")],
+    ),
+)
+def test_variables_that_are_not_pairs_raise_type_error(variables):
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    with pytest.raises(TypeError, match="Variables must be a mapping or an iterable of"):
+        bundle.get_translation("hello-user", variables=variables)
+
+
+def test_nested_variables_are_flattened():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    result = bundle.get_translation(
+        "nested-user",
+        variables={"user": {"name": "Ana", "address": {"city": "Lisbon"}}},
+        use_isolating=False,
+    )
+
+    assert result == "Hello, Ana from Lisbon."
+
+
+def test_nested_variables_with_custom_separator():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"], variable_separator="-")
+
+    result = bundle.get_translation(
+        "nested-user-dashed",
+        variables={"user": {"name": "Ana"}},
+        use_isolating=False,
+    )
+
+    assert result == "Hello, Ana."
+
+
+def test_nested_variable_keys_must_be_strings():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    with pytest.raises(TypeError, match="Variable key not a str, got 1."):
+        bundle.get_translation("nested-user", variables={"user": {1: "Ana"}})
+
+
 def test_invalid_language():
     with pytest.raises(ValueError) as exc_info:
         fluent.Bundle("$", [])
@@ -100,7 +183,6 @@ def test_invalid_variable_keys_raise_type_error(key):
     (
         object(),
         34.3,
-        1_000_000_000_000,  # Larger than signed long integer.
     ),
 )
 def test_invalid_variable_values_use_key_instead(value):
@@ -111,6 +193,110 @@ def test_invalid_variable_values_use_key_instead(value):
     assert result == f"Hello, {BIDI_OPEN}user{BIDI_CLOSE}"
 
 
+@pytest.mark.parametrize(
+    "value",
+    (
+        object(),
+        34.3,
+    ),
+)
+def test_invalid_variable_values_raise_type_error_with_strict_types(value):
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    with pytest.raises(TypeError, match="Variable 'user' has an unsupported value, got"):
+        bundle.get_translation("hello-user", variables={"user": value}, strict_types=True)
+
+
+def test_supported_variable_values_with_strict_types():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    result = bundle.get_translation("hello-user", variables={"user": "Bob"}, strict_types=True)
+
+    assert result == f"Hello, {BIDI_OPEN}Bob{BIDI_CLOSE}"
+
+
+def test_none_variables_are_missing():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    result = bundle.get_translation("hello-user", {"user": None}, use_isolating=False)
+
+    assert result == "Hello, {$user}"
+    with pytest.raises(fluent.FormatError) as exc_info:
+        bundle.get_translation("hello-user", {"user": None}, errors="raise")
+    assert exc_info.value.variable_name == "user"
+
+
+def test_none_variables_as_empty_strings():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"], none_as="empty")
+
+    result = bundle.get_translation("hello-user", {"user": None}, use_isolating=False)
+
+    assert result == "Hello, "
+
+
+BOOL_FTL = """
+admin = { $is_admin ->
+    [true] Admin
+   *[false] User
+}
+count = { $is_admin ->
+    [1] One
+   *[other] Other
+}
+"""
+
+
+def test_bool_variables_as_strings():
+    bundle = fluent.Bundle("en", [("bools", BOOL_FTL)])
+
+    assert bundle.get_translation("admin", {"is_admin": True}) == "Admin"
+    assert bundle.get_translation("admin", {"is_admin": False}) == "User"
+
+
+def test_bool_variables_as_numbers():
+    bundle = fluent.Bundle("en", [("bools", BOOL_FTL)], bool_as="number")
+
+    assert bundle.get_translation("count", {"is_admin": True}) == "One"
+    assert bundle.get_translation("count", {"is_admin": False}) == "Other"
+
+
+def test_invalid_variable_policies():
+    with pytest.raises(ValueError, match="none_as must be 'empty' or 'missing', not 'zero'."):
+        fluent.Bundle("en", [data_dir / "en.ftl"], none_as="zero")
+    with pytest.raises(ValueError, match="bool_as must be 'number' or 'string', not 'yes'."):
+        fluent.Bundle("en", [data_dir / "en.ftl"], bool_as="yes")
+
+
+@pytest.mark.parametrize(
+    "value, expected",
+    (
+        (1_000_000_000_000, "1,000,000,000,000"),  # Larger than signed long integer.
+        (-1_000_000_000_000, "-1,000,000,000,000"),
+        (2**53 - 1, "9,007,199,254,740,991"),
+        # Too large to be a Fluent number without losing precision, so passed as a string.
+        (2**53 + 1, "9007199254740993"),
+        (2**64 + 1, "18446744073709551617"),
+        (10**30, "1000000000000000000000000000000"),
+    ),
+)
+def test_large_integer_variables(value, expected):
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    result = bundle.get_translation(
+        "apples", variables={"numberOfApples": value}, use_isolating=False
+    )
+
+    assert result == f"{expected} apples"
+
+
+def test_large_integer_variables_are_numbers_in_selectors():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    result = bundle.get_translation("with-selector", variables={"number": 1_000_000_000_001})
+
+    assert result == "Something else"
+
+
 def test_fr_basic():
     bundle = fluent.Bundle("fr", [data_dir / "fr.ftl"])
     assert bundle.get_translation("hello-world") == "Bonjour le monde!"
@@ -161,130 +347,5025 @@ def test_id_not_found():
 
 
 def test_file_not_found():
-    with pytest.raises(FileNotFoundError):
+    with pytest.raises(FileNotFoundError) as exc_info:
         fluent.Bundle("fr", [data_dir / "none.ftl"])
 
+    assert str(exc_info.value.filename) == str(data_dir / "none.ftl")
 
-@pytest.mark.parametrize("pass_strict_argument_explicitly", (True, False))
-def test_parses_other_parts_of_file_that_contains_errors_in_non_strict_mode(
-    pass_strict_argument_explicitly,
-):
-    kwargs = dict(strict=False) if pass_strict_argument_explicitly else {}
 
-    bundle = fluent.Bundle("fr", [data_dir / "errors.ftl"], **kwargs)
-    translation = bundle.get_translation("valid-message")
+def test_directory_is_not_a_file():
+    with pytest.raises(IsADirectoryError):
+        fluent.Bundle("fr", [data_dir])
 
-    assert translation == "I'm valid."
 
+def test_invalid_utf8(tmp_path):
+    (tmp_path / "latin1.ftl").write_bytes("greeting = Bonjour, ça va ?".encode("latin-1"))
 
-def test_raises_parser_error_on_file_that_contains_errors_in_strict_mode():
-    filename = data_dir / "errors.ftl"
+    with pytest.raises(UnicodeDecodeError) as exc_info:
+        fluent.Bundle("fr", [tmp_path / "latin1.ftl"])
 
-    with pytest.raises(fluent.ParserError) as exc_info:
-        fluent.Bundle("fr", [filename], strict=True)
+    assert exc_info.value.__notes__ == [f"while reading {tmp_path / 'latin1.ftl'}"]
 
-    message = str(exc_info.value)
 
-    # Recombine first line if it was too long
-    lines = message.split("\n")
-    if lines[1].endswith(".ftl"):
-        lines = [(lines[0] + lines[1]).replace("  │ ", ""), *lines[2:]]
-    message = "\n".join(lines)
-    # End recombination
+@pytest.mark.parametrize(
+    "kwargs, expected",
+    (
+        ({"encoding": "latin-1"}, "Bonjour, ça va ?"),
+        ({"errors": "replace"}, "Bonjour, \ufffda va ?"),
+    ),
+)
+def test_file_encoding(tmp_path, kwargs, expected):
+    (tmp_path / "latin1.ftl").write_bytes("greeting = Bonjour, ça va ?".encode("latin-1"))
 
-    expected = f"""\
-  × Error when parsing {filename}
-   ╭─[1:16]
- 1 │ invalid-message
-   ·                ┬
-   ·                ╰── Expected a token starting with "="
- 2 │ 
- 3 │ valid-message = I'm valid.
-   ╰────
+    bundle = fluent.Bundle("fr", [tmp_path / "latin1.ftl"], **kwargs)
+
+    assert bundle.get_translation("greeting") == expected
+
+
+def test_bytes_and_file_sources(tmp_path):
+    (tmp_path / "text.ftl").write_text("farewell = Au revoir")
+
+    with (tmp_path / "text.ftl").open() as text_file:
+        bundle = fluent.Bundle(
+            "fr",
+            [
+                "greeting = Bonjour, ça va ?".encode("latin-1"),
+                io.BytesIO(b"thanks = Merci { missing }"),
+                text_file,
+            ],
+            encoding="latin-1",
+        )
+
+    assert bundle.get_translation("greeting") == "Bonjour, ça va ?"
+    assert bundle.get_translation("farewell") == "Au revoir"
+    assert [(error.filename, error.message_id) for error in bundle.validation_errors()] == [
+        ("<file>", "thanks")
+    ]
+
+
+def test_zip_archive_members(tmp_path):
+    with zipfile.ZipFile(tmp_path / "translations.zip", "w") as archive:
+        archive.writestr("de/main.ftl", "hello = Hallo\nbye = { missing }")
+
+    bundle = fluent.Bundle("de", [f"{tmp_path / 'translations.zip'}!/de/main.ftl"])
+
+    assert bundle.get_translation("hello") == "Hallo"
+    [error] = bundle.validation_errors()
+    assert error.filename == f"{tmp_path / 'translations.zip'}!/de/main.ftl"
+
+
+def test_missing_zip_archive_member(tmp_path):
+    with zipfile.ZipFile(tmp_path / "translations.zip", "w") as archive:
+        archive.writestr("de/main.ftl", "hello = Hallo")
+
+    with pytest.raises(FileNotFoundError) as exc_info:
+        fluent.Bundle("de", [f"{tmp_path / 'translations.zip'}!/fr/main.ftl"])
+
+    assert str(exc_info.value.filename) == f"{tmp_path / 'translations.zip'}!/fr/main.ftl"
+
+
+def test_reload(tmp_path):
+    (tmp_path / "messages.ftl").write_text("hello = Hello")
+    bundle = fluent.Bundle("en", [tmp_path / "messages.ftl"])
+    bundle.add_ftl_string("extra", "extra = Extra")
+    bundle.add_function("SHOUT", lambda text: str(text).upper())
+
+    (tmp_path / "messages.ftl").write_text('hello = { SHOUT("hi") }\nbye = { missing }')
+    errors = bundle.reload()
+
+    assert bundle.get_translation("hello") == "HI"
+    assert bundle.get_translation("extra") == "Extra"
+    assert [error.message for error in errors] == ["Unknown message: missing"]
+
+
+def test_failed_reload_keeps_the_bundle(tmp_path):
+    (tmp_path / "messages.ftl").write_text("hello = Hello")
+    bundle = fluent.Bundle("en", [tmp_path / "messages.ftl"], strict=True)
+
+    (tmp_path / "messages.ftl").write_text("hello = { missing }")
+    with pytest.raises(fluent.ParserError, match="Unknown message: missing"):
+        bundle.reload()
+
+    assert bundle.get_translation("hello") == "Hello"
+
+
+def test_copy_bundle():
+    bundle = fluent.Bundle("en", [("messages", "hello = Hello")])
+
+    copy = bundle.copy()
+    copy.add_ftl_string("extra", "extra = Extra")
+
+    assert copy.get_translation("hello") == "Hello"
+    assert "extra" in copy
+    assert "extra" not in bundle
+
+
+SUBSET_FTL = """
+-brand = Acme
+checkout-title = Checkout at { -brand }
+checkout-pay = Pay now
+account-title = Your account
 """
-    assert message == expected
 
 
-def test_parser_error_str():
-    assert str(fluent.ParserError) == "<class 'rustfluent.ParserError'>"
+def test_subset_by_prefix():
+    bundle = fluent.Bundle("en", [("messages", SUBSET_FTL)])
 
+    checkout = bundle.subset(prefix="checkout-")
 
-# Attribute access tests
+    assert checkout.message_ids() == ["checkout-title", "checkout-pay"]
+    assert checkout.get_translation("checkout-title", use_isolating=False) == "Checkout at Acme"
 
 
-def test_basic_attribute_access():
-    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
-    assert bundle.get_translation("welcome-message.title") == "Welcome to our site"
+def test_subset_by_predicate():
+    bundle = fluent.Bundle("en", [("messages", SUBSET_FTL)])
 
+    titles = bundle.subset(predicate=lambda message_id: message_id.endswith("-title"))
 
-def test_regular_message_still_works_with_attributes():
-    """Test that accessing the main message value still works when it has attributes."""
-    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
-    assert bundle.get_translation("welcome-message") == "Welcome!"
+    assert titles.message_ids() == ["checkout-title", "account-title"]
 
 
-def test_multiple_attributes_on_same_message():
-    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
-    assert bundle.get_translation("login-input.placeholder") == "email@example.com"
-    assert bundle.get_translation("login-input.aria-label") == "Login input value"
-    assert bundle.get_translation("login-input.title") == "Type your login email"
+def test_subset_needs_a_filter():
+    with pytest.raises(TypeError, match="needs a prefix or a predicate"):
+        fluent.Bundle("en", []).subset()
 
 
-def test_attribute_with_variables():
-    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
-    result = bundle.get_translation("greeting.formal", variables={"name": "Alice"})
-    assert result == f"Hello, {BIDI_OPEN}Alice{BIDI_CLOSE}"
+def test_override_message():
+    bundle = fluent.Bundle(
+        ["en", "fr"], {"en": [("en", "hello = Hello")], "fr": [data_dir / "fr.ftl"]}
+    )
 
+    bundle.override_message("hello", "hello = Hi")
+    bundle.override_message("new", "new = New")
 
-def test_attribute_with_variables_use_isolating_off():
-    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
-    result = bundle.get_translation(
-        "greeting.informal",
-        variables={"name": "Bob"},
-        use_isolating=False,
+    assert bundle.get_translation("hello") == "Hi"
+    assert bundle.get_translation("new") == "New"
+    assert bundle.validation_errors() == []
+
+
+@pytest.mark.parametrize("snippet", ("other = Other", "hello = Hi\nother = Other"))
+def test_override_message_must_define_only_the_message(snippet):
+    bundle = fluent.Bundle("en", [("en", "hello = Hello")])
+
+    with pytest.raises(ValueError, match="must define 'hello' and nothing else"):
+        bundle.override_message("hello", snippet)
+
+
+def test_override_message_with_syntax_error():
+    bundle = fluent.Bundle("en", [("en", "hello = Hello")])
+
+    with pytest.raises(fluent.ParserError):
+        bundle.override_message("hello", "hello")
+
+    assert bundle.get_translation("hello") == "Hello"
+
+
+def test_rename():
+    bundle = fluent.Bundle(
+        "en",
+        [
+            ("main.ftl", "hello = Hello\nwelcome = { hello }, welcome!\n"),
+            ("other.ftl", "bye = Bye\n"),
+            ("more.ftl", "title = { hello.title } { hello }\n"),
+        ],
     )
-    assert result == "Hi Bob!"
 
+    updated = bundle.rename("hello", "greeting")
 
-def test_attribute_on_message_without_main_value():
-    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
-    assert bundle.get_translation("form-button.submit") == "Submit Form"
-    assert bundle.get_translation("form-button.cancel") == "Cancel"
-    assert bundle.get_translation("form-button.reset") == "Reset Form"
+    assert updated == {
+        "main.ftl": "greeting = Hello\nwelcome = { greeting }, welcome!\n",
+        "more.ftl": "title = { greeting.title } { greeting }\n",
+    }
+    assert bundle.get_translation("welcome") == "Hello, welcome!"
+    assert not bundle.has_message("hello")
+    assert bundle.message_ids() == ["greeting", "welcome", "bye", "title"]
 
 
-def test_message_without_value_raises_error():
-    """Test that accessing a message without a value (only attributes) raises an error."""
-    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
-    with pytest.raises(ValueError, match="form-button - Message has no value"):
-        bundle.get_translation("form-button")
+def test_rename_term():
+    bundle = fluent.Bundle(
+        ["en", "fr"],
+        {
+            "en": [("en.ftl", "-brand = Acme\nabout = About { -brand(case: \"short\") }\n")],
+            "fr": [("fr.ftl", "-brand = Acme\nabout = À propos de { -brand }\n")],
+        },
+    )
 
+    updated = bundle.rename("-brand", "-company")
 
-def test_missing_message_with_attribute_syntax_raises_error():
-    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
-    with pytest.raises(ValueError, match="nonexistent not found"):
-        bundle.get_translation("nonexistent.title")
+    assert updated["en.ftl"] == '-company = Acme\nabout = About { -company(case: "short") }\n'
+    assert updated["fr.ftl"] == "-company = Acme\nabout = À propos de { -company }\n"
+    assert bundle.get_translation("about") == "About Acme"
+    assert bundle.term_ids() == ["-company"]
 
 
-def test_missing_attribute_raises_error():
-    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
-    with pytest.raises(
-        ValueError,
-        match="welcome-message.nonexistent - Attribute 'nonexistent' not found on message 'welcome-message'",
-    ):
-        bundle.get_translation("welcome-message.nonexistent")
+@pytest.mark.parametrize(
+    "old_id, new_id, error",
+    (
+        ("missing", "renamed", "missing not found"),
+        ("hello", "bye", "bye already exists"),
+        ("hello", "-hello", "Invalid ID '-hello' to rename 'hello' to"),
+        ("hello", "1hello", "Invalid ID '1hello'"),
+    ),
+)
+def test_rename_errors(old_id, new_id, error):
+    bundle = fluent.Bundle("en", [("main.ftl", "hello = Hello\nbye = Bye\n")])
+
+    with pytest.raises(ValueError, match=error):
+        bundle.rename(old_id, new_id)
+
+
+def test_remove_message():
+    bundle = fluent.Bundle(
+        ["en", "fr"],
+        {"en": [("en", "hello-world = Hello\nbye = Bye")], "fr": [data_dir / "fr.ftl"]},
+    )
+
+    bundle.remove_message("hello-world")
+
+    assert not bundle.has_message("hello-world")
+    assert bundle.get_translation("bye") == "Bye"
+    with pytest.raises(ValueError, match="hello-world not found"):
+        bundle.remove_message("hello-world")
+
+
+def test_compiled_messages_follow_overrides_and_removals():
+    bundle = fluent.Bundle("en", [("en", "hello = Hello\nbye = Bye")])
+    hello = bundle.compile("hello")
+    bye = bundle.compile("bye")
+
+    bundle.override_message("hello", "hello = Hi")
+    assert hello() == "Hi"
+    bundle.remove_message("bye")
+    with pytest.raises(ValueError, match="bye not found"):
+        bye()
+
+
+def test_prefixes():
+    bundle = fluent.Bundle(
+        "en",
+        [
+            ("emails", "title = Your receipt\nsubject = { title }"),
+            ("pages", "-brand = Acme\ntitle = Welcome to { -brand }"),
+        ],
+        prefixes={"emails": "emails-", "pages": "pages-"},
+    )
+
+    assert bundle.message_ids() == ["emails-title", "emails-subject", "pages-title"]
+    assert bundle.get_translation("emails-subject") == "Your receipt"
+    assert bundle.get_translation("pages-title", use_isolating=False) == "Welcome to Acme"
+
+
+def test_prefixes_are_added_again_on_reload(tmp_path):
+    (tmp_path / "emails.ftl").write_text("title = Your receipt")
+    bundle = fluent.Bundle(
+        "en", [tmp_path / "emails.ftl"], prefixes={tmp_path / "emails.ftl": "emails-"}
+    )
+
+    (tmp_path / "emails.ftl").write_text("title = Your order")
+    bundle.reload()
+
+    assert bundle.get_translation("emails-title") == "Your order"
 
 
 @pytest.mark.parametrize(
-    "identifier,expected",
+    "prefixes, message",
     (
-        ("welcome-message", "Welcome!"),
-        ("welcome-message.title", "Welcome to our site"),
-        ("welcome-message.aria-label", "Welcome greeting"),
-        ("login-input", "Email"),
-        ("login-input.placeholder", "email@example.com"),
+        ({"other": "other-"}, "Prefix given for 'other', which is not one of the FTL files."),
+        ({"emails": "emails/"}, "Invalid prefix: 'emails/'"),
     ),
 )
-def test_attribute_and_message_access_parameterized(identifier, expected):
-    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
-    assert bundle.get_translation(identifier) == expected
+def test_invalid_prefixes(prefixes, message):
+    with pytest.raises(ValueError, match=re.escape(message)):
+        fluent.Bundle("en", [("emails", "title = Title")], prefixes=prefixes)
+
+
+def test_unknown_encoding():
+    with pytest.raises(LookupError, match="unknown encoding: klingon"):
+        fluent.Bundle("fr", [data_dir / "fr.ftl"], encoding="klingon")
+
+
+@pytest.mark.parametrize("pass_strict_argument_explicitly", (True, False))
+def test_parses_other_parts_of_file_that_contains_errors_in_non_strict_mode(
+    pass_strict_argument_explicitly,
+):
+    kwargs = dict(strict=False) if pass_strict_argument_explicitly else {}
+
+    bundle = fluent.Bundle("fr", [data_dir / "errors.ftl"], **kwargs)
+    translation = bundle.get_translation("valid-message")
+
+    assert translation == "I'm valid."
+
+
+def test_get_junk():
+    bundle = fluent.Bundle("fr", [data_dir / "errors.ftl", ("valid", "valid = Valid")])
+
+    [junk] = bundle.get_junk()
+
+    assert junk.content.startswith("invalid-message")
+    assert junk.filename == str(data_dir / "errors.ftl")
+    assert (junk.line, junk.column, junk.byte_start) == (1, 1, 0)
+    assert junk.errors == ['Expected a token starting with "="']
+
+
+def test_get_errors_by_file():
+    sources = [
+        data_dir / "errors.ftl",
+        ("valid", "valid = Valid"),
+        ("unknown", "hello = { greeting }\nbye = { -brand }"),
+    ]
+    bundle = fluent.Bundle("fr", sources)
+
+    errors = bundle.get_errors_by_file()
+
+    assert list(errors) == [str(data_dir / "errors.ftl"), "unknown"]
+    [junk] = errors[str(data_dir / "errors.ftl")]["parse"]
+    assert junk.content.startswith("invalid-message")
+    assert errors[str(data_dir / "errors.ftl")]["validation"] == []
+    assert errors["unknown"]["parse"] == []
+    assert [error.error_type for error in errors["unknown"]["validation"]] == [
+        "UnknownMessage",
+        "UnknownTerm",
+    ]
+
+
+def test_get_errors_by_file_without_errors():
+    bundle = fluent.Bundle("en", [("valid", "valid = Valid")])
+
+    assert bundle.get_errors_by_file() == {}
+
+
+def test_validation_error_can_be_created():
+    bundle = fluent.Bundle("en", [("unknown", "hello = { greeting }")])
+    [error] = bundle.validation_errors()
+
+    created = fluent.ValidationError(
+        "UnknownMessage",
+        "hello",
+        error.message,
+        filename="unknown",
+        line=1,
+        column=11,
+        byte_start=10,
+        byte_end=18,
+        suggestions=error.suggestions,
+    )
+
+    assert created == error
+    assert hash(created) == hash(error)
+    assert created != fluent.ValidationError("UnknownMessage", "hello", error.message)
+    assert created.severity == "error"
+
+
+def test_validation_error_with_an_invalid_severity():
+    with pytest.raises(ValueError, match="must be 'error', 'warning' or 'info', not 'fatal'"):
+        fluent.ValidationError("UnknownMessage", "hello", "Unknown message", severity="fatal")
+
+
+def test_validation_error_can_be_pickled():
+    bundle = fluent.Bundle("en", [("unknown", "hello = { greeting }")])
+    [error] = bundle.validation_errors()
+
+    assert pickle.loads(pickle.dumps(error)) == error
+
+
+def test_validation_error_to_dict():
+    error = fluent.ValidationError(
+        "UnknownTerm", "bye", "Unknown term: -brand", severity="warning", filename="unknown"
+    )
+
+    assert error.to_dict() == {
+        "filename": "unknown",
+        "line": None,
+        "column": None,
+        "byte_start": None,
+        "byte_end": None,
+        "code": "RF0003",
+        "error_type": "UnknownTerm",
+        "message_id": "bye",
+        "message": "Unknown term: -brand",
+        "severity": "warning",
+        "suggestions": [],
+    }
+
+
+def test_junk_can_be_created_compared_and_pickled():
+    bundle = fluent.Bundle("en", [("broken", "broken = {\n")])
+    [junk] = bundle.get_junk()
+
+    created = fluent.Junk(
+        junk.content,
+        "broken",
+        junk.line,
+        junk.column,
+        junk.byte_start,
+        junk.byte_end,
+        errors=junk.errors,
+    )
+
+    assert created == junk
+    assert {created, junk} == {junk}
+    assert pickle.loads(pickle.dumps(junk)) == junk
+    assert created.to_dict() == {
+        "content": junk.content,
+        "filename": "broken",
+        "line": junk.line,
+        "column": junk.column,
+        "byte_start": junk.byte_start,
+        "byte_end": junk.byte_end,
+        "errors": junk.errors,
+        "code": "RF0017",
+        "severity": "error",
+    }
+
+
+def test_serialize_resource():
+    source = "# About hello\nhello   =   Hello\n    .title =  Title\n    .aria-label = Label\n"
+
+    result = fluent.serialize_resource(source)
+
+    assert result == "# About hello\nhello = Hello\n    .title = Title\n    .aria-label = Label\n"
+
+
+def test_serialize_resource_is_stable():
+    source = (data_dir / "en.ftl").read_text()
+
+    result = fluent.serialize_resource(source)
+
+    assert fluent.serialize_resource(result) == result
+    expected = fluent.Bundle("en", [data_dir / "en.ftl"]).message_ids()
+    assert fluent.Bundle("en", [("en", result)]).message_ids() == expected
+
+
+def test_serialize_resource_with_junk():
+    source = "hello = Hello\nbroken = {\n"
+
+    assert fluent.serialize_resource(source) == "hello = Hello\n"
+    assert "broken = {" in fluent.serialize_resource(source, with_junk=True)
+
+
+PARSE_FTL = """\
+## Greetings
+
+# Shown on the home page.
+hello = Hello, { $name }!
+    .title = { -brand(case: "short") }
+emails = { NUMBER($count) ->
+   *[other] { $count } emails
+}
+"""
+
+
+def span_source(source, node):
+    return source[node["span"]["start"] : node["span"]["end"]]
+
+
+def test_parse():
+    resource = fluent.parse(PARSE_FTL)
+
+    assert resource["type"] == "Resource"
+    group, hello, emails = resource["body"]
+    assert group == {
+        "type": "GroupComment",
+        "content": "Greetings",
+        "span": {"type": "Span", "start": 0, "end": 12},
+    }
+    assert hello["type"] == "Message"
+    assert hello["id"]["name"] == "hello"
+    assert hello["comment"]["content"] == "Shown on the home page."
+    text, placeable, exclamation = hello["value"]["elements"]
+    assert text["value"] == "Hello, "
+    assert placeable["expression"]["type"] == "VariableReference"
+    assert placeable["expression"]["id"]["name"] == "name"
+    assert exclamation["value"] == "!"
+    (title,) = hello["attributes"]
+    term = title["value"]["elements"][0]["expression"]
+    assert term["type"] == "TermReference"
+    assert term["id"]["name"] == "brand"
+    (argument,) = term["arguments"]["named"]
+    assert argument["name"]["name"] == "case"
+    assert argument["value"] == {
+        "type": "StringLiteral",
+        "value": "short",
+        "span": argument["value"]["span"],
+    }
+    select = emails["value"]["elements"][0]["expression"]
+    assert select["type"] == "SelectExpression"
+    assert select["selector"]["id"]["name"] == "NUMBER"
+    (variant,) = select["variants"]
+    assert variant["default"] is True
+    assert variant["key"]["type"] == "Identifier"
+    assert variant["key"]["name"] == "other"
+    json.dumps(resource)
+
+
+def test_parse_spans():
+    resource = fluent.parse(PARSE_FTL)
+    _, hello, emails = resource["body"]
+
+    assert span_source(PARSE_FTL, hello) == (
+        'hello = Hello, { $name }!\n    .title = { -brand(case: "short") }'
+    )
+    assert span_source(PARSE_FTL, hello["comment"]) == "# Shown on the home page."
+    placeable = hello["value"]["elements"][1]
+    assert span_source(PARSE_FTL, placeable) == "{ $name }"
+    assert span_source(PARSE_FTL, placeable["expression"]) == "$name"
+    title = hello["attributes"][0]
+    assert span_source(PARSE_FTL, title) == '.title = { -brand(case: "short") }'
+    term = title["value"]["elements"][0]["expression"]
+    assert span_source(PARSE_FTL, term) == '-brand(case: "short")'
+    assert span_source(PARSE_FTL, term["arguments"]["named"][0]["value"]) == '"short"'
+    select = emails["value"]["elements"][0]["expression"]
+    assert span_source(PARSE_FTL, select["variants"][0]) == "*[other] { $count } emails"
+    assert span_source(PARSE_FTL, emails).endswith("emails\n}")
+
+
+def test_parse_junk():
+    resource = fluent.parse("hello = Hello\nbroken {\n")
+
+    hello, junk = resource["body"]
+    assert hello["type"] == "Message"
+    assert junk["type"] == "Junk"
+    assert junk["content"].startswith("broken {")
+    (annotation,) = junk["annotations"]
+    assert annotation["type"] == "Annotation"
+    assert annotation["message"] == 'Expected a token starting with "="'
+
+
+def test_format_ftl():
+    source = "# About hello\nhello   =   Hello\n    .title =  Title\n"
+
+    assert fluent.format_ftl(source) == fluent.serialize_resource(source)
+
+
+def test_format_ftl_indent():
+    source = "message =\n    Line one\n      indented\n    .title = Title\n"
+
+    result = fluent.format_ftl(source, indent=2)
+
+    assert result == "message =\n  Line one\n    indented\n  .title = Title\n"
+    bundle = fluent.Bundle("en", [("formatted", result)])
+    assert bundle.get_translation("message") == "Line one\n  indented"
+
+
+SELECT_FTL = """
+emails = { $count ->
+    [one] One email
+   *[other] { $count } emails:
+        first line
+          second line
+}
+"""
+
+
+def test_format_ftl_indent_keeps_select_expressions():
+    result = fluent.format_ftl(SELECT_FTL, indent=2)
+
+    assert result != fluent.format_ftl(SELECT_FTL)
+    assert fluent.format_ftl(result) == fluent.format_ftl(SELECT_FTL)
+    original = fluent.Bundle("en", [("original", SELECT_FTL)])
+    formatted = fluent.Bundle("en", [("formatted", result)])
+    for count in (1, 5):
+        expected = original.get_translation("emails", {"count": count}, use_isolating=False)
+        actual = formatted.get_translation("emails", {"count": count}, use_isolating=False)
+        assert actual == expected
+
+
+def test_format_ftl_keeps_junk():
+    source = "hello = Hello\nbroken = {\n   oops\n"
+
+    result = fluent.format_ftl(source, indent=2)
+
+    assert result.startswith("hello = Hello\n")
+    assert "broken = {\n   oops\n" in result
+
+
+def test_format_ftl_sort():
+    source = (
+        "## Group B\n\nzeta = Z\n# About alpha\nalpha = A\n\n"
+        "## Group A\n\nbeta = B\n-alpha-term = Term\n"
+    )
+
+    result = fluent.format_ftl(source, sort=True)
+
+    assert fluent.Bundle("en", [("sorted", result)]).message_ids() == ["alpha", "zeta", "beta"]
+    assert result.index("## Group B") < result.index("alpha") < result.index("zeta")
+    assert result.index("# About alpha\nalpha = A") < result.index("## Group A")
+    assert result.index("-alpha-term") < result.index("beta")
+
+
+def test_format_ftl_path(tmp_path):
+    path = tmp_path / "messages.ftl"
+    path.write_text("hello   =   Hello")
+
+    assert fluent.format_ftl(path) == "hello = Hello\n"
+
+
+def test_format_ftl_missing_file(tmp_path):
+    with pytest.raises(FileNotFoundError) as exc_info:
+        fluent.format_ftl(tmp_path / "missing.ftl")
+
+    assert str(exc_info.value.filename) == str(tmp_path / "missing.ftl")
+
+
+def test_format_ftl_encoding(tmp_path):
+    path = tmp_path / "messages.ftl"
+    path.write_bytes("hello   =   Grüße".encode("latin-1"))
+
+    assert fluent.format_ftl(path, encoding="latin-1") == "hello = Grüße\n"
+
+
+def test_format_ftl_invalid_indent():
+    with pytest.raises(ValueError, match="indent must be at least 1."):
+        fluent.format_ftl("hello = Hello", indent=0)
+
+
+SKELETON_FTL = """
+# The greeting on the home page.
+hello = Hello, { $name }!
+    .title = Welcome
+-brand = Acme
+emails = { $count ->
+    [one] One email
+   *[other] { $count } emails
+}
+broken = {
+"""
+
+
+def test_generate_skeleton_empty():
+    result = fluent.generate_skeleton([("en", SKELETON_FTL)])
+
+    assert result.startswith('# The greeting on the home page.\nhello = { "" }\n')
+    bundle = fluent.Bundle("fr", [("fr", result)])
+    assert bundle.message_ids(include_attributes=True) == ["hello", "hello.title", "emails"]
+    assert bundle.get_translation("hello") == ""
+    assert bundle.get_translation("hello.title") == ""
+    assert bundle.get_junk() == []
+
+
+def test_generate_skeleton_copy():
+    result = fluent.generate_skeleton([("en", SKELETON_FTL)], mode="copy")
+
+    assert result == fluent.serialize_resource(SKELETON_FTL)
+
+
+def test_generate_skeleton_pseudo():
+    result = fluent.generate_skeleton([("en", SKELETON_FTL)], mode="pseudo")
+
+    bundle = fluent.Bundle("fr", [("fr", result)])
+    hello = bundle.get_translation("hello", {"name": "Bob"}, use_isolating=False)
+    assert hello == "Ħḗŀŀǿ, Bob!"
+    assert bundle.get_translation("emails", {"count": 1}) == "Ǿƞḗ ḗḿȧīŀ"
+    assert "# The greeting on the home page." in result
+
+
+def test_generate_skeleton_from_several_files(tmp_path):
+    (tmp_path / "first.ftl").write_text("hello = Hello\nbye = Bye")
+    (tmp_path / "second.ftl").write_text("hello = Hi")
+
+    result = fluent.generate_skeleton(
+        [tmp_path / "first.ftl", tmp_path / "second.ftl"], mode="copy"
+    )
+
+    assert result == "bye = Bye\n\nhello = Hi\n"
+
+
+def test_generate_skeleton_invalid_mode():
+    expected = "mode must be 'copy', 'empty' or 'pseudo', not 'blank'."
+    with pytest.raises(ValueError, match=re.escape(expected)):
+        fluent.generate_skeleton([("en", "hello = Hello")], mode="blank")
+
+
+OLD_FTL = """
+hello = Hello
+bye = Goodbye
+    .title = Leaving
+login = Log in
+    .title = Log in to your account
+    .aria-label = Log in
+-brand = Acme
+"""
+
+NEW_FTL = """
+# A new comment, and different formatting.
+hello   =   Hello
+login = Log in
+    .title = Sign in to your account
+    .placeholder = Email
+-brand = Acme Inc.
+welcome = Welcome
+    .title = Welcome!
+"""
+
+
+def test_diff_resources():
+    diff = fluent.diff_resources(("old.ftl", OLD_FTL), ("new.ftl", NEW_FTL))
+
+    assert [message.message_id for message in diff.added] == ["welcome"]
+    assert [message.message_id for message in diff.removed] == ["bye"]
+    assert [message.message_id for message in diff.changed] == ["login", "-brand"]
+    assert repr(diff) == (
+        "ResourceDiff(added=['welcome'], removed=['bye'], changed=['login', '-brand'])"
+    )
+
+
+def test_diff_resources_changes():
+    diff = fluent.diff_resources(("old.ftl", OLD_FTL), ("new.ftl", NEW_FTL))
+
+    [added] = diff.added
+    assert (added.value_changed, added.attributes) == (True, ["title"])
+    [removed] = diff.removed
+    assert (removed.value_changed, removed.attributes) == (True, ["title"])
+    login, brand = diff.changed
+    assert login.value_changed is False
+    assert login.attributes == ["title", "placeholder", "aria-label"]
+    assert (brand.value_changed, brand.attributes) == (True, [])
+    assert repr(brand) == "MessageDiff(message_id='-brand', value_changed=True, attributes=[])"
+
+
+def test_diff_resources_spans():
+    diff = fluent.diff_resources(("old.ftl", OLD_FTL), ("new.ftl", NEW_FTL))
+
+    [added] = diff.added
+    assert (added.filename, added.line, added.column) == ("new.ftl", 8, 1)
+    assert NEW_FTL[added.byte_start : added.byte_end] == "welcome"
+    [removed] = diff.removed
+    assert (removed.filename, removed.line, removed.column) == ("old.ftl", 3, 1)
+    brand = diff.changed[1]
+    assert NEW_FTL[brand.byte_start : brand.byte_end] == "brand"
+
+
+def test_diff_resources_files(tmp_path):
+    (tmp_path / "old.ftl").write_text("hello = Hello")
+    (tmp_path / "new.ftl").write_text("hello = Hello\n")
+
+    diff = fluent.diff_resources(tmp_path / "old.ftl", tmp_path / "new.ftl")
+
+    assert (diff.added, diff.removed, diff.changed) == ([], [], [])
+
+
+def test_diff_resources_missing_file(tmp_path):
+    with pytest.raises(FileNotFoundError):
+        fluent.diff_resources(tmp_path / "old.ftl", tmp_path / "new.ftl")
+
+
+BASE_FTL = """\
+# The greeting.
+hello = Hello
+bye = Bye
+title = Title
+"""
+
+
+def merged_messages(source):
+    bundle = fluent.Bundle("en", [("merged", source)])
+    return {message_id: bundle.get_translation(message_id) for message_id in bundle}
+
+
+def test_merge_resources():
+    ours = BASE_FTL.replace("Bye", "Goodbye")
+    theirs = BASE_FTL.replace("Title", "Heading") + "welcome = Welcome\n"
+
+    result = fluent.merge_resources(("base", BASE_FTL), ("ours", ours), ("theirs", theirs))
+
+    assert result.conflicts == []
+    assert merged_messages(result.source) == {
+        "hello": "Hello",
+        "bye": "Goodbye",
+        "title": "Heading",
+        "welcome": "Welcome",
+    }
+    assert "# The greeting." in result.source
+    assert repr(result) == "MergeResult(conflicts=[])"
+
+
+def test_merge_resources_inserts_additions_in_position():
+    theirs = BASE_FTL.replace("bye = Bye\n", "bye = Bye\nwave = Wave\n")
+
+    result = fluent.merge_resources(("base", BASE_FTL), ("ours", BASE_FTL), ("theirs", theirs))
+
+    assert list(merged_messages(result.source)) == ["hello", "bye", "wave", "title"]
+
+
+def test_merge_resources_deletions():
+    ours = BASE_FTL.replace("bye = Bye\n", "")
+    theirs = BASE_FTL.replace("# The greeting.\n", "").replace("title = Title\n", "")
+
+    result = fluent.merge_resources(("base", BASE_FTL), ("ours", ours), ("theirs", theirs))
+
+    assert result.conflicts == []
+    assert merged_messages(result.source) == {"hello": "Hello"}
+    assert "#" not in result.source
+
+
+def test_merge_resources_keeps_comments_added_in_either():
+    ours = "## Greetings\n\n" + BASE_FTL
+    theirs = BASE_FTL + "\n# Shown on every page.\nfooter = Footer\n"
+
+    result = fluent.merge_resources(("base", BASE_FTL), ("ours", ours), ("theirs", theirs))
+
+    assert "## Greetings" in result.source
+    assert "# Shown on every page." in result.source
+    assert "footer" in merged_messages(result.source)
+
+
+def test_merge_resources_conflicts():
+    ours = BASE_FTL.replace("Hello", "Hi").replace("bye = Bye\n", "") + "new = Ours\n"
+    theirs = BASE_FTL.replace("Hello", "Hey").replace("Bye", "Ciao") + "new = Theirs\n"
+
+    result = fluent.merge_resources(("base", BASE_FTL), ("ours", ours), ("theirs", theirs))
+
+    assert [(conflict.message_id, conflict.kind) for conflict in result.conflicts] == [
+        ("hello", "changed_in_both"),
+        ("new", "added_in_both"),
+        ("bye", "deleted_in_ours"),
+    ]
+    assert merged_messages(result.source) == {"hello": "Hi", "title": "Title", "new": "Ours"}
+    assert repr(result) == "MergeResult(conflicts=['hello', 'new', 'bye'])"
+
+
+def test_merge_resources_conflict_versions():
+    ours = BASE_FTL.replace("Bye", "Goodbye")
+    theirs = BASE_FTL.replace("bye = Bye\n", "")
+
+    result = fluent.merge_resources(("base", BASE_FTL), ("ours", ours), ("theirs", theirs))
+
+    [conflict] = result.conflicts
+    assert conflict.kind == "deleted_in_theirs"
+    assert (conflict.base, conflict.ours, conflict.theirs) == (
+        "bye = Bye\n",
+        "bye = Goodbye\n",
+        None,
+    )
+    assert repr(conflict) == "MergeConflict(message_id='bye', kind='deleted_in_theirs')"
+    assert merged_messages(result.source)["bye"] == "Goodbye"
+
+
+def test_merge_resources_files(tmp_path):
+    for name in ("base", "ours", "theirs"):
+        (tmp_path / f"{name}.ftl").write_text(BASE_FTL)
+
+    result = fluent.merge_resources(
+        tmp_path / "base.ftl", tmp_path / "ours.ftl", tmp_path / "theirs.ftl"
+    )
+
+    assert merged_messages(result.source) == merged_messages(BASE_FTL)
+
+
+def test_merge_resources_missing_file(tmp_path):
+    with pytest.raises(FileNotFoundError):
+        fluent.merge_resources(
+            tmp_path / "base.ftl", tmp_path / "ours.ftl", tmp_path / "theirs.ftl"
+        )
+
+
+def test_ftl_to_po():
+    po = fluent.ftl_to_po(("en.ftl", SKELETON_FTL))
+
+    assert po.startswith('msgid ""\nmsgstr "Content-Type: text/plain; charset=UTF-8\\n"\n')
+    assert (
+        "#. The greeting on the home page.\n"
+        "#: en.ftl:3\n"
+        'msgctxt "hello"\n'
+        'msgid "Hello, { $name }!"\n'
+        'msgstr ""\n'
+    ) in po
+    assert 'msgctxt "hello.title"\nmsgid "Welcome"\nmsgstr ""\n' in po
+    assert 'msgctxt "-brand"\nmsgid "Acme"\nmsgstr ""\n' in po
+    assert (
+        'msgctxt "emails"\n'
+        'msgid "One email"\n'
+        'msgid_plural "{ $count } emails"\n'
+        'msgstr[0] ""\n'
+        'msgstr[1] ""\n'
+    ) in po
+    assert "broken" not in po
+
+
+def test_ftl_to_po_with_reference():
+    translation = """\
+hello = Привет, { $name }!
+emails = { $count ->
+    [one] { $count } письмо
+    [few] { $count } письма
+   *[many] { $count } писем
+}
+"""
+    po = fluent.ftl_to_po(
+        ("ru.ftl", translation), reference_path=("en.ftl", SKELETON_FTL), locale="ru"
+    )
+
+    assert '"Language: ru\\n"' in po
+    assert 'msgid "Hello, { $name }!"\nmsgstr "Привет, { $name }!"\n' in po
+    assert 'msgid "Welcome"\nmsgstr ""\n' in po
+    assert (
+        'msgstr[0] "{ $count } письмо"\n'
+        'msgstr[1] "{ $count } письма"\n'
+        'msgstr[2] "{ $count } писем"\n'
+        'msgstr[3] "{ $count } писем"\n'
+    ) in po
+
+
+def test_ftl_to_po_multiline():
+    po = fluent.ftl_to_po(("en.ftl", 'quote = He said "Hi"\n    and left.\n'))
+
+    assert 'msgid ""\n"He said \\"Hi\\"\\n"\n"and left."\nmsgstr ""\n' in po
+
+
+def test_po_to_ftl_round_trip():
+    po = fluent.ftl_to_po(("en.ftl", SKELETON_FTL))
+
+    ftl = fluent.po_to_ftl(("en.po", po), include_untranslated=True)
+
+    assert ftl == fluent.serialize_resource(SKELETON_FTL)
+
+
+def test_po_to_ftl_translations():
+    translation = "hello = Hallo, { $name }!\nemails = { $count } E-Mails\n"
+    po = fluent.ftl_to_po(("de.ftl", translation), reference_path=("en.ftl", SKELETON_FTL))
+
+    ftl = fluent.po_to_ftl(("de.po", po))
+
+    bundle = fluent.Bundle("de", [("de", ftl)])
+    assert bundle.message_ids() == ["hello", "emails"]
+    assert bundle.get_comment("hello") == "The greeting on the home page."
+    assert bundle.get_translation("hello", {"name": "Bob"}, use_isolating=False) == "Hallo, Bob!"
+    assert bundle.get_translation("emails", {"count": 1}, use_isolating=False) == "1 E-Mails"
+
+
+GETTEXT_PO = r"""
+msgid ""
+msgstr ""
+"Language: ru\n"
+
+# Shown in the header.
+#, python-format
+msgid "Hello, %(name)s!"
+msgstr "Привет, %(name)s!"
+
+#, python-format
+msgid "%d file"
+msgid_plural "%d files"
+msgstr[0] "%d файл"
+msgstr[1] "%d файла"
+msgstr[2] "%d файлов"
+
+msgid "Untranslated"
+msgstr ""
+
+#, fuzzy
+msgid "Fuzzy"
+msgstr "Нечёткий"
+
+#, python-brace-format
+msgid "Welcome, {user}!"
+msgstr "Добро пожаловать, {user}! {{}}"
+
+msgctxt "month"
+msgid "May"
+msgstr "Май"
+
+msgid "Literal {braces"
+msgstr "Литерал {braces"
+
+#~ msgid "Obsolete"
+#~ msgstr "Устаревший"
+"""
+
+
+def test_po_to_ftl_from_gettext():
+    ftl = fluent.po_to_ftl(("ru.po", GETTEXT_PO))
+
+    bundle = fluent.Bundle("ru", [("ru", ftl)])
+
+    def translate(identifier, variables):
+        return bundle.get_translation(identifier, variables, use_isolating=False)
+
+    assert bundle.message_ids() == ["hello", "file", "welcome", "month", "literal-braces"]
+    assert bundle.get_comment("hello") == "Shown in the header."
+    assert translate("hello", {"name": "Bob"}) == "Привет, Bob!"
+    assert translate("file", {"count": 1}) == "1 файл"
+    assert translate("file", {"count": 3}) == "3 файла"
+    assert translate("file", {"count": 5}) == "5 файлов"
+    welcome = translate("welcome", {"user": "Bob"})
+    assert welcome == "Добро пожаловать, Bob! {}"
+    assert bundle.get_translation("month") == "Май"
+    assert bundle.get_translation("literal-braces") == "Литерал {braces"
+    assert bundle.get_junk() == []
+
+
+def test_po_to_ftl_include_untranslated():
+    ftl = fluent.po_to_ftl(("ru.po", GETTEXT_PO), include_untranslated=True)
+
+    bundle = fluent.Bundle("ru", [("ru", ftl)])
+    assert bundle.get_translation("untranslated") == "Untranslated"
+    assert bundle.get_translation("fuzzy") == "Fuzzy"
+
+
+def test_po_to_ftl_locale():
+    po = 'msgctxt "files"\nmsgid "{ $n } file"\nmsgid_plural "{ $n } files"\n'
+    po += 'msgstr[0] "{ $n } fichier"\nmsgstr[1] "{ $n } fichiers"\n'
+
+    ftl = fluent.po_to_ftl(("fr.po", po), locale="fr")
+
+    bundle = fluent.Bundle("fr", [("fr", ftl)])
+    assert bundle.get_translation("files", {"n": 1}, use_isolating=False) == "1 fichier"
+    assert bundle.get_translation("files", {"n": 2}, use_isolating=False) == "2 fichiers"
+
+
+def test_po_to_ftl_invalid():
+    with pytest.raises(ValueError, match="fr.po, line 2: expected a quoted string"):
+        fluent.po_to_ftl(("fr.po", 'msgid "hello"\nmsgstr hello\n'))
+    with pytest.raises(ValueError, match="Invalid language: '!!'"):
+        fluent.po_to_ftl(("fr.po", ""), locale="!!")
+    with pytest.raises(ValueError, match="Invalid language: '!!'"):
+        fluent.ftl_to_po(("fr.ftl", ""), locale="!!")
+
+
+def test_po_files(tmp_path):
+    (tmp_path / "en.ftl").write_text("hello = Hello\n")
+
+    (tmp_path / "en.po").write_text(fluent.ftl_to_po(tmp_path / "en.ftl"))
+
+    ftl = fluent.po_to_ftl(tmp_path / "en.po", include_untranslated=True)
+    assert ftl == "hello = Hello\n"
+
+
+def test_export_xliff(tmp_path):
+    reference = fluent.Bundle("en", [("en", SKELETON_FTL)])
+    target = fluent.Bundle("de", [("de", "hello = Hallo, { $name }!\n-brand = Acme & Co\n")])
+
+    fluent.export_xliff(reference, target, tmp_path / "de.xlf")
+
+    xliff = (tmp_path / "de.xlf").read_text()
+    assert 'version="2.0" srcLang="en" trgLang="de"' in xliff
+    assert '<note category="comment">The greeting on the home page.</note>' in xliff
+    assert '<data id="d1">{ $name }</data>' in xliff
+    assert '<source>Hello, <ph id="1" dataRef="d1"/>!</source>' in xliff
+    assert '<target>Hallo, <ph id="1" dataRef="d1"/>!</target>' in xliff
+    assert '<target>Acme &amp; Co</target>' in xliff
+    assert '<segment state="initial">\n        <source>Welcome</source>\n      </segment>' in xliff
+    namespace = "{urn:oasis:names:tc:xliff:document:2.0}"
+    root = ElementTree.parse(tmp_path / "de.xlf").getroot()
+    unit_ids = [unit.get("id") for unit in root.iter(f"{namespace}unit")]
+    assert unit_ids == ["hello", "hello.title", "-brand", "emails"]
+
+
+def test_xliff_round_trip(tmp_path):
+    bundle = fluent.Bundle("en", [("en", SKELETON_FTL)])
+
+    fluent.export_xliff(bundle, bundle, tmp_path / "en.xlf")
+
+    assert fluent.import_xliff(tmp_path / "en.xlf") == fluent.serialize_resource(SKELETON_FTL)
+
+
+def test_import_xliff(tmp_path):
+    (tmp_path / "fr.xlf").write_text(
+        """\
+<?xml version="1.0" encoding="UTF-8"?>
+<xliff xmlns="urn:oasis:names:tc:xliff:document:2.0" version="2.0" srcLang="en" trgLang="fr">
+  <file id="f1">
+    <unit id="greeting">
+      <notes>
+        <note>Shown on the home page.</note>
+        <note>Keep it short.</note>
+      </notes>
+      <originalData>
+        <data id="d1">{ $first }</data>
+        <data id="d2">{ $last }</data>
+      </originalData>
+      <segment>
+        <source>Hello, <ph id="1" dataRef="d1"/> <ph id="2" dataRef="d2"/> {x}!</source>
+        <target><ph id="2" dataRef="d2"/> <mrk id="m">{x}</mrk> <ph id="1" dataRef="d1"/></target>
+      </segment>
+    </unit>
+    <unit id="bye">
+      <segment>
+        <source>Bye</source>
+        <target>Au revoir</target>
+      </segment>
+      <ignorable>
+        <source> </source>
+      </ignorable>
+      <segment>
+        <source>for now</source>
+        <target>pour l'instant</target>
+      </segment>
+    </unit>
+    <unit id="untranslated">
+      <segment>
+        <source>Untranslated</source>
+      </segment>
+    </unit>
+  </file>
+</xliff>
+"""
+    )
+
+    ftl = fluent.import_xliff(tmp_path / "fr.xlf")
+
+    bundle = fluent.Bundle("fr", [("fr", ftl)])
+    assert bundle.message_ids() == ["greeting", "bye"]
+    assert bundle.get_comment("greeting") == "Shown on the home page.\nKeep it short."
+    variables = {"first": "Ada", "last": "Lovelace"}
+    greeting = bundle.get_translation("greeting", variables, use_isolating=False)
+    assert greeting == "Lovelace {x} Ada"
+    assert bundle.get_translation("bye") == "Au revoir pour l'instant"
+    assert bundle.get_junk() == []
+
+
+def test_import_xliff_missing_file(tmp_path):
+    with pytest.raises(FileNotFoundError):
+        fluent.import_xliff(tmp_path / "fr.xlf")
+
+
+def test_to_ftl():
+    bundle = fluent.Bundle(
+        "en",
+        [("first", "# Greeting\nhello = Hello\nbye = Bye"), ("second", "hello = Hi")],
+    )
+
+    assert bundle.to_ftl() == "bye = Bye\n\nhello = Hi\n"
+
+
+def test_to_ftl_includes_overrides_and_removals():
+    bundle = fluent.Bundle("en", [("en", "hello = Hello\nbye = Bye\nstay = Stay")])
+
+    bundle.override_message("hello", "hello = Hi")
+    bundle.remove_message("bye")
+
+    assert bundle.to_ftl() == "stay = Stay\n\nhello = Hi\n"
+
+
+def test_to_ftl_language():
+    bundle = fluent.Bundle(
+        ["en", "fr"], {"en": [("en", "hello = Hello")], "fr": [("fr", "hello = Bonjour")]}
+    )
+
+    assert bundle.to_ftl() == "hello = Hello\n"
+    assert bundle.to_ftl("fr") == "hello = Bonjour\n"
+
+    with pytest.raises(ValueError, match="Invalid language"):
+        bundle.to_ftl("!!")
+
+
+def test_to_ftl_with_junk():
+    bundle = fluent.Bundle("en", [("en", "hello = Hello\nbroken = {\n")])
+
+    assert bundle.to_ftl() == "hello = Hello\n"
+    assert "broken = {" in bundle.to_ftl(with_junk=True)
+
+
+JSON_FTL = """
+-brand = Acme
+hello = Hello, { $name }!
+welcome = Welcome to { -brand }
+    .title = { NUMBER($count) } new
+emails =
+    { $count ->
+        [one] One email
+       *[other] { $count } emails
+    }
+"""
+
+
+def test_to_json():
+    bundle = fluent.Bundle("en", [("en", JSON_FTL)])
+
+    assert json.loads(bundle.to_json()) == {
+        "hello": "Hello, {{name}}!",
+        "welcome": "Welcome to Acme",
+        "emails": "{{count}} emails",
+    }
+    assert list(json.loads(bundle.to_json(include_attributes=True))) == [
+        "hello",
+        "welcome",
+        "welcome.title",
+        "emails",
+    ]
+    assert json.loads(bundle.to_json(include_attributes=True))["welcome.title"] == "{{count}} new"
+
+
+def test_to_json_raw():
+    bundle = fluent.Bundle("en", [("en", JSON_FTL)])
+
+    messages = json.loads(bundle.to_json("raw"))
+
+    assert messages["hello"] == "Hello, { $name }!"
+    assert messages["welcome"] == "Welcome to { -brand }"
+    assert "*[other] { $count } emails" in messages["emails"]
+
+
+def test_to_json_fallback():
+    bundle = fluent.Bundle(
+        ["fr", "en"],
+        {"fr": [("fr", "hello = Bonjour")], "en": [("en", "hello = Hello\nbye = Bye")]},
+    )
+
+    assert json.loads(bundle.to_json()) == {"hello": "Bonjour", "bye": "Bye"}
+
+
+def test_to_json_invalid_mode():
+    bundle = fluent.Bundle("en", [("en", JSON_FTL)])
+
+    with pytest.raises(ValueError, match="variables_mode must be 'placeholder' or 'raw'"):
+        bundle.to_json("icu")
+
+
+@pytest.mark.parametrize(
+    "pseudo, expected",
+    (
+        ("accented", "Ħḗŀŀǿ, Bob!"),
+        ("expanded", "Ħḗḗŀŀǿǿ, Bob!"),
+        ("bidi", "\u202eHǝʅʅo\u202c, Bob!"),
+    ),
+)
+def test_pseudo_localization(pseudo, expected):
+    bundle = fluent.Bundle("en", [("pseudo", "hello = Hello, { $name }!")], pseudo=pseudo)
+
+    result = bundle.get_translation("hello", variables={"name": "Bob"}, use_isolating=False)
+
+    assert result == expected
+
+
+def test_pseudo_localization_of_whole_messages_and_terms():
+    source = "-brand = Acme\nabout = About { -brand }"
+    bundle = fluent.Bundle("en", [("pseudo", source)], pseudo="accented")
+
+    assert bundle.get_translation("about", use_isolating=False) == "Ȧƀǿŭŧ Ȧƈḿḗ"
+    assert bundle.get_term("-brand").value == "Ȧƈḿḗ"
+
+
+def test_pseudo_localization_is_kept_by_copies():
+    bundle = fluent.Bundle("en", [("pseudo", "hello = Hello")], pseudo="accented")
+    bundle.override_message("hello", "hello = Hi")
+
+    assert bundle.get_translation("hello") == "Ħī"
+    assert bundle.copy().get_translation("hello") == "Ħī"
+
+
+def test_no_pseudo_localization_by_default():
+    bundle = fluent.Bundle("en", [("pseudo", "hello = Hello")])
+
+    assert bundle.get_translation("hello") == "Hello"
+
+
+def test_invalid_pseudo_localization():
+    expected = "pseudo must be 'accented', 'bidi' or 'expanded', not 'fake'."
+    with pytest.raises(ValueError, match=expected):
+        fluent.Bundle("en", [("pseudo", "hello = Hello")], pseudo="fake")
+
+
+
+def test_set_transform():
+    bundle = fluent.Bundle("en", [("transform", "hello = Hello, { $name }!")])
+    bundle.set_transform(str.upper)
+
+    result = bundle.get_translation("hello", variables={"name": "Bob"}, use_isolating=False)
+
+    assert result == "HELLO, BOB!"
+
+
+def test_set_transform_after_pseudo_localization():
+    bundle = fluent.Bundle("en", [("transform", "hello = Hello")], pseudo="accented")
+    bundle.set_transform(lambda text: f"[{text}]")
+
+    assert bundle.get_translation("hello") == "[Ħḗŀŀǿ]"
+
+
+def test_set_transform_applies_to_batches_and_copies():
+    bundle = fluent.Bundle("en", [("transform", "hello = Hello\nbye = Bye")])
+    bundle.set_transform(str.upper)
+
+    assert bundle.get_translations([("hello", None), ("bye", None)]) == ["HELLO", "BYE"]
+    assert bundle.copy().get_translation("bye") == "BYE"
+
+
+def test_set_transform_not_applied_to_default():
+    bundle = fluent.Bundle("en", [("transform", "hello = Hello")])
+    bundle.set_transform(str.upper)
+
+    assert bundle.get_translation("missing", default="Fallback") == "Fallback"
+
+
+def test_remove_transform():
+    bundle = fluent.Bundle("en", [("transform", "hello = Hello")])
+    bundle.set_transform(str.upper)
+    bundle.set_transform(None)
+
+    assert bundle.get_translation("hello") == "Hello"
+
+
+def test_set_transform_not_callable():
+    bundle = fluent.Bundle("en", [("transform", "hello = Hello")])
+
+    with pytest.raises(TypeError, match="Transform is not callable, got 1."):
+        bundle.set_transform(1)
+
+
+def test_set_transform_must_return_str():
+    bundle = fluent.Bundle("en", [("transform", "hello = Hello")])
+    bundle.set_transform(len)
+
+    with pytest.raises(TypeError, match="The transform must return a str, got 5."):
+        bundle.get_translation("hello")
+
+
+def test_set_transform_exception_is_raised():
+    def transform(text):
+        raise RuntimeError("Transform failed")
+
+    bundle = fluent.Bundle("en", [("transform", "hello = Hello")])
+    bundle.set_transform(transform)
+
+    with pytest.raises(RuntimeError, match="Transform failed"):
+        bundle.get_translation("hello")
+
+
+
+def test_strip_isolation():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"], strip_isolation=True)
+
+    result = bundle.get_translation("hello-user", variables={"user": "Bob"})
+
+    assert result == "Hello, Bob"
+
+
+def test_strip_isolation_per_call():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+    variables = {"user": "Bob"}
+
+    assert bundle.get_translation("hello-user", variables, strip_isolation=True) == "Hello, Bob"
+    assert bundle.get_translation("hello-user", variables) == f"Hello, {BIDI_OPEN}Bob{BIDI_CLOSE}"
+
+
+def test_strip_isolation_overridden_per_call():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"], strip_isolation=True)
+
+    result = bundle.get_translation("hello-user", {"user": "Bob"}, strip_isolation=False)
+
+    assert result == f"Hello, {BIDI_OPEN}Bob{BIDI_CLOSE}"
+
+
+def test_strip_isolation_in_batches_and_compiled_messages():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"], strip_isolation=True)
+    hello_user = bundle.compile("hello-user")
+
+    result = bundle.get_translations([("hello-user", {"user": "Bob"})])
+
+    assert result == ["Hello, Bob"]
+    assert hello_user({"user": "Bob"}) == "Hello, Bob"
+    isolated = hello_user({"user": "Bob"}, strip_isolation=False)
+    assert isolated == f"Hello, {BIDI_OPEN}Bob{BIDI_CLOSE}"
+
+
+STATS_FTL = """
+greeting = Hello, { $name }
+    .title = Greeting
+farewell = Bye
+unused = Unused
+"""
+
+
+def test_stats_count_formatted_messages():
+    bundle = fluent.Bundle("en", [("stats", STATS_FTL)])
+    bundle.enable_stats()
+
+    bundle.get_translation("greeting", variables={"name": "Bob"})
+    bundle.get_translation("greeting")
+    bundle.get_translation("greeting.title")
+    bundle.get_translations([("farewell", None), ("farewell", None)])
+    bundle.compile("farewell")()
+
+    stats = bundle.stats()
+
+    assert list(stats) == ["greeting", "farewell", "unused", "greeting.title"]
+    assert (stats["greeting"].formatted, stats["greeting"].errors) == (2, 1)
+    assert (stats["farewell"].formatted, stats["farewell"].errors) == (3, 0)
+    assert (stats["unused"].formatted, stats["unused"].errors) == (0, 0)
+    assert stats["greeting.title"].formatted == 1
+    assert repr(stats["unused"]) == "MessageStats(formatted=0, errors=0)"
+
+
+def test_stats_do_not_count_defaults_for_missing_messages():
+    bundle = fluent.Bundle("en", [("stats", STATS_FTL)])
+    bundle.enable_stats()
+
+    bundle.get_translation("missing", default="Missing")
+
+    assert "missing" not in bundle.stats()
+
+
+def test_stats_reset():
+    bundle = fluent.Bundle("en", [("stats", STATS_FTL)])
+    bundle.enable_stats()
+    bundle.get_translation("farewell")
+
+    assert bundle.stats(reset=True)["farewell"].formatted == 1
+    assert bundle.stats()["farewell"].formatted == 0
+
+
+def test_enable_stats_again_keeps_counts():
+    bundle = fluent.Bundle("en", [("stats", STATS_FTL)])
+    bundle.enable_stats()
+    bundle.get_translation("farewell")
+    bundle.enable_stats()
+
+    assert bundle.stats()["farewell"].formatted == 1
+
+
+def test_stats_must_be_enabled():
+    bundle = fluent.Bundle("en", [("stats", STATS_FTL)])
+
+    with pytest.raises(ValueError, match="Stats aren't enabled"):
+        bundle.stats()
+
+
+def test_copies_do_not_share_stats():
+    bundle = fluent.Bundle("en", [("stats", STATS_FTL)])
+    bundle.enable_stats()
+
+    copy = bundle.copy()
+
+    with pytest.raises(ValueError, match="Stats aren't enabled"):
+        copy.stats()
+
+
+def test_raises_parser_error_on_file_that_contains_errors_in_strict_mode():
+    filename = data_dir / "errors.ftl"
+
+    with pytest.raises(fluent.ParserError) as exc_info:
+        fluent.Bundle("fr", [filename], strict=True)
+
+    [error] = exc_info.value.exceptions
+    message = str(error)
+
+    # Recombine first line if it was too long
+    lines = message.split("\n")
+    if lines[1].endswith(".ftl"):
+        lines = [(lines[0] + lines[1]).replace("  │ ", ""), *lines[2:]]
+    message = "\n".join(lines)
+    # End recombination
+
+    expected = f"""\
+  × Error when parsing {filename}
+   ╭─[1:16]
+ 1 │ invalid-message
+   ·                ┬
+   ·                ╰── Expected a token starting with "="
+ 2 │ 
+ 3 │ valid-message = I'm valid.
+   ╰────
+"""
+    assert message == expected
+
+
+def test_parser_error_str():
+    assert str(fluent.ParserError) == "<class 'rustfluent.ParserError'>"
+
+
+# Attribute access tests
+
+
+def test_basic_attribute_access():
+    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
+    assert bundle.get_translation("welcome-message.title") == "Welcome to our site"
+
+
+def test_regular_message_still_works_with_attributes():
+    """Test that accessing the main message value still works when it has attributes."""
+    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
+    assert bundle.get_translation("welcome-message") == "Welcome!"
+
+
+def test_multiple_attributes_on_same_message():
+    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
+    assert bundle.get_translation("login-input.placeholder") == "email@example.com"
+    assert bundle.get_translation("login-input.aria-label") == "Login input value"
+    assert bundle.get_translation("login-input.title") == "Type your login email"
+
+
+def test_attribute_with_variables():
+    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
+    result = bundle.get_translation("greeting.formal", variables={"name": "Alice"})
+    assert result == f"Hello, {BIDI_OPEN}Alice{BIDI_CLOSE}"
+
+
+def test_attribute_with_variables_use_isolating_off():
+    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
+    result = bundle.get_translation(
+        "greeting.informal",
+        variables={"name": "Bob"},
+        use_isolating=False,
+    )
+    assert result == "Hi Bob!"
+
+
+def test_attribute_on_message_without_main_value():
+    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
+    assert bundle.get_translation("form-button.submit") == "Submit Form"
+    assert bundle.get_translation("form-button.cancel") == "Cancel"
+    assert bundle.get_translation("form-button.reset") == "Reset Form"
+
+
+def test_message_without_value_raises_error():
+    """Test that accessing a message without a value (only attributes) raises an error."""
+    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
+    with pytest.raises(ValueError, match="form-button - Message has no value"):
+        bundle.get_translation("form-button")
+
+
+def test_missing_message_with_attribute_syntax_raises_error():
+    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
+    with pytest.raises(ValueError, match="nonexistent not found"):
+        bundle.get_translation("nonexistent.title")
+
+
+def test_missing_message_suggests_similar_ids():
+    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
+
+    with pytest.raises(ValueError) as exc_info:
+        bundle.get_translation("welcome-mesage")
+
+    assert str(exc_info.value) == "welcome-mesage not found. Did you mean 'welcome-message'?"
+    assert exc_info.value.suggestions == ["welcome-message"]
+
+
+def test_missing_message_without_similar_ids():
+    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
+
+    with pytest.raises(ValueError) as exc_info:
+        bundle.get_message("nonexistent")
+
+    assert str(exc_info.value) == "nonexistent not found"
+    assert exc_info.value.suggestions == []
+
+
+def test_missing_message_suggestions_with_attribute_syntax():
+    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
+
+    with pytest.raises(ValueError, match="Did you mean 'greeting'") as exc_info:
+        bundle.get_translation("greting.title")
+
+    assert exc_info.value.suggestions == ["greeting"]
+
+
+def test_missing_message_with_default_does_not_raise():
+    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
+
+    assert bundle.get_translation("greting", default="fallback") == "fallback"
+
+
+def test_missing_attribute_raises_error():
+    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
+    with pytest.raises(
+        ValueError,
+        match="welcome-message.nonexistent - Attribute 'nonexistent' not found on message 'welcome-message'",
+    ):
+        bundle.get_translation("welcome-message.nonexistent")
+
+
+@pytest.mark.parametrize(
+    "identifier,expected",
+    (
+        ("welcome-message", "Welcome!"),
+        ("welcome-message.title", "Welcome to our site"),
+        ("welcome-message.aria-label", "Welcome greeting"),
+        ("login-input", "Email"),
+        ("login-input.placeholder", "email@example.com"),
+    ),
+)
+def test_attribute_and_message_access_parameterized(identifier, expected):
+    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
+    assert bundle.get_translation(identifier) == expected
+
+
+# Message group tests
+
+
+def test_groups():
+    bundle = fluent.Bundle("en", [data_dir / "groups.ftl"])
+
+    groups = bundle.groups()
+
+    assert [group.name for group in groups] == ["Checkout", "Emails"]
+    assert groups[0].comment == "Checkout\nOwned by the payments team."
+    assert groups[0].message_ids == ["checkout-title", "checkout-pay"]
+    assert groups[0].filename == str(data_dir / "groups.ftl")
+    assert groups[1].comment == "Emails"
+    assert groups[1].message_ids == ["email-subject", "email-footer"]
+
+
+def test_groups_are_empty_without_group_comments():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    assert bundle.groups() == []
+
+
+# Compiled message tests
+
+
+def test_compiled_message():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    hello_user = bundle.compile("hello-user")
+
+    assert hello_user.identifier == "hello-user"
+    assert hello_user.variables == ["user"]
+    assert hello_user({"user": "Bob"}) == f"Hello, {BIDI_OPEN}Bob{BIDI_CLOSE}"
+    assert hello_user({"user": "Bob"}, use_isolating=False) == "Hello, Bob"
+
+
+def test_compiled_attribute():
+    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
+
+    formal = bundle.compile("greeting.formal")
+
+    assert formal.variables == ["name"]
+    assert formal(variables={"name": "Alice"}, use_isolating=False) == "Hello, Alice"
+
+
+def test_compiled_message_uses_overriding_resource():
+    bundle = fluent.Bundle("en", [data_dir / "fr.ftl", data_dir / "en_hello.ftl"])
+
+    assert bundle.compile("hello-world")() == "Hello World"
+
+
+def test_compiled_message_variables_include_selectors():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    with_selector = bundle.compile("with-selector")
+
+    assert with_selector.variables == ["number"]
+    assert with_selector({"number": 1}) == "One"
+
+
+def test_compiled_message_strict_types():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    with pytest.raises(TypeError, match="Variable 'user' has an unsupported value"):
+        bundle.compile("hello-user")({"user": object()}, strict_types=True)
+
+
+@pytest.mark.parametrize(
+    "identifier, error",
+    (
+        ("missing", "missing not found"),
+        ("form-button", "form-button - Message has no value."),
+        (
+            "welcome-message.nonexistent",
+            "welcome-message.nonexistent - Attribute 'nonexistent' not found on message 'welcome-message'.",
+        ),
+    ),
+)
+def test_compile_errors(identifier, error):
+    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
+
+    with pytest.raises(ValueError) as exc_info:
+        bundle.compile(identifier)
+
+    assert str(exc_info.value) == error
+
+
+# Required variable tests
+
+REFERENCES_FTL = """
+-brand = { $case ->
+   *[nominative] Firefox
+    [genitive] Firefox's
+}
+greeting = Hello, { $user }!
+welcome = { greeting } You have { $count } messages.
+    .title = { welcome } ({ $folder })
+branded = { -brand(case: "genitive") } app for { $platform } ({ $case })
+loop-a = { $a } { loop-b }
+loop-b = { $b } { loop-a }
+missing-reference = { $value } { missing }
+"""
+
+
+@pytest.mark.parametrize(
+    "identifier, expected",
+    (
+        ("greeting", ["user"]),
+        ("welcome", ["user", "count"]),
+        ("welcome.title", ["user", "count", "folder"]),
+        ("branded", ["platform", "case"]),
+        ("loop-a", ["a", "b"]),
+        ("missing-reference", ["value"]),
+    ),
+)
+def test_get_required_variables(identifier, expected):
+    bundle = fluent.Bundle("en", [("references", REFERENCES_FTL)])
+
+    assert bundle.get_required_variables(identifier) == expected
+
+
+def test_compiled_message_variables_include_referenced_messages():
+    bundle = fluent.Bundle("en", [("references", REFERENCES_FTL)])
+
+    assert bundle.compile("welcome.title").variables == ["user", "count", "folder"]
+
+
+def test_get_required_variables_missing_message():
+    bundle = fluent.Bundle("en", [("references", REFERENCES_FTL)])
+
+    with pytest.raises(ValueError, match="missing not found"):
+        bundle.get_required_variables("missing")
+
+
+def test_get_required_variables_all():
+    bundle = fluent.Bundle("en", [("references", REFERENCES_FTL)])
+
+    assert bundle.get_required_variables_all() == {
+        "greeting": ["user"],
+        "welcome": ["user", "count"],
+        "welcome.title": ["user", "count", "folder"],
+        "branded": ["platform", "case"],
+        "loop-a": ["a", "b"],
+        "loop-b": ["b", "a"],
+        "missing-reference": ["value"],
+    }
+
+
+def test_messages_using_variable():
+    bundle = fluent.Bundle("en", [("references", REFERENCES_FTL)])
+
+    assert bundle.messages_using_variable("user") == ["greeting", "welcome"]
+    assert bundle.messages_using_variable("$folder") == ["welcome"]
+    assert bundle.messages_using_variable("a") == ["loop-a", "loop-b"]
+    assert bundle.messages_using_variable("unused") == []
+
+
+def test_messages_using_variable_include_attributes():
+    bundle = fluent.Bundle("en", [("references", REFERENCES_FTL)])
+
+    assert bundle.messages_using_variable("user", include_attributes=True) == [
+        "greeting",
+        "welcome",
+        "welcome.title",
+    ]
+    assert bundle.messages_using_variable("folder", include_attributes=True) == ["welcome.title"]
+
+
+SEARCH_FTL = """\
+-brand = Acme
+welcome =
+    Welcome to our
+    shop, { $name }!
+    .title = Welcome
+checkout = Pay with { -brand }
+"""
+
+
+def test_search():
+    bundle = fluent.Bundle("en", [("search.ftl", SEARCH_FTL)])
+
+    (found,) = bundle.search("our shop")
+
+    assert found.id == "welcome"
+    assert found.text == "Welcome to our\nshop, { $name }!"
+    assert found.text[found.start : found.end] == "our\nshop"
+    assert (found.filename, found.line, found.column) == ("search.ftl", 3, 16)
+
+
+def test_search_attributes_and_terms():
+    bundle = fluent.Bundle("en", [("search.ftl", SEARCH_FTL)])
+
+    assert [found.id for found in bundle.search("Welcome")] == ["welcome", "welcome.title"]
+    assert [found.id for found in bundle.search("Welcome", in_attributes=False)] == ["welcome"]
+    assert [found.id for found in bundle.search("Acme")] == ["-brand"]
+
+
+def test_search_placeables():
+    bundle = fluent.Bundle("en", [("search.ftl", SEARCH_FTL)])
+
+    (found,) = bundle.search("{ -brand }")
+
+    assert found.id == "checkout"
+    assert (found.line, found.column) == (6, 21)
+
+
+def test_search_regex():
+    bundle = fluent.Bundle("en", [("search.ftl", SEARCH_FTL)])
+
+    matches = bundle.search(r"\b[A-Z]\w+", regex=True, in_attributes=False)
+
+    assert [(found.id, found.text[found.start : found.end]) for found in matches] == [
+        ("-brand", "Acme"),
+        ("welcome", "Welcome"),
+        ("checkout", "Pay"),
+    ]
+
+
+def test_search_every_language():
+    bundle = fluent.Bundle(
+        ["fr", "en"], {"fr": [("fr.ftl", "hello = Bonjour")], "en": [("en.ftl", "hello = Bon")]}
+    )
+
+    assert [found.filename for found in bundle.search("Bon")] == ["fr.ftl", "en.ftl"]
+
+
+def test_search_empty():
+    bundle = fluent.Bundle("en", [("search.ftl", SEARCH_FTL)])
+
+    with pytest.raises(ValueError, match="pattern must not be empty"):
+        bundle.search(" ")
+
+
+def test_get_required_variables_all_includes_attributes_of_messages_without_values():
+    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
+
+    variables = bundle.get_required_variables_all()
+
+    assert "form-button" not in variables
+    assert variables["form-button.submit"] == []
+
+
+def test_get_required_variables_all_uses_first_language_with_each_message():
+    bundle = fluent.Bundle(
+        ["fr", "en"],
+        {
+            "fr": [("fr", "hello = Bonjour { $name }")],
+            "en": [("en", "hello = Hello { $user }\ngoodbye = Goodbye { $user }")],
+        },
+    )
+
+    assert bundle.get_required_variables_all() == {"hello": ["name"], "goodbye": ["user"]}
+
+
+TYPES_FTL = """
+emails = { $count ->
+    [one] One email from { $sender }
+   *[other] { $count } emails from { $sender }
+}
+invoice = { NUMBER($amount, minimumFractionDigits: 2) } due { DATETIME($due) }
+pronoun = { $gender ->
+    [male] he
+    [female] she
+   *[other] they
+}
+exact = { $count ->
+    [0] none
+   *[other] some
+}
+platform = { PLATFORM($os) }
+summary = { emails } { pronoun }
+"""
+
+
+@pytest.mark.parametrize(
+    "identifier, expected",
+    (
+        ("emails", {"count": "number", "sender": "string"}),
+        ("invoice", {"amount": "number", "due": "datetime"}),
+        ("pronoun", {"gender": "string"}),
+        ("exact", {"count": "number"}),
+        ("platform", {"os": "unknown"}),
+        ("summary", {"count": "number", "sender": "string", "gender": "string"}),
+    ),
+)
+def test_get_variable_types(identifier, expected):
+    bundle = fluent.Bundle("en", [("types", TYPES_FTL)])
+
+    assert bundle.get_variable_types(identifier) == expected
+
+
+def test_get_variable_types_conflicting_uses_are_unknown():
+    bundle = fluent.Bundle("en", [("types", "conflict = { NUMBER($value) } { DATETIME($value) }")])
+
+    assert bundle.get_variable_types("conflict") == {"value": "unknown"}
+
+
+def test_get_variable_types_of_lists():
+    bundle = fluent.Bundle("en", [("types", "order = { LIST($items, type: \"unit\") }")])
+
+    assert bundle.get_variable_types("order") == {"items": "list"}
+
+
+# Error mode tests
+
+
+def test_errors_are_ignored_by_default():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    assert bundle.get_translation("hello-user", use_isolating=False) == "Hello, {$user}"
+
+
+def test_errors_raise_for_missing_variables():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    expected = r"Errors when formatting hello-user: Resolver error: Unknown variable: \$user"
+    with pytest.raises(fluent.FormatError, match=expected) as exc_info:
+        bundle.get_translation("hello-user", errors="raise")
+
+    assert exc_info.value.errors == ["Resolver error: Unknown variable: $user"]
+    assert exc_info.value.message_id == "hello-user"
+    assert exc_info.value.variable_name == "user"
+    assert exc_info.value.reference is None
+
+
+def test_errors_raise_for_unknown_references():
+    bundle = fluent.Bundle("en", [("references", "broken = { missing } and { -missing }")])
+
+    with pytest.raises(fluent.FormatError) as exc_info:
+        bundle.get_translation("broken", errors="raise")
+
+    assert exc_info.value.errors == [
+        "Resolver error: Unknown message: missing",
+        "Resolver error: Unknown term: -missing",
+    ]
+    assert exc_info.value.variable_name is None
+    assert exc_info.value.reference == "missing"
+
+
+@pytest.mark.parametrize(
+    "source, reference",
+    (
+        ("broken = { -missing.attr ->\n   *[x] { $user }\n}", "-missing.attr"),
+        ("greeting = Hi\nbroken = { greeting.title }", "greeting.title"),
+        ("broken = { UNKNOWN() }", "UNKNOWN()"),
+        ("no-value =\n    .title = Title\nbroken = { no-value }", "no-value"),
+    ),
+)
+def test_format_errors_identify_the_reference(source, reference):
+    bundle = fluent.Bundle("en", [("references", source)])
+
+    with pytest.raises(fluent.FormatError) as exc_info:
+        bundle.get_translation("broken", errors="raise")
+
+    assert exc_info.value.message_id == "broken"
+    assert exc_info.value.reference == reference
+
+
+def test_format_errors_identify_the_cyclic_reference():
+    source = "start = { -first }\n-first = { -second }\n-second = { -first }"
+    bundle = fluent.Bundle("en", [("cycle", source)])
+
+    with pytest.raises(fluent.FormatError, match="Cyclical dependency") as exc_info:
+        bundle.get_translation("start", errors="raise")
+
+    assert exc_info.value.reference == "-first"
+
+
+def test_format_error_can_be_created():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    with pytest.raises(fluent.FormatError) as exc_info:
+        bundle.get_translation("hello-user", errors="raise")
+
+    expected = fluent.FormatError(
+        "Errors when formatting hello-user: Resolver error: Unknown variable: $user (RF1001)",
+        "hello-user",
+        errors=["Resolver error: Unknown variable: $user"],
+        variable_name="user",
+        codes=["RF1001"],
+    )
+    assert exc_info.value == expected
+    assert hash(exc_info.value) == hash(expected)
+    assert str(expected) == expected.message
+    assert expected.args == (expected.message,)
+    assert fluent.FormatError("Error", "hello").errors == []
+    assert fluent.FormatError("Error", "hello", severity="warning").severity == "warning"
+
+
+def test_format_error_can_be_raised():
+    with pytest.raises(fluent.FormatError, match="Broken") as exc_info:
+        raise fluent.FormatError("Broken", "broken", reference="missing")
+
+    assert exc_info.value.reference == "missing"
+
+
+def test_format_error_can_be_pickled():
+    bundle = fluent.Bundle("en", [("references", "broken = { missing }")])
+
+    with pytest.raises(fluent.FormatError) as exc_info:
+        bundle.get_translation("broken", errors="raise")
+
+    unpickled = pickle.loads(pickle.dumps(exc_info.value))
+    assert unpickled == exc_info.value
+    assert str(unpickled) == str(exc_info.value)
+    assert unpickled.to_dict() == {
+        "message": (
+            "Errors when formatting broken: Resolver error: Unknown message: missing (RF1003)"
+        ),
+        "message_id": "broken",
+        "errors": ["Resolver error: Unknown message: missing"],
+        "variable_name": None,
+        "reference": "missing",
+        "severity": "error",
+        "codes": ["RF1003"],
+    }
+
+
+def test_errors_raise_for_unsupported_variable_types():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    with pytest.raises(TypeError, match="Variable 'user' has an unsupported value"):
+        bundle.get_translation("hello-user", variables={"user": object()}, errors="raise")
+
+
+def test_errors_raise_from_compiled_messages_and_batches():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    with pytest.raises(fluent.FormatError):
+        bundle.compile("hello-user")(errors="raise")
+    with pytest.raises(fluent.FormatError, match="hello-user"):
+        bundle.get_translations([("hello-world", None), ("hello-user", None)], errors="raise")
+
+
+def test_no_errors_with_errors_raise():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    result = bundle.get_translation("hello-user", variables={"user": "Bob"}, errors="raise")
+
+    assert result == f"Hello, {BIDI_OPEN}Bob{BIDI_CLOSE}"
+
+
+def test_invalid_errors_value():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    expected = "errors must be 'ignore', 'raise', 'log' or a callable, not 'strict'."
+    with pytest.raises(ValueError, match=expected):
+        bundle.get_translation("hello-world", errors="strict")
+
+
+def test_errors_must_be_a_string_or_callable():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    expected = "errors must be 'ignore', 'raise', 'log' or a callable, not int."
+    with pytest.raises(TypeError, match=expected):
+        bundle.get_translation("hello-world", errors=1)
+
+
+def test_errors_log(caplog):
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    with caplog.at_level("WARNING", logger="rustfluent"):
+        result = bundle.get_translation("hello-user", use_isolating=False, errors="log")
+
+    assert result == "Hello, {$user}"
+    [record] = caplog.records
+    assert record.name == "rustfluent"
+    assert record.levelname == "WARNING"
+    assert record.getMessage() == (
+        "Error when formatting hello-user for en: Resolver error: Unknown variable: $user"
+    )
+    assert (record.message_id, record.locale, record.error_type) == (
+        "hello-user",
+        "en",
+        "UnknownVariable",
+    )
+
+
+def test_strict_variables_raise_for_unexpected_variables():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    with pytest.raises(fluent.FormatError, match=r"Unexpected variable: \$stale") as exc_info:
+        bundle.get_translation(
+            "hello-user", variables={"user": "Bob", "stale": 1}, strict_variables=True
+        )
+
+    assert exc_info.value.errors == ["Unexpected variable: $stale"]
+    assert exc_info.value.codes == ["RF1011"]
+    assert exc_info.value.variable_name is None
+
+
+def test_strict_variables_raise_for_missing_variables():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    with pytest.raises(fluent.FormatError) as exc_info:
+        bundle.get_translation("hello-user", variables={"stale": 1}, strict_variables=True)
+
+    # The missing variable is reported once, not again as an unknown variable.
+    assert exc_info.value.errors == ["Missing variable: $user", "Unexpected variable: $stale"]
+    assert exc_info.value.codes == ["RF1010", "RF1011"]
+    assert exc_info.value.variable_name == "user"
+
+
+def test_strict_variables_check_variables_of_every_variant():
+    source = "greeting = { $count ->\n *[other] Hi { $name }\n}"
+    bundle = fluent.Bundle("en", [("selector", source)])
+
+    with pytest.raises(fluent.FormatError, match=r"Missing variable: \$name"):
+        bundle.get_translation("greeting", variables={"count": 1}, strict_variables=True)
+
+
+def test_strict_variables_pass_with_the_exact_variables():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+    variables = {"user": {"name": "Bob", "address": {"city": "Paris"}}}
+
+    result = bundle.get_translation(
+        "nested-user", variables=variables, use_isolating=False, strict_variables=True
+    )
+
+    assert result == "Hello, Bob from Paris."
+    assert bundle.get_translation("hello-world", strict_variables=True) == "Hello World"
+
+
+def test_strict_variables_are_reported_by_errors(caplog):
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+    reported = []
+
+    with caplog.at_level("WARNING", logger="rustfluent"):
+        result = bundle.get_translation(
+            "hello-user",
+            variables={"user": "Bob", "stale": 1},
+            use_isolating=False,
+            errors="log",
+            strict_variables=True,
+        )
+    bundle.get_translation(
+        "hello-user", variables={"stale": 1}, errors=reported.append, strict_variables=True
+    )
+
+    assert result == "Hello, Bob"
+    assert [(r.getMessage(), r.error_type, r.code) for r in caplog.records] == [
+        (
+            "Error when formatting hello-user for en: Unexpected variable: $stale",
+            "UnexpectedVariable",
+            "RF1011",
+        )
+    ]
+    [error] = reported
+    assert error.errors == ["Missing variable: $user", "Unexpected variable: $stale"]
+
+
+def test_variable_schema_raises_for_wrong_types():
+    bundle = fluent.Bundle("en", [("cart", "cart-total = Total: { $amount } for { $user }")])
+    bundle.set_variable_schema({"cart-total": {"amount": "number", "user": "string"}})
+
+    with pytest.raises(fluent.FormatError) as exc_info:
+        bundle.get_translation("cart-total", variables={"amount": "12", "user": "Bob"})
+
+    assert exc_info.value.errors == ["Variable $amount should be a number, not a string"]
+    assert exc_info.value.codes == ["RF1012"]
+    assert exc_info.value.variable_name == "amount"
+    result = bundle.get_translation(
+        "cart-total", variables={"amount": 12, "user": "Bob"}, use_isolating=False
+    )
+    assert result == "Total: 12 for Bob"
+
+
+def test_variable_schema_checks_dates_and_lists():
+    bundle = fluent.Bundle("en", [("dates", "due = Due { $when } for { $names }")])
+    bundle.set_variable_schema({"due": {"$when": "datetime", "names": "list"}})
+    variables = {"when": date(2020, 1, 5), "names": ["Ann", "Bob"]}
+
+    assert bundle.get_translation("due", variables=variables, use_isolating=False)
+    with pytest.raises(fluent.FormatError) as exc_info:
+        bundle.get_translation("due", variables={"when": 5, "names": "Ann"})
+
+    assert exc_info.value.errors == [
+        "Variable $names should be a list, not a string",
+        "Variable $when should be a datetime, not a number",
+    ]
+
+
+def test_variable_schema_from_comments():
+    source = """
+# $amount (Number) - the total
+# $currency (Money) - not a type Fluent has
+cart-total = { $amount } { $currency }
+    .label = Total of { $amount }
+"""
+    bundle = fluent.Bundle("en", [("cart", source)])
+    bundle.set_variable_schema(from_comments=True)
+
+    with pytest.raises(fluent.FormatError, match=r"\$amount should be a number"):
+        bundle.get_translation("cart-total.label", variables={"amount": "a lot"})
+    bundle.get_translation("cart-total", variables={"amount": 1, "currency": 2})
+
+    # The schema passed wins over the comments.
+    bundle.set_variable_schema({"cart-total": {"amount": "string"}}, from_comments=True)
+    bundle.get_translation("cart-total", variables={"amount": "a lot", "currency": "EUR"})
+
+
+def test_variable_schema_is_reported_by_errors():
+    bundle = fluent.Bundle("en", [("cart", "cart-total = Total: { $amount }")])
+    bundle.set_variable_schema({"cart-total": {"amount": "number"}})
+    reported = []
+
+    result = bundle.get_translation(
+        "cart-total", variables={"amount": "12"}, use_isolating=False, errors=reported.append
+    )
+
+    assert result == "Total: 12"
+    [error] = reported
+    assert error.codes == ["RF1012"]
+    bundle.set_variable_schema()
+    assert bundle.get_translation("cart-total", variables={"amount": "12"}, use_isolating=False)
+
+
+def test_variable_schema_applies_to_every_way_of_formatting():
+    source = "cart-total = Total: { $amount }\n    .label = Total of { $amount }"
+    bundle = fluent.Bundle("en", [("cart", source)])
+    bundle.set_variable_schema({"cart-total": {"amount": "number"}})
+    variables = {"amount": "12"}
+
+    with pytest.raises(fluent.FormatError, match="RF1012"):
+        bundle.compile("cart-total")(variables)
+    with pytest.raises(fluent.FormatError, match="RF1012"):
+        bundle.get_translations([("cart-total", variables)])
+    with pytest.raises(fluent.FormatError, match="RF1012"):
+        bundle.get_translation_with_attributes("cart-total", variables)
+    with pytest.raises(fluent.FormatError, match="RF1012"):
+        bundle.get_translation_parts("cart-total.label", variables)
+
+
+def test_variable_schema_rejects_unknown_types():
+    bundle = fluent.Bundle("en", [("cart", "cart-total = Total: { $amount }")])
+
+    with pytest.raises(ValueError, match=r"Unknown type 'money' for \$amount in cart-total"):
+        bundle.set_variable_schema({"cart-total": {"amount": "money"}})
+
+
+def test_errors_log_each_error_with_its_type(caplog):
+    bundle = fluent.Bundle(
+        ["de", "en"],
+        {"de": [("de", "other = Andere")], "en": [("en", "broken = { missing } { -missing }")]},
+    )
+
+    with caplog.at_level("WARNING", logger="rustfluent"):
+        bundle.get_translations([("broken", None)], errors="log")
+
+    assert [(r.message_id, r.locale, r.error_type, r.code) for r in caplog.records] == [
+        ("broken", "en", "UnknownMessage", "RF1003"),
+        ("broken", "en", "UnknownTerm", "RF1004"),
+    ]
+
+
+def test_errors_log_nothing_without_errors(caplog):
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    with caplog.at_level("WARNING", logger="rustfluent"):
+        bundle.get_translation("hello-user", variables={"user": "Bob"}, errors="log")
+
+    assert caplog.records == []
+
+
+def test_errors_callback_is_called_with_format_errors():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+    reported = []
+
+    result = bundle.get_translation("hello-user", use_isolating=False, errors=reported.append)
+
+    assert result == "Hello, {$user}"
+    [error] = reported
+    assert isinstance(error, fluent.FormatError)
+    assert error.message_id == "hello-user"
+    assert error.variable_name == "user"
+
+
+def test_errors_callback_is_not_called_without_errors():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+    reported = []
+
+    bundle.get_translation("hello-user", variables={"user": "Bob"}, errors=reported.append)
+
+    assert reported == []
+
+
+def test_errors_callback_for_compiled_messages_and_batches():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+    reported = []
+
+    bundle.compile("hello-user")(errors=reported.append)
+    translations = bundle.get_translations(
+        [("hello-world", None), ("hello-user", None), ("hello-user", {"user": "Bob"})],
+        use_isolating=False,
+        errors=reported.append,
+    )
+
+    assert translations == ["Hello World", "Hello, {$user}", "Hello, Bob"]
+    assert [error.message_id for error in reported] == ["hello-user", "hello-user"]
+
+
+def test_errors_callback_exceptions_propagate():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    def callback(error):
+        raise RuntimeError("Reported")
+
+    with pytest.raises(RuntimeError, match="Reported"):
+        bundle.get_translation("hello-user", errors=callback)
+
+
+def test_errors_callback_does_not_imply_strict_types():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+    reported = []
+
+    result = bundle.get_translation(
+        "hello-user", variables={"user": object()}, use_isolating=False, errors=reported.append
+    )
+
+    # The unsupported value is shown as the variable's name, as without `errors`.
+    assert result == "Hello, user"
+    assert reported == []
+
+
+# Missing message default tests
+
+
+@pytest.mark.parametrize(
+    "identifier",
+    ("missing", "form-button", "welcome-message.nonexistent"),
+)
+def test_default_is_returned_for_missing_messages(identifier):
+    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
+
+    assert bundle.get_translation(identifier, default=identifier) == identifier
+
+
+def test_default_is_not_used_for_existing_messages():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    assert bundle.get_translation("hello-world", default="fallback") == "Hello World"
+
+
+def test_default_in_batch_translation():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    result = bundle.get_translations([("hello-world", None), ("missing", None)], default="")
+
+    assert result == ["Hello World", ""]
+
+
+# Identifier fallback tests
+
+
+@pytest.mark.parametrize(
+    "identifiers, expected",
+    (
+        (["hello-world", "hello-user"], "Hello World"),
+        (["hello-world-v2", "hello-world"], "Hello World"),
+        (["missing", "hello-world.missing", "hello-world"], "Hello World"),
+    ),
+)
+def test_identifier_fallback(identifiers, expected):
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    assert bundle.get_translation(identifiers) == expected
+
+
+def test_identifier_fallback_reports_first_identifier_when_none_found():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    with pytest.raises(ValueError, match="hello-world-v2 not found"):
+        bundle.get_translation(["hello-world-v2", "hello-world-v1"])
+
+
+def test_identifier_fallback_with_default():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    assert bundle.get_translation(["missing-v2", "missing"], default="fallback") == "fallback"
+
+
+def test_identifier_fallback_requires_an_identifier():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    with pytest.raises(ValueError, match="At least one identifier is required."):
+        bundle.get_translation([])
+
+
+# Message existence tests
+
+
+@pytest.mark.parametrize(
+    "identifier, expected",
+    (
+        ("welcome-message", True),
+        ("welcome-message.title", True),
+        ("welcome-message.nonexistent", False),
+        ("form-button", False),
+        ("form-button.submit", True),
+        ("missing", False),
+    ),
+)
+def test_has_message(identifier, expected):
+    bundle = fluent.Bundle("en", [data_dir / "attributes.ftl"])
+
+    assert bundle.has_message(identifier) is expected
+    assert (identifier in bundle) is expected
+
+
+def test_has_message_uses_fallback_languages():
+    bundle = fluent.Bundle(
+        ["fr", "en"],
+        {"fr": [("fr", "hello = Bonjour")], "en": [("en", "hello = Hello\ngoodbye = Goodbye")]},
+    )
+
+    assert "hello" in bundle
+    assert "goodbye" in bundle
+
+
+# Message ID tests
+
+
+def test_message_ids():
+    bundle = fluent.Bundle(
+        "en",
+        [
+            ("first", "one = One\ntwo = Two\n    .title = Second"),
+            ("second", "three = Three\none = 1"),
+        ],
+    )
+
+    assert bundle.message_ids() == ["one", "two", "three"]
+    assert bundle.message_ids(include_attributes=True) == ["one", "two", "two.title", "three"]
+    assert list(bundle) == ["one", "two", "three"]
+    assert len(bundle) == 3
+
+
+def test_message_ids_include_fallback_languages():
+    bundle = fluent.Bundle(
+        ["fr", "en"],
+        {"fr": [("fr", "hello = Bonjour")], "en": [("en", "goodbye = Goodbye\nhello = Hello")]},
+    )
+
+    assert bundle.message_ids() == ["hello", "goodbye"]
+    assert len(bundle) == 2
+
+
+# Term tests
+
+TERMS_FTL = """
+-brand = { $case ->
+   *[nominative] Firefox
+    [genitive] Firefox's
+}
+    .gender = masculine
+-company = Mozilla
+about = About { -brand }
+"""
+
+
+def test_term_ids():
+    overrides = "-company = Mozilla Foundation\n-vendor = Acme"
+    bundle = fluent.Bundle("en", [("terms", TERMS_FTL), ("overrides", overrides)])
+
+    assert bundle.term_ids() == ["-brand", "-company", "-vendor"]
+
+
+@pytest.mark.parametrize("term_id", ("-brand", "brand"))
+def test_get_term(term_id):
+    bundle = fluent.Bundle("en", [("terms", TERMS_FTL)])
+
+    term = bundle.get_term(term_id)
+
+    assert term.id == "-brand"
+    assert term.value == "Firefox"
+    assert term.attributes == {"gender": "masculine"}
+
+
+def test_get_missing_term_suggests_similar_terms():
+    bundle = fluent.Bundle("en", [("terms", TERMS_FTL)])
+
+    with pytest.raises(ValueError, match="Did you mean '-company'") as exc_info:
+        bundle.get_term("compnay")
+
+    assert exc_info.value.suggestions == ["-company"]
+
+
+def test_get_term_uses_overriding_resource():
+    bundle = fluent.Bundle("en", [("terms", TERMS_FTL), ("overrides", "-company = Acme")])
+
+    assert bundle.get_term("-company").value == "Acme"
+
+
+def test_get_missing_term():
+    bundle = fluent.Bundle("en", [("terms", TERMS_FTL)])
+
+    with pytest.raises(ValueError, match="-missing not found"):
+        bundle.get_term("-missing")
+
+
+# Message object tests
+
+MESSAGES_FTL = """
+# Shown on the login page.
+# $user is the user's display name.
+login-title = Welcome back, { $user }!
+    .aria-label = Log in as { $user } to { $site }
+
+form-button =
+    .submit = Submit { $count } items
+"""
+
+
+def test_get_message():
+    bundle = fluent.Bundle("en", [("messages", MESSAGES_FTL)])
+
+    message = bundle.get_message("login-title")
+
+    assert message.id == "login-title"
+    assert message.attributes == ["aria-label"]
+    assert message.has_value is True
+    assert message.comment == "Shown on the login page.\n$user is the user's display name."
+    assert message.variables == ["user", "site"]
+
+
+def test_get_message_without_value_or_comment():
+    bundle = fluent.Bundle("en", [("messages", MESSAGES_FTL)])
+
+    message = bundle.get_message("form-button")
+
+    assert message.attributes == ["submit"]
+    assert message.has_value is False
+    assert message.comment is None
+    assert message.variables == ["count"]
+
+
+def test_get_missing_message():
+    bundle = fluent.Bundle("en", [("messages", MESSAGES_FTL)])
+
+    with pytest.raises(ValueError, match="missing not found"):
+        bundle.get_message("missing")
+
+
+# Translation with attributes tests
+
+
+def test_get_translation_with_attributes():
+    bundle = fluent.Bundle("en", [("messages", MESSAGES_FTL)])
+
+    result = bundle.get_translation_with_attributes(
+        "login-title", {"user": "Ana", "site": "Example"}, use_isolating=False
+    )
+
+    assert result == {
+        "value": "Welcome back, Ana!",
+        "attributes": {"aria-label": "Log in as Ana to Example"},
+    }
+
+
+def test_get_translation_with_attributes_without_value():
+    bundle = fluent.Bundle("en", [("messages", MESSAGES_FTL)])
+
+    result = bundle.get_translation_with_attributes("form-button", {"count": 3})
+
+    assert result == {
+        "value": None,
+        "attributes": {"submit": f"Submit {BIDI_OPEN}3{BIDI_CLOSE} items"},
+    }
+
+
+def test_get_translation_with_attributes_errors():
+    bundle = fluent.Bundle("en", [("messages", MESSAGES_FTL)])
+
+    with pytest.raises(ValueError, match="missing not found"):
+        bundle.get_translation_with_attributes("missing")
+    with pytest.raises(fluent.FormatError, match="login-title.aria-label"):
+        bundle.get_translation_with_attributes("login-title", {"user": "Ana"}, errors="raise")
+
+
+PARTS_FTL = """
+hello = Hello, { $name }!
+items = { NUMBER($count) } items in { -brand }
+-brand = Acme
+broken = Hello, { $missing }!
+"""
+
+
+def test_get_translation_parts():
+    bundle = fluent.Bundle("en", [("parts", PARTS_FTL)])
+
+    parts = bundle.get_translation_parts("hello", {"name": "Bob"})
+
+    assert [(part.type, part.value, part.name) for part in parts] == [
+        ("literal", "Hello, ", None),
+        ("placeable", "Bob", "name"),
+        ("literal", "!", None),
+    ]
+
+
+def test_get_translation_parts_of_functions_and_terms():
+    bundle = fluent.Bundle("en", [("parts", PARTS_FTL)])
+
+    parts = bundle.get_translation_parts("items", {"count": 3})
+
+    assert [(part.type, part.value, part.name) for part in parts] == [
+        ("placeable", "3", "count"),
+        ("literal", " items in ", None),
+        ("placeable", "Acme", None),
+    ]
+
+
+def test_get_translation_parts_repr():
+    bundle = fluent.Bundle("en", [("parts", PARTS_FTL)])
+
+    literal, placeable, _ = bundle.get_translation_parts("hello", {"name": "Bob"})
+
+    assert repr(literal) == 'MessagePart(type="literal", value="Hello, ")'
+    assert repr(placeable) == 'MessagePart(type="placeable", value="Bob", name="name")'
+
+
+def test_get_translation_parts_with_pseudo_localization():
+    bundle = fluent.Bundle("en", [("parts", PARTS_FTL)], pseudo="accented")
+
+    parts = bundle.get_translation_parts("hello", {"name": "Bob"})
+
+    assert [part.value for part in parts] == ["Ħḗŀŀǿ, ", "Bob", "!"]
+
+
+def test_get_translation_parts_errors():
+    bundle = fluent.Bundle("en", [("parts", PARTS_FTL)])
+
+    parts = bundle.get_translation_parts("broken")
+    assert [part.value for part in parts] == ["Hello, ", "{$missing}", "!"]
+    with pytest.raises(fluent.FormatError, match="broken"):
+        bundle.get_translation_parts("broken", errors="raise")
+    with pytest.raises(ValueError, match="missing not found"):
+        bundle.get_translation_parts("missing")
+
+
+# Comment tests
+
+COMMENTS_FTL = """
+### Strings for the checkout flow.
+
+## Payment
+## Shown when paying for an order.
+
+# The total, including tax.
+total = Total: { $amount }
+
+# The brand shown in the footer.
+-brand = Acme
+
+##
+
+footer = Thanks for shopping with { -brand }
+"""
+
+
+@pytest.mark.parametrize(
+    "identifier, expected",
+    (
+        ("total", "The total, including tax."),
+        ("-brand", "The brand shown in the footer."),
+        ("footer", None),
+    ),
+)
+def test_get_comment(identifier, expected):
+    bundle = fluent.Bundle("en", [("comments", COMMENTS_FTL)])
+
+    assert bundle.get_comment(identifier) == expected
+
+
+@pytest.mark.parametrize("identifier", ("missing", "-missing"))
+def test_get_comment_missing_entry(identifier):
+    bundle = fluent.Bundle("en", [("comments", COMMENTS_FTL)])
+
+    with pytest.raises(ValueError, match=f"{identifier} not found"):
+        bundle.get_comment(identifier)
+
+
+SOURCE_FTL = """\
+-brand = Acme
+
+# Shown on the home page.
+hello = Hello, { -brand }!
+
+emails =
+    { $count ->
+        [one] One email
+       *[other] { $count } emails
+    }
+    .title = Emails
+bye = Bye
+"""
+
+
+def test_get_message_source():
+    bundle = fluent.Bundle("en", [("main.ftl", SOURCE_FTL)])
+
+    source = bundle.get_message_source("emails")
+
+    assert source.id == "emails"
+    assert source.source.startswith("emails =\n    { $count ->")
+    assert source.source.endswith("    .title = Emails")
+    assert source.filename == "main.ftl"
+    assert (source.line, source.column) == (6, 1)
+    assert SOURCE_FTL[source.byte_start : source.byte_end] == source.source
+
+
+def test_get_message_source_leaves_out_comment():
+    bundle = fluent.Bundle("en", [("main.ftl", SOURCE_FTL)])
+
+    source = bundle.get_message_source("hello")
+
+    assert source.source == "hello = Hello, { -brand }!"
+    assert source.line == 4
+
+
+def test_get_message_source_term():
+    bundle = fluent.Bundle("en", [("main.ftl", SOURCE_FTL)])
+
+    source = bundle.get_message_source("-brand")
+
+    assert source.id == "-brand"
+    assert source.source == "-brand = Acme"
+    assert (source.line, source.byte_start) == (1, 0)
+
+
+def test_get_message_source_fallback():
+    bundle = fluent.Bundle(
+        ["fr", "en"], {"fr": [("fr.ftl", "hello = Bonjour")], "en": [("en.ftl", SOURCE_FTL)]}
+    )
+
+    assert bundle.get_message_source("hello").source == "hello = Bonjour"
+    assert bundle.get_message_source("bye").filename == "en.ftl"
+
+
+@pytest.mark.parametrize("identifier", ("missing", "-missing", "hello.title"))
+def test_get_message_source_missing(identifier):
+    bundle = fluent.Bundle("en", [("main.ftl", SOURCE_FTL)])
+
+    with pytest.raises(ValueError, match="not found"):
+        bundle.get_message_source(identifier)
+
+
+TAGS_FTL = """\
+### tags: shop
+
+## Checkout
+## tags: checkout
+
+# Shown at the top.
+# tags: email, checkout
+checkout-title = Checkout
+checkout-pay = Pay
+
+##
+
+# tags:  promo ,
+banner = Sale!
+footer = Footer
+"""
+
+
+def test_message_tags():
+    bundle = fluent.Bundle("en", [("tags.ftl", TAGS_FTL)])
+
+    assert bundle.get_message("checkout-title").tags == ["email", "checkout", "shop"]
+    assert bundle.get_message("checkout-pay").tags == ["checkout", "shop"]
+    assert bundle.get_message("banner").tags == ["promo", "shop"]
+    assert bundle.get_message("footer").tags == ["shop"]
+
+
+def test_messages_with_tag():
+    bundle = fluent.Bundle("en", [("tags.ftl", TAGS_FTL), ("other.ftl", "other = Other")])
+
+    assert bundle.messages_with_tag("checkout") == ["checkout-title", "checkout-pay"]
+    assert bundle.messages_with_tag("email") == ["checkout-title"]
+    assert bundle.messages_with_tag("shop") == [
+        "checkout-title",
+        "checkout-pay",
+        "banner",
+        "footer",
+    ]
+    assert bundle.messages_with_tag("missing") == []
+
+
+def test_messages_with_tag_uses_first_language_with_each_message():
+    bundle = fluent.Bundle(
+        ["fr", "en"],
+        {
+            "fr": [("fr", "# tags: greeting\nhello = Bonjour")],
+            "en": [("en", "# tags: greeting\nhello = Hello\n# tags: greeting\nbye = Bye")],
+        },
+    )
+
+    assert bundle.messages_with_tag("greeting") == ["hello", "bye"]
+
+
+VARIABLE_DOCS_FTL = """\
+# Shown on the home page.
+# $user (String) - the user's display name
+# $count (Number): how many new messages they have
+# $brand
+# $user is also shown in the header.
+welcome = Welcome, { $user }! You have { $count } new messages.
+
+# $user (String) - the user's name
+# $stale - no longer used
+greeting = Hello, { $user }! { signature }
+
+signature = From { $sender }
+
+undocumented = { $anything }
+"""
+
+
+def test_get_variable_docs():
+    bundle = fluent.Bundle("en", [("docs.ftl", VARIABLE_DOCS_FTL)])
+
+    docs = bundle.get_variable_docs("welcome")
+
+    assert [(doc.name, doc.type, doc.description) for doc in docs] == [
+        ("user", "String", "the user's display name"),
+        ("count", "Number", "how many new messages they have"),
+        ("brand", None, None),
+    ]
+    assert repr(docs[2]) == 'VariableDoc(name="brand", type=None, description=None)'
+    assert bundle.get_variable_docs("undocumented") == []
+    with pytest.raises(ValueError):
+        bundle.get_variable_docs("missing")
+
+
+def test_validation_errors_for_variable_docs():
+    bundle = fluent.Bundle("en", [("docs.ftl", VARIABLE_DOCS_FTL)])
+
+    errors = [
+        (error.message_id, error.message, error.line)
+        for error in bundle.validation_errors()
+        if error.error_type == "VariableDocMismatch"
+    ]
+
+    assert errors == [
+        ("welcome", "Documented variable $brand isn't used by the message", 4),
+        ("greeting", "Documented variable $stale isn't used by the message", 9),
+        ("greeting", "Variable $sender isn't documented in the message's comment", 10),
+    ]
+
+
+def test_variable_docs_mismatch_fails_strict_bundles():
+    source = "# $name - the name\nhello = Hello, { $user }!"
+
+    with pytest.raises(fluent.ParserErrorGroup) as exc_info:
+        fluent.Bundle("en", [("docs", source)], strict=True)
+
+    # The undocumented $user and the unused $name are both reported.
+    errors = exc_info.value.exceptions
+    assert len(errors) == 2
+    assert all(isinstance(error, fluent.VariableDocMismatchError) for error in errors)
+    bundle = fluent.Bundle("en", [("docs", source)], ignore=["VariableDocMismatch"])
+    assert bundle.validation_errors() == []
+
+
+def test_message_group_and_resource_comments():
+    bundle = fluent.Bundle("en", [("comments", COMMENTS_FTL)])
+
+    total = bundle.get_message("total")
+    footer = bundle.get_message("footer")
+
+    assert total.group_comment == "Payment\nShown when paying for an order."
+    assert total.resource_comment == "Strings for the checkout flow."
+    assert footer.group_comment is None
+    assert footer.resource_comment == "Strings for the checkout flow."
+
+
+# Bundle comparison tests
+
+
+def test_compare_bundles():
+    reference_ftl = "hello = Hello\nbye = Bye\nform = Form\n    .title = Title\n    .label = Label"
+    target_ftl = "hello = Bonjour\nform = Formulaire\n    .title = Titre\nold = Vieux"
+    reference = fluent.Bundle("en", [("en", reference_ftl)])
+    target = fluent.Bundle("fr", [("fr", target_ftl)])
+
+    comparison = fluent.compare_bundles(reference, target)
+
+    assert comparison.missing == ["bye", "form.label"]
+    assert comparison.obsolete == ["old"]
+    assert not comparison
+
+
+def test_compare_complete_bundles():
+    reference = fluent.Bundle("en", [("en", "hello = Hello")])
+    target = fluent.Bundle("fr", [("fr", "hello = Bonjour")])
+
+    comparison = fluent.compare_bundles(reference, target)
+
+    assert comparison.missing == []
+    assert comparison.obsolete == []
+    assert comparison
+
+
+def test_compare_bundles_ignores_fallback_languages():
+    reference = fluent.Bundle("en", [("en", "hello = Hello\nbye = Bye")])
+    target = fluent.Bundle(
+        ["fr", "en"],
+        {"fr": [("fr", "hello = Bonjour")], "en": [("en", "hello = Hello\nbye = Bye")]},
+    )
+
+    assert fluent.compare_bundles(reference, target).missing == ["bye"]
+
+
+def test_coverage():
+    reference_ftl = "hello = Hello\nbye = Bye\nform = Form\n    .title = Title\nhelp = Help"
+    reference = fluent.Bundle("en", [("en", reference_ftl)])
+    target = fluent.Bundle("fr", [("fr", "hello = Bonjour\nform = Formulaire\nold = Vieux")])
+
+    report = fluent.coverage(reference, target)
+
+    assert (report.language, report.total, report.translated) == ("fr", 4, 2)
+    assert report.percentage == 50.0
+    assert report.untranslated == ["bye", "help"]
+    assert repr(report) == 'CoverageReport(language="fr", translated=2, total=4, percentage=50.0)'
+
+
+def test_coverage_of_empty_bundles():
+    reference = fluent.Bundle("en", [("en", "")])
+    target = fluent.Bundle("fr", [("fr", "")])
+
+    assert fluent.coverage(reference, target, threshold=100).percentage == 100.0
+
+
+def test_coverage_threshold():
+    reference = fluent.Bundle("en", [("en", "hello = Hello\nbye = Bye\nhelp = Help")])
+    target = fluent.Bundle("fr", [("fr", "hello = Bonjour\nbye = Au revoir")])
+
+    assert fluent.coverage(reference, target, threshold=60).translated == 2
+    with pytest.raises(fluent.CoverageError, match=r"fr is 66\.7% translated") as exc_info:
+        fluent.coverage(reference, target, threshold=90)
+    assert exc_info.value.report.untranslated == ["help"]
+    with pytest.raises(ValueError, match="threshold must be between 0 and 100"):
+        fluent.coverage(reference, target, threshold=101)
+
+
+# Validation tests
+
+PLURALS_FTL = """
+apples = { $count ->
+    [one] { $count } яблоко
+   *[other] { $count } яблок
+}
+emails = { $count ->
+    [one] { $count } письмо
+    [few] { $count } письма
+    [many] { $count } писем
+   *[other] { $count } письма
+}
+greeting = { $gender ->
+    [male] Он
+   *[other] Они
+}
+exact = { NUMBER($count) ->
+    [0] ничего
+   *[other] что-то
+}
+-files = { $count ->
+    [one] файл
+   *[many] файлов
+}
+"""
+
+
+def test_validation_errors_for_missing_plural_categories():
+    bundle = fluent.Bundle("ru", [("plurals", PLURALS_FTL)])
+
+    errors = bundle.validation_errors()
+
+    assert [(error.error_type, error.message_id, error.message) for error in errors] == [
+        (
+            "MissingPluralCategory",
+            "apples",
+            "Plural variants for 'ru' don't cover: few, many",
+        ),
+        (
+            "MissingPluralCategory",
+            "-files",
+            "Plural variants for 'ru' don't cover: few, other",
+        ),
+    ]
+
+
+def test_no_validation_errors_when_plural_categories_are_covered():
+    bundle = fluent.Bundle("en", [("plurals", PLURALS_FTL)])
+
+    assert [error.message_id for error in bundle.validation_errors()] == ["-files"]
+
+
+def test_validation_errors_raise_in_strict_mode():
+    with pytest.raises(fluent.ParserError, match="apples: Plural variants for 'ru'"):
+        fluent.Bundle("ru", [("plurals", PLURALS_FTL)], strict=True)
+
+
+def test_validation_errors_for_unknown_references():
+    bundle = fluent.Bundle(
+        "en", [("references", "broken = { missing } { -missing } { missing }\nfine = { broken }")]
+    )
+
+    errors = bundle.validation_errors()
+
+    assert [(error.error_type, error.message_id, error.message) for error in errors] == [
+        ("UnknownMessage", "broken", "Unknown message: missing"),
+        ("UnknownTerm", "broken", "Unknown term: -missing"),
+    ]
+
+
+def test_validation_errors_suggest_similar_ids():
+    bundle = fluent.Bundle(
+        "en",
+        [
+            (
+                "references",
+                "welcome-message = Hi\nwelcome-massage = Hi\n-brand-name = Acme\n"
+                "broken = { welcom-mesage } { -brand-nmae } { goodbye }",
+            )
+        ],
+    )
+
+    errors = bundle.validation_errors()
+
+    assert [(error.message, error.suggestions) for error in errors] == [
+        ("Unknown message: welcom-mesage", ["welcome-message", "welcome-massage"]),
+        ("Unknown term: -brand-nmae", ["-brand-name"]),
+        ("Unknown message: goodbye", []),
+    ]
+
+
+def test_validation_errors_suggestions_in_strict_errors():
+    with pytest.raises(fluent.ParserError, match="Did you mean 'greeting'\\?"):
+        fluent.Bundle("en", [("references", "greeting = Hi\nbye = { greting }")], strict=True)
+
+
+def test_validation_errors_have_source_locations():
+    bundle = fluent.Bundle("en", [("references", "fine = Fine\nbroken = { -missing }")])
+
+    [error] = bundle.validation_errors()
+
+    assert error.filename == "references"
+    assert (error.line, error.column) == (2, 12)
+    assert (error.byte_start, error.byte_end) == (23, 31)
+    assert str(error) == "references:2:12: broken: Unknown term: -missing (RF0003 UnknownTerm)"
+
+
+def test_plural_validation_errors_locate_the_selector():
+    source = "apples = { $count ->\n    [one] apple\n   *[other] apples\n}"
+    bundle = fluent.Bundle("ru", [("plurals", source)])
+
+    [error] = bundle.validation_errors()
+
+    assert (error.line, error.column, error.byte_start, error.byte_end) == (1, 12, 11, 17)
+
+
+def test_references_to_later_files_are_valid():
+    bundle = fluent.Bundle(
+        "en",
+        [
+            ("first", "welcome = Welcome to { -brand }! { tagline }"),
+            ("second", "-brand = Acme\ntagline = The best"),
+        ],
+        strict=True,
+    )
+
+    assert bundle.validation_errors() == []
+
+
+def test_validation_errors_for_cycles_across_files():
+    bundle = fluent.Bundle(
+        "en",
+        [
+            ("first", "greeting = Hello { signature }"),
+            ("second", "signature = Regards, { greeting }"),
+        ],
+    )
+
+    errors = bundle.validation_errors()
+
+    assert [(error.error_type, error.message_id, error.message) for error in errors] == [
+        (
+            "CyclicReference",
+            "greeting",
+            "Cyclic reference between greeting, signature: "
+            "greeting -> signature -> greeting (in first, second)",
+        ),
+    ]
+
+
+def test_each_cycle_is_reported_once():
+    bundle = fluent.Bundle(
+        "en",
+        [
+            (
+                "cycles",
+                "\n".join(
+                    [
+                        "intro = { c-step } { outro }",
+                        "c-step = { b-step }",
+                        "b-step = { a-step } { b-step }",
+                        "a-step = { c-step }",
+                        "outro = { outro }",
+                    ]
+                ),
+            )
+        ],
+    )
+
+    errors = bundle.validation_errors()
+
+    assert [(error.message_id, error.message) for error in errors] == [
+        (
+            "a-step",
+            "Cyclic reference between a-step, b-step, c-step: "
+            "a-step -> c-step -> b-step -> a-step (in cycles)",
+        ),
+        ("outro", "Cyclic reference between outro: outro -> outro (in cycles)"),
+    ]
+
+
+def test_references_to_own_attributes_are_not_cycles():
+    bundle = fluent.Bundle("en", [("attributes", "button = { button.label }\n    .label = Save")])
+
+    assert bundle.validation_errors() == []
+
+
+RULES_SOURCES = [
+    ("first", 'brand-line = { -brand("short") }\nwelcome = Welcome'),
+    ("second", "-brand = Acme\nwelcome = Hello"),
+]
+
+
+def test_validation_errors_for_duplicates_and_positional_term_arguments():
+    bundle = fluent.Bundle("en", RULES_SOURCES)
+
+    errors = bundle.validation_errors()
+
+    assert [(error.error_type, error.message_id, error.message) for error in errors] == [
+        (
+            "IgnoredPositionalArgument",
+            "brand-line",
+            "Positional arguments to -brand are ignored, only named arguments are passed to terms",
+        ),
+        ("DuplicateMessageId", "welcome", "Message is already defined in first"),
+    ]
+    assert [error.severity for error in errors] == ["warning", "error"]
+
+
+PLACEHOLDER_SOURCES = {
+    "en": [
+        (
+            "en.ftl",
+            """\
+-brand = Acme
+welcome = Welcome, { $name }! { -brand } has { $count ->
+        [one] one new message
+       *[other] { $count } new messages
+    }.
+profile = Profile
+    .title = { $name }'s profile
+signature = { -brand } team
+""",
+        )
+    ],
+    "fr": [
+        (
+            "fr.ftl",
+            """\
+-brand = Acmé
+welcome = Bienvenue ! { -brand } a { $count } nouveaux messages.
+profile = Profil
+    .title = Profil de { $user }
+signature = L'équipe { -brand }
+""",
+        )
+    ],
+}
+
+
+def test_validation_errors_for_placeholders():
+    bundle = fluent.Bundle(["fr", "en"], PLACEHOLDER_SOURCES, reference_language="en")
+
+    errors = bundle.validation_errors()
+
+    assert [(error.error_type, error.message_id, error.message) for error in errors] == [
+        (
+            "PlaceholderMismatch",
+            "welcome",
+            "Translation is missing { $name }, which is in the en message",
+        ),
+        (
+            "PlaceholderMismatch",
+            "profile",
+            "Translation of .title is missing { $name }, which is in the en message",
+        ),
+        (
+            "PlaceholderMismatch",
+            "profile",
+            "Translation of .title has { $user }, which isn't in the en message",
+        ),
+    ]
+    assert (errors[0].filename, errors[0].line) == ("fr.ftl", 2)
+
+
+def test_placeholders_arent_checked_without_a_reference_language():
+    bundle = fluent.Bundle(["fr", "en"], PLACEHOLDER_SOURCES)
+
+    assert bundle.validation_errors() == []
+    assert bundle.copy().validation_errors() == []
+
+
+def test_placeholder_mismatch_fails_strict_bundles():
+    with pytest.raises(fluent.ParserErrorGroup) as exc_info:
+        fluent.Bundle(["fr", "en"], PLACEHOLDER_SOURCES, strict=True, reference_language="en")
+
+    assert {type(error) for error in exc_info.value.exceptions} == {
+        fluent.PlaceholderMismatchError
+    }
+
+
+def test_invalid_reference_language():
+    with pytest.raises(ValueError, match="'de' is not one of the bundle's languages."):
+        fluent.Bundle(["fr", "en"], PLACEHOLDER_SOURCES, reference_language="de")
+
+MARKUP_FTL = """\
+balanced = <strong>Hello</strong>, <a href="{ $url }">click</a><br>1 < 2<br/>
+unclosed = <strong>Hello
+stray = Hello</em>
+crossed = <b><i>Hello</b></i>
+select = { $count ->
+        [one] <b>One</b>
+       *[other] <b>{ $count }</b>
+    }
+link = Link
+    .title = <em>Title
+"""
+
+
+def test_markup_mismatch_is_optional():
+    bundle = fluent.Bundle("en", [("markup.ftl", MARKUP_FTL)])
+
+    assert bundle.validation_errors() == []
+
+
+def test_validation_errors_for_unbalanced_markup():
+    bundle = fluent.Bundle("en", [("markup.ftl", MARKUP_FTL)], rules=["MarkupMismatch"])
+
+    errors = bundle.validation_errors()
+
+    assert [(error.message_id, error.message) for error in errors] == [
+        ("unclosed", "<strong> isn't closed"),
+        ("stray", "</em> doesn't close an open tag"),
+        ("crossed", "<i> isn't closed"),
+        ("crossed", "</i> doesn't close an open tag"),
+        ("link", "<em> isn't closed in .title"),
+    ]
+    assert {error.error_type for error in errors} == {"MarkupMismatch"}
+    linted = fluent.validate([("markup.ftl", MARKUP_FTL)], rules=["MarkupMismatch"])
+    assert [error.message for error in linted] == [error.message for error in errors]
+
+
+def test_validation_errors_for_markup_against_the_reference_language():
+    sources = {
+        "en": [("en.ftl", "email = <strong>Hi</strong>, see <a>here</a>.")],
+        "de": [("de.ftl", "email = <strong>Hallo<strong>, siehe <em>hier</em>.")],
+    }
+    bundle = fluent.Bundle(
+        ["de", "en"], sources, rules=["MarkupMismatch"], reference_language="en"
+    )
+
+    assert [error.message for error in bundle.validation_errors()] == [
+        "<strong> isn't closed",
+        "<strong> isn't closed",
+        "Translation is missing <a>, which is in the en message",
+        "Translation has <em>, which isn't in the en message",
+    ]
+
+LINT_FTL = """\
+-brand = Acme's
+welcome = Welcome to { -brand }, it's great…
+long = { $count ->
+        [one] One short message
+       *[other] { $count } messages that are much, much longer
+    }
+    .title = Short
+trailing = Hello \n    world\t
+Bad_ID = Bad
+"""
+
+
+def test_lint_rules():
+    bundle = fluent.Bundle(
+        "en",
+        [("lint.ftl", LINT_FTL)],
+        lint={
+            "max_length": 32,
+            "forbidden_characters": "…",
+            "quotes": "curly",
+            "trailing_whitespace": True,
+            "id_pattern": r"-?[a-z][a-z0-9-]*",
+        },
+    )
+
+    errors = bundle.validation_errors()
+
+    assert [(error.error_type, error.message_id, error.message) for error in errors] == [
+        ("QuoteStyle", "welcome", "Straight quote ' instead of a curly one"),
+        ("ForbiddenCharacter", "welcome", "Forbidden character … (U+2026)"),
+        ("MaxLength", "long", "Value is 36 characters long, longer than the maximum of 32"),
+        ("TrailingWhitespace", "trailing", "Line ends with whitespace"),
+        ("TrailingWhitespace", "trailing", "Line ends with whitespace"),
+        ("IdNaming", "Bad_ID", "ID doesn't match the naming convention -?[a-z][a-z0-9-]*"),
+    ]
+    assert [(error.line, error.column) for error in errors[:2]] == [(2, 36), (2, 44)]
+    assert [(error.line, error.column) for error in errors[3:5]] == [(8, 17), (9, 10)]
+
+
+def test_lint_rules_for_validate():
+    errors = fluent.validate([("lint.ftl", LINT_FTL)], lint={"quotes": "straight"})
+    assert errors == []
+
+    errors = fluent.validate([("lint.ftl", "hello = “Hi”")], lint={"quotes": "straight"})
+    assert [error.message for error in errors] == [
+        "Curly quote “ instead of a straight one",
+        "Curly quote ” instead of a straight one",
+    ]
+
+
+def test_lint_errors_fail_strict_bundles():
+    sources = [("lint", "hello = Hello, world!")]
+
+    with pytest.raises(fluent.ParserErrorGroup) as exc_info:
+        fluent.Bundle("en", sources, strict=True, lint={"max_length": 10})
+
+    [error] = exc_info.value.exceptions
+    assert isinstance(error, fluent.LintError)
+    assert error.error.error_type == "MaxLength"
+
+
+@pytest.mark.parametrize(
+    "lint, error",
+    [
+        ({"max_width": 10}, ValueError),
+        ({"max_length": "10"}, TypeError),
+        ({"quotes": "smart"}, ValueError),
+    ],
+)
+def test_invalid_lint_rules(lint, error):
+    with pytest.raises(error):
+        fluent.Bundle("en", [("lint.ftl", LINT_FTL)], lint=lint)
+
+def test_validation_rules_and_ignore():
+    only_duplicates = fluent.Bundle("en", RULES_SOURCES, rules=["DuplicateMessageId"])
+    ignoring_duplicates = fluent.Bundle("en", RULES_SOURCES, ignore={"DuplicateMessageId"})
+
+    assert [error.error_type for error in only_duplicates.validation_errors()] == [
+        "DuplicateMessageId"
+    ]
+    assert [error.error_type for error in ignoring_duplicates.validation_errors()] == [
+        "IgnoredPositionalArgument"
+    ]
+
+
+def test_validation_warnings_dont_fail_strict_bundles():
+    bundle = fluent.Bundle(
+        "en",
+        RULES_SOURCES,
+        strict=True,
+        ignore=["IgnoredPositionalArgument"],
+        severity={"DuplicateMessageId": "warning"},
+    )
+
+    [error] = bundle.validation_errors()
+
+    assert (error.error_type, error.severity) == ("DuplicateMessageId", "warning")
+    with pytest.raises(fluent.ParserError, match="IgnoredPositionalArgument"):
+        fluent.Bundle(
+            "en",
+            RULES_SOURCES,
+            strict=True,
+            severity={"DuplicateMessageId": "warning", "IgnoredPositionalArgument": "error"},
+        )
+
+
+def test_validation_errors_have_codes():
+    bundle = fluent.Bundle("en", RULES_SOURCES)
+
+    assert [(error.code, error.error_type) for error in bundle.validation_errors()] == [
+        ("RF0006", "IgnoredPositionalArgument"),
+        ("RF0005", "DuplicateMessageId"),
+    ]
+    assert fluent.ValidationError("Custom", "hello", "Custom problem").code is None
+
+
+def test_validation_rules_by_code():
+    bundle = fluent.Bundle(
+        "en", RULES_SOURCES, ignore=["RF0006"], severity={"RF0005": "info", "RF0017": "info"}
+    )
+
+    [error] = bundle.validation_errors()
+
+    assert (error.error_type, error.severity) == ("DuplicateMessageId", "info")
+    assert fluent.Bundle("en", RULES_SOURCES, rules={"RF0006"}).validation_errors()[0].code == (
+        "RF0006"
+    )
+    with pytest.raises(ValueError, match="Unknown validation rule: 'RF0017'"):
+        fluent.Bundle("en", [], ignore=["RF0017"])
+
+
+def test_ignored_positional_arguments_are_warnings_by_default():
+    bundle = fluent.Bundle("en", RULES_SOURCES, strict=True, ignore=["DuplicateMessageId"])
+
+    [error] = bundle.validation_errors()
+
+    assert (error.error_type, error.severity) == ("IgnoredPositionalArgument", "warning")
+
+
+def test_info_errors_dont_fail_strict_bundles_or_warn():
+    with warnings.catch_warnings():
+        warnings.simplefilter("error")
+
+        bundle = fluent.Bundle(
+            "en",
+            RULES_SOURCES,
+            strict=True,
+            warn=True,
+            severity={"DuplicateMessageId": "info", "IgnoredPositionalArgument": "info"},
+        )
+
+    assert [error.severity for error in bundle.validation_errors()] == ["info", "info"]
+
+
+def test_parse_error_severity():
+    sources = [data_dir / "errors.ftl"]
+
+    bundle = fluent.Bundle("fr", sources, strict=True, severity={"ParseError": "warning"})
+
+    [junk] = bundle.get_junk()
+    assert junk.severity == "warning"
+    assert json.loads(bundle.error_report())[0]["severity"] == "warning"
+    bundle.add_ftl_string("invalid", "invalid-message")
+    with pytest.warns(fluent.ParseWarning):
+        fluent.Bundle("fr", sources, warn=True, severity={"ParseError": "warning"})
+    with warnings.catch_warnings():
+        warnings.simplefilter("error")
+        fluent.Bundle("fr", sources, warn=True, severity={"ParseError": "info"})
+
+
+def test_strict_parsing_without_strict_validation():
+    bundle = fluent.Bundle("en", RULES_SOURCES, strict=True, strict_validation=False)
+
+    assert len(bundle.validation_errors()) == 2
+    with pytest.raises(fluent.ParserError):
+        bundle.add_ftl_string("invalid", "invalid-message")
+    with pytest.raises(fluent.ParserError, match="Error when parsing"):
+        fluent.Bundle("fr", [data_dir / "errors.ftl"], strict_parse=True)
+
+
+def test_strict_validation_without_strict_parsing():
+    bundle = fluent.Bundle("fr", [data_dir / "errors.ftl"], strict_validation=True)
+
+    assert bundle.get_translation("valid-message") == "I'm valid."
+    with pytest.raises(fluent.ParserError, match="Validation errors in first"):
+        fluent.Bundle(
+            "en",
+            RULES_SOURCES,
+            strict=True,
+            strict_parse=False,
+            severity={"IgnoredPositionalArgument": "error"},
+        )
+
+
+def test_warnings_for_validation_errors():
+    with pytest.warns(fluent.ValidationWarning) as record:
+        fluent.Bundle("en", RULES_SOURCES, warn=True)
+
+    assert [warning.category for warning in record] == [
+        fluent.IgnoredPositionalArgumentWarning,
+        fluent.DuplicateMessageWarning,
+    ]
+    assert str(record[1].message) == (
+        "second:2:1: welcome: Message is already defined in first (RF0005 DuplicateMessageId)"
+    )
+    assert issubclass(fluent.DuplicateMessageWarning, UserWarning)
+
+
+def test_warnings_for_syntax_errors():
+    with pytest.warns(fluent.ParseWarning, match=r"Error when parsing .*errors\.ftl:\d+:\d+: "):
+        fluent.Bundle("fr", [data_dir / "errors.ftl"], warn=True)
+
+
+def test_warnings_can_be_raised_by_warning_filters():
+    with warnings.catch_warnings():
+        warnings.simplefilter("error", fluent.DuplicateMessageWarning)
+
+        with pytest.raises(fluent.DuplicateMessageWarning):
+            fluent.Bundle("en", RULES_SOURCES, warn=True)
+
+
+def test_no_warnings_by_default():
+    with warnings.catch_warnings():
+        warnings.simplefilter("error")
+
+        fluent.Bundle("en", RULES_SOURCES)
+
+
+def test_strict_errors_are_raised_as_a_group():
+    sources = [*RULES_SOURCES, ("invalid", "invalid-message")]
+
+    with pytest.raises(fluent.ParserErrorGroup) as exc_info:
+        fluent.Bundle("en", sources, strict=True)
+
+    group = exc_info.value
+    assert isinstance(group, ExceptionGroup)
+    assert isinstance(group, fluent.ParserError)
+    assert [type(error) for error in group.exceptions] == [
+        fluent.ParserError,
+        fluent.DuplicateMessageError,
+    ]
+    assert "Error when parsing invalid" in str(group.exceptions[0])
+    duplicate = group.exceptions[1]
+    assert isinstance(duplicate, fluent.BundleValidationError)
+    assert (duplicate.error.error_type, duplicate.error.message_id) == (
+        "DuplicateMessageId",
+        "welcome",
+    )
+
+
+def test_strict_errors_show_each_affected_file():
+    sources = [
+        ("broken", "first-invalid\nsecond-invalid\n"),
+        ("references", "welcome = { -missing }\n"),
+        ("mixed", "also-invalid\nbye = { farewell }\n"),
+    ]
+
+    with pytest.raises(fluent.ParserErrorGroup) as exc_info:
+        fluent.Bundle("en", sources, strict=True)
+
+    message = str(exc_info.value)
+    assert "Error when parsing broken" in message
+    assert "Validation errors in references" in message
+    assert "welcome: Unknown term: -missing (RF0003 UnknownTerm)" in message
+    assert "Errors in mixed" in message
+    assert "bye: Unknown message: farewell (RF0002 UnknownMessage)" in message
+    # Both syntax errors in the first file are shown in one snippet.
+    assert message.count("Error when parsing broken") == 1
+    assert len(exc_info.value.exceptions) == 5
+
+
+def test_diagnostic_style_none():
+    sources = [data_dir / "errors.ftl", ("references", "welcome = { -missing }\n")]
+
+    with pytest.raises(fluent.ParserErrorGroup) as exc_info:
+        fluent.Bundle("fr", sources, strict=True, diagnostic_style="none")
+
+    # The group's `str()` adds the number of sub-exceptions to its message.
+    assert exc_info.value.message.splitlines()[:4] == [
+        f"Error when parsing {data_dir / 'errors.ftl'}:",
+        f'{data_dir / "errors.ftl"}:1:16: Expected a token starting with "="',
+        "Validation errors in references:",
+        "references:1:13: welcome: Unknown term: -missing (RF0003 UnknownTerm)",
+    ]
+    # The errors are attached as they are with any style.
+    assert exc_info.value.exceptions[-1].error.error_type == "UnknownTerm"
+
+
+def test_diagnostic_style_plain():
+    with pytest.raises(fluent.ParserError) as exc_info:
+        fluent.Bundle("fr", [data_dir / "errors.ftl"], strict=True, diagnostic_style="plain")
+
+    message = str(exc_info.value)
+    assert "Error when parsing" in message
+    assert 'Expected a token starting with "="' in message
+    assert message.isascii()
+
+
+def test_diagnostic_style_rich_honours_no_color(monkeypatch):
+    monkeypatch.setenv("NO_COLOR", "1")
+
+    with pytest.raises(fluent.ParserError) as exc_info:
+        fluent.Bundle("fr", [data_dir / "errors.ftl"], strict=True)
+
+    message = str(exc_info.value)
+    assert "Error when parsing" in message
+    assert not message.isascii()
+    assert "\x1b[" not in message
+
+
+def test_diagnostic_style_applies_to_added_strings():
+    bundle = fluent.Bundle("en", [], strict=True, diagnostic_style="none")
+
+    with pytest.raises(fluent.ParserError) as exc_info:
+        bundle.add_ftl_string("invalid", "invalid-message")
+
+    assert str(exc_info.value).startswith("Error when parsing invalid:\ninvalid:1:")
+
+
+def test_invalid_diagnostic_style():
+    with pytest.raises(ValueError, match="diagnostic_style must be 'none', 'plain' or 'rich'"):
+        fluent.Bundle("en", [], diagnostic_style="fancy")
+
+
+def test_strict_errors_can_be_handled_by_type():
+    handled = []
+
+    try:
+        fluent.Bundle("en", RULES_SOURCES, strict=True)
+    except* fluent.DuplicateMessageError as group:
+        handled.extend(error.error.message_id for error in group.exceptions)
+    except* fluent.BundleValidationError:
+        pass
+
+    assert handled == ["welcome"]
+
+
+def test_invalid_validation_rules():
+    with pytest.raises(ValueError, match="Unknown validation rule: 'NoSuchRule'"):
+        fluent.Bundle("en", [], ignore=["NoSuchRule"])
+    with pytest.raises(ValueError, match="must be 'error', 'warning' or 'info', not 'fatal'"):
+        fluent.Bundle("en", [], severity={"UnknownTerm": "fatal"})
+
+
+DUPLICATE_SOURCES = [
+    ("base", "welcome = Welcome\ntitle = Title\ntitle = Second title"),
+    ("overrides", "welcome = Hello"),
+]
+
+
+def test_duplicates_in_later_files_are_reported_by_default():
+    bundle = fluent.Bundle("en", DUPLICATE_SOURCES)
+
+    assert [(error.message_id, error.message) for error in bundle.validation_errors()] == [
+        ("title", "Message is already defined in base"),
+        ("welcome", "Message is already defined in base"),
+    ]
+    assert bundle.get_translation("welcome") == "Hello"
+
+
+@pytest.mark.parametrize(
+    "on_duplicate, expected", (("last_wins", "Hello"), ("first_wins", "Welcome"))
+)
+def test_duplicate_policies(on_duplicate, expected):
+    bundle = fluent.Bundle("en", DUPLICATE_SOURCES, on_duplicate=on_duplicate)
+
+    assert bundle.get_translation("welcome") == expected
+    assert bundle.get_translation("title") == "Second title"
+    assert [error.message_id for error in bundle.validation_errors()] == ["title"]
+
+
+def test_duplicates_raise_with_error_policy():
+    with pytest.raises(fluent.ParserError) as exc_info:
+        fluent.Bundle("en", DUPLICATE_SOURCES, on_duplicate="error")
+
+    assert [type(error) for error in exc_info.value.exceptions] == [
+        fluent.DuplicateMessageError,
+        fluent.DuplicateMessageError,
+    ]
+
+
+def test_invalid_duplicate_policy():
+    with pytest.raises(ValueError, match="on_duplicate must be 'error', 'first_wins'"):
+        fluent.Bundle("en", [], on_duplicate="ignore")
+
+
+def test_baseline_only_fails_on_new_errors(tmp_path):
+    baseline = tmp_path / "baseline.json"
+    fluent.Bundle("en", RULES_SOURCES, baseline=baseline).write_baseline()
+
+    bundle = fluent.Bundle("en", RULES_SOURCES, strict=True, baseline=baseline)
+
+    assert len(bundle.validation_errors()) == 2
+    with pytest.raises(fluent.ParserError, match="Unknown message: farewell"):
+        fluent.Bundle(
+            "en", [*RULES_SOURCES, ("third", "bye = { farewell }")], strict=True, baseline=baseline
+        )
+
+
+def test_baseline_file_contents(tmp_path):
+    bundle = fluent.Bundle("en", RULES_SOURCES, ignore=["IgnoredPositionalArgument"])
+
+    bundle.write_baseline(tmp_path / "baseline.json")
+
+    assert json.loads((tmp_path / "baseline.json").read_text()) == [
+        {
+            "code": "RF0005",
+            "error_type": "DuplicateMessageId",
+            "message_id": "welcome",
+            "message": "Message is already defined in first",
+        }
+    ]
+
+
+def test_baselines_without_codes_are_accepted(tmp_path):
+    baseline = tmp_path / "baseline.json"
+    entry = {"message_id": "welcome", "message": "Message is already defined in first"}
+    baseline.write_text(json.dumps([{"error_type": "DuplicateMessageId", **entry}]))
+
+    fluent.Bundle("en", RULES_SOURCES, strict=True, baseline=baseline)
+
+
+def test_invalid_baselines(tmp_path):
+    (tmp_path / "baseline.json").write_text('[{"error_type": "UnknownTerm"}]')
+
+    with pytest.raises(ValueError, match="an error has no 'message_id'"):
+        fluent.Bundle("en", [], baseline=tmp_path / "baseline.json")
+    with pytest.raises(ValueError, match="the bundle has no baseline file"):
+        fluent.Bundle("en", []).write_baseline()
+
+
+def test_validate_on_demand():
+    bundle = fluent.Bundle("en", [data_dir / "errors.ftl", *RULES_SOURCES])
+
+    with pytest.raises(fluent.ParserError) as exc_info:
+        bundle.validate()
+
+    assert isinstance(exc_info.value, ExceptionGroup)
+    assert [type(error) for error in exc_info.value.exceptions] == [
+        fluent.DuplicateMessageError,
+    ]
+
+
+def test_validate_on_demand_passes_warnings():
+    bundle = fluent.Bundle(
+        "en",
+        RULES_SOURCES,
+        severity={"DuplicateMessageId": "warning", "IgnoredPositionalArgument": "warning"},
+    )
+
+    bundle.validate()
+
+
+def test_error_report():
+    bundle = fluent.Bundle("en", [data_dir / "errors.ftl", ("other", "bye = { farewell }")])
+
+    report = json.loads(bundle.error_report())
+
+    assert report == [
+        {
+            "filename": str(data_dir / "errors.ftl"),
+            "line": 1,
+            "column": 16,
+            "byte_start": 15,
+            "byte_end": 16,
+            "code": "RF0017",
+            "error_type": "ParseError",
+            "message_id": None,
+            "message": 'Expected a token starting with "="',
+            "severity": "error",
+            "suggestions": [],
+        },
+        {
+            "filename": "other",
+            "line": 1,
+            "column": 9,
+            "byte_start": 8,
+            "byte_end": 16,
+            "code": "RF0002",
+            "error_type": "UnknownMessage",
+            "message_id": "bye",
+            "message": "Unknown message: farewell",
+            "severity": "error",
+            "suggestions": [],
+        },
+    ]
+
+
+def test_error_report_unknown_format():
+    with pytest.raises(ValueError, match="format must be 'json', not 'xml'"):
+        fluent.Bundle("en", []).error_report("xml")
+
+
+def test_validate_files():
+    errors = fluent.validate(
+        [data_dir / "errors.ftl", *RULES_SOURCES, ("plurals", PLURALS_FTL)],
+        locale="ru",
+        ignore=["IgnoredPositionalArgument"],
+    )
+
+    assert [(error.error_type, error.message_id) for error in errors] == [
+        ("DuplicateMessageId", "welcome"),
+        ("MissingPluralCategory", "apples"),
+        ("MissingPluralCategory", "-files"),
+    ]
+
+
+def test_validate_files_without_locale_skips_plural_checks():
+    errors = fluent.validate([("plurals", PLURALS_FTL), ("other", "bye = { farewell }")])
+
+    assert [error.message for error in errors] == ["Unknown message: farewell"]
+
+
+def test_validate_files_invalid_locale():
+    with pytest.raises(ValueError, match="Invalid language: '!!'"):
+        fluent.validate([], locale="!!")
+
+
+UNUSED_FTL = """
+-brand = Acme
+-unused-brand = Unused
+-product = { -brand } Widget
+    .short = { -unused-helper }
+-unused-helper = Widget
+welcome = Welcome to { -product }!
+title = { welcome }
+    .tooltip = { footer }
+footer = Copyright { -brand }
+obsolete = No longer used
+"""
+
+
+def test_find_unused_terms():
+    bundle = fluent.Bundle("en", [("unused", UNUSED_FTL)])
+
+    unused = bundle.find_unused()
+
+    assert [(error.error_type, error.message_id) for error in unused] == [
+        ("UnusedTerm", "-unused-brand")
+    ]
+
+
+def test_find_unused_messages():
+    bundle = fluent.Bundle("en", [("unused", UNUSED_FTL)])
+
+    unused = bundle.find_unused(used_ids={"title.tooltip"})
+
+    assert [(error.error_type, error.message_id) for error in unused] == [
+        ("UnusedTerm", "-unused-brand"),
+        ("UnusedMessage", "obsolete"),
+    ]
+
+
+def test_unused_and_cyclic_errors_name_their_files(tmp_path):
+    (tmp_path / "brand.ftl").write_text("-old-brand = Acme\n")
+    (tmp_path / "loop.ftl").write_text("welcome = Hi\n\nping = { pong }\npong = { ping }\n")
+    bundle = fluent.Bundle("en", [tmp_path / "brand.ftl", tmp_path / "loop.ftl"])
+
+    [unused] = bundle.find_unused()
+    [cycle] = bundle.validation_errors()
+
+    assert (unused.filename, unused.line, unused.column) == (str(tmp_path / "brand.ftl"), 1, 1)
+    assert (unused.byte_start, unused.byte_end) == (0, 10)
+    assert (cycle.filename, cycle.line, cycle.column) == (str(tmp_path / "loop.ftl"), 3, 1)
+
+
+GRAPH_FTL = """
+-brand = Acme
+    .legal = yes
+-product = { -brand } Cloud
+title = { -product }
+    .tooltip = { -brand.legal ->
+        [yes] Registered
+       *[other] Unregistered
+    } { footer }
+footer = Copyright { -brand }
+plain = Hello { $name }
+"""
+
+
+def test_reference_graph():
+    bundle = fluent.Bundle("en", [("graph", GRAPH_FTL)])
+
+    assert bundle.reference_graph() == {
+        "-brand": [],
+        "-product": ["-brand"],
+        "title": ["-product", "-brand", "footer"],
+        "footer": ["-brand"],
+        "plain": [],
+    }
+
+
+def test_reference_graph_dot():
+    bundle = fluent.Bundle("en", [("graph", "-brand = Acme\nfooter = { -brand }\n")])
+
+    assert bundle.reference_graph(format="dot") == (
+        'digraph {\n    "-brand";\n    "footer" -> "-brand";\n}\n'
+    )
+
+
+def test_reference_graph_language_and_format():
+    bundle = fluent.Bundle(
+        ["en", "fr"], {"en": [("en", GRAPH_FTL)], "fr": [("fr", "bye = { -brand }")]}
+    )
+
+    assert bundle.reference_graph("fr") == {"bye": ["-brand"]}
+    with pytest.raises(ValueError, match="format must be 'dict' or 'dot', not 'svg'"):
+        bundle.reference_graph(format="svg")
+
+
+def test_referencing():
+    bundle = fluent.Bundle("en", [("graph", GRAPH_FTL)])
+
+    assert bundle.referencing("-brand") == ["-product", "title", "footer"]
+    assert bundle.referencing("-product") == ["title"]
+    assert bundle.referencing("footer") == ["title"]
+    assert bundle.referencing("plain") == []
+
+
+def test_referencing_transitive():
+    bundle = fluent.Bundle(
+        "en", [("graph", "-a = A\n-b = { -a }\nc = { -b }\nd = { c }\ne = E\n")]
+    )
+
+    assert bundle.referencing("-a") == ["-b"]
+    assert bundle.referencing("-a", transitive=True) == ["-b", "c", "d"]
+
+
+def test_referencing_missing():
+    bundle = fluent.Bundle("en", [("graph", GRAPH_FTL)])
+
+    with pytest.raises(ValueError, match="-missing not found"):
+        bundle.referencing("-missing")
+
+
+# Custom function tests
+
+
+def test_add_function():
+    bundle = fluent.Bundle("en", [data_dir / "functions.ftl"])
+    bundle.add_function("PLATFORM", lambda: "Linux")
+
+    result = bundle.get_translation("platform-message", use_isolating=False)
+
+    assert result == "Running on Linux."
+
+
+def test_add_function_with_positional_and_named_arguments():
+    bundle = fluent.Bundle("en", [data_dir / "functions.ftl"])
+    bundle.add_function("SHOUT", lambda value, suffix="": f"{value.upper()}{suffix}")
+
+    result = bundle.get_translation("shout-message", variables={"name": "Bob"})
+
+    assert result == "BOB!"
+
+
+def test_add_function_numbers_are_passed_as_numbers():
+    bundle = fluent.Bundle("en", [data_dir / "functions.ftl"])
+    bundle.add_function("DOUBLE", lambda value: value * 2)
+
+    result = bundle.get_translation("double-message", variables={"count": 21})
+
+    assert result == "42"
+
+
+def test_add_function_exceptions_are_raised():
+    bundle = fluent.Bundle("en", [data_dir / "functions.ftl"])
+
+    def platform():
+        raise RuntimeError("Unknown platform")
+
+    bundle.add_function("PLATFORM", platform)
+
+    with pytest.raises(RuntimeError, match="Unknown platform"):
+        bundle.get_translation("platform-message")
+
+
+@pytest.mark.parametrize("name", ("platform", "1PLATFORM", "", "PLAT FORM"))
+def test_add_function_invalid_name(name):
+    bundle = fluent.Bundle("en", [data_dir / "functions.ftl"])
+
+    with pytest.raises(ValueError, match="Invalid function name"):
+        bundle.add_function(name, lambda: "Linux")
+
+
+def test_add_function_not_callable():
+    bundle = fluent.Bundle("en", [data_dir / "functions.ftl"])
+
+    with pytest.raises(TypeError, match="Function 'PLATFORM' is not callable"):
+        bundle.add_function("PLATFORM", "Linux")
+
+
+def test_add_function_twice():
+    bundle = fluent.Bundle("en", [data_dir / "functions.ftl"])
+    bundle.add_function("PLATFORM", lambda: "Linux")
+
+    with pytest.raises(ValueError, match="Function 'PLATFORM' is already registered."):
+        bundle.add_function("PLATFORM", lambda: "macOS")
+
+
+# Value adapter tests
+
+
+class Money:
+    def __init__(self, amount, currency):
+        self.amount = amount
+        self.currency = currency
+
+
+class Plan(enum.Enum):
+    BASIC = "basic"
+    PREMIUM = "premium"
+
+
+class Tier:
+    def __fluent_value__(self):
+        return "gold"
+
+
+def test_register_adapter():
+    bundle = fluent.Bundle("en", [data_dir / "numbers.ftl"])
+    bundle.register_adapter(Money, lambda money: Decimal(money.amount))
+
+    result = bundle.get_translation("price", {"amount": Money("19.99", "EUR")})
+
+    assert result == "€19.99"
+
+
+def test_register_adapter_for_enums():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+    bundle.register_adapter(Plan, lambda plan: plan.value)
+
+    result = bundle.get_translation("hello-user", {"user": Plan.PREMIUM}, use_isolating=False)
+
+    assert result == "Hello, premium"
+
+
+def test_register_adapter_again_replaces_it():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+    bundle.register_adapter(Plan, lambda plan: plan.value)
+    bundle.register_adapter(Plan, lambda plan: plan.name)
+
+    result = bundle.get_translation("hello-user", {"user": Plan.BASIC}, use_isolating=False)
+
+    assert result == "Hello, BASIC"
+
+
+def test_adapters_take_precedence_over_built_in_types():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+    bundle.register_adapter(str, str.upper)
+
+    result = bundle.get_translation("hello-user", {"user": "Bob"}, use_isolating=False)
+
+    assert result == "Hello, BOB"
+
+
+def test_fluent_value_method():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    result = bundle.get_translation("hello-user", {"user": Tier()}, use_isolating=False)
+
+    assert result == "Hello, gold"
+
+
+def test_adapted_values_are_not_adapted_again():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+    bundle.register_adapter(Money, lambda money: money)
+
+    result = bundle.get_translation("hello-user", {"user": Money(1, "EUR")}, use_isolating=False)
+
+    assert result == "Hello, user"
+
+
+def test_adapters_are_kept_by_copies_and_pickles():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+    bundle.register_adapter(uuid.UUID, str)
+    value = uuid.UUID(int=1)
+
+    for copy in (bundle.copy(), pickle.loads(pickle.dumps(bundle))):
+        result = copy.get_translation("hello-user", {"user": value}, use_isolating=False)
+        assert result == f"Hello, {value}"
+
+
+def test_register_adapter_not_callable():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    with pytest.raises(TypeError, match="Adapter for 'Money' is not callable, got 1."):
+        bundle.register_adapter(Money, 1)
+
+
+# Number formatting tests
+
+
+@pytest.mark.parametrize(
+    "language, message_id, variables, expected",
+    (
+        ("en", "plain-number", {"number": 1234567}, "1,234,567"),
+        ("de", "plain-number", {"number": 1234567}, "1.234.567"),
+        ("fr", "plain-number", {"number": 1234567}, "1\u202f234\u202f567"),
+        ("de-CH", "plain-number", {"number": 1234567}, "1’234’567"),
+        # Spanish doesn't group four digit numbers.
+        ("es", "plain-number", {"number": 1234}, "1234"),
+        ("es", "plain-number", {"number": 12345}, "12.345"),
+        ("en", "fraction-digits", {"number": 1234}, "1,234.00"),
+        ("de", "fraction-digits", {"number": -1234}, "-1.234,00"),
+        ("en", "percent", {"ratio": 12}, "1,200%"),
+        ("de", "percent", {"ratio": 1}, "100\u00a0%"),
+        ("en", "no-grouping", {"number": 1234567}, "1234567"),
+        ("en", "price", {"amount": 5}, "€5.00"),
+        ("de", "price", {"amount": 1234}, "1.234,00\u00a0€"),
+        ("en", "price-code", {"amount": 5}, "USD5.00"),
+        ("en", "plain-number", {"number": Decimal("1234.50")}, "1,234.50"),
+        ("de", "plain-number", {"number": Decimal("-0.1")}, "-0,1"),
+        ("en", "plain-number", {"number": Decimal("1.5E-4")}, "0.00015"),
+        ("en", "plain-number", {"number": Decimal("1E+3")}, "1,000"),
+        ("en", "fraction-digits", {"number": Decimal("2.5")}, "2.50"),
+        ("de", "price", {"amount": Decimal("19.99")}, "19,99\u00a0€"),
+    ),
+)
+def test_number_formatting(language, message_id, variables, expected):
+    bundle = fluent.Bundle(language, [data_dir / "numbers.ftl"])
+
+    result = bundle.get_translation(message_id, variables=variables, use_isolating=False)
+
+    assert result == expected
+
+
+def test_number_formatting_in_custom_function_results():
+    bundle = fluent.Bundle("de", [data_dir / "functions.ftl"])
+    bundle.add_function("DOUBLE", lambda value: value * 2.5)
+
+    result = bundle.get_translation("double-message", variables={"count": 1000})
+
+    assert result == "2.500"
+
+
+@pytest.mark.parametrize(
+    "language, number, expected",
+    (
+        ("en", fluent.Number(5, style="currency", currency="EUR"), "€5.00"),
+        ("de", fluent.Number(1234.5, style="currency", currency="EUR"), "1.234,50\u00a0€"),
+        ("en", fluent.Number(0.25, style="percent"), "25%"),
+        ("en", fluent.Number(1234567, use_grouping=False), "1234567"),
+        ("en", fluent.Number(3, minimum_integer_digits=2), "03"),
+        ("en", fluent.Number(2, minimum_fraction_digits=2), "2.00"),
+        ("en", fluent.Number(Decimal("1.50")), "1.50"),
+        ("en", fluent.Number(Decimal("1.50"), maximum_fraction_digits=1), "1.5"),
+        ("en", fluent.Number(1.23456, maximum_significant_digits=3), "1.23"),
+        (
+            "en",
+            fluent.Number(5, style="currency", currency="USD", currency_display="code"),
+            "USD5.00",
+        ),
+    ),
+)
+def test_number_class(language, number, expected):
+    bundle = fluent.Bundle(language, [data_dir / "numbers.ftl"])
+
+    result = bundle.get_translation("plain-number", {"number": number}, use_isolating=False)
+
+    assert result == expected
+
+
+def test_number_class_options_are_overridden_by_number_function():
+    bundle = fluent.Bundle("en", [data_dir / "numbers.ftl"])
+    number = fluent.Number(2, minimum_fraction_digits=1)
+
+    result = bundle.get_translation("fraction-digits", {"number": number}, use_isolating=False)
+
+    assert result == "2.00"
+
+
+ORDINAL_FTL = """
+place = { $place ->
+    [one] {$place}st
+    [two] {$place}nd
+    [few] {$place}rd
+   *[other] {$place}th
+}
+"""
+
+
+@pytest.mark.parametrize(
+    "number, expected",
+    (
+        (fluent.Number(1, type="ordinal"), "1st"),
+        (fluent.Number(3, type="ordinal"), "3rd"),
+        (fluent.Number(11, type="ordinal"), "11th"),
+        (fluent.Number(3), "3th"),
+    ),
+)
+def test_number_class_plural_rules(number, expected):
+    bundle = fluent.Bundle("en", [("ordinal", ORDINAL_FTL)])
+
+    assert bundle.get_translation("place", {"place": number}, use_isolating=False) == expected
+
+
+def test_number_class_repr():
+    number = fluent.Number(5, style="currency", currency="EUR", minimum_fraction_digits=0)
+
+    assert number.value == 5
+    expected = "Number(5.0, style='currency', currency='EUR', minimum_fraction_digits=0)"
+    assert repr(number) == expected
+
+
+@pytest.mark.parametrize(
+    "options, expected",
+    (
+        ({"style": "money"}, "style must be 'currency', 'decimal' or 'percent', not 'money'."),
+        ({"style": "currency"}, "currency is required with style='currency'."),
+        ({"currency_display": "name"}, "currency_display must be 'code' or 'symbol', not 'name'."),
+        ({"type": "plural"}, "type must be 'cardinal' or 'ordinal', not 'plural'."),
+    ),
+)
+def test_number_class_invalid_options(options, expected):
+    with pytest.raises(ValueError, match=re.escape(expected)):
+        fluent.Number(1, **options)
+
+
+@pytest.mark.parametrize(
+    "locale, value, plural_type, expected",
+    (
+        ("en", 1, None, "one"),
+        ("en", 2, None, "other"),
+        ("en", 1.5, None, "other"),
+        # The trailing zero is kept, as for variables.
+        ("en", Decimal("1.0"), None, "other"),
+        ("ru", 5, None, "many"),
+        ("ru", 22, None, "few"),
+        ("pl", 3, "cardinal", "few"),
+        ("ar", 0, None, "zero"),
+        ("en-GB", 1, None, "one"),
+        ("en", 3, "ordinal", "few"),
+        ("en", 11, "ordinal", "other"),
+        ("en", fluent.Number(2, type="ordinal"), None, "two"),
+        ("en", fluent.Number(2, type="ordinal"), "cardinal", "other"),
+        ("en", 10**30, None, "other"),
+        # Languages without plural rules use English's.
+        ("tlh", 1, None, "one"),
+    ),
+)
+def test_plural_category(locale, value, plural_type, expected):
+    assert fluent.plural_category(locale, value, type=plural_type) == expected
+
+
+def test_plural_category_matches_selector():
+    bundle = fluent.Bundle("en", [("ordinal", ORDINAL_FTL)])
+    number = fluent.Number(23, type="ordinal")
+
+    assert fluent.plural_category("en", number) == "few"
+    assert bundle.get_translation("place", {"place": number}, use_isolating=False) == "23rd"
+
+
+def test_plural_category_invalid_locale():
+    with pytest.raises(ValueError, match="Invalid language: '!!'"):
+        fluent.plural_category("!!", 1)
+
+
+def test_plural_category_invalid_type():
+    expected = "type must be 'cardinal' or 'ordinal', not 'plural'."
+    with pytest.raises(ValueError, match=re.escape(expected)):
+        fluent.plural_category("en", 1, type="plural")
+
+
+@pytest.mark.parametrize("value", ("1", None, True))
+def test_plural_category_not_a_number(value):
+    with pytest.raises(TypeError, match="Value must be a number"):
+        fluent.plural_category("en", value)
+
+
+def test_locale():
+    locale = fluent.Locale("zh_hant_tw")
+
+    assert str(locale) == "zh-Hant-TW"
+    assert repr(locale) == "Locale('zh-Hant-TW')"
+    assert locale.language == "zh"
+    assert locale.script == "Hant"
+    assert locale.region == "TW"
+    assert locale.variants == []
+
+
+def test_locale_without_script_or_region():
+    locale = fluent.Locale("de-1996")
+
+    assert locale.script is None
+    assert locale.region is None
+    assert locale.variants == ["1996"]
+
+
+@pytest.mark.parametrize(
+    "tag, expected",
+    (
+        ("en", "en-Latn-US"),
+        ("sr", "sr-Cyrl-RS"),
+        ("zh-TW", "zh-Hant-TW"),
+        ("en-GB", "en-Latn-GB"),
+    ),
+)
+def test_locale_maximize(tag, expected):
+    locale = fluent.Locale(tag)
+
+    assert str(locale.maximize()) == expected
+    assert locale.maximize().minimize() == fluent.Locale(tag)
+    # Locales are immutable.
+    assert str(locale) == tag
+
+
+@pytest.mark.parametrize(
+    "tag, expected",
+    (("EN_us", "en-US"), ("sr-latn-rs", "sr-Latn-RS"), ("fr", "fr")),
+)
+def test_locale_canonicalize(tag, expected):
+    assert fluent.Locale.canonicalize(tag) == expected
+
+
+def test_locale_equality_and_hashing():
+    assert fluent.Locale("en_US") == fluent.Locale("en-us")
+    assert fluent.Locale("en-US") != fluent.Locale("en-GB")
+    assert len({fluent.Locale("en_US"), fluent.Locale("en-US")}) == 1
+
+
+@pytest.mark.parametrize("tag", ("!!", "e", "en-US-US"))
+def test_locale_invalid(tag):
+    with pytest.raises(ValueError, match="Invalid language"):
+        fluent.Locale(tag)
+
+    with pytest.raises(ValueError, match="Invalid language"):
+        fluent.Locale.canonicalize(tag)
+
+
+@pytest.mark.parametrize(
+    "header, expected",
+    (
+        ("fr-CH, fr;q=0.9, en;q=0.8, de;q=0.7, *;q=0.5", ["fr-CH", "fr", "en", "de"]),
+        ("en;q=0.5, de, fr;q=0.8", ["de", "fr", "en"]),
+        # Equal quality values keep the header's order.
+        ("de;q=0.8, en-gb;q=0.8", ["de", "en-GB"]),
+        ("en_US", ["en-US"]),
+        ("en;Q=0.5, de", ["de", "en"]),
+        # Languages with q=0 aren't acceptable.
+        ("en, fr;q=0", ["en"]),
+        # Malformed entries are skipped.
+        ("en;q=abc, !!, fr;q=2, de;q=0.3, , es", ["es", "de"]),
+        ("en, en;q=0.5", ["en"]),
+        ("", []),
+        ("*", []),
+    ),
+)
+def test_parse_accept_language(header, expected):
+    assert fluent.parse_accept_language(header) == expected
+
+
+def test_negotiate_languages_from_header():
+    header = "fr-CH, fr;q=0.9, en;q=0.8"
+
+    result = fluent.negotiate_languages(header, ["de", "en-US", "fr"])
+
+    assert result == ["fr", "en-US"]
+
+
+def test_negotiate_languages_keeps_available_tags():
+    result = fluent.negotiate_languages(["en-US"], ["en_US", fluent.Locale("de")])
+
+    assert result == ["en_US"]
+
+
+def test_negotiate_languages_default():
+    assert fluent.negotiate_languages("de", ["en", "fr"], default="en") == ["en"]
+    assert fluent.negotiate_languages("fr", ["en", "fr"], default="en") == ["fr", "en"]
+    assert fluent.negotiate_languages("", ["en", "fr"]) == []
+
+
+def test_negotiate_languages_skips_invalid_requested_languages():
+    assert fluent.negotiate_languages(["!!", "fr"], ["en", "fr"]) == ["fr"]
+
+
+@pytest.mark.parametrize(
+    "strategy, expected",
+    (
+        ("filtering", ["en-GB", "en-US", "fr"]),
+        # The default is added after the matches, as with filtering.
+        ("matching", ["en-GB", "fr", "en-US"]),
+        ("lookup", ["en-GB"]),
+    ),
+)
+def test_negotiate_languages_strategies(strategy, expected):
+    available = ["en-US", "en-GB", "fr"]
+
+    result = fluent.negotiate_languages(
+        ["en-GB", "fr"], available, default="en-US", strategy=strategy
+    )
+
+    assert result == expected
+
+
+def test_negotiate_languages_for_bundle():
+    ftl_filenames = {"en": [data_dir / "en.ftl"], "fr": [data_dir / "fr.ftl"]}
+    languages = fluent.negotiate_languages("fr-FR, en;q=0.5", list(ftl_filenames))
+
+    bundle = fluent.Bundle(languages, ftl_filenames)
+
+    assert bundle.get_translation("hello-world") == "Bonjour le monde!"
+
+
+@pytest.mark.parametrize(
+    "options, expected",
+    (
+        ({"available": ["!!"]}, "Invalid language: '!!'"),
+        ({"default": "!!"}, "Invalid language: '!!'"),
+        (
+            {"strategy": "best"},
+            "strategy must be 'filtering', 'lookup' or 'matching', not 'best'.",
+        ),
+        ({"strategy": "lookup"}, "A default is required with strategy='lookup'."),
+    ),
+)
+def test_negotiate_languages_invalid_options(options, expected):
+    options = {"requested": "en", "available": ["en"], **options}
+
+    with pytest.raises(ValueError, match=re.escape(expected)):
+        fluent.negotiate_languages(**options)
+
+
+def test_bundle_accepts_locale():
+    bundle = fluent.Bundle(fluent.Locale("en_US"), [data_dir / "en.ftl"])
+
+    assert bundle.get_translation("hello-world") == "Hello World"
+
+
+def test_bundle_accepts_locale_fallback_chain():
+    bundle = fluent.Bundle(
+        [fluent.Locale("fr"), "en"],
+        {"fr": [data_dir / "fr.ftl"], "en": [data_dir / "en.ftl"]},
+    )
+
+    assert bundle.get_translation("hello-world") == "Bonjour le monde!"
+
+
+@pytest.mark.parametrize(
+    "number, expected",
+    (
+        (Decimal("1"), "One"),
+        # The trailing zero is kept, so it isn't the same number as 1 for plural rules.
+        (Decimal("1.0"), "Something else"),
+        (Decimal("2"), "Something else"),
+    ),
+)
+def test_decimal_selector(number, expected):
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    result = bundle.get_translation("with-selector", variables={"number": number})
+
+    assert result == expected
+
+
+def test_decimal_plural_categories_keep_trailing_zeros():
+    source = "items = { $count ->\n [one] item\n *[other] items\n}"
+    bundle = fluent.Bundle("en", [("plural", source)])
+
+    assert bundle.get_translation("items", {"count": Decimal("1")}) == "item"
+    assert bundle.get_translation("items", {"count": Decimal("1.00")}) == "items"
+
+
+def test_decimal_in_custom_function_results():
+    bundle = fluent.Bundle("de", [data_dir / "functions.ftl"])
+    bundle.add_function("DOUBLE", lambda value: Decimal("2.50") * value)
+
+    result = bundle.get_translation("double-message", variables={"count": 1000})
+
+    assert result == "2.500,00"
+
+
+def test_number_function_cannot_be_replaced():
+    bundle = fluent.Bundle("en", [data_dir / "numbers.ftl"])
+
+    with pytest.raises(ValueError, match="Function 'NUMBER' is already registered."):
+        bundle.add_function("NUMBER", lambda value: value)
+
+
+# Date formatting tests
+
+
+@pytest.mark.parametrize(
+    "language, message_id, expected",
+    (
+        ("en", "plain-date", "1/5/2020"),
+        ("en-GB", "plain-date", "05/01/2020"),
+        ("de", "plain-date", "5.1.2020"),
+        ("en", "long-month", "January 2020"),
+        ("fr", "long-month", "janvier 2020"),
+        ("es", "long-month", "enero de 2020"),
+        ("en", "full-date", "Sunday, January 5, 2020"),
+        ("de", "full-date", "Sonntag, 5. Januar 2020"),
+        ("en", "short-date", "Jan 5"),
+        ("en", "time", "3:04\u202fPM"),
+        ("de", "time", "15:04"),
+        ("en", "date-and-time", "Jan 5, 2020, 3:04\u202fPM"),
+        ("fr", "date-and-time", "5 janv. 2020 15:04"),
+        ("ru", "full-date", "воскресенье, 5 января 2020 г."),
+        ("ru", "long-month", "январь 2020 г."),
+        ("pl", "full-date", "niedziela, 5 stycznia 2020"),
+        ("cs", "plain-date", "5. 1. 2020"),
+        ("sv", "plain-date", "2020-01-05"),
+        ("fi", "time", "15.04"),
+    ),
+)
+def test_datetime_formatting(language, message_id, expected):
+    bundle = fluent.Bundle(language, [data_dir / "dates.ftl"])
+
+    result = bundle.get_translation(
+        message_id, variables={"date": datetime(2020, 1, 5, 15, 4, 5)}, use_isolating=False
+    )
+
+    assert result == expected
+
+
+def test_datetime_formatting_of_dates():
+    bundle = fluent.Bundle("en", [data_dir / "dates.ftl"])
+
+    result = bundle.get_translation(
+        "full-date", variables={"date": date(2020, 1, 5)}, use_isolating=False
+    )
+
+    assert result == "Sunday, January 5, 2020"
+
+
+def test_datetime_formatting_uses_local_time_of_aware_datetimes():
+    bundle = fluent.Bundle("de", [data_dir / "dates.ftl"])
+    value = datetime(2020, 1, 5, 15, 4, 5, tzinfo=timezone(timedelta(hours=-5)))
+
+    result = bundle.get_translation("time", variables={"date": value}, use_isolating=False)
+
+    assert result == "15:04"
+
+
+def test_datetime_formatting_converts_aware_datetimes_to_timezone():
+    bundle = fluent.Bundle("de", [data_dir / "dates.ftl"], timezone="Europe/Berlin")
+    value = datetime(2020, 1, 5, 15, 4, 5, tzinfo=timezone(timedelta(hours=-5)))
+
+    result = bundle.get_translation("time", variables={"date": value}, use_isolating=False)
+
+    assert result == "21:04"
+
+
+def test_datetime_formatting_leaves_naive_datetimes_in_timezone():
+    bundle = fluent.Bundle("de", [data_dir / "dates.ftl"], timezone="Europe/Berlin")
+    value = datetime(2020, 1, 5, 15, 4, 5)
+
+    result = bundle.get_translation("time", variables={"date": value}, use_isolating=False)
+
+    assert result == "15:04"
+
+
+def test_datetime_timezone_is_kept_by_copies():
+    bundle = fluent.Bundle("de", [data_dir / "dates.ftl"], timezone="UTC")
+    value = datetime(2020, 1, 5, 15, 4, 5, tzinfo=timezone(timedelta(hours=-5)))
+
+    result = bundle.copy().get_translation("time", {"date": value}, use_isolating=False)
+
+    assert result == "20:04"
+
+
+def test_invalid_timezone():
+    with pytest.raises(ValueError, match="Invalid timezone: 'Mars/Olympus'"):
+        fluent.Bundle("de", [data_dir / "dates.ftl"], timezone="Mars/Olympus")
+
+
+def test_datetime_function_passes_dates_to_custom_functions():
+    bundle = fluent.Bundle("en", [data_dir / "dates.ftl"])
+    bundle.add_function("NEXT_DAY", lambda value: value + timedelta(days=1))
+
+    result = bundle.get_translation(
+        "next-day", variables={"date": date(2020, 1, 5)}, use_isolating=False
+    )
+
+    assert result == "January 6"
+
+
+LISTS_FTL = """
+items = { $items }
+and = { LIST($items) }
+or = { LIST($items, type: "disjunction") }
+unit = { LIST($items, type: "unit") }
+"""
+
+
+@pytest.mark.parametrize(
+    "language, message_id, items, expected",
+    (
+        ("en", "and", ["tea", "cake", "scones"], "tea, cake, and scones"),
+        ("en", "and", ("tea", "cake"), "tea and cake"),
+        ("en", "and", ["tea"], "tea"),
+        ("en", "and", [], ""),
+        ("en-GB", "and", ["tea", "cake", "scones"], "tea, cake and scones"),
+        ("en", "or", ["tea", "cake", "scones"], "tea, cake, or scones"),
+        ("en", "unit", ["tea", "cake", "scones"], "tea, cake, scones"),
+        ("en", "items", ["tea", "cake", "scones"], "tea, cake, and scones"),
+        ("de", "and", ["Tee", "Kuchen", "Scones"], "Tee, Kuchen und Scones"),
+        ("fr", "or", ["thé", "gâteau"], "thé ou gâteau"),
+    ),
+)
+def test_list_formatting(language, message_id, items, expected):
+    bundle = fluent.Bundle(language, [("lists", LISTS_FTL)])
+
+    result = bundle.get_translation(message_id, {"items": items}, use_isolating=False)
+
+    assert result == expected
+
+
+def test_lists_of_other_values_are_unsupported():
+    bundle = fluent.Bundle("en", [("lists", LISTS_FTL)])
+
+    with pytest.raises(TypeError, match="Variable 'items' has an unsupported value"):
+        bundle.get_translation("items", {"items": ["tea", 1]}, strict_types=True)
+
+
+def test_lists_are_passed_to_custom_functions():
+    bundle = fluent.Bundle("en", [("lists", "count = { COUNT($items) }")])
+    bundle.add_function("COUNT", len)
+
+    assert bundle.get_translation("count", {"items": ["tea", "cake"]}) == "2"
+
+
+# Language fallback tests
+
+
+def test_language_fallback_chain():
+    bundle = fluent.Bundle(
+        ["fr", "en"], {"fr": [data_dir / "fr.ftl"], "en": [data_dir / "en.ftl"]}
+    )
+
+    assert bundle.get_translation("hello-world") == "Bonjour le monde!"
+    assert bundle.get_translation("with-selector", variables={"number": 1}) == "One"
+
+
+def test_language_fallback_uses_the_fallback_language_for_formatting():
+    bundle = fluent.Bundle(
+        ["fr", "en"], {"fr": [data_dir / "fr.ftl"], "en": [data_dir / "en.ftl"]}
+    )
+
+    result = bundle.get_translation(
+        "apples", variables={"numberOfApples": 1000}, use_isolating=False
+    )
+
+    assert result == "1,000 apples"
+
+
+def test_language_fallback_with_compile():
+    bundle = fluent.Bundle(
+        ["fr", "en"], {"fr": [data_dir / "fr.ftl"], "en": [data_dir / "en.ftl"]}
+    )
+
+    assert bundle.compile("hello-world")() == "Bonjour le monde!"
+    assert bundle.compile("apples")({"numberOfApples": 2}, use_isolating=False) == "2 apples"
+
+
+def test_language_fallback_message_not_found():
+    bundle = fluent.Bundle(
+        ["fr", "en"], {"fr": [data_dir / "fr.ftl"], "en": [data_dir / "en.ftl"]}
+    )
+
+    with pytest.raises(ValueError, match="missing-message not found"):
+        bundle.get_translation("missing-message")
+
+
+def test_language_chain_with_shared_files():
+    bundle = fluent.Bundle(["de", "en"], [data_dir / "en.ftl"])
+
+    result = bundle.get_translation(
+        "apples", variables={"numberOfApples": 1000}, use_isolating=False
+    )
+
+    assert result == "1.000 apples"
+
+
+def test_language_fallback_files_for_unknown_language():
+    with pytest.raises(ValueError, match="FTL files given for 'de'"):
+        fluent.Bundle(["fr", "en"], {"de": [data_dir / "en.ftl"]})
+
+
+def test_language_chain_must_not_be_empty():
+    with pytest.raises(ValueError, match="At least one language is required."):
+        fluent.Bundle([], [data_dir / "en.ftl"])
+
+
+# Localization tests
+
+
+def test_localization_with_root_loader():
+    l10n = fluent.Localization(["fr", "en"], ["main.ftl"], data_dir / "l10n" / "{locale}")
+
+    assert l10n.format_value("hello-world") == "Bonjour le monde!"
+    assert l10n.format_value("goodbye") == "Goodbye"
+    assert l10n.format_value("hello-user", {"user": "Bob"}) == f"Hello, {BIDI_OPEN}Bob{BIDI_CLOSE}"
+
+
+def test_localization_skips_missing_resources():
+    l10n = fluent.Localization(
+        ["de", "en"], ["main.ftl", "missing.ftl"], str(data_dir / "l10n" / "{locale}")
+    )
+
+    assert l10n.format_value("hello-world") == "Hello World"
+
+
+def test_localization_with_callable_loader():
+    calls = []
+
+    def loader(locale, resource_id):
+        calls.append((locale, resource_id))
+        return data_dir / f"{locale}.ftl" if locale == "fr" else None
+
+    l10n = fluent.Localization(["fr", "en"], ["main.ftl"], loader)
+
+    assert l10n.format_value("hello-world") == "Bonjour le monde!"
+    assert calls == [("fr", "main.ftl"), ("en", "main.ftl")]
+
+
+def test_localization_message_not_found():
+    l10n = fluent.Localization(["fr", "en"], ["main.ftl"], data_dir / "l10n" / "{locale}")
+
+    with pytest.raises(ValueError, match="missing-message not found"):
+        l10n.format_value("missing-message")
+
+
+def test_localization_invalid_loader():
+    with pytest.raises(TypeError, match="Resource loader must be a path or a callable"):
+        fluent.Localization(["en"], ["main.ftl"], 42)
+
+
+# FTL string tests
+
+
+def test_ftl_source_in_constructor():
+    bundle = fluent.Bundle("en", [("greetings", "hello = Hello!"), data_dir / "en.ftl"])
+
+    assert bundle.get_translation("hello") == "Hello!"
+    assert bundle.get_translation("hello-world") == "Hello World"
+
+
+def test_add_ftl_string():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    bundle.add_ftl_string("overrides", "hello-world = Hi World\nnew-message = New")
+
+    assert bundle.get_translation("hello-world") == "Hi World"
+    assert bundle.get_translation("new-message") == "New"
+
+
+def test_add_ftl_string_to_fallback_language():
+    bundle = fluent.Bundle(["fr", "en"], {"fr": [data_dir / "fr.ftl"]})
+
+    bundle.add_ftl_string("english", "goodbye = Goodbye", language="en")
+
+    assert bundle.get_translation("goodbye") == "Goodbye"
+    assert bundle.get_translation("hello-world") == "Bonjour le monde!"
+
+
+def test_add_ftl_string_to_unknown_language():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    with pytest.raises(ValueError, match="'de' is not one of the bundle's languages."):
+        bundle.add_ftl_string("german", "hello = Hallo", language="de")
+
+
+def test_add_ftl_string_reports_name_in_strict_mode():
+    bundle = fluent.Bundle("en", [], strict=True)
+
+    with pytest.raises(fluent.ParserError, match="Error when parsing db:messages/42"):
+        bundle.add_ftl_string("db:messages/42", "invalid-message")
+
+
+def test_add_ftl_string_skips_errors_in_non_strict_mode():
+    bundle = fluent.Bundle("en", [])
+
+    bundle.add_ftl_string("partly-valid", "invalid-message\nvalid-message = I'm valid.")
+
+    assert bundle.get_translation("valid-message") == "I'm valid."
+
+
+# Shared bundle tests
+
+
+def test_get_translation_from_multiple_threads():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    def translate(index):
+        return bundle.get_translation(
+            "hello-user", variables={"user": f"User {index}"}, use_isolating=index % 2 == 0
+        )
+
+    with concurrent.futures.ThreadPoolExecutor(max_workers=8) as executor:
+        results = list(executor.map(translate, range(100)))
+
+    assert results == [
+        f"Hello, {BIDI_OPEN}User {index}{BIDI_CLOSE}" if index % 2 == 0 else f"Hello, User {index}"
+        for index in range(100)
+    ]
+
+
+def test_get_translation_from_within_custom_function():
+    bundle = fluent.Bundle("en", [data_dir / "functions.ftl", data_dir / "en.ftl"])
+    bundle.add_function("PLATFORM", lambda: bundle.get_translation("hello-world"))
+
+    result = bundle.get_translation("platform-message", use_isolating=False)
+
+    assert result == "Running on Hello World."
+
+
+# Batch translation tests
+
+
+def test_get_translations():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    results = bundle.get_translations(
+        [
+            ("hello-world", None),
+            ("hello-user", {"user": "Bob"}),
+            ("apples", {"numberOfApples": 1000}),
+        ],
+        use_isolating=False,
+    )
+
+    assert results == ["Hello World", "Hello, Bob", "1,000 apples"]
+
+
+def test_get_translations_preserves_order():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    results = bundle.get_translations(
+        [("hello-user", {"user": f"User {index}"}) for index in range(500)], use_isolating=False
+    )
+
+    assert results == [f"Hello, User {index}" for index in range(500)]
+
+
+def test_get_translations_message_not_found():
+    bundle = fluent.Bundle("en", [data_dir / "en.ftl"])
+
+    with pytest.raises(ValueError, match="missing-message not found"):
+        bundle.get_translations([("hello-world", None), ("missing-message", None)])
+
+
+def test_get_translations_with_custom_functions():
+    bundle = fluent.Bundle("en", [data_dir / "functions.ftl"])
+    bundle.add_function("DOUBLE", lambda value: value * 2)
+
+    results = bundle.get_translations(
+        [("double-message", {"count": count}) for count in range(50)]
+    )
+
+    assert results == [str(count * 2) for count in range(50)]
+
+
+def test_get_translations_raises_custom_function_exceptions():
+    bundle = fluent.Bundle("en", [data_dir / "functions.ftl"])
+
+    def platform():
+        raise RuntimeError("Unknown platform")
+
+    bundle.add_function("PLATFORM", platform)
+
+    with pytest.raises(RuntimeError, match="Unknown platform"):
+        bundle.get_translations([("platform-message", None)] * 10)
+
+
+# Bundle cache tests
+
+
+def test_bundle_cache_round_trip(tmp_path):
+    bundle = fluent.Bundle(
+        ["fr", "en"],
+        {"fr": [data_dir / "fr.ftl"], "en": [data_dir / "en.ftl", ("extra", "extra = Extra")]},
+        variable_separator="-",
+    )
+    bundle.save_cache(tmp_path / "bundle.cache")
+
+    cached = fluent.Bundle.from_cache(tmp_path / "bundle.cache")
+
+    assert cached.get_translation("hello-world") == "Bonjour le monde!"
+    assert cached.get_translation("extra") == "Extra"
+    result = cached.get_translation(
+        "nested-user-dashed", {"user": {"name": "Bob"}}, use_isolating=False
+    )
+    assert result == "Hello, Bob."
+
+
+def test_bundle_cache_detects_changed_files(tmp_path):
+    ftl_file = tmp_path / "messages.ftl"
+    ftl_file.write_text("hello = Hello")
+    fluent.Bundle("en", [ftl_file]).save_cache(tmp_path / "bundle.cache")
+    ftl_file.write_text("hello = Hi")
+
+    with pytest.raises(ValueError, match="Bundle cache is stale: '.*messages.ftl' has changed."):
+        fluent.Bundle.from_cache(tmp_path / "bundle.cache")
+
+    cached = fluent.Bundle.from_cache(tmp_path / "bundle.cache", verify=False)
+    assert cached.get_translation("hello") == "Hello"
+
+
+def test_bundle_cache_of_prefixed_files(tmp_path):
+    ftl_file = tmp_path / "emails.ftl"
+    ftl_file.write_text("title = Your receipt")
+    bundle = fluent.Bundle("en", [ftl_file], prefixes={ftl_file: "emails-"})
+    bundle.save_cache(tmp_path / "bundle.cache")
+
+    cached = fluent.Bundle.from_cache(tmp_path / "bundle.cache")
+
+    assert cached.get_translation("emails-title") == "Your receipt"
+
+
+def test_bundle_cache_of_files_in_other_encodings(tmp_path):
+    ftl_file = tmp_path / "messages.ftl"
+    ftl_file.write_bytes("title = Reçu".encode("latin-1"))
+    bundle = fluent.Bundle("fr", [ftl_file], encoding="latin-1")
+    bundle.save_cache(tmp_path / "bundle.cache")
+
+    cached = fluent.Bundle.from_cache(tmp_path / "bundle.cache")
+
+    assert cached.get_translation("title") == "Reçu"
+
+
+def test_bundle_cache_of_files_with_duplicates(tmp_path):
+    (tmp_path / "base.ftl").write_text("title = Your receipt")
+    (tmp_path / "emails.ftl").write_text("title = Receipt\nsubject = { title }")
+    ftl_files = [tmp_path / "base.ftl", tmp_path / "emails.ftl"]
+    bundle = fluent.Bundle("en", ftl_files, on_duplicate="first_wins")
+    bundle.save_cache(tmp_path / "bundle.cache")
+
+    cached = fluent.Bundle.from_cache(tmp_path / "bundle.cache")
+
+    assert cached.get_translation("subject") == "Your receipt"
+
+
+def bundle_with_settings():
+    bundle = fluent.Bundle(
+        "de",
+        [data_dir / "dates.ftl", ("main", "greeting = Hallo { $name }!\nflag = { $flag }")],
+        variable_separator="-",
+        severity={"MaxLength": "warning"},
+        on_duplicate="last_wins",
+        pseudo="accented",
+        strip_isolation=True,
+        timezone="Europe/Berlin",
+        none_as="empty",
+        bool_as="number",
+        lint={"max_length": 10},
+        diagnostic_style="plain",
+    )
+    bundle.set_variable_schema({"greeting": {"name": "string"}})
+    return bundle
+
+
+def assert_same_settings(bundle, other):
+    aware = datetime(2020, 1, 5, 15, 4, 5, tzinfo=timezone(timedelta(hours=-5)))
+    for identifier, variables in (
+        ("greeting", {"name": None}),
+        ("flag", {"flag": True}),
+        ("time", {"date": aware}),
+    ):
+        expected = bundle.get_translation(identifier, variables)
+        assert other.get_translation(identifier, variables) == expected
+    assert other.validation_errors() == bundle.validation_errors()
+    with pytest.raises(fluent.FormatError, match=r"\$name should be a string"):
+        other.get_translation("greeting", {"name": 5})
+
+
+def test_bundle_cache_keeps_settings(tmp_path):
+    bundle = bundle_with_settings()
+    bundle.save_cache(tmp_path / "bundle.cache")
+
+    assert_same_settings(bundle, fluent.Bundle.from_cache(tmp_path / "bundle.cache"))
+
+
+def test_bundle_cache_invalid_file(tmp_path):
+    (tmp_path / "bundle.cache").write_bytes(b"not a cache")
+
+    with pytest.raises(ValueError, match="Invalid bundle cache"):
+        fluent.Bundle.from_cache(tmp_path / "bundle.cache")
+
+
+def test_bundle_cache_missing_file(tmp_path):
+    with pytest.raises(FileNotFoundError):
+        fluent.Bundle.from_cache(tmp_path / "missing.cache")
+
+
+def platform():
+    return "Linux"
+
+
+def test_pickle_bundle():
+    bundle = fluent.Bundle(
+        ["fr", "en"],
+        {
+            "fr": [data_dir / "fr.ftl"],
+            "en": [data_dir / "en.ftl", ("extra", "extra = Extra { PLATFORM() }")],
+        },
+        variable_separator="-",
+    )
+    bundle.add_function("PLATFORM", platform)
+
+    unpickled = pickle.loads(pickle.dumps(bundle))
+
+    assert unpickled.get_translation("hello-world") == "Bonjour le monde!"
+    assert unpickled.get_translation("extra", use_isolating=False) == "Extra Linux"
+    result = unpickled.get_translation(
+        "nested-user-dashed", {"user": {"name": "Bob"}}, use_isolating=False
+    )
+    assert result == "Hello, Bob."
+
+
+def test_pickle_bundle_with_unpicklable_function():
+    bundle = fluent.Bundle("en", [])
+    bundle.add_function("PLATFORM", lambda: "Linux")
+
+    with pytest.raises(pickle.PicklingError, match="Function 'PLATFORM' can't be pickled"):
+        pickle.dumps(bundle)
+
+
+def test_pickle_bundle_keeps_settings():
+    bundle = bundle_with_settings()
+    bundle.set_transform(str.upper)
+
+    assert_same_settings(bundle, pickle.loads(pickle.dumps(bundle)))
+
+
+# Parallel parsing tests
+
+
+def test_many_resources_are_added_in_order():
+    sources = [(f"source-{index}", f"message = Message {index}") for index in range(100)]
+
+    bundle = fluent.Bundle("en", sources)
+
+    assert bundle.get_translation("message") == "Message 99"
+
+
+def test_first_invalid_resource_is_reported_in_strict_mode():
+    sources = [(f"source-{index}", "message = Valid") for index in range(50)]
+    sources[10] = ("source-10", "invalid-message")
+    sources[40] = ("source-40", "invalid-message")
+
+    with pytest.raises(fluent.ParserError, match="Error when parsing source-10"):
+        fluent.Bundle("en", sources, strict=True)